@@ -41,6 +41,7 @@ pub use crate::mod_params::RxMode;
 
 pub use embedded_hal_async::delay::DelayNs;
 use interface::*;
+use lr1110::LrFhssParams;
 use mod_params::*;
 use mod_traits::*;
 
@@ -256,6 +257,34 @@ where
         Ok(())
     }
 
+    /// Prepare the radio for an LR-FHSS send operation, on LoRa chips supporting it
+    pub async fn prepare_for_lr_fhss_tx(
+        &mut self,
+        lr_fhss_params: &LrFhssParams,
+        hop_sequence_id: u16,
+        frequency_in_hz: u32,
+        output_power: i32,
+        buffer: &[u8],
+    ) -> Result<(), RadioError> {
+        self.prepare_modem(frequency_in_hz).await?;
+
+        self.radio_kind
+            .set_tx_power_and_ramp_time(output_power, None, true)
+            .await?;
+        self.radio_kind.ensure_ready(self.radio_mode).await?;
+        if self.radio_mode != RadioMode::Standby {
+            self.radio_kind.set_standby().await?;
+            self.radio_mode = RadioMode::Standby;
+        }
+
+        self.radio_kind
+            .prepare_lr_fhss_tx(lr_fhss_params, hop_sequence_id, frequency_in_hz, buffer)
+            .await?;
+        self.radio_mode = RadioMode::Transmit;
+        self.radio_kind.set_irq_params(Some(self.radio_mode)).await?;
+        Ok(())
+    }
+
     /// Execute a transmit operation
     ///
     /// # Warning
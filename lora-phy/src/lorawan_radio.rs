@@ -1,5 +1,9 @@
 #![allow(missing_docs)]
 
+use super::lr1110::{
+    LR_FHSS_DEFAULT_SYNC_WORD, LrFhssBandwidth, LrFhssCodingRate, LrFhssGrid, LrFhssModulationType, LrFhssParams,
+    LrFhssV1Params, lr_fhss_get_hop_sequence_count,
+};
 use super::mod_params::{PacketParams, RadioError};
 use super::mod_traits::RadioKind;
 use super::{DelayNs, LoRa, RxMode};
@@ -7,10 +11,17 @@ use super::{DelayNs, LoRa, RxMode};
 use lora_modulation::BaseBandModulationParams;
 use lorawan_device::async_device::{
     Timings,
-    radio::{PhyRxTx, RxConfig, RxMode as LorawanRxMode, RxQuality, RxStatus, TxConfig},
+    radio::{
+        self, Modulation, PhyRxTx, RfConfig, RxConfig, RxMode as LorawanRxMode, RxQuality, RxStatus,
+        SupportedModulations, TxConfig,
+    },
 };
 
 const DEFAULT_RX_WINDOW_LEAD_TIME: u32 = 50;
+/// Class B beacons are sent with a 10 symbol preamble.
+const BEACON_PREAMBLE_SYMBOLS: u16 = 10;
+/// Interval between RSSI samples while sensing a channel for listen-before-talk.
+const LBT_RSSI_SAMPLE_PERIOD_US: u32 = 250;
 
 /// LoRaWAN radio implementation.
 ///
@@ -95,34 +106,83 @@ where
 
     const MAX_RADIO_POWER: u8 = P;
 
+    const MODULATIONS: SupportedModulations = SupportedModulations {
+        fsk: false,
+        lr_fhss: RK::SUPPORTS_LR_FHSS,
+    };
+
     async fn tx(&mut self, config: TxConfig, buffer: &[u8]) -> Result<u32, Self::PhyError> {
-        let mdltn_params = self.lora.create_modulation_params(
-            config.rf.bb.sf,
-            config.rf.bb.bw,
-            config.rf.bb.cr,
-            config.rf.frequency,
-        )?;
-        let mut tx_pkt_params = self
-            .lora
-            .create_tx_packet_params(8, false, true, false, &mdltn_params)?;
+        match config.rf.modulation {
+            Modulation::LoRa => {
+                let mdltn_params = self.lora.create_modulation_params(
+                    config.rf.bb.sf,
+                    config.rf.bb.bw,
+                    config.rf.bb.cr,
+                    config.rf.frequency,
+                )?;
+                let mut tx_pkt_params = self
+                    .lora
+                    .create_tx_packet_params(8, false, true, false, &mdltn_params)?;
 
-        self.lora
-            .prepare_for_tx(&mdltn_params, &mut tx_pkt_params, config.pw.into(), buffer)
-            .await?;
+                self.lora
+                    .prepare_for_tx(&mdltn_params, &mut tx_pkt_params, config.pw.into(), buffer)
+                    .await?;
+            }
+            Modulation::LrFhss(params) => {
+                let params = lr_fhss_params(params);
+                self.lora
+                    .prepare_for_lr_fhss_tx(
+                        &params,
+                        hop_sequence_id(&params, buffer),
+                        config.rf.frequency,
+                        config.pw.into(),
+                        buffer,
+                    )
+                    .await?;
+            }
+            Modulation::Fsk(_) => return Err(RadioError::ModulationUnsupported.into()),
+        }
         self.lora.tx().await?;
         Ok(0)
     }
 
+    async fn channel_clear(
+        &mut self,
+        rf: RfConfig,
+        threshold_dbm: i16,
+        duration_us: u32,
+    ) -> Result<bool, Self::PhyError> {
+        self.lora.listen(rf.frequency, rf.bb.bw).await?;
+        let mut clear = true;
+        let mut elapsed_us = 0;
+        while clear && elapsed_us < duration_us {
+            self.lora.delay.delay_us(LBT_RSSI_SAMPLE_PERIOD_US).await;
+            elapsed_us += LBT_RSSI_SAMPLE_PERIOD_US;
+            clear = self.lora.get_rssi().await? < threshold_dbm;
+        }
+        self.lora.enter_standby().await?;
+        Ok(clear)
+    }
+
     async fn setup_rx(&mut self, config: RxConfig) -> Result<(), Self::PhyError> {
+        if config.rf.modulation != Modulation::LoRa {
+            return Err(RadioError::ModulationUnsupported.into());
+        }
         let mdltn_params = self.lora.create_modulation_params(
             config.rf.bb.sf,
             config.rf.bb.bw,
             config.rf.bb.cr,
             config.rf.frequency,
         )?;
-        let rx_pkt_params = self
-            .lora
-            .create_rx_packet_params(8, false, 255, true, true, &mdltn_params)?;
+        let rx_pkt_params = match config.mode {
+            LorawanRxMode::Beacon { len, .. } => {
+                self.lora
+                    .create_rx_packet_params(BEACON_PREAMBLE_SYMBOLS, true, len, false, false, &mdltn_params)?
+            }
+            _ => self
+                .lora
+                .create_rx_packet_params(8, false, 255, true, true, &mdltn_params)?,
+        };
         self.lora
             .prepare_for_rx(RxMode::from(config.mode, config.rf.bb), &mdltn_params, &rx_pkt_params)
             .await?;
@@ -172,6 +232,42 @@ impl RxMode {
                 let num_symbols = PREAMBLE_SYMBOLS + bb.delay_in_symbols(ms);
                 RxMode::Single(num_symbols)
             }
+            LorawanRxMode::Beacon { ms, .. } => {
+                let num_symbols = BEACON_PREAMBLE_SYMBOLS + bb.delay_in_symbols(ms);
+                RxMode::Single(num_symbols)
+            }
         }
     }
 }
+
+fn lr_fhss_params(params: radio::LrFhssParams) -> LrFhssParams {
+    LrFhssParams {
+        lr_fhss_params: LrFhssV1Params {
+            sync_word: LR_FHSS_DEFAULT_SYNC_WORD,
+            modulation_type: LrFhssModulationType::Gmsk488,
+            coding_rate: match params.coding_rate {
+                radio::LrFhssCodingRate::_1_3 => LrFhssCodingRate::Cr1_3,
+                radio::LrFhssCodingRate::_2_3 => LrFhssCodingRate::Cr2_3,
+            },
+            grid: match params.grid {
+                radio::LrFhssGrid::_3_9KHz => LrFhssGrid::Grid3906Hz,
+                radio::LrFhssGrid::_25_4KHz => LrFhssGrid::Grid25391Hz,
+            },
+            enable_hopping: true,
+            bandwidth: match params.bandwidth {
+                radio::LrFhssBandwidth::_137KHz => LrFhssBandwidth::Bw136719Hz,
+                radio::LrFhssBandwidth::_336KHz => LrFhssBandwidth::Bw335938Hz,
+                radio::LrFhssBandwidth::_1523KHz => LrFhssBandwidth::Bw1523438Hz,
+            },
+            header_count: params.header_count(),
+        },
+        device_offset: 0,
+    }
+}
+
+/// Spread uplinks over the hop sequences without a random number generator, by hashing the
+/// frame, whose frame counter and MIC change with every uplink.
+fn hop_sequence_id(params: &LrFhssParams, frame: &[u8]) -> u16 {
+    let hash = frame.iter().fold(0u16, |hash, &b| hash.rotate_left(5) ^ b as u16);
+    hash % lr_fhss_get_hop_sequence_count(params)
+}
@@ -613,6 +613,8 @@ pub struct Config {
 pub struct Lr1110<SPI, IV> {
     intf: Lr1110SpiInterface<SPI, IV>,
     config: Config,
+    /// The LR-FHSS packet engine was enabled for a transmission and LoRa must be restored
+    lr_fhss: bool,
 }
 
 impl<SPI, IV> Lr1110<SPI, IV>
@@ -623,7 +625,11 @@ where
     /// Create an instance of the RadioKind implementation for the LR1110 chip
     pub fn new(spi: SPI, iv: IV, config: Config) -> Self {
         let intf = Lr1110SpiInterface::new(spi, iv);
-        Self { intf, config }
+        Self {
+            intf,
+            config,
+            lr_fhss: false,
+        }
     }
 
     // =========================================================================
@@ -1909,6 +1915,8 @@ where
     SPI: SpiDevice<u8>,
     IV: InterfaceVariant,
 {
+    const SUPPORTS_LR_FHSS: bool = true;
+
     async fn init_lora(&mut self, sync_word: u16) -> Result<(), RadioError> {
         // Initialize system (DC-DC, TCXO, calibration)
         self.init_system().await?;
@@ -2137,6 +2145,14 @@ where
     }
 
     async fn set_modulation_params(&mut self, mdltn_params: &ModulationParams) -> Result<(), RadioError> {
+        // Restore the LoRa packet engine after an LR-FHSS transmission
+        if self.lr_fhss {
+            let opcode = RadioOpCode::SetPktType.bytes();
+            let cmd = [opcode[0], opcode[1], PacketType::LoRa.value()];
+            self.write_command(&cmd).await?;
+            self.lr_fhss = false;
+        }
+
         let spreading_factor_val = spreading_factor_value(mdltn_params.spreading_factor)?;
         let bandwidth_val = bandwidth_value(mdltn_params.bandwidth)?;
         let coding_rate_val = coding_rate_value(mdltn_params.coding_rate)?;
@@ -2264,6 +2280,20 @@ where
         self.write_command(&cmd).await
     }
 
+    async fn prepare_lr_fhss_tx(
+        &mut self,
+        params: &LrFhssParams,
+        hop_sequence_id: u16,
+        frequency_in_hz: u32,
+        payload: &[u8],
+    ) -> Result<(), RadioError> {
+        // Same sequence as the SWDM001 LR-FHSS demo: packet type, frequency, then the frame
+        self.lr_fhss_init().await?;
+        self.lr_fhss = true;
+        self.set_channel(frequency_in_hz).await?;
+        self.lr_fhss_build_frame(params, hop_sequence_id, payload).await
+    }
+
     async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError> {
         self.intf.iv.enable_rf_switch_rx().await?;
 
@@ -890,6 +890,70 @@ async fn test_lr_fhss_build_frame() {
     assert_eq!(radio.intf.spi, reference_radio.inner);
 }
 
+#[tokio::test]
+async fn test_prepare_lr_fhss_tx() {
+    use crate::lr1110::{
+        LR_FHSS_DEFAULT_SYNC_WORD, LrFhssBandwidth, LrFhssCodingRate, LrFhssGrid, LrFhssModulationType, LrFhssParams,
+        LrFhssV1Params,
+    };
+    let payload = [0x40, 0x01, 0x02, 0x03, 0x04, 0x80, 0x07, 0x00];
+
+    // LR-FHSS packet engine, frequency and frame, then LoRa again before the RX windows
+    let mut reference_radio = reference();
+    reference_radio.lr_fhss_init();
+    reference_radio.set_rf_freq(868_100_000);
+    reference_radio.lr_fhss_build_frame(
+        &sys::lr11xx_lr_fhss_params_t {
+            lr_fhss_params: sys::lr_fhss_v1_params_t {
+                sync_word: LR_FHSS_DEFAULT_SYNC_WORD.as_ptr(),
+                modulation_type: sys::lr_fhss_v1_modulation_type_e_LR_FHSS_V1_MODULATION_TYPE_GMSK_488,
+                cr: sys::lr_fhss_v1_cr_e_LR_FHSS_V1_CR_1_3,
+                grid: sys::lr_fhss_v1_grid_e_LR_FHSS_V1_GRID_3906_HZ,
+                bw: sys::lr_fhss_v1_bw_e_LR_FHSS_V1_BW_136719_HZ,
+                enable_hopping: true,
+                header_count: 3,
+            },
+            device_offset: 0,
+        },
+        7,
+        &payload,
+    );
+    reference_radio.set_pkt_type(sys::lr11xx_radio_pkt_type_t_LR11XX_RADIO_PKT_TYPE_LORA);
+    reference_radio.set_lora_mod_params(&sys::lr11xx_radio_mod_params_lora_t {
+        sf: sys::lr11xx_radio_lora_sf_t_LR11XX_RADIO_LORA_SF11,
+        bw: sys::lr11xx_radio_lora_bw_t_LR11XX_RADIO_LORA_BW_125,
+        cr: sys::lr11xx_radio_lora_cr_t_LR11XX_RADIO_LORA_CR_4_5,
+        ldro: 1,
+    });
+
+    let mut radio = get_lr1110();
+    radio
+        .prepare_lr_fhss_tx(
+            &LrFhssParams {
+                lr_fhss_params: LrFhssV1Params {
+                    sync_word: LR_FHSS_DEFAULT_SYNC_WORD,
+                    modulation_type: LrFhssModulationType::Gmsk488,
+                    coding_rate: LrFhssCodingRate::Cr1_3,
+                    grid: LrFhssGrid::Grid3906Hz,
+                    enable_hopping: true,
+                    bandwidth: LrFhssBandwidth::Bw136719Hz,
+                    header_count: 3,
+                },
+                device_offset: 0,
+            },
+            7,
+            868_100_000,
+            &payload,
+        )
+        .await
+        .unwrap();
+    let params = radio
+        .create_modulation_params(SpreadingFactor::_11, Bandwidth::_125KHz, CodingRate::_4_5, 868_100_000)
+        .unwrap();
+    radio.set_modulation_params(&params).await.unwrap();
+    assert_eq!(radio.intf.spi, reference_radio.inner);
+}
+
 #[tokio::test]
 async fn test_regmem() {
     let words = [0xDEAD_BEEF_u32, 0xCAFE_BABE];
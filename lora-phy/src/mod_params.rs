@@ -30,6 +30,7 @@ pub enum RadioError {
     ReceiveTimeout,
     DutyCycleUnsupported,
     RngUnsupported,
+    ModulationUnsupported,
 }
 
 /// Status for a received packet
@@ -1,5 +1,6 @@
 use embedded_hal_async::delay::DelayNs;
 
+use crate::lr1110::LrFhssParams;
 use crate::mod_params::*;
 
 /// Functions implemented for an embedded framework for an MCU/LoRa chip combination
@@ -33,6 +34,8 @@ pub enum IrqState {
 /// LoRa physical layer API
 #[allow(async_fn_in_trait)]
 pub trait RadioKind {
+    /// Whether the LoRa chip transmits LR-FHSS, see [`RadioKind::prepare_lr_fhss_tx`]
+    const SUPPORTS_LR_FHSS: bool = false;
     /// Initialize lora radio
     ///
     /// The sync word is given in the 16-bit sx126x register form; the legacy
@@ -92,6 +95,17 @@ pub trait RadioKind {
     async fn set_payload(&mut self, payload: &[u8]) -> Result<(), RadioError>;
     /// Perform a transmit operation
     async fn do_tx(&mut self) -> Result<(), RadioError>;
+    /// Set up an LR-FHSS frame of `payload` on `frequency_in_hz`, hopping with `hop_sequence_id`,
+    /// for a subsequent send operation. Only implemented by LoRa chips supporting LR-FHSS.
+    async fn prepare_lr_fhss_tx(
+        &mut self,
+        _params: &LrFhssParams,
+        _hop_sequence_id: u16,
+        _frequency_in_hz: u32,
+        _payload: &[u8],
+    ) -> Result<(), RadioError> {
+        Err(RadioError::ModulationUnsupported)
+    }
     /// Set up to perform a receive operation (single-shot, continuous, or duty cycle)
     async fn do_rx(&mut self, rx_mode: RxMode) -> Result<(), RadioError>;
     /// Get an available packet made available as the result of a receive operation
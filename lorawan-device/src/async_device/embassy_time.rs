@@ -31,4 +31,8 @@ impl Timer for EmbassyTimer {
     async fn delay_ms(&mut self, millis: u64) {
         embassy_time::Timer::after_millis(millis).await
     }
+
+    fn now_ms(&self) -> u64 {
+        Instant::now().as_millis()
+    }
 }
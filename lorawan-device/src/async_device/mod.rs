@@ -2,7 +2,7 @@
 //! allowing for asynchronous radio implementations. Requires the `async` feature.
 use super::mac::{self, FcntDown, Frame, Mac, Window};
 pub use super::{
-    Downlink, JoinMode,
+    DevNonce, Downlink, JoinMode, JoinNonce,
     mac::{NetworkCredentials, SendData, Session},
     region::{self, Region},
 };
@@ -17,6 +17,10 @@ use crate::{
 
 pub mod radio;
 
+mod queue;
+pub use queue::UplinkPriority;
+use queue::UplinkQueue;
+
 #[cfg(feature = "embassy-time")]
 mod embassy_time;
 #[cfg(feature = "embassy-time")]
@@ -56,12 +60,13 @@ use self::radio::RxStatus;
 ///   providing a random seed
 /// - N: The size of the radio buffer. Generally, this should be set to 256 to support the largest possible LoRa frames.
 /// - D: The amount of downlinks that may be buffered. This is used to support Class C operation. See below for more.
+/// - U: The amount of uplinks that may be queued with [`queue_uplink`](Device::queue_uplink).
 ///
 /// Note that the const generics N and D are used to configure the size of the radio buffer and the number of downlinks
 /// that may be buffered. The defaults are 256 and 1 respectively which should be fine for Class A devices. **For Class
 /// C operation**, it is recommended to increase D to at least 2, if not 3. This is because during the RX1/RX2 windows
 /// after a Class A transmit, it is possible to receive Class C downlinks (in additional to any RX1/RX2 responses!).
-pub struct Device<R, T, G, const N: usize = 256, const D: usize = 1>
+pub struct Device<R, T, G, const N: usize = 256, const D: usize = 1, const U: usize = 1>
 where
     R: radio::PhyRxTx + Timings,
     T: radio::Timer,
@@ -74,8 +79,24 @@ where
     mac: Mac,
     radio_buffer: RadioBuffer<N>,
     downlink: Vec<Downlink, D>,
+    uplink_queue: UplinkQueue<U>,
     #[cfg(feature = "class-c")]
     class_c: bool,
+    #[cfg(feature = "class-b")]
+    class_b: ClassB,
+}
+
+/// Device-side timing of Class B operation.
+#[cfg(feature = "class-b")]
+#[derive(Default)]
+struct ClassB {
+    /// The timer was reset at the end of the last received beacon, so ping slots and the next
+    /// beacon can be scheduled from it. Any uplink resets the timer and clears this.
+    synced: bool,
+    /// Time on air of the last received beacon.
+    beacon_toa_ms: u32,
+    /// Beacon periods spent searching, to follow beacons hopping over channels.
+    search_periods: u32,
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -83,6 +104,8 @@ where
 pub enum Error<R> {
     Radio(R),
     Mac(mac::Error),
+    /// The uplink queue is full.
+    UplinkQueueFull,
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -110,6 +133,18 @@ pub enum ListenResponse {
     DownlinkReceived(FcntDown),
     #[cfg(feature = "multicast")]
     Multicast(MulticastResponse),
+    /// A beacon was received, `time` is its GPS time in seconds.
+    #[cfg(feature = "class-b")]
+    BeaconLocked {
+        time: u32,
+    },
+    /// The expected beacon was not received, ping slots keep being opened with widened windows.
+    #[cfg(feature = "class-b")]
+    BeaconMissed,
+    /// No beacon was received for two hours, ping slots are no longer opened until a beacon is
+    /// received again.
+    #[cfg(feature = "class-b")]
+    BeaconLost,
 }
 
 #[cfg(feature = "multicast")]
@@ -166,7 +201,7 @@ where
     }
 }
 
-impl<R, T, G, const N: usize, const D: usize> Device<R, T, G, N, D>
+impl<R, T, G, const N: usize, const D: usize, const U: usize> Device<R, T, G, N, D, U>
 where
     R: radio::PhyRxTx + Timings,
     T: radio::Timer,
@@ -183,12 +218,13 @@ where
 
     /// Create a new [`Device`] and provide an optional [`Session`].
     pub fn new_with_session(
-        region: region::Configuration,
+        mut region: region::Configuration,
         radio: R,
         timer: T,
         rng: G,
         session: Option<Session>,
     ) -> Self {
+        region.set_modulations(R::MODULATIONS);
         let mut mac = Mac::new(region, R::MAX_RADIO_POWER, R::ANTENNA_GAIN);
         if let Some(session) = session {
             mac.set_session(session);
@@ -200,8 +236,11 @@ where
             radio_buffer: RadioBuffer::new(),
             timer,
             downlink: Vec::new(),
+            uplink_queue: UplinkQueue::new(),
             #[cfg(feature = "class-c")]
             class_c: false,
+            #[cfg(feature = "class-b")]
+            class_b: ClassB::default(),
         }
     }
 
@@ -212,6 +251,24 @@ where
         self.class_c = true;
     }
 
+    /// Enables Class B behavior with a ping slot every `2^periodicity` seconds (`0..=7`).
+    ///
+    /// The PingSlotInfoReq is sent with the next uplink and ping slots are opened by
+    /// [`class_b_listen`](Device::class_b_listen) once the network answered it and a beacon was
+    /// received.
+    #[cfg(feature = "class-b")]
+    pub fn enable_class_b(&mut self, periodicity: u8) -> Result<(), Error<R::PhyError>> {
+        Ok(self.mac.enable_class_b(periodicity)?)
+    }
+
+    /// Disables Class B behavior. Uplinks no longer signal Class B, which lets the network know
+    /// that ping slots are closed.
+    #[cfg(feature = "class-b")]
+    pub fn disable_class_b(&mut self) {
+        self.mac.disable_class_b();
+        self.class_b = ClassB::default();
+    }
+
     /// Sets the port range for frames sent to multicast groups. Warning: this exclusively handles
     /// these frames in the multicast context and, therefore, unicast frames in this range will not
     /// be handled. Defaults to `201..=205`.
@@ -279,6 +336,58 @@ where
         self.mac.get_session()
     }
 
+    /// JoinNonce of the last accepted LoRaWAN 1.1 JoinAccept. Store it in non-volatile memory
+    /// and restore it with [`Device::set_join_nonce`], so replayed JoinAccepts are rejected
+    /// across reboots.
+    pub fn get_join_nonce(&self) -> Option<JoinNonce> {
+        self.mac.join_nonce()
+    }
+
+    /// Restore the JoinNonce of the last accepted LoRaWAN 1.1 JoinAccept. A value older than
+    /// the one already known is ignored.
+    pub fn set_join_nonce(&mut self, join_nonce: JoinNonce) {
+        self.mac.set_join_nonce(join_nonce)
+    }
+
+    /// DevNonce of the last join request. With the default [`mac::DevNonceStrategy::Counter`],
+    /// store it in non-volatile memory (or use [`Self::snapshot_state`]) and restore it with
+    /// [`Device::set_dev_nonce`], so join requests are not rejected as replays after a reboot.
+    pub fn get_dev_nonce(&self) -> Option<DevNonce> {
+        self.mac.dev_nonce()
+    }
+
+    /// Restore the DevNonce of the last join request. A value older than the one already known is
+    /// ignored.
+    pub fn set_dev_nonce(&mut self, dev_nonce: DevNonce) {
+        self.mac.set_dev_nonce(dev_nonce)
+    }
+
+    /// Set how the DevNonce of join requests is chosen, a counter by default.
+    pub fn set_dev_nonce_strategy(&mut self, strategy: mac::DevNonceStrategy) {
+        self.mac.configuration.dev_nonce_strategy = strategy;
+    }
+
+    /// Snapshot of the session, the configuration and channel plan set by the network, and the
+    /// multicast sessions, to be kept in non-volatile memory. It fits in
+    /// [`DeviceState::MAX_LEN`](mac::DeviceState::MAX_LEN) bytes.
+    pub fn snapshot_state(&self) -> mac::DeviceState {
+        self.mac.snapshot_state()
+    }
+
+    /// Restore a snapshot taken with [`Self::snapshot_state`], eg: after a reboot instead of
+    /// joining again. The device must be configured for the same region.
+    pub fn restore_state(&mut self, state: &mac::DeviceState) -> Result<(), mac::StateError> {
+        self.mac.restore_state(state)
+    }
+
+    /// Whether the state saved by [`Self::snapshot_state`] changed since the last call, ie: a new
+    /// snapshot should be written. Joins, uplinks (which move the frame counter) and downlinks
+    /// (which may carry MAC commands) raise it; settings changed by the application, eg:
+    /// [`Self::set_datarate`], do not.
+    pub fn take_state_dirty(&mut self) -> bool {
+        self.mac.take_state_dirty()
+    }
+
     pub fn get_region(&mut self) -> &region::Configuration {
         &self.mac.region
     }
@@ -321,6 +430,104 @@ where
         }
     }
 
+    /// Ask the network for its time with DeviceTimeReq on the next uplink. Once answered, the
+    /// [`DeviceClock`](mac::DeviceClock) is synchronized, see [`Self::gps_time_ms`].
+    pub fn request_device_time(&mut self) -> Result<(), Error<R::PhyError>> {
+        Ok(self.mac.request_device_time()?)
+    }
+
+    /// The device clock, synchronized by DeviceTimeAns, in the time base of `Timer::now_ms`.
+    pub fn get_clock(&self) -> &mac::DeviceClock {
+        self.mac.clock()
+    }
+
+    /// Set the battery level reported when the network asks for the device status with
+    /// DevStatusReq. It is [`mac::BatteryLevel::Unknown`] until set, and should be updated as the
+    /// application measures it.
+    pub fn set_battery_level(&mut self, level: mac::BatteryLevel) {
+        self.mac.configuration.battery_level = level;
+    }
+
+    /// Largest application payload which `send` accepts for the next uplink, given the current
+    /// data rate, the dwell time, and the MAC commands which go along in FOpts. Larger payloads
+    /// fail with [`mac::Error::PayloadTooLarge`]. It is 0 when the device is not joined.
+    pub fn max_payload_len(&self) -> usize {
+        self.mac.max_payload_len()
+    }
+
+    /// Limit uplinks to the payload sizes which LoRaWAN repeaters can relay. Disabled by default.
+    pub fn set_repeater_compatible(&mut self, enabled: bool) {
+        self.mac.configuration.repeater_compatible = enabled;
+    }
+
+    /// Ask the network for a link check with LinkCheckReq on the next uplink. The answer is
+    /// available with [`Self::take_link_status`].
+    pub fn request_link_check(&mut self) -> Result<(), Error<R::PhyError>> {
+        Ok(self.mac.request_link_check()?)
+    }
+
+    /// Take the answer to the last link check, if it was received.
+    pub fn take_link_status(&mut self) -> Option<mac::LinkStatus> {
+        self.mac.take_link_status()
+    }
+
+    /// Fails with `mac::Error::MacCommandsDropped` if answers to MAC commands were dropped since
+    /// the last call, as the room for pending MAC commands was used up, typically by a large burst
+    /// of MAC commands in a downlink. The response to the uplink which received that downlink is
+    /// not affected.
+    pub fn check_dropped_mac_commands(&mut self) -> Result<(), Error<R::PhyError>> {
+        Ok(self.mac.check_dropped_mac_commands()?)
+    }
+
+    /// Take the oldest change of the MAC configuration by the network which was not taken yet.
+    /// Up to 8 events are kept, the oldest ones are dropped first.
+    pub fn take_mac_event(&mut self) -> Option<mac::MacEvent> {
+        self.mac.take_mac_event()
+    }
+
+    /// Uplink statistics of the channel on `frequency`, for the last 16 channels used.
+    #[cfg(feature = "channel-stats")]
+    pub fn channel_stats(&self, frequency: u32) -> Option<region::ChannelStats> {
+        self.mac.channel_stats(frequency)
+    }
+
+    /// Leave channels which failed repeatedly out of the channel selection for a while, as long as
+    /// other channels enabled by the network are available. Disabled with `None`, the default.
+    #[cfg(feature = "channel-stats")]
+    pub fn set_channel_avoidance(&mut self, avoidance: Option<region::ChannelAvoidance>) {
+        self.mac.set_channel_avoidance(avoidance)
+    }
+
+    /// Forget the statistics of all channels, which ends their avoidance.
+    #[cfg(feature = "channel-stats")]
+    pub fn clear_channel_stats(&mut self) {
+        self.mac.clear_channel_stats()
+    }
+
+    /// Milliseconds since the GPS epoch, once the device clock is synchronized.
+    pub fn gps_time_ms(&self) -> Option<u64> {
+        self.mac.clock().gps_time_ms(self.timer.now_ms())
+    }
+
+    /// Milliseconds since the Unix epoch (UTC), once the device clock is synchronized.
+    pub fn unix_time_ms(&self) -> Option<u64> {
+        self.mac.clock().unix_time_ms(self.timer.now_ms())
+    }
+
+    /// Set how confirmed uplinks which are not acknowledged are retransmitted. By default they
+    /// are not, and `send` returns [`SendResponse::NoAck`] after the first transmission.
+    pub fn set_retransmission_policy(&mut self, policy: mac::RetransmissionPolicy) {
+        self.mac.configuration.retransmission = policy;
+    }
+
+    /// Milliseconds until the regulatory duty cycle, and the aggregated duty cycle set by the
+    /// network with DutyCycleReq, allow the next uplink (a join request while not joined).
+    ///
+    /// While it is not 0, `send` and `join` fail with [`mac::Error::DutyCycleRestricted`].
+    pub fn time_until_next_tx(&self) -> u64 {
+        self.mac.time_until_next_tx(self.timer.now_ms())
+    }
+
     /// Join the LoRaWAN network asynchronously. The returned future completes when
     /// the LoRaWAN network has been joined successfully, or an error has occurred.
     ///
@@ -329,30 +536,136 @@ where
     /// Note that for a Class C enabled device, you must repeatedly send *confirmed* uplink until
     /// LoRaWAN Network Server (LNS) confirmation after joining.
     pub async fn join(&mut self, join_mode: &JoinMode) -> Result<JoinResponse, Error<R::PhyError>> {
-        match join_mode {
+        let credentials = match join_mode {
             JoinMode::OTAA { deveui, appeui, appkey } => {
-                let (tx_config, rx_windows, _) = self.mac.join_otaa::<G, N>(
-                    &mut self.rng,
-                    NetworkCredentials::new(*appeui, *deveui, *appkey),
-                    &mut self.radio_buffer,
-                );
-
-                // Transmit the join payload
-                let ms = self
-                    .radio
-                    .tx(tx_config, self.radio_buffer.as_ref_for_read())
-                    .await
-                    .map_err(Error::Radio)?;
-
-                // Receive join response within RX window
-                self.timer.reset();
-                Ok(self.rx_downlink(&Frame::Join, ms, &rx_windows).await?.into())
+                NetworkCredentials::new(*appeui, *deveui, *appkey)
+            }
+            JoinMode::OTAA1_1 { deveui, joineui, nwkkey, appkey } => {
+                NetworkCredentials::new_1_1(*joineui, *deveui, *nwkkey, *appkey)
             }
             JoinMode::ABP { nwkskey, appskey, devaddr } => {
                 self.mac.join_abp(*nwkskey, *appskey, *devaddr);
-                Ok(JoinResponse::JoinSuccess)
+                return Ok(JoinResponse::JoinSuccess);
+            }
+        };
+        let (mut tx_config, mut rx_windows, _) = self.mac.join_otaa::<G, N>(
+            &mut self.rng,
+            credentials,
+            &mut self.radio_buffer,
+            Some(self.timer.now_ms()),
+        )?;
+
+        // Transmit the join payload
+        let ms = Self::transmit(
+            &mut self.radio,
+            &mut self.mac,
+            &mut self.rng,
+            &mut self.radio_buffer,
+            self.timer.now_ms(),
+            &Frame::Join,
+            &mut tx_config,
+            Some(&mut rx_windows),
+        )
+        .await?;
+
+        // Receive join response within RX window
+        self.timer.reset();
+        #[cfg(feature = "class-b")]
+        {
+            self.class_b.synced = false;
+        }
+        Ok(self.rx_downlink(&Frame::Join, ms, &rx_windows).await?.into())
+    }
+
+    /// Join the network with OTAA, sending join requests until one is accepted or
+    /// `policy.max_attempts` were sent.
+    ///
+    /// The first join request is sent after a random delay, the next ones once the join duty
+    /// cycle (36 s of airtime per hour during the first hour, 36 s per 10 hours during the next
+    /// 10 hours, then 8.7 s per 24 hours) and the regulatory duty cycle allow it, plus a random
+    /// delay. The first one also waits for these duty cycles, which join requests of a previous
+    /// call may still hold. Successive join requests rotate through the data rates, and in US915 and AU915
+    /// through the subbands (after the join bias, if set). The configured data rate is restored
+    /// afterwards.
+    pub async fn join_with_retries(
+        &mut self,
+        join_mode: &JoinMode,
+        policy: &mac::JoinRetryPolicy,
+    ) -> Result<JoinResponse, Error<R::PhyError>> {
+        if let JoinMode::ABP { .. } = join_mode {
+            return self.join(join_mode).await;
+        }
+        let datarate = self.mac.configuration.data_rate;
+        let mut delay_ms =
+            self.mac.join_initial_delay_ms(&mut self.rng, policy, self.timer.now_ms());
+        let mut attempt = 0;
+        let response = loop {
+            if delay_ms > 0 {
+                self.timer.delay_ms(delay_ms).await;
+            }
+            if policy.rotate_datarate {
+                self.mac.configuration.data_rate = self.mac.join_datarate(datarate, attempt);
+            }
+            let response = self.join(join_mode).await;
+            attempt += 1;
+            match response {
+                Ok(JoinResponse::NoJoinAccept)
+                    if policy.max_attempts.is_none_or(|max| attempt < max) => {}
+                response => break response,
             }
+            delay_ms = self.mac.join_retry_delay_ms(&mut self.rng, self.timer.now_ms());
+        };
+        self.mac.configuration.data_rate = datarate;
+        response
+    }
+
+    /// Set the credentials securing rejoin requests, needed when the session was restored with
+    /// [`Self::restore_state`] instead of joined by this device with LoRaWAN 1.1 OTAA.
+    pub fn set_rejoin_credentials(&mut self, credentials: NetworkCredentials) {
+        self.mac.set_rejoin_credentials(credentials);
+    }
+
+    /// Rejoin request which is due: asked for by the network with ForceRejoinReq, or periodic
+    /// once enabled with RejoinParamSetupReq. The application sends it with [`Self::rejoin`]
+    /// between its uplinks.
+    pub fn pending_rejoin(&mut self) -> Option<mac::RejoinType> {
+        self.mac.pending_rejoin(Some(self.timer.now_ms()))
+    }
+
+    /// Send a rejoin request of a LoRaWAN 1.1 device and wait for the JoinAccept. On
+    /// [`JoinResponse::JoinSuccess`] the device switches to the new session, downlinks not taken
+    /// yet are kept; otherwise the current session goes on.
+    pub async fn rejoin(
+        &mut self,
+        rejoin_type: mac::RejoinType,
+    ) -> Result<JoinResponse, Error<R::PhyError>> {
+        let (mut tx_config, mut rx_windows, _) = self.mac.rejoin::<G, N>(
+            &mut self.rng,
+            rejoin_type,
+            &mut self.radio_buffer,
+            Some(self.timer.now_ms()),
+        )?;
+
+        let ms = Self::transmit(
+            &mut self.radio,
+            &mut self.mac,
+            &mut self.rng,
+            &mut self.radio_buffer,
+            self.timer.now_ms(),
+            &Frame::Data,
+            &mut tx_config,
+            Some(&mut rx_windows),
+        )
+        .await?;
+        self.mac.tx_done(self.timer.now_ms());
+
+        // The JoinAccept is expected in the join receive windows
+        self.timer.reset();
+        #[cfg(feature = "class-b")]
+        {
+            self.class_b.synced = false;
         }
+        Ok(self.rx_downlink(&Frame::Join, ms, &rx_windows).await?.into())
     }
 
     /// Send data on a given port with the expected confirmation. If downlink data is provided, the
@@ -372,21 +685,167 @@ where
         confirmed: bool,
     ) -> Result<SendResponse, Error<R::PhyError>> {
         // Prepare transmission buffer
-        let (tx_config, rx_windows, _fcnt_up) = self.mac.send::<G, N>(
+        let prepared = self.mac.send::<G, N>(
             &mut self.rng,
             &mut self.radio_buffer,
             &SendData { data, fport, confirmed },
+            Some(self.timer.now_ms()),
         )?;
-        // Transmit our data packet
-        let ms = self
-            .radio
-            .tx(tx_config, self.radio_buffer.as_ref_for_read())
-            .await
-            .map_err(Error::Radio)?;
+        Ok(self.transmit_uplink(prepared).await?.into())
+    }
 
-        // Wait for received data within window
-        self.timer.reset();
-        Ok(self.rx_downlink(&Frame::Data, ms, &rx_windows).await?.into())
+    /// Queue an uplink, sent by [`Self::send_queued`] after the queued uplinks of the same or
+    /// higher priority. Fails with [`Error::UplinkQueueFull`] when `U` uplinks are queued already.
+    pub fn queue_uplink(
+        &mut self,
+        data: &[u8],
+        fport: u8,
+        confirmed: bool,
+        priority: UplinkPriority,
+    ) -> Result<(), Error<R::PhyError>> {
+        match self.uplink_queue.push(data, fport, confirmed, priority) {
+            true => Ok(()),
+            false => Err(Error::UplinkQueueFull),
+        }
+    }
+
+    /// Number of uplinks waiting in the queue.
+    pub fn queued_uplinks(&self) -> usize {
+        self.uplink_queue.len()
+    }
+
+    /// Drop all the queued uplinks.
+    pub fn clear_uplink_queue(&mut self) {
+        self.uplink_queue.clear();
+    }
+
+    /// Send the next uplink: the queued uplink of the highest priority, or when none is queued,
+    /// an uplink without FPort carrying the MAC answers the network waits for (RXParamSetupAns,
+    /// DlChannelAns, RXTimingSetupAns) or ADRACKReq, if an uplink is due for them. Returns `None`
+    /// when there is nothing to send.
+    ///
+    /// A queued uplink which cannot be sent (eg: because of the duty cycle) stays first in the
+    /// queue.
+    pub async fn send_queued(&mut self) -> Result<Option<SendResponse>, Error<R::PhyError>> {
+        if let Some(uplink) = self.uplink_queue.pop() {
+            let prepared = self.mac.send::<G, N>(
+                &mut self.rng,
+                &mut self.radio_buffer,
+                &SendData { data: &uplink.data, fport: uplink.fport, confirmed: uplink.confirmed },
+                Some(self.timer.now_ms()),
+            );
+            let prepared = match prepared {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    self.uplink_queue.requeue(uplink);
+                    return Err(e.into());
+                }
+            };
+            return Ok(Some(self.transmit_uplink(prepared).await?.into()));
+        }
+        if self.mac.mac_uplink_due() {
+            return Ok(Some(self.send_mac_uplink().await?.into()));
+        }
+        Ok(None)
+    }
+
+    async fn send_mac_uplink(&mut self) -> Result<mac::Response, Error<R::PhyError>> {
+        let prepared = self.mac.send_mac_uplink::<G, N>(
+            &mut self.rng,
+            &mut self.radio_buffer,
+            Some(self.timer.now_ms()),
+        )?;
+        self.transmit_uplink(prepared).await
+    }
+
+    /// Transmit the uplink prepared in the radio buffer and receive in its RX windows, repeating
+    /// it as set by NbTrans or the retransmission policy.
+    async fn transmit_uplink(
+        &mut self,
+        prepared: (radio::TxConfig, mac::RxWindows, mac::FcntUp),
+    ) -> Result<mac::Response, Error<R::PhyError>> {
+        let (mut tx_config, mut rx_windows, _fcnt_up) = prepared;
+        loop {
+            // Transmit our data packet
+            let ms = Self::transmit(
+                &mut self.radio,
+                &mut self.mac,
+                &mut self.rng,
+                &mut self.radio_buffer,
+                self.timer.now_ms(),
+                &Frame::Data,
+                &mut tx_config,
+                Some(&mut rx_windows),
+            )
+            .await?;
+            self.mac.tx_done(self.timer.now_ms());
+
+            // Wait for received data within window
+            self.timer.reset();
+            #[cfg(feature = "class-b")]
+            {
+                self.class_b.synced = false;
+            }
+            if let Some(response) = self.rx_windows(&Frame::Data, ms, &rx_windows).await? {
+                // Downlink data stays available with `take_downlink`.
+                return Ok(response);
+            }
+
+            // Repeat the uplink as requested by NbTrans, or retransmit an unacknowledged confirmed
+            // uplink after ACK_TIMEOUT, once the duty cycle allows.
+            let Some(delay_ms) = self.mac.repetition_delay_ms(&mut self.rng) else {
+                return Ok(self.mac.rx2_complete());
+            };
+            let wait_ms = self.mac.time_until_next_tx(self.timer.now_ms()).max(delay_ms.into());
+            if wait_ms > 0 {
+                self.timer.delay_ms(wait_ms).await;
+            }
+            match self.mac.repeat_send::<G, N>(
+                &mut self.rng,
+                &mut self.radio_buffer,
+                Some(self.timer.now_ms()),
+            ) {
+                Some(repetition) => (tx_config, rx_windows, _) = repetition?,
+                None => return Ok(self.mac.rx2_complete()),
+            }
+        }
+    }
+
+    /// Transmit the frame prepared in the radio buffer, after listen-before-talk if the region
+    /// requires it. A busy channel is given up for another one, for which the frame and its RX
+    /// windows are prepared again.
+    #[allow(clippy::too_many_arguments)]
+    async fn transmit(
+        radio: &mut R,
+        mac: &mut Mac,
+        rng: &mut G,
+        radio_buffer: &mut RadioBuffer<N>,
+        now_ms: u64,
+        frame: &Frame,
+        tx_config: &mut radio::TxConfig,
+        mut rx_windows: Option<&mut mac::RxWindows>,
+    ) -> Result<u32, Error<R::PhyError>> {
+        if let Some(lbt) = mac.get_listen_before_talk() {
+            while !radio
+                .channel_clear(tx_config.rf, lbt.threshold_dbm, lbt.duration_us)
+                .await
+                .map_err(Error::Radio)?
+            {
+                let (retry_config, retry_windows) =
+                    mac.channel_busy(rng, radio_buffer, frame, tx_config, Some(now_ms))?;
+                *tx_config = retry_config;
+                if let Some(rx_windows) = rx_windows.as_deref_mut() {
+                    *rx_windows = retry_windows;
+                }
+            }
+        }
+        radio.tx(*tx_config, radio_buffer.as_ref_for_read()).await.map_err(Error::Radio)
+    }
+
+    /// Transmissions used by the last `send`, including NbTrans repetitions and retransmissions
+    /// of a confirmed uplink.
+    pub fn send_attempts(&self) -> u8 {
+        self.mac.attempts()
     }
 
     /// Take the downlink data from the device. This is typically called after a
@@ -470,6 +929,9 @@ where
         debug!("Configuring RXC window with config {}.", rx_config);
         self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
         let mut response = None;
+        // The timer is busy with the window timeout, uplinks answering RXC frames are accounted at
+        // the start of the wait.
+        let now_ms = self.timer.now_ms();
         let timeout_fut = self.timer.at(duration.into());
         pin_mut!(timeout_fut);
         let mut maybe_timeout_fut = Some(timeout_fut);
@@ -500,6 +962,7 @@ where
                         &mut self.rng,
                         mac_response,
                         Some(rx_config),
+                        now_ms,
                     )
                     .await?
                     {
@@ -535,6 +998,19 @@ where
         window_delay: u32,
         rx_windows: &mac::RxWindows,
     ) -> Result<mac::Response, Error<R::PhyError>> {
+        match self.rx_windows(frame, window_delay, rx_windows).await? {
+            Some(response) => Ok(response),
+            None => Ok(self.mac.rx2_complete()),
+        }
+    }
+
+    /// Listen in the RX1 and RX2 windows, returns `None` if nothing was received.
+    async fn rx_windows(
+        &mut self,
+        frame: &Frame,
+        window_delay: u32,
+        rx_windows: &mac::RxWindows,
+    ) -> Result<Option<mac::Response>, Error<R::PhyError>> {
         self.radio_buffer.clear();
 
         let rx1_start_delay = self.mac.get_rx_delay(frame, &Window::_1) + window_delay
@@ -551,7 +1027,7 @@ where
 
         if let Some(response) = self.rx_listen(&rx_config.rf).await? {
             debug!("RX1 received {}", response);
-            return Ok(response);
+            return Ok(Some(response));
         }
 
         let rx2_start_delay = self.mac.get_rx_delay(frame, &Window::_2) + window_delay
@@ -567,10 +1043,10 @@ where
 
         if let Some(response) = self.rx_listen(&rx_config.rf).await? {
             debug!("RX2 received {}", response);
-            return Ok(response);
+            return Ok(Some(response));
         }
         debug!("RX2 did not receive anything.");
-        Ok(self.mac.rx2_complete())
+        Ok(None)
     }
 
     /// Helper function to handle MAC responses and perform common actions
@@ -582,6 +1058,7 @@ where
         rng: &mut G,
         response: mac::Response,
         rx_config: Option<RxConfig>,
+        now_ms: u64,
     ) -> Result<Option<mac::Response>, Error<R::PhyError>> {
         radio_buffer.clear();
         match response {
@@ -593,20 +1070,30 @@ where
             }
             #[cfg(feature = "certification")]
             mac::Response::UplinkPrepared => {
-                let (tx_config, _fcnt_up) =
-                    mac.certification_setup_send::<G, N>(rng, radio_buffer)?;
-                radio.tx(tx_config, radio_buffer.as_ref_for_read()).await.map_err(Error::Radio)?;
+                let (mut tx_config, _fcnt_up) =
+                    mac.certification_setup_send::<G, N>(rng, radio_buffer, Some(now_ms))?;
+                let frame = Frame::Data;
+                Self::transmit(radio, mac, rng, radio_buffer, now_ms, &frame, &mut tx_config, None)
+                    .await?;
                 Ok(Some(mac.rx2_complete()))
             }
             #[cfg(feature = "multicast")]
             mac::Response::Multicast(mut response) => {
                 if response.is_transmit_request() {
-                    let (tx_config, _fcnt_up) =
-                        mac.multicast_setup_send::<G, N>(rng, radio_buffer)?;
-                    radio
-                        .tx(tx_config, radio_buffer.as_ref_for_read())
-                        .await
-                        .map_err(Error::Radio)?;
+                    let (mut tx_config, _fcnt_up) =
+                        mac.multicast_setup_send::<G, N>(rng, radio_buffer, Some(now_ms))?;
+                    let frame = Frame::Data;
+                    Self::transmit(
+                        radio,
+                        mac,
+                        rng,
+                        radio_buffer,
+                        now_ms,
+                        &frame,
+                        &mut tx_config,
+                        None,
+                    )
+                    .await?;
                     if let Some(rx_config) = rx_config {
                         radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
                     }
@@ -636,7 +1123,7 @@ where
                     let mac_response = self.mac.handle_rx::<N, D>(
                         &mut self.radio_buffer,
                         &mut self.downlink,
-                        q.snr(),
+                        q,
                         rf_config,
                     );
                     Self::handle_mac_response(
@@ -646,6 +1133,7 @@ where
                         &mut self.rng,
                         mac_response,
                         None,
+                        self.timer.now_ms(),
                     )
                     .await?
                 }
@@ -659,6 +1147,17 @@ where
     /// listening to RXC frames. The caller is expected to be awaiting this message at all times.
     #[cfg(feature = "class-c")]
     pub async fn rxc_listen(&mut self) -> Result<ListenResponse, Error<R::PhyError>> {
+        // Answers to RXParamSetupReq and the like are sent as soon as the duty cycle allows rather
+        // than waiting for the next uplink of the application. Until then, or when every channel
+        // is busy, the device keeps listening and tries again on the next call.
+        if self.mac.mac_uplink_due() && self.mac.time_until_next_tx(self.timer.now_ms()) == 0 {
+            match self.send_mac_uplink().await {
+                Ok(mac::Response::RxComplete | mac::Response::NoAck)
+                | Err(Error::Mac(mac::Error::ChannelBusy)) => (),
+                Ok(response) => return Ok(response.into()),
+                Err(e) => return Err(e),
+            }
+        }
         let rx_config = self.mac.get_rxc_config();
         loop {
             let (sz, q) =
@@ -677,6 +1176,7 @@ where
                 &mut self.rng,
                 mac_response,
                 Some(rx_config),
+                self.timer.now_ms(),
             )
             .await?
             {
@@ -686,6 +1186,170 @@ where
     }
 }
 
+#[cfg(feature = "class-b")]
+impl<R, T, G, const N: usize, const D: usize, const U: usize> Device<R, T, G, N, D, U>
+where
+    R: radio::PhyRxTx + Timings,
+    T: radio::Timer,
+    G: RngCore,
+{
+    /// Tracks the Class B beacon and listens during ping slots. The returned future completes on
+    /// each beacon (received, missed or lost) and on each ping-slot downlink, so the caller is
+    /// expected to await it in a loop while not sending uplinks.
+    ///
+    /// Until a beacon is received, the receiver is kept open for a whole beacon period at a time.
+    /// As any uplink resets the timer used to follow the beacon, the beacon is searched again
+    /// afterwards.
+    pub async fn class_b_listen(&mut self) -> Result<ListenResponse, Error<R::PhyError>> {
+        use mac::class_b::{self, BEACON_PERIOD_MS, Beacon};
+
+        loop {
+            let beacon = self.mac.configuration.class_b.beacon;
+            let Beacon::Locked { time, missed, next_slot } = beacon else {
+                // Without a beacon yet, the channel of beacons hopping over channels is unknown.
+                let beacon_time =
+                    self.class_b.search_periods.wrapping_mul(lorawan::beacon::BEACON_PERIOD);
+                if let Some(response) = self.beacon_search(beacon_time).await? {
+                    return Ok(response);
+                }
+                continue;
+            };
+            if !self.class_b.synced {
+                let beacon_time = time.wrapping_add(lorawan::beacon::BEACON_PERIOD);
+                if let Some(response) = self.beacon_search(beacon_time).await? {
+                    return Ok(response);
+                }
+                continue;
+            }
+
+            let widening = beacon.window_widening_ms();
+            let lead_time = self.radio.get_rx_window_lead_time_ms() + widening;
+            let window_ms = self.radio.get_rx_window_buffer() + 2 * widening;
+            // The timer was reset at the end of the last received beacon, `missed` periods ago.
+            let period_start = missed as u64 * BEACON_PERIOD_MS as u64;
+            let at = |ms: u32| (period_start + ms as u64).saturating_sub(lead_time as u64);
+            let toa = self.class_b.beacon_toa_ms;
+
+            if let Some((rx_config, offset)) = self.mac.get_ping_slot_rx_config(time, window_ms) {
+                // Ping slots are only opened once the periodicity was acknowledged.
+                let periodicity = self.mac.configuration.class_b.periodicity.unwrap();
+                if next_slot < class_b::ping_nb(periodicity) {
+                    self.mac.configuration.class_b.beacon =
+                        Beacon::Locked { time, missed, next_slot: next_slot + 1 };
+                    let start = class_b::ping_slot_start_ms(offset, periodicity, next_slot);
+                    self.radio.low_power().await.map_err(Error::Radio)?;
+                    self.timer.at(at(start.saturating_sub(toa))).await;
+                    debug!("Configuring ping slot with config {}.", rx_config);
+                    self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
+                    if let Some(response) = self.ping_slot_listen(&rx_config.rf).await? {
+                        return Ok(response.into());
+                    }
+                    continue;
+                }
+            }
+
+            // Beacon opening the next period
+            let beacon_time = time.wrapping_add(lorawan::beacon::BEACON_PERIOD);
+            let rx_config = self.mac.get_beacon_rx_config(beacon_time, window_ms);
+            self.radio.low_power().await.map_err(Error::Radio)?;
+            self.timer.at(at(BEACON_PERIOD_MS - toa)).await;
+            debug!("Configuring beacon window with config {}.", rx_config);
+            self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
+            if let Some(response) = self.beacon_rx(&rx_config).await? {
+                return Ok(response);
+            }
+            return Ok(if self.mac.configuration.class_b.beacon.miss() {
+                ListenResponse::BeaconMissed
+            } else {
+                self.class_b = ClassB::default();
+                ListenResponse::BeaconLost
+            });
+        }
+    }
+
+    /// Keep the receiver open for a beacon period on the channel of the beacon at `beacon_time`.
+    async fn beacon_search(
+        &mut self,
+        beacon_time: u32,
+    ) -> Result<Option<ListenResponse>, Error<R::PhyError>> {
+        let rx_config = self.mac.get_beacon_rx_config(beacon_time, mac::class_b::BEACON_PERIOD_MS);
+        debug!("Searching beacon with config {}.", rx_config);
+        self.radio.setup_rx(rx_config).await.map_err(Error::Radio)?;
+        if let Some(response) = self.beacon_rx(&rx_config).await? {
+            return Ok(Some(response));
+        }
+        self.class_b.search_periods = self.class_b.search_periods.wrapping_add(1);
+        let class_b = &mut self.mac.configuration.class_b;
+        if matches!(class_b.beacon, mac::class_b::Beacon::Locked { .. }) {
+            return Ok(Some(if class_b.beacon.miss() {
+                ListenResponse::BeaconMissed
+            } else {
+                self.class_b = ClassB::default();
+                ListenResponse::BeaconLost
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Receive a beacon and synchronize on it. Returns `None` if nothing valid was received.
+    async fn beacon_rx(
+        &mut self,
+        rx_config: &RxConfig,
+    ) -> Result<Option<ListenResponse>, Error<R::PhyError>> {
+        let status =
+            self.radio.rx_single(self.radio_buffer.as_mut()).await.map_err(Error::Radio)?;
+        self.radio.low_power().await.map_err(Error::Radio)?;
+        let RxStatus::Rx(sz, _) = status else {
+            return Ok(None);
+        };
+        self.radio_buffer.set_pos(sz);
+        let time = self.mac.handle_beacon(&self.radio_buffer);
+        self.radio_buffer.clear();
+        let Some(time) = time else {
+            return Ok(None);
+        };
+        // Periods are scheduled from the end of the beacon, as it was the last timer reset.
+        self.timer.reset();
+        let len = self.mac.region.get_beacon_layout().frame_len() as u8;
+        self.class_b = ClassB {
+            synced: true,
+            beacon_toa_ms: rx_config.rf.bb.time_on_air_us(Some(10), false, len) / 1000,
+            search_periods: 0,
+        };
+        self.mac.configuration.class_b.beacon.lock(time);
+        Ok(Some(ListenResponse::BeaconLocked { time }))
+    }
+
+    async fn ping_slot_listen(
+        &mut self,
+        rf_config: &RfConfig,
+    ) -> Result<Option<mac::Response>, Error<R::PhyError>> {
+        let status =
+            self.radio.rx_single(self.radio_buffer.as_mut()).await.map_err(Error::Radio)?;
+        self.radio.low_power().await.map_err(Error::Radio)?;
+        let RxStatus::Rx(sz, q) = status else {
+            return Ok(None);
+        };
+        self.radio_buffer.set_pos(sz);
+        let mac_response = self.mac.handle_rxc::<N, D>(
+            &mut self.radio_buffer,
+            &mut self.downlink,
+            q.snr(),
+            rf_config,
+        )?;
+        Self::handle_mac_response(
+            &mut self.radio_buffer,
+            &mut self.mac,
+            &mut self.radio,
+            &mut self.rng,
+            mac_response,
+            None,
+            self.timer.now_ms(),
+        )
+        .await
+    }
+}
+
 /// Allows to fine-tune the beginning and end of the receive windows for a specific board and runtime.
 pub trait Timings {
     /// How many milliseconds before the RX window should the SPI transaction start?
@@ -0,0 +1,65 @@
+//! Bounded queue of application uplinks, sent by priority with `Device::send_queued`.
+use heapless::Vec;
+
+/// Priority of a queued uplink. Uplinks of higher priority are sent first, those of the same
+/// priority in the order they were queued.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum UplinkPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+pub(crate) struct QueuedUplink {
+    pub(crate) data: Vec<u8, 256>,
+    pub(crate) fport: u8,
+    pub(crate) confirmed: bool,
+    priority: UplinkPriority,
+}
+
+pub(crate) struct UplinkQueue<const U: usize> {
+    uplinks: Vec<QueuedUplink, U>,
+}
+
+impl<const U: usize> UplinkQueue<U> {
+    pub(crate) const fn new() -> Self {
+        Self { uplinks: Vec::new() }
+    }
+
+    /// Queue an uplink, returns `false` if the queue is full or `data` does not fit.
+    pub(crate) fn push(
+        &mut self,
+        data: &[u8],
+        fport: u8,
+        confirmed: bool,
+        priority: UplinkPriority,
+    ) -> bool {
+        let Ok(data) = Vec::from_slice(data) else {
+            return false;
+        };
+        self.uplinks.push(QueuedUplink { data, fport, confirmed, priority }).is_ok()
+    }
+
+    /// Take the oldest uplink of the highest priority.
+    pub(crate) fn pop(&mut self) -> Option<QueuedUplink> {
+        let priority = self.uplinks.iter().map(|uplink| uplink.priority).max()?;
+        let index = self.uplinks.iter().position(|uplink| uplink.priority == priority)?;
+        Some(self.uplinks.remove(index))
+    }
+
+    /// Put back an uplink taken with [`Self::pop`] which could not be sent, so it is next again.
+    pub(crate) fn requeue(&mut self, uplink: QueuedUplink) {
+        // There is room for the uplink which was just taken.
+        let _ = self.uplinks.insert(0, uplink);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.uplinks.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.uplinks.clear();
+    }
+}
@@ -1,4 +1,7 @@
-pub use crate::radio::{RfConfig, RxConfig, RxMode, RxQuality, TxConfig};
+pub use crate::radio::{
+    FskParams, LrFhssBandwidth, LrFhssCodingRate, LrFhssGrid, LrFhssParams, Modulation, RfConfig,
+    RxConfig, RxMode, RxQuality, SupportedModulations, TxConfig,
+};
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub struct Error<E>(pub E);
@@ -25,6 +28,10 @@ pub trait Timer {
 
     /// Delay for millis milliseconds
     async fn delay_ms(&mut self, millis: u64);
+
+    /// Milliseconds elapsed since an arbitrary fixed instant (eg: boot), unaffected by `reset`.
+    /// Used to account for the regulatory duty cycle of transmissions.
+    fn now_ms(&self) -> u64;
 }
 
 /// An asynchronous radio implementation that can transmit and receive data.
@@ -39,10 +46,26 @@ pub trait PhyRxTx: Sized {
     /// the value of maximum power will be used as an upper bound.
     const MAX_RADIO_POWER: u8;
 
+    /// Modulations the radio supports besides LoRa. Data rates of other modulations are refused
+    /// in LinkADRReq, and skipped by the data rate backoff and the join data rate rotation.
+    const MODULATIONS: SupportedModulations = SupportedModulations::LORA;
+
     /// Transmit data buffer with the given transceiver configuration. The returned future
     /// should only complete once data have been transmitted.
     async fn tx(&mut self, config: TxConfig, buf: &[u8]) -> Result<u32, Self::PhyError>;
 
+    /// Listen on the channel of `rf` for `duration_us` and tell whether the RSSI stayed below
+    /// `threshold_dbm`, for listen-before-talk before an uplink. Radios which cannot sense the
+    /// channel report it as clear.
+    async fn channel_clear(
+        &mut self,
+        _rf: RfConfig,
+        _threshold_dbm: i16,
+        _duration_us: u32,
+    ) -> Result<bool, Self::PhyError> {
+        Ok(true)
+    }
+
     /// Configures the radio to receive data. This future should not actually await the data itself.
     async fn setup_rx(&mut self, config: RxConfig) -> Result<(), Self::PhyError>;
 
@@ -60,7 +60,7 @@ async fn eu868_devstatusreq_test() {
         _ => panic!(),
     }
 
-    // TODO: Battery value is hardcoded to 255 in MAC for now
+    // Battery level is unknown until set by the application
     let expected_ans = [0x06, 255, device.radio.snr_scaled()];
 
     // Check whether uplink has been populated with requested MAC:DevstatusAns command
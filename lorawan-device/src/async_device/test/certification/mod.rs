@@ -16,8 +16,12 @@ mod mac_common;
 mod dlchannelreq_eu868;
 mod mac_priority;
 mod newchannelreq_eu868;
+#[cfg(feature = "region-ru864")]
+mod newchannelreq_ru864;
 mod oversized_payload_eu868;
 mod rxparamsetup_eu868;
+#[cfg(feature = "region-ru864")]
+mod rxparamsetup_ru864;
 
 /// Parses the uplink, checks the MIC, and decrypts it in place, allowing
 /// access to payload contents
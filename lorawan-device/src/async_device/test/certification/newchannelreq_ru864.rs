@@ -0,0 +1,137 @@
+//! LoRaWAN 1.0.4 Certification testcases
+//!
+//! NewChannelReq for RU864 (2.5.2)
+//! * Add/remove read-only default channels
+//! * Add/remove single channel
+use super::{build_mac, util};
+use crate::async_device::SendResponse;
+use crate::radio::RfConfig;
+use crate::test_util::Uplink;
+
+use lorawan::maccommands::parse_uplink_mac_commands;
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[tokio::test]
+/// NewChannelReq: Add/remove read-only default channels, then add and remove a single channel
+async fn newchannelreq_ru864() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::RU864::new_ru864().into());
+    let send_await_complete = Arc::new(Mutex::new(false));
+
+    // Step 1: send uplink, TCL attempts to modify the read-only channels
+    let complete = send_await_complete.clone();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 1, false).await;
+        let mut complete = complete.lock().await;
+        *complete = true;
+        (device, response)
+    });
+
+    timer.fire_most_recent().await;
+    fn tcl_1(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // NewChannelReq(ChIndex=0, Freq=866100000, DrRange=50)
+        // NewChannelReq(ChIndex=1, Freq=0, DrRange=50)
+        build_mac(buf, "070008288450070100000050", 1)
+    }
+    radio.handle_rxtx(tcl_1).await;
+
+    let (mut device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::DownlinkReceived(1)) => {}
+        _ => panic!(),
+    }
+
+    if let Some(session) = device.mac.get_session() {
+        let data = session.uplink.mac_commands();
+        assert_eq!(parse_uplink_mac_commands(data).count(), 2);
+        assert_eq!(session.uplink.mac_commands(), [0x07, 0x00, 0x07, 0x00]);
+    }
+
+    // Step 2: send uplink, TCL adds a channel after the two default ones
+    let complete = send_await_complete.clone();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 2, false).await;
+        let mut complete = complete.lock().await;
+        *complete = true;
+        (device, response)
+    });
+
+    timer.fire_most_recent().await;
+    fn tcl_2(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // NewChannelReq(ChIndex=2, Freq=866100000, DrRange=50)
+        build_mac(buf, "070208288450", 2)
+    }
+    radio.handle_rxtx(tcl_2).await;
+
+    let (mut device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::DownlinkReceived(2)) => {}
+        _ => panic!(),
+    }
+
+    if let Some(session) = device.mac.get_session() {
+        assert_eq!(session.uplink.mac_commands(), [0x07, 0x03]);
+        let channel_mask = device.mac.region.channel_mask_get();
+        assert_eq!(channel_mask.is_enabled(2), Ok(true))
+    }
+
+    // Step 3: send uplink, TCL removes the channel
+    let complete = send_await_complete.clone();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        let mut complete = complete.lock().await;
+        *complete = true;
+        (device, response)
+    });
+
+    timer.fire_most_recent().await;
+    fn tcl_3(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // NewChannelReq(ChIndex=2, Freq=0, DrRange=50)
+        build_mac(buf, "070200000050", 3)
+    }
+    radio.handle_rxtx(tcl_3).await;
+
+    let (device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::DownlinkReceived(3)) => {}
+        _ => panic!(),
+    }
+
+    if let Some(session) = device.mac.get_session() {
+        assert_eq!(session.uplink.mac_commands(), [0x07, 0x03]);
+        let channel_mask = device.mac.region.channel_mask_get();
+        assert_eq!(channel_mask.is_enabled(2), Ok(false))
+    }
+}
+
+#[tokio::test]
+/// NewChannelReq: Frequencies outside of RU864 are rejected
+async fn newchannelreq_invalid_frequency_ru864() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::RU864::new_ru864().into());
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 1, false).await;
+        (device, response)
+    });
+
+    timer.fire_most_recent().await;
+    fn tcl_1(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // NewChannelReq(ChIndex=2, Freq=863500000, DrRange=50), in EU868 only
+        build_mac(buf, "070278c28350", 1)
+    }
+    radio.handle_rxtx(tcl_1).await;
+
+    let (device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::DownlinkReceived(1)) => {}
+        _ => panic!(),
+    }
+
+    if let Some(session) = device.mac.get_session() {
+        // Channel frequency NACK, data rate range ACK
+        assert_eq!(session.uplink.mac_commands(), [0x07, 0x02]);
+    }
+}
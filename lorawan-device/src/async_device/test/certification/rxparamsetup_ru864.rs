@@ -0,0 +1,106 @@
+//! LoRaWAN 1.0.4 Certification testcases
+//! Based on LoRaWAN 1.0.4 End Device Certification Test Specification v1.6.1
+//!
+//! RXParamSetupReq for RU864 region
+use super::{build_mac, decrypt_uplink, util};
+use crate::async_device::SendResponse;
+use crate::radio::RfConfig;
+use crate::test_util::Uplink;
+
+use lora_modulation::{Bandwidth, SpreadingFactor};
+use lorawan::types::DR;
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[tokio::test]
+/// 2.5.4. RXParamSetupReq test for RU864, starting from the default RX windows
+async fn rxparamsetup_ru864() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::RU864::new_ru864().into());
+    let send_await_complete = Arc::new(Mutex::new(false));
+
+    // Step 1: send uplink, TCL ignores it
+    let complete = send_await_complete.clone();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 1, false).await;
+        let mut complete = complete.lock().await;
+        *complete = true;
+        (device, response)
+    });
+
+    // RX1 is on the uplink channel, one of the two default channels
+    timer.fire_most_recent().await;
+    let uplink = radio.get_last_uplink().await.tx_config().rf.frequency;
+    assert!([868_900_000, 869_100_000].contains(&uplink));
+    radio.handle_timeout().await;
+    let rx_conf = radio.get_rxconfig().await.unwrap();
+    assert_eq!(rx_conf.rf.frequency, uplink);
+    // RX2 defaults to DR0 at 869.1 MHz
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let rx_conf = radio.get_rxconfig().await.unwrap();
+    assert_eq!(rx_conf.rf.frequency, 869_100_000);
+    assert_eq!(rx_conf.rf.bb.sf, SpreadingFactor::_12);
+    assert_eq!(rx_conf.rf.bb.bw, Bandwidth::_125KHz);
+
+    let (mut device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::RxComplete) => (),
+        _ => panic!(),
+    }
+
+    // Step 2: send uplink, TCL responds with MAC:RXParamSetupReq
+    let complete = send_await_complete.clone();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 2, false).await;
+        let mut complete = complete.lock().await;
+        *complete = true;
+        (device, response)
+    });
+
+    fn tcl_2(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // RxParamSetupReq: RX1DRoffset=1, RX2DataRate=DR3 (SF9BW125), Frequency=864900000
+        build_mac(buf, "051328f983", 1)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(tcl_2).await;
+
+    let (mut device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::DownlinkReceived(1)) => {}
+        _ => panic!(),
+    }
+    assert_eq!(device.mac.configuration.rx1_dr_offset, 1);
+    assert_eq!(device.mac.configuration.rx2_data_rate, Some(DR::_3));
+    assert_eq!(device.mac.configuration.rx2_frequency, Some(864_900_000));
+
+    // Step 3: send uplink, TCL ignores it
+    let complete = send_await_complete.clone();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        let mut complete = complete.lock().await;
+        *complete = true;
+        (device, response)
+    });
+
+    // RX1
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    // RX2 follows RXParamSetupReq
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let rx_conf = radio.get_rxconfig().await.unwrap();
+    assert_eq!(rx_conf.rf.frequency, 864_900_000);
+    assert_eq!(rx_conf.rf.bb.sf, SpreadingFactor::_9);
+
+    let (_device, response) = task.await.unwrap();
+    match response {
+        Ok(SendResponse::RxComplete) => (),
+        _ => panic!(),
+    }
+    assert!(*send_await_complete.lock().await);
+    // Check that our mac response was present
+    let mut uplink = radio.get_last_uplink().await;
+    assert_eq!(decrypt_uplink(&mut uplink).fhdr().f_opts(), [0x05, 0x07]);
+}
@@ -0,0 +1,171 @@
+use super::radio::RadioChannel;
+use super::timer::TimerChannel;
+use super::{Device, util};
+use crate::async_device::{ListenResponse, SendResponse};
+use crate::radio::{RfConfig, RxMode};
+use crate::test_util::{Uplink, get_crypto, get_dev_addr};
+use core::num::NonZeroU8;
+use lorawan::beacon::{BeaconCreator, BeaconLayout};
+use lorawan::creator::{DataFrame, Payload};
+use lorawan::parser::DataFrameType;
+
+/// A US915 beacon sent at `TIME`.
+fn beacon<const TIME: u32>(
+    _uplink: Option<Uplink>,
+    _config: RfConfig,
+    rx_buffer: &mut [u8],
+) -> usize {
+    let mut creator = BeaconCreator::new(BeaconLayout::LONG);
+    let bytes = creator.set_time(TIME).build();
+    rx_buffer[..bytes.len()].copy_from_slice(bytes);
+    bytes.len()
+}
+
+fn ping_slot_info_ans(_uplink: Option<Uplink>, _config: RfConfig, rx_buffer: &mut [u8]) -> usize {
+    let frame = DataFrame {
+        frame_type: DataFrameType::UnconfirmedDown,
+        dev_addr: get_dev_addr(),
+        fcnt: 1,
+        payload: Payload::MacCommands(&[0x10]),
+        ..Default::default()
+    };
+    let finished = frame.build_into(rx_buffer, &get_crypto(), Some(&get_crypto())).unwrap();
+    finished.len()
+}
+
+fn ping_slot_downlink(_uplink: Option<Uplink>, _config: RfConfig, rx_buffer: &mut [u8]) -> usize {
+    let frame = DataFrame {
+        frame_type: DataFrameType::UnconfirmedDown,
+        dev_addr: get_dev_addr(),
+        fcnt: 2,
+        payload: Payload::Data { f_port: NonZeroU8::new(3).unwrap(), data: &[1, 2, 3] },
+        ..Default::default()
+    };
+    let finished = frame.build_into(rx_buffer, &get_crypto(), Some(&get_crypto())).unwrap();
+    finished.len()
+}
+
+async fn setup_with_class_b() -> (RadioChannel, TimerChannel, Device) {
+    let (radio, timer, mut device) = util::setup_with_session();
+    device.enable_class_b(7).unwrap();
+    let session = device.mac.get_session().unwrap();
+    assert_eq!(session.uplink.mac_commands(), [0x10, 0x07]);
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(ping_slot_info_ans).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert_eq!(device.mac.configuration.class_b.periodicity, Some(7));
+    (radio, timer, device)
+}
+
+#[tokio::test]
+async fn test_class_b_beacon_lock_and_ping_slot() {
+    let (radio, timer, mut device) = setup_with_class_b().await;
+
+    // The first beacon is searched without waiting for the timer
+    let task = tokio::spawn(async move {
+        let response = device.class_b_listen().await;
+        (device, response)
+    });
+    radio.handle_rxtx(beacon::<1_280>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::BeaconLocked { time: 1_280 })));
+    let rx_config = radio.get_rxconfig().await.unwrap();
+    assert!(matches!(rx_config.mode, RxMode::Beacon { len: 23, .. }));
+    assert!(device.mac.configuration.class_b.is_active());
+
+    // Periodicity 7 opens a single ping slot per beacon period
+    let task = tokio::spawn(async move {
+        let response = device.class_b_listen().await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(ping_slot_downlink).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::DownlinkReceived(2))));
+    let rx_config = radio.get_rxconfig().await.unwrap();
+    assert!(matches!(rx_config.mode, RxMode::Single { .. }));
+    assert_eq!(device.take_downlink().unwrap().data, [1, 2, 3]);
+
+    // Following beacon
+    let task = tokio::spawn(async move {
+        let response = device.class_b_listen().await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(beacon::<1_408>).await;
+    let (_, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::BeaconLocked { time: 1_408 })));
+}
+
+#[tokio::test]
+async fn test_class_b_missed_beacon_widens_windows() {
+    let (radio, timer, mut device) = setup_with_class_b().await;
+
+    let task = tokio::spawn(async move {
+        let response = device.class_b_listen().await;
+        (device, response)
+    });
+    radio.handle_rxtx(beacon::<0>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::BeaconLocked { time: 0 })));
+
+    let mut windows = [0; 2];
+    for window in windows.iter_mut() {
+        // The ping slot is empty
+        let task = tokio::spawn(async move {
+            let response = device.class_b_listen().await;
+            (device, response)
+        });
+        timer.fire_most_recent().await;
+        radio.handle_timeout().await;
+        // So is the beacon window
+        timer.fire_most_recent().await;
+        radio.handle_timeout().await;
+        let (d, response) = task.await.unwrap();
+        device = d;
+        assert!(matches!(response, Ok(ListenResponse::BeaconMissed)));
+        let Some(rx_config) = radio.get_rxconfig().await else { panic!() };
+        let RxMode::Beacon { ms, .. } = rx_config.mode else { panic!() };
+        *window = ms;
+    }
+    assert!(windows[1] > windows[0]);
+    // Uplinks still signal Class B while ping slots are kept open
+    assert!(device.mac.configuration.class_b.is_active());
+}
+
+#[tokio::test]
+async fn test_class_b_ping_slot_channel_req() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn ping_slot_channel_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // PingSlotChannelReq - 923.3 MHz, DR8
+        let frame = DataFrame {
+            frame_type: DataFrameType::UnconfirmedDown,
+            dev_addr: get_dev_addr(),
+            fcnt: 1,
+            payload: Payload::MacCommands(&[0x11, 0x68, 0xe2, 0x8c, 0x08]),
+            ..Default::default()
+        };
+        frame.build_into(buf, &get_crypto(), Some(&get_crypto())).unwrap().len()
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(ping_slot_channel_req).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    let class_b = device.mac.configuration.class_b;
+    assert_eq!(class_b.ping_slot_frequency, Some(923_300_000));
+    assert_eq!(class_b.ping_slot_data_rate, Some(crate::region::DR::_8));
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [0x11, 0x03]);
+}
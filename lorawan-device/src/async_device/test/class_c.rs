@@ -4,6 +4,7 @@ use crate::radio::RfConfig;
 use crate::test_util::{Uplink, get_crypto, get_dev_addr};
 use core::num::NonZeroU8;
 use lorawan::creator::{DataFrame, Payload};
+use lorawan::maccommands::{UplinkMacCommand, parse_uplink_mac_commands};
 use lorawan::parser::DataFrameType;
 
 pub fn class_c_downlink<const FCNT_DOWN: u32>(
@@ -99,3 +100,104 @@ async fn test_class_c_async_down() {
     }
     let _ = device.take_downlink().unwrap();
 }
+
+/// Answer the first uplink with RXParamSetupReq in FOpts.
+fn rx_param_setup_req(_uplink: Option<Uplink>, _config: RfConfig, rx_buffer: &mut [u8]) -> usize {
+    // RX2 on 923.3 MHz at DR8
+    let f_opts = [0x05, 0x08, 0x68, 0xe2, 0x8c];
+    let frame = DataFrame {
+        frame_type: DataFrameType::UnconfirmedDown,
+        dev_addr: get_dev_addr(),
+        fcnt: 1,
+        f_opts: &f_opts,
+        ..Default::default()
+    };
+    let finished = frame.build_into(rx_buffer, &get_crypto(), Some(&get_crypto())).unwrap();
+    finished.len()
+}
+
+/// Check that the uplink only carries RXParamSetupAns, and answer it.
+fn rx_param_setup_ans(uplink: Option<Uplink>, config: RfConfig, rx_buffer: &mut [u8]) -> usize {
+    let uplink = uplink.unwrap();
+    assert_eq!(uplink.f_port(), None);
+    let f_opts = uplink.f_opts();
+    let mut cmds = parse_uplink_mac_commands(&f_opts);
+    assert!(matches!(cmds.next(), Some(Ok(UplinkMacCommand::RXParamSetupAns(_)))));
+    class_c_downlink::<2>(Some(uplink), config, rx_buffer)
+}
+
+#[tokio::test]
+async fn test_class_c_answers_rx_param_setup_req() {
+    let (radio, timer, mut device) = util::setup_with_session_class_c().await;
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(rx_param_setup_req).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+
+    // Listening first sends the answer
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(rx_param_setup_ans).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::DownlinkReceived(2))));
+    assert!(device.take_downlink().is_some());
+}
+
+/// Check that the uplink only carries RXParamSetupAns, and answer it with FCntDown 3.
+fn rx_param_setup_ans_3(uplink: Option<Uplink>, config: RfConfig, rx_buffer: &mut [u8]) -> usize {
+    let uplink = uplink.unwrap();
+    assert_eq!(uplink.f_port(), None);
+    let f_opts = uplink.f_opts();
+    let mut cmds = parse_uplink_mac_commands(&f_opts);
+    assert!(matches!(cmds.next(), Some(Ok(UplinkMacCommand::RXParamSetupAns(_)))));
+    class_c_downlink::<3>(Some(uplink), config, rx_buffer)
+}
+
+#[cfg(feature = "region-eu868")]
+#[tokio::test]
+async fn test_class_c_defers_answer_under_duty_cycle() {
+    let region = crate::region::Configuration::new(crate::region::Region::EU868);
+    let (radio, timer, mut device) = util::session_with_region(region);
+    device.enable_class_c();
+    timer.freeze_clock();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(rx_param_setup_req).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert!(device.time_until_next_tx() > 0);
+
+    // The duty cycle holds the answer back, the device keeps listening
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    radio.handle_rxtx(class_c_downlink::<2>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::DownlinkReceived(2))));
+    assert!(device.take_downlink().is_some());
+
+    // Once the duty cycle allows it, listening first sends the answer
+    timer.advance_ms(3_600_000);
+    let task = tokio::spawn(async move {
+        let response = device.rxc_listen().await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(rx_param_setup_ans_3).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(ListenResponse::DownlinkReceived(3))));
+    assert!(device.take_downlink().is_some());
+}
@@ -4,7 +4,7 @@ use crate::radio::RfConfig;
 use crate::test_util::{Uplink, get_crypto};
 
 use lorawan::creator::{DataFrame, Payload};
-use lorawan::maccommands::parse_uplink_mac_commands;
+use lorawan::maccommands::{UplinkMacCommand, parse_uplink_mac_commands};
 use lorawan::parser::DataFrameType;
 use lorawan::types::ChannelMask;
 
@@ -371,6 +371,32 @@ async fn linkadrreq_dynamic_invalid() {
     assert_eq!(data, [3, 6, 3, 6, 3, 6]);
 }
 
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn linkadrreq_unsupported_modulation() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn addreq_fsk(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // LinkADRReq, DR7 (FSK), 0, 0007, 01
+        build_frm_payload(buf, "037007000001", 2)
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(addreq_fsk).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(_))));
+    // The test radio only supports LoRa: the data rate is refused
+    let data = device.mac.get_session().unwrap().uplink.mac_commands();
+    assert_eq!(data, [3, 5]);
+    assert_ne!(device.mac.configuration.data_rate, crate::region::DR::_7);
+}
+
 fn newchannelreq_invalid_eu868(
     _uplink: Option<Uplink>,
     _config: RfConfig,
@@ -551,3 +577,516 @@ async fn maccommands_in_frmpayload() {
         panic!("Session not joined?");
     }
 }
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn dutycyclereq_limits_aggregated_duty_cycle() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn duty_cycle_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // DutyCycleReq - MaxDCycle = 10, ie: 1/1024
+        build_frm_payload(buf, "040a", 1)
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(duty_cycle_req).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert_eq!(device.mac.configuration.max_duty_cycle, 10);
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [4]);
+}
+
+#[tokio::test]
+#[cfg(feature = "region-as923-1")]
+async fn txparamsetupreq_limits_dwell_time_and_eirp() {
+    let (radio, timer, mut device) = util::session_with_region(crate::region::Configuration::new(
+        crate::region::Region::AS923_1,
+    ));
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn tx_param_setup_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // TXParamSetupReq - Uplink and downlink dwell time, MaxEIRP = 14 dBm
+        build_frm_payload(buf, "0934", 1)
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(tx_param_setup_req).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    let tx_params = crate::mac::TxParams {
+        uplink_dwell_time: true,
+        downlink_dwell_time: true,
+        max_eirp: Some(14),
+    };
+    assert_eq!(device.mac.configuration.tx_params, tx_params);
+    assert_eq!(*device.get_session().unwrap().tx_params(), tx_params);
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [9]);
+
+    let task = tokio::spawn(async move { device.send(&[1, 2, 3], 3, false).await });
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    assert!(matches!(task.await.unwrap(), Ok(SendResponse::RxComplete)));
+
+    // DR0 and DR1 exceed the 400 ms dwell time
+    let uplink = radio.get_last_uplink().await;
+    let tx_config = uplink.tx_config();
+    assert_eq!(tx_config.rf.bb.sf, lora_modulation::SpreadingFactor::_10);
+    assert_eq!(tx_config.rf.max_payload_len, 19);
+    assert!(tx_config.pw <= 14);
+}
+
+#[tokio::test]
+#[cfg(feature = "region-us915")]
+async fn txparamsetupreq_ignored_in_us915() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn tx_param_setup_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_frm_payload(buf, "0934", 1)
+    }
+
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(tx_param_setup_req).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert_eq!(device.mac.configuration.tx_params, Default::default());
+    assert!(device.mac.get_session().unwrap().uplink.mac_commands().is_empty());
+}
+
+fn link_adr_req_nb_trans(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+    // LinkADRReq - DR3, all 125 kHz channels on, NbTrans = 3
+    build_frm_payload(buf, "0330ff0063", 1)
+}
+
+#[tokio::test]
+#[cfg(feature = "region-us915")]
+async fn linkadrreq_nb_trans_repeats_unconfirmed_uplinks() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(link_adr_req_nb_trans).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert_eq!(device.mac.configuration.nb_trans, 3);
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [3, 7]);
+
+    let fcnt_up = device.get_session().unwrap().fcnt_up;
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    for _ in 0..3 {
+        timer.fire_most_recent().await;
+        assert_eq!(radio.get_last_uplink().await.fcnt() as u32, fcnt_up);
+        radio.handle_timeout().await;
+        timer.fire_most_recent().await;
+        radio.handle_timeout().await;
+    }
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert_eq!(device.get_session().unwrap().fcnt_up, fcnt_up + 1);
+    assert_eq!(device.send_attempts(), 3);
+}
+
+#[tokio::test]
+#[cfg(feature = "region-us915")]
+async fn linkadrreq_nb_trans_stops_on_downlink() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(link_adr_req_nb_trans).await;
+    let (mut device, _) = task.await.unwrap();
+
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    // Nothing received after the first transmission
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    fn empty_downlink(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_frm_payload(buf, "", 2)
+    }
+    // The repetition is answered, no further transmission follows
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(empty_downlink).await;
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(2))));
+    assert_eq!(device.send_attempts(), 2);
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn nb_trans_repetitions_change_channel() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    device.mac.configuration.nb_trans = 3;
+    for _ in 0..4 {
+        let task = tokio::spawn(async move {
+            let response = device.send(&[1, 2, 3], 3, false).await;
+            (device, response)
+        });
+        let mut previous = None;
+        for _ in 0..3 {
+            timer.fire_most_recent().await;
+            let frequency = radio.get_last_uplink().await.tx_config().rf.frequency;
+            // The three default channels leave two others to repeat the uplink on
+            assert_ne!(Some(frequency), previous);
+            previous = Some(frequency);
+            radio.handle_timeout().await;
+            timer.fire_most_recent().await;
+            radio.handle_timeout().await;
+        }
+        let response;
+        (device, response) = task.await.unwrap();
+        assert!(matches!(response, Ok(SendResponse::RxComplete)));
+        assert_eq!(device.send_attempts(), 3);
+    }
+}
+
+#[tokio::test]
+#[cfg(feature = "region-us915")]
+async fn devicetimereq_synchronizes_clock() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    timer.freeze_clock();
+    assert_eq!(device.gps_time_ms(), None);
+    device.request_device_time().unwrap();
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [0x0d]);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn device_time_ans(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // DeviceTimeAns - 1_000_000_000 s and 128/256 s since the GPS epoch
+        build_frm_payload(buf, "0d00ca9a3b80", 1)
+    }
+    // The answer arrives in RX1, one second after the end of the uplink
+    timer.fire_most_recent().await;
+    timer.advance_ms(1_000);
+    radio.handle_rxtx(device_time_ans).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert!(device.get_clock().is_synchronized());
+    assert_eq!(device.gps_time_ms(), Some(1_000_000_001_500));
+    timer.advance_ms(500);
+    assert_eq!(device.gps_time_ms(), Some(1_000_000_002_000));
+    let unix_offset_ms =
+        (crate::mac::GPS_EPOCH_UNIX_SECONDS - crate::mac::GPS_UTC_LEAP_SECONDS) * 1000;
+    assert_eq!(device.unix_time_ms(), Some(1_000_000_002_000 + unix_offset_ms));
+}
+
+#[tokio::test]
+#[cfg(feature = "region-us915")]
+async fn linkcheckreq_reports_link_status() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    device.request_link_check().unwrap();
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [0x02]);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn link_check_ans(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // LinkCheckAns - margin 20 dB, 3 gateways
+        build_frm_payload(buf, "021403", 1)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(link_check_ans).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    let status = device.take_link_status().unwrap();
+    assert_eq!(status.margin, 20);
+    assert_eq!(status.gateway_count, 3);
+    assert_eq!(status.rssi, -80);
+    assert_eq!(status.snr, 0);
+    assert_eq!(device.take_link_status(), None);
+}
+
+#[tokio::test]
+#[cfg(feature = "region-us915")]
+async fn devstatusreq_reports_battery_and_margin() {
+    use crate::mac::BatteryLevel;
+
+    let (radio, timer, mut device) = util::setup_with_session();
+    device.set_battery_level(BatteryLevel::from_percent(50));
+    // Out of the range of the 6-bit margin
+    device.radio.set_snr(-40);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+
+    fn dev_status_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_frm_payload(buf, "06", 1)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(dev_status_req).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    // Battery level 127, margin -32
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [0x06, 127, 0x20]);
+
+    device.set_battery_level(BatteryLevel::ExternalPower);
+    device.radio.set_snr(7);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    fn dev_status_req_2(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        build_frm_payload(buf, "06", 2)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(dev_status_req_2).await;
+
+    let (device, _) = task.await.unwrap();
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands(), [0x06, 0, 7]);
+    assert_eq!(BatteryLevel::from_percent(0), BatteryLevel::Level(1));
+    assert_eq!(BatteryLevel::from_percent(100), BatteryLevel::Level(254));
+}
+
+#[tokio::test]
+async fn rxtimingsetupans_sent_without_fport() {
+    let (radio, timer, mut device) = util::setup_with_session();
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    fn rx_timing_setup_req(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // RXTimingSetupReq - RX1 delay of 2 s
+        build_frm_payload(buf, "0802", 1)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(rx_timing_setup_req).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+
+    // The device sends the answer on its own, in FOpts of an uplink without FPort
+    let task = tokio::spawn(async move {
+        let response = device.send_queued().await;
+        (device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    let uplink = radio.get_last_uplink().await;
+    // Trigger end of RX1
+    radio.handle_timeout().await;
+    // Trigger start of RX2
+    timer.fire_most_recent().await;
+    // Trigger end of RX2
+    radio.handle_timeout().await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(Some(SendResponse::RxComplete))));
+    assert_eq!(uplink.f_port(), None);
+    let f_opts = uplink.f_opts();
+    let mut cmds = parse_uplink_mac_commands(&f_opts);
+    assert!(matches!(cmds.next(), Some(Ok(UplinkMacCommand::RXTimingSetupAns(_)))));
+    // Until a downlink arrives the answer is repeated with the next uplinks, but no longer
+    // calls for an uplink of its own.
+    assert!(matches!(device.send_queued().await, Ok(None)));
+}
+
+/// NewChannelReq for channels 3 to 10 in EU868, whose answers overflow FOpts.
+fn new_channel_req_burst(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+    let cmds: std::string::String =
+        (3..=10).map(|channel| format!("07{channel:02x}88668450")).collect();
+    build_frm_payload(buf, &cmds, 1)
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn newchannelans_overflow_sent_separately() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(new_channel_req_burst).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands().len(), 16);
+
+    // The next uplink with application data carries the 7 answers which fit in FOpts...
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    let uplink = radio.get_last_uplink().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert_eq!(uplink.f_port(), Some(3));
+    let f_opts = uplink.f_opts();
+    assert_eq!(f_opts.len(), 14);
+    assert!(
+        parse_uplink_mac_commands(&f_opts)
+            .all(|cmd| matches!(cmd, Ok(UplinkMacCommand::NewChannelAns(_))))
+    );
+
+    // ...and the last one is sent with a MAC-only uplink of its own.
+    let task = tokio::spawn(async move {
+        let response = device.send_queued().await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    let uplink = radio.get_last_uplink().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(Some(SendResponse::RxComplete))));
+    assert_eq!(uplink.f_port(), None);
+    assert_eq!(uplink.f_opts(), [0x07, 0x03]);
+    assert!(matches!(device.send_queued().await, Ok(None)));
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn newchannelans_overflow_sent_on_port_0() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(new_channel_req_burst).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+
+    // Without application data all the answers go in the FRMPayload on FPort 0
+    let task = tokio::spawn(async move {
+        let response = device.send_queued().await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    let uplink = radio.get_last_uplink().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(Some(SendResponse::RxComplete))));
+    assert_eq!(uplink.f_port(), Some(0));
+    assert!(uplink.f_opts().is_empty());
+    let cmds = uplink.port_0_mac_commands();
+    assert_eq!(cmds.len(), 16);
+    assert_eq!(parse_uplink_mac_commands(&cmds).count(), 8);
+    assert!(device.mac.get_session().unwrap().uplink.mac_commands().is_empty());
+    assert!(matches!(device.send_queued().await, Ok(None)));
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn dropped_mac_commands_reported() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    fn dev_status_req_burst(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // 50 DevStatusReq, whose answers take 150 bytes
+        build_frm_payload(buf, &"06".repeat(50), 1)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(dev_status_req_burst).await;
+    let (mut device, response) = task.await.unwrap();
+    // The downlink is still reported, the dropped answers separately
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+    // Requests only fail when their own command does not fit, leaving the earlier drop reported
+    assert!(device.request_link_check().is_ok());
+    assert!(device.request_link_check().is_ok());
+    assert!(matches!(
+        device.request_link_check(),
+        Err(crate::async_device::Error::Mac(crate::mac::Error::MacCommandsDropped))
+    ));
+    assert!(matches!(
+        device.check_dropped_mac_commands(),
+        Err(crate::async_device::Error::Mac(crate::mac::Error::MacCommandsDropped))
+    ));
+    assert!(device.check_dropped_mac_commands().is_ok());
+    // The answers which fit are kept, with the two LinkCheckReq
+    assert_eq!(device.mac.get_session().unwrap().uplink.mac_commands().len(), 128);
+}
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn mac_events_report_changes() {
+    let (radio, timer, mut device) =
+        util::session_with_region(crate::region::EU868::new_eu868().into());
+    device.set_datarate(crate::region::DR::_0);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    fn mac_commands(_uplink: Option<Uplink>, _config: RfConfig, buf: &mut [u8]) -> usize {
+        // NewChannelReq - add new channel to slot 3
+        // LinkADRReq - SF7BW125, channelmask in bank = 1, mask = 0b111
+        // RXTimingSetupReq - RX1 delay of 2 s
+        // LinkADRReq - SF7BW125, mask = 0 (rejected)
+        build_frm_payload(buf, "07038866845003500700010802035000000001", 1)
+    }
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(mac_commands).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(1))));
+
+    use crate::mac::{Change, MacEvent};
+    let event = device.take_mac_event();
+    assert!(matches!(event, Some(MacEvent::NewChannel { accepted: true, index: 3, .. })));
+    let Some(MacEvent::LinkAdr { accepted: true, data_rate, tx_power, nb_trans, channel_mask }) =
+        device.take_mac_event()
+    else {
+        panic!("Expected an accepted LinkAdr event");
+    };
+    assert_eq!(data_rate, Change { before: crate::region::DR::_0, after: crate::region::DR::_5 });
+    // TXPower 0 is the max EIRP of 16 dBm
+    assert_eq!(tx_power, Change { before: None, after: Some(16) });
+    assert!(!nb_trans.changed());
+    assert!(channel_mask.changed());
+    assert_eq!(&channel_mask.after.as_ref()[..2], [0x07, 0x00]);
+    let rx1_delay_ms = Change { before: 1000, after: 2000 };
+    assert_eq!(device.take_mac_event(), Some(MacEvent::RxTimingSetup { rx1_delay_ms }));
+    let Some(MacEvent::LinkAdr { accepted: false, data_rate, channel_mask, .. }) =
+        device.take_mac_event()
+    else {
+        panic!("Expected a rejected LinkAdr event");
+    };
+    assert!(!data_rate.changed());
+    assert!(!channel_mask.changed());
+    assert!(device.take_mac_event().is_none());
+}
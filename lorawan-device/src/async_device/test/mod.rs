@@ -21,13 +21,16 @@ mod certification;
 
 mod maccommands;
 
+#[cfg(feature = "class-b")]
+mod class_b;
+
 #[cfg(feature = "class-c")]
 mod class_c;
 
 #[cfg(feature = "multicast")]
 mod multicast;
 
-type Device = crate::async_device::Device<TestRadio, TestTimer, rand_core::OsRng, 512, 4>;
+type Device = crate::async_device::Device<TestRadio, TestTimer, rand_core::OsRng, 512, 4, 4>;
 
 #[tokio::test]
 async fn test_join_rx1() {
@@ -98,6 +101,109 @@ async fn test_no_join_accept() {
     }
 }
 
+#[tokio::test]
+async fn test_join_with_retries_walks_subbands() {
+    let (radio, timer, mut device) = setup();
+    let datarate = device.get_datarate();
+    let policy = mac::JoinRetryPolicy { max_initial_delay_ms: 0, ..Default::default() };
+    let task = tokio::spawn(async move {
+        let response = device.join_with_retries(&get_otaa_credentials(), &policy).await;
+        (device, response)
+    });
+
+    let mut channels = std::vec::Vec::new();
+    for _ in 0..2 {
+        // Trigger beginning of RX1
+        timer.fire_most_recent().await;
+        let rf = radio.get_last_uplink().await.tx_config().rf;
+        channels.push(match rf.bb.bw {
+            lora_modulation::Bandwidth::_500KHz => 64 + (rf.frequency - 903_000_000) / 1_600_000,
+            _ => (rf.frequency - 902_300_000) / 200_000,
+        });
+        // Trigger end of RX1
+        radio.handle_timeout().await;
+        // Trigger start of RX2
+        timer.fire_most_recent().await;
+        // Trigger end of RX2
+        radio.handle_timeout().await;
+        // Trigger end of the back-off
+        timer.fire_most_recent().await;
+    }
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_join_request::<3>).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::JoinSuccess)));
+    // The second join request is sent on the next subband
+    assert_eq!(channels[1], (channels[0] + 8) % 72);
+    assert_eq!(device.get_datarate(), datarate);
+}
+
+#[tokio::test]
+async fn test_join_with_retries_max_attempts() {
+    let (radio, timer, mut device) = setup();
+    let policy = mac::JoinRetryPolicy {
+        max_attempts: Some(2),
+        max_initial_delay_ms: 0,
+        rotate_datarate: true,
+    };
+    let task = tokio::spawn(async move {
+        let response = device.join_with_retries(&get_otaa_credentials(), &policy).await;
+        (device, response)
+    });
+
+    for attempt in 0..2 {
+        if attempt > 0 {
+            // Trigger end of the back-off
+            timer.fire_most_recent().await;
+        }
+        // Trigger beginning of RX1
+        timer.fire_most_recent().await;
+        // Trigger end of RX1
+        radio.handle_timeout().await;
+        // Trigger start of RX2
+        timer.fire_most_recent().await;
+        // Trigger end of RX2
+        radio.handle_timeout().await;
+    }
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::NoJoinAccept)));
+    assert_eq!(device.get_dev_nonce(), Some(DevNonce::from_value(1)));
+}
+
+#[tokio::test]
+async fn test_join_dev_nonce_counter() {
+    let (radio, timer, mut device) = setup();
+    let mut dev_nonces = std::vec::Vec::new();
+    for _ in 0..2 {
+        let task = tokio::spawn(async move {
+            let response = device.join(&get_otaa_credentials()).await;
+            (device, response)
+        });
+        // Trigger beginning of RX1
+        timer.fire_most_recent().await;
+        dev_nonces.push(radio.get_last_uplink().await.dev_nonce());
+        // Trigger end of RX1
+        radio.handle_timeout().await;
+        // Trigger start of RX2
+        timer.fire_most_recent().await;
+        // Trigger end of RX2
+        radio.handle_timeout().await;
+        let response;
+        (device, response) = task.await.unwrap();
+        assert!(matches!(response, Ok(JoinResponse::NoJoinAccept)));
+    }
+    assert_eq!(dev_nonces, [0, 1]);
+    assert_eq!(device.get_dev_nonce(), Some(DevNonce::from_value(1)));
+    assert!(device.take_state_dirty());
+
+    device.set_dev_nonce(DevNonce::from_value(0xFFFF));
+    let response = device.join(&get_otaa_credentials()).await;
+    assert!(matches!(response, Err(Error::Mac(mac::Error::DevNonceExhausted))));
+}
+
 #[tokio::test]
 async fn test_join_accept_dl_settings_applied() {
     let (radio, timer, mut async_device) = setup();
@@ -118,6 +224,84 @@ async fn test_join_accept_dl_settings_applied() {
     assert_eq!(device.mac.configuration.rx2_data_rate, Some(region::DR::_10));
 }
 
+#[tokio::test]
+async fn test_join_1_1() {
+    let (radio, timer, mut async_device) = setup();
+    let task = tokio::spawn(async move {
+        let response = async_device.join(&get_otaa_1_1_credentials()).await;
+        (async_device, response)
+    });
+
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_join_request_1_1::<7>).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::JoinSuccess)));
+    assert_eq!(device.get_join_nonce(), Some(JoinNonce::from_value(7)));
+    let session = device.get_session().unwrap();
+    assert_eq!(session.lorawan_1_1().unwrap().join_nonce, JoinNonce::from_value(7));
+    assert!(!session.lorawan_1_1().unwrap().rekey_confirmed());
+}
+
+#[tokio::test]
+async fn test_join_1_1_rejects_replayed_join_nonce() {
+    let (radio, timer, mut async_device) = setup();
+    async_device.set_join_nonce(JoinNonce::from_value(7));
+    let task = tokio::spawn(async move {
+        let response = async_device.join(&get_otaa_1_1_credentials()).await;
+        (async_device, response)
+    });
+
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    // A JoinAccept which does not advance the JoinNonce is ignored
+    radio.handle_rxtx(handle_join_request_1_1::<7>).await;
+    // Trigger end of RX1
+    radio.handle_timeout().await;
+    // Trigger start of RX2
+    timer.fire_most_recent().await;
+    // Trigger end of RX2
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::NoJoinAccept)));
+    assert!(device.get_session().is_none());
+    assert_eq!(device.get_join_nonce(), Some(JoinNonce::from_value(7)));
+}
+
+#[tokio::test]
+async fn test_rejoin_1_1_keeps_queued_downlinks() {
+    let (radio, timer, mut device) = setup();
+    let task = tokio::spawn(async move {
+        let response = device.join(&get_otaa_1_1_credentials()).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_join_request_1_1::<7>).await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::JoinSuccess)));
+    assert_eq!(device.pending_rejoin(), None);
+    let downlink = Downlink { data: Vec::from_slice(&[1, 2, 3]).unwrap(), fport: 1 };
+    assert!(device.downlink.push(downlink).is_ok());
+
+    let task = tokio::spawn(async move {
+        let response = device.rejoin(mac::RejoinType::Type1).await;
+        (device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    radio.handle_rxtx(handle_rejoin_request_1_1::<8>).await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(JoinResponse::JoinSuccess)));
+    assert_eq!(device.get_join_nonce(), Some(JoinNonce::from_value(8)));
+    let session = device.get_session().unwrap();
+    assert_eq!(session.devaddr().value(), 0x2000);
+    assert_eq!(session.lorawan_1_1().unwrap().rj_count1(), 1);
+    assert_eq!(device.take_downlink().unwrap().data, [1, 2, 3]);
+}
+
 #[tokio::test]
 async fn test_join_accept_dl_settings_invalid_values_ignored() {
     let (radio, timer, mut async_device) = setup();
@@ -232,6 +416,238 @@ async fn test_confirmed_uplink_no_ack() {
     assert!(*send_await_complete.lock().await);
 }
 
+#[tokio::test]
+async fn test_state_snapshot_restore() {
+    let (radio, timer, mut device) = setup_with_session();
+    assert!(!device.take_state_dirty());
+    device.set_datarate(region::DR::_3);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    // Trigger beginning of RX1
+    timer.fire_most_recent().await;
+    // Trigger end of RX1
+    radio.handle_timeout().await;
+    // Trigger start of RX2
+    timer.fire_most_recent().await;
+    // Trigger end of RX2
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    assert!(device.take_state_dirty());
+    assert!(!device.take_state_dirty());
+    let state = device.snapshot_state();
+
+    let (_radio, _timer, mut restored) = setup();
+    restored.restore_state(&state).unwrap();
+    assert_eq!(restored.get_session().unwrap().fcnt_up, 1);
+    assert_eq!(restored.get_datarate(), region::DR::_3);
+    assert_eq!(restored.get_region().channel_mask_get(), device.get_region().channel_mask_get());
+    assert!(!restored.take_state_dirty());
+}
+
+#[tokio::test]
+async fn test_send_queued_by_priority() {
+    let (radio, timer, mut device) = setup_with_session();
+    assert!(matches!(device.send_queued().await, Ok(None)));
+    let queued = [
+        (1, UplinkPriority::Low),
+        (2, UplinkPriority::Normal),
+        (3, UplinkPriority::High),
+        (4, UplinkPriority::Normal),
+    ];
+    for (fport, priority) in queued {
+        assert!(device.queue_uplink(&[fport], fport, false, priority).is_ok());
+    }
+    let response = device.queue_uplink(&[5], 5, false, UplinkPriority::High);
+    assert!(matches!(response, Err(Error::UplinkQueueFull)));
+    assert_eq!(device.queued_uplinks(), 4);
+
+    let mut fports = std::vec::Vec::new();
+    for _ in 0..4 {
+        let task = tokio::spawn(async move {
+            let response = device.send_queued().await;
+            (device, response)
+        });
+        // Trigger beginning of RX1
+        timer.fire_most_recent().await;
+        fports.push(radio.get_last_uplink().await.f_port());
+        // Trigger end of RX1
+        radio.handle_timeout().await;
+        // Trigger start of RX2
+        timer.fire_most_recent().await;
+        // Trigger end of RX2
+        radio.handle_timeout().await;
+        let response;
+        (device, response) = task.await.unwrap();
+        assert!(matches!(response, Ok(Some(SendResponse::RxComplete))));
+    }
+    assert_eq!(fports, [Some(3), Some(2), Some(4), Some(1)]);
+    assert_eq!(device.queued_uplinks(), 0);
+}
+
+#[tokio::test]
+async fn test_send_payload_too_large() {
+    let (_radio, _timer, mut device) = setup_with_session();
+    device.set_datarate(region::DR::_0);
+    // MAC payload of 19 bytes at US915 DR0, less the FHDR and FPort
+    assert_eq!(device.max_payload_len(), 11);
+    let response = device.send(&[0; 12], 1, false).await;
+    assert!(matches!(response, Err(Error::Mac(crate::mac::Error::PayloadTooLarge { max: 11 }))));
+    assert_eq!(device.get_session().unwrap().fcnt_up, 0);
+
+    device.set_datarate(region::DR::_3);
+    assert_eq!(device.max_payload_len(), 242);
+    device.set_repeater_compatible(true);
+    assert_eq!(device.max_payload_len(), 222);
+    // Pending MAC commands take room in FOpts
+    assert!(device.request_link_check().is_ok());
+    assert_eq!(device.max_payload_len(), 221);
+}
+
+#[tokio::test]
+async fn test_confirmed_uplink_retransmission_no_ack() {
+    let (radio, timer, mut device) = setup_with_session();
+    device.set_datarate(region::DR::_3);
+    device.set_retransmission_policy(crate::mac::RetransmissionPolicy {
+        retries: 2,
+        datarate_step_down: true,
+    });
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, true).await;
+        (device, response)
+    });
+
+    let mut uplinks = std::vec::Vec::new();
+    for attempt in 0..3 {
+        if attempt > 0 {
+            // Trigger end of ACK_TIMEOUT
+            timer.fire_most_recent().await;
+        }
+        // Trigger beginning of RX1
+        timer.fire_most_recent().await;
+        uplinks.push(radio.get_last_uplink().await);
+        // Trigger end of RX1
+        radio.handle_timeout().await;
+        // Trigger start of RX2
+        timer.fire_most_recent().await;
+        // Trigger end of RX2
+        radio.handle_timeout().await;
+    }
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::NoAck)));
+    assert_eq!(device.send_attempts(), 3);
+    assert!(uplinks.iter().all(|uplink| uplink.fcnt() == 0));
+    // The data rate steps down for the third transmission
+    let sf = |uplink: &Uplink| uplink.tx_config().rf.bb.sf;
+    assert_eq!(sf(&uplinks[0]), sf(&uplinks[1]));
+    assert_eq!(sf(&uplinks[2]), lora_modulation::SpreadingFactor::_8);
+    assert_eq!(device.get_datarate(), region::DR::_2);
+}
+
+#[tokio::test]
+async fn test_confirmed_uplink_retransmission_ack() {
+    let (radio, timer, mut device) = setup_with_session();
+    device.set_retransmission_policy(crate::mac::RetransmissionPolicy {
+        retries: 3,
+        ..Default::default()
+    });
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, true).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    // Trigger end of ACK_TIMEOUT, then beginning of RX1 of the retransmission
+    timer.fire_most_recent().await;
+    timer.fire_most_recent().await;
+    // The retransmission carries FCnt 0 and is acknowledged
+    radio.handle_rxtx(handle_data_uplink_with_link_adr_req::<0, 0>).await;
+
+    let (device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::DownlinkReceived(0))));
+    assert_eq!(device.send_attempts(), 2);
+}
+
+#[cfg(feature = "channel-stats")]
+#[tokio::test]
+async fn test_channel_avoided_after_missed_ack() {
+    let (radio, timer, mut device) = setup_with_session();
+    // The avoidance lasts 10 minutes, which the free-running test clock would skip over
+    timer.freeze_clock();
+    device.set_channel_avoidance(Some(region::ChannelAvoidance {
+        max_consecutive_failures: 1,
+        ..Default::default()
+    }));
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, true).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    let failed = radio.get_last_uplink().await.tx_config().rf.frequency;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::NoAck)));
+    let stats = device.channel_stats(failed).unwrap();
+    assert_eq!((stats.transmissions, stats.missed_acks), (1, 1));
+    assert_eq!(stats.ack_rate_percent(), Some(0));
+
+    // The failed channel is not used again while other channels are available
+    for _ in 0..8 {
+        let task = tokio::spawn(async move {
+            let response = device.send(&[1, 2, 3], 3, false).await;
+            (device, response)
+        });
+        timer.fire_most_recent().await;
+        assert_ne!(radio.get_last_uplink().await.tx_config().rf.frequency, failed);
+        radio.handle_timeout().await;
+        timer.fire_most_recent().await;
+        radio.handle_timeout().await;
+        device = task.await.unwrap().0;
+    }
+    assert_eq!(device.channel_stats(failed).unwrap().transmissions, 1);
+    device.clear_channel_stats();
+    assert!(device.channel_stats(failed).is_none());
+}
+
+#[cfg(feature = "region-as923-1")]
+#[tokio::test]
+async fn test_listen_before_talk_busy_channel() {
+    let region = region::Configuration::new(region::Region::AS923_1);
+    assert_eq!(region.get_listen_before_talk(), Some(region::ListenBeforeTalk::ARIB_STD_T108));
+    let (radio, timer, mut device) = util::session_with_region(region);
+    radio.report_busy(1);
+    let task = tokio::spawn(async move {
+        let response = device.send(&[1, 2, 3], 3, false).await;
+        (device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let (mut device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+    // The uplink was sent on the other default channel
+    let busy = radio.busy_channels().await;
+    assert_eq!(busy.len(), 1);
+    let uplink = radio.get_last_uplink().await;
+    assert_ne!(uplink.tx_config().rf.frequency, busy[0]);
+    assert_eq!(uplink.fcnt(), 0);
+
+    // Both default channels are busy
+    radio.report_busy(2);
+    let response = device.send(&[1, 2, 3], 3, false).await;
+    assert!(matches!(response, Err(Error::Mac(mac::Error::ChannelBusy))));
+}
+
 #[tokio::test]
 async fn test_confirmed_uplink_with_ack_rx1() {
     let (radio, timer, mut async_device) = setup_with_session();
@@ -374,3 +790,38 @@ async fn invalid_maccommands_in_frmpayload() {
         }
     }
 }
+
+#[tokio::test]
+#[cfg(feature = "region-eu868")]
+async fn test_duty_cycle_blocks_uplinks() {
+    let (radio, timer, mut async_device) =
+        util::session_with_region(region::EU868::new_eu868().into());
+    timer.freeze_clock();
+    assert_eq!(async_device.time_until_next_tx(), 0);
+
+    let task = tokio::spawn(async move {
+        let response = async_device.send(&[1, 2, 3], 3, false).await;
+        (async_device, response)
+    });
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    let (mut async_device, response) = task.await.unwrap();
+    assert!(matches!(response, Ok(SendResponse::RxComplete)));
+
+    // The default channels share the 868.0..868.6 MHz sub-band, which is limited to 1%
+    let wait_ms = async_device.time_until_next_tx();
+    assert!(wait_ms > 0);
+    let response = async_device.send(&[1, 2, 3], 3, false).await;
+    assert!(matches!(response, Err(Error::Mac(mac::Error::DutyCycleRestricted))));
+
+    timer.advance_ms(wait_ms);
+    assert_eq!(async_device.time_until_next_tx(), 0);
+    let task = tokio::spawn(async move { async_device.send(&[1, 2, 3], 3, false).await });
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    timer.fire_most_recent().await;
+    radio.handle_timeout().await;
+    assert!(matches!(task.await.unwrap(), Ok(SendResponse::RxComplete)));
+}
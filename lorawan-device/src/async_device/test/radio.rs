@@ -1,6 +1,7 @@
 use super::*;
 use crate::async_device::radio::{PhyRxTx, RxConfig, RxStatus};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::{
     sync::{Mutex, mpsc},
     time,
@@ -10,13 +11,25 @@ impl TestRadio {
         let (tx, rx) = mpsc::channel(2);
         let last_rxconfig = Arc::new(Mutex::new(None));
         let last_uplink = Arc::new(Mutex::new(None));
+        let busy_count = Arc::new(AtomicUsize::new(0));
+        let busy_channels = Arc::new(Mutex::new(std::vec::Vec::new()));
         (
             RadioChannel {
                 tx,
                 last_uplink: last_uplink.clone(),
                 last_rxconfig: last_rxconfig.clone(),
+                busy_count: busy_count.clone(),
+                busy_channels: busy_channels.clone(),
+            },
+            Self {
+                rx,
+                last_rxconfig,
+                last_uplink,
+                current_config: None,
+                snr: 0,
+                busy_count,
+                busy_channels,
             },
-            Self { rx, last_rxconfig, last_uplink, current_config: None, snr: 0 },
         )
     }
 
@@ -42,6 +55,9 @@ pub struct TestRadio {
     last_uplink: Arc<Mutex<Option<Uplink>>>,
     rx: mpsc::Receiver<Msg>,
     snr: i8,
+    /// Channel sensings left which find the channel busy.
+    busy_count: Arc<AtomicUsize>,
+    busy_channels: Arc<Mutex<std::vec::Vec<u32>>>,
 }
 
 impl PhyRxTx for TestRadio {
@@ -59,6 +75,21 @@ impl PhyRxTx for TestRadio {
         Ok(length as u32)
     }
 
+    async fn channel_clear(
+        &mut self,
+        rf: RfConfig,
+        _threshold_dbm: i16,
+        _duration_us: u32,
+    ) -> Result<bool, Self::PhyError> {
+        let busy = self
+            .busy_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| count.checked_sub(1));
+        if busy.is_ok() {
+            self.busy_channels.lock().await.push(rf.frequency);
+        }
+        Ok(busy.is_err())
+    }
+
     async fn setup_rx(&mut self, config: RxConfig) -> Result<(), Self::PhyError> {
         self.current_config = Some(config);
         // Make current rx configuration available for test harness
@@ -118,6 +149,8 @@ pub struct RadioChannel {
     last_rxconfig: Arc<Mutex<Option<RxConfig>>>,
     last_uplink: Arc<Mutex<Option<Uplink>>>,
     tx: mpsc::Sender<Msg>,
+    busy_count: Arc<AtomicUsize>,
+    busy_channels: Arc<Mutex<std::vec::Vec<u32>>>,
 }
 
 impl RadioChannel {
@@ -130,6 +163,16 @@ impl RadioChannel {
         self.tx.send(Msg::Timeout).await.unwrap();
     }
 
+    /// Find the channel busy on the next `count` channel sensings of listen-before-talk.
+    pub fn report_busy(&self, count: usize) {
+        self.busy_count.store(count, Ordering::SeqCst);
+    }
+
+    /// Frequencies of the channels found busy so far.
+    pub async fn busy_channels(&self) -> std::vec::Vec<u32> {
+        self.busy_channels.lock().await.clone()
+    }
+
     pub async fn get_rxconfig(&self) -> Option<RxConfig> {
         let rxconf = self.last_rxconfig.lock().await;
         *rxconf
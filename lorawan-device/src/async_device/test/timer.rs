@@ -1,14 +1,31 @@
 use crate::async_device::radio::Timer;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 use tokio::sync::{Mutex, mpsc};
 
+/// Unless frozen, the clock moves forward by this much every time it is read, so that tests
+/// sending back-to-back uplinks are not held back by the duty cycle.
+const FREE_RUNNING_STEP_MS: u64 = 3_600_000;
+
 impl TestTimer {
     pub fn new() -> (TimerChannel, Self) {
         let tx = Arc::new(Mutex::new(HashMap::new()));
         let armed_count = Arc::new(Mutex::new(0));
+        let now_ms = Arc::new(AtomicU64::new(0));
+        let step_ms = Arc::new(AtomicU64::new(FREE_RUNNING_STEP_MS));
         (
-            TimerChannel { tx: tx.clone(), armed_count: armed_count.clone() },
-            Self { tx, armed_count },
+            TimerChannel {
+                tx: tx.clone(),
+                armed_count: armed_count.clone(),
+                now_ms: now_ms.clone(),
+                step_ms: step_ms.clone(),
+            },
+            Self { tx, armed_count, now_ms, step_ms },
         )
     }
 }
@@ -16,6 +33,8 @@ impl TestTimer {
 pub struct TestTimer {
     armed_count: Arc<Mutex<usize>>,
     tx: Arc<Mutex<HashMap<usize, mpsc::Sender<()>>>>,
+    now_ms: Arc<AtomicU64>,
+    step_ms: Arc<AtomicU64>,
 }
 
 impl TestTimer {
@@ -40,12 +59,18 @@ impl Timer for TestTimer {
     async fn delay_ms(&mut self, _millis: u64) {
         self.create_channel_and_await().await;
     }
+
+    fn now_ms(&self) -> u64 {
+        self.now_ms.fetch_add(self.step_ms.load(Ordering::Relaxed), Ordering::Relaxed)
+    }
 }
 
 /// A channel for the test fixture to trigger fires and to check calls.
 pub struct TimerChannel {
     armed_count: Arc<Mutex<usize>>,
     tx: Arc<Mutex<HashMap<usize, mpsc::Sender<()>>>>,
+    now_ms: Arc<AtomicU64>,
+    step_ms: Arc<AtomicU64>,
 }
 
 impl TimerChannel {
@@ -67,6 +92,18 @@ impl TimerChannel {
         }
     }
 
+    /// Stop the clock returned by `now_ms` from moving on its own.
+    #[allow(unused)]
+    pub fn freeze_clock(&self) {
+        self.step_ms.store(0, Ordering::Relaxed);
+    }
+
+    /// Move the clock returned by `now_ms` forward, which firing timers does not do.
+    #[allow(unused)]
+    pub fn advance_ms(&self, millis: u64) {
+        self.now_ms.fetch_add(millis, Ordering::Relaxed);
+    }
+
     pub async fn get_armed_count(&self) -> usize {
         *self.armed_count.lock().await
     }
@@ -29,8 +29,8 @@ pub mod nb_device;
 use nb_device::state::State;
 
 pub use lorawan::{
-    keys::{AppEui, AppKey, AppSKey, Crypto, DevEui, NwkSKey},
-    parser::DevAddr,
+    keys::{AppEui, AppKey, AppSKey, Crypto, DevEui, NwkKey, NwkSKey},
+    parser::{DevAddr, DevNonce, JoinNonce},
 };
 
 #[deprecated(since = "0.12.2", note = "Please use `NwkSKey` instead")]
@@ -73,6 +73,22 @@ pub trait Timings {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Join the network using either OTAA or ABP.
 pub enum JoinMode {
-    OTAA { deveui: DevEui, appeui: AppEui, appkey: AppKey },
-    ABP { nwkskey: NwkSKey, appskey: AppSKey, devaddr: DevAddr },
+    OTAA {
+        deveui: DevEui,
+        appeui: AppEui,
+        appkey: AppKey,
+    },
+    /// OTAA for a LoRaWAN 1.1 device, which falls back to 1.0.x when the network does not set
+    /// OptNeg in the JoinAccept.
+    OTAA1_1 {
+        deveui: DevEui,
+        joineui: AppEui,
+        nwkkey: NwkKey,
+        appkey: AppKey,
+    },
+    ABP {
+        nwkskey: NwkSKey,
+        appskey: AppSKey,
+        devaddr: DevAddr,
+    },
 }
@@ -100,6 +100,7 @@ impl Certification {
         buf: &mut RadioBuffer<N>,
         configuration: &mac::Configuration,
         region: &crate::region::Configuration,
+        tx_channel: &crate::region::TxChannel,
     ) -> mac::Result<mac::FcntUp> {
         let send_data = mac::SendData {
             fport: CERTIFICATION_PORT,
@@ -107,9 +108,13 @@ impl Certification {
             confirmed: false,
         };
         match &mut state {
-            mac::State::Joined(session) => {
-                Ok(session.prepare_buffer::<N>(&send_data, buf, configuration, region))
-            }
+            mac::State::Joined(session) => Ok(session.prepare_buffer::<N>(
+                Some(&send_data),
+                buf,
+                configuration,
+                region,
+                tx_channel,
+            )),
             mac::State::Otaa(_) => Err(mac::Error::NotJoined),
             mac::State::Unjoined => Err(mac::Error::NotJoined),
         }
@@ -0,0 +1,137 @@
+//! Class B beacon tracking and ping-slot scheduling.
+//!
+//! All times are expressed in milliseconds since the start of the beacon opening the current
+//! beacon period.
+use lorawan::beacon::{self, BEACON_PERIOD};
+use lorawan::types::DR;
+
+/// Beacon period in milliseconds.
+pub(crate) const BEACON_PERIOD_MS: u32 = BEACON_PERIOD * 1000;
+/// Interval reserved for the beacon broadcast at the start of a period, no ping slot is scheduled.
+pub(crate) const BEACON_RESERVED_MS: u32 = 2_120;
+/// Length of a ping slot.
+pub(crate) const SLOT_LEN_MS: u32 = 30;
+/// Without beacons, a device keeps its ping slots open for at most 120 minutes.
+const MAX_BEACONLESS_PERIODS: u32 = 120 * 60 / BEACON_PERIOD;
+/// Clock drift budget used to widen the beacon and ping-slot windows, in parts per million.
+const CLOCK_DRIFT_PPM: u32 = 40;
+
+/// Class B parameters negotiated with the network.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub(crate) struct Configuration {
+    /// Periodicity sent in PingSlotInfoReq and awaiting PingSlotInfoAns.
+    pub(crate) pending_periodicity: Option<u8>,
+    /// Periodicity acknowledged by the network, ping slots are only opened once it is set.
+    pub(crate) periodicity: Option<u8>,
+    /// Ping slot frequency set by PingSlotChannelReq, region default when `None`.
+    pub(crate) ping_slot_frequency: Option<u32>,
+    /// Ping slot data rate set by PingSlotChannelReq, beacon data rate when `None`.
+    pub(crate) ping_slot_data_rate: Option<DR>,
+    /// Beacon frequency set by BeaconFreqReq, region default when `None`.
+    pub(crate) beacon_frequency: Option<u32>,
+    /// Beacon synchronization state.
+    pub(crate) beacon: Beacon,
+}
+
+impl Configuration {
+    /// Whether ping slots are opened, which uplinks signal with the FCtrl ClassB bit.
+    pub(crate) fn is_active(&self) -> bool {
+        self.periodicity.is_some() && matches!(self.beacon, Beacon::Locked { .. })
+    }
+}
+
+/// State of the beacon synchronization.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub(crate) enum Beacon {
+    /// No beacon received yet (or lock lost), the device is searching.
+    #[default]
+    Searching,
+    /// Following the beacon period opened at `time` (GPS seconds).
+    Locked {
+        time: u32,
+        /// Consecutive beacon periods for which no beacon was received.
+        missed: u32,
+        /// Index of the next ping slot of this period.
+        next_slot: u16,
+    },
+}
+
+impl Beacon {
+    /// Synchronize on a received beacon.
+    pub(crate) fn lock(&mut self, time: u32) {
+        *self = Beacon::Locked { time, missed: 0, next_slot: 0 };
+    }
+
+    /// Move to the next period without having received its beacon. Returns `false` once the
+    /// beacon-less operation limit is reached and the device fell back to searching.
+    pub(crate) fn miss(&mut self) -> bool {
+        if let Beacon::Locked { time, missed, .. } = *self
+            && missed + 1 < MAX_BEACONLESS_PERIODS
+        {
+            *self = Beacon::Locked {
+                time: time.wrapping_add(BEACON_PERIOD),
+                missed: missed + 1,
+                next_slot: 0,
+            };
+            return true;
+        }
+        *self = Beacon::Searching;
+        false
+    }
+
+    /// Time by which the beacon and ping-slot windows are opened early (and kept open late) to
+    /// compensate for the clock drift accumulated since the last received beacon.
+    pub(crate) fn window_widening_ms(&self) -> u32 {
+        match self {
+            Beacon::Searching => 0,
+            Beacon::Locked { missed, .. } => {
+                (missed + 1) * BEACON_PERIOD_MS / 1_000 * CLOCK_DRIFT_PPM / 1_000
+            }
+        }
+    }
+}
+
+/// Number of ping slots per beacon period for the given periodicity.
+pub(crate) const fn ping_nb(periodicity: u8) -> u16 {
+    1 << (7 - (periodicity & 0x07))
+}
+
+/// Start of the `slot`-th ping slot of a period, given the period's ping offset.
+pub(crate) fn ping_slot_start_ms(ping_offset: u16, periodicity: u8, slot: u16) -> u32 {
+    let slots = ping_offset as u32 + slot as u32 * beacon::ping_period(periodicity) as u32;
+    BEACON_RESERVED_MS + slots * SLOT_LEN_MS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ping_slots_fit_in_beacon_window() {
+        for periodicity in 0..=7 {
+            let last_slot = ping_nb(periodicity) - 1;
+            let max_offset = beacon::ping_period(periodicity) - 1;
+            let start = ping_slot_start_ms(max_offset, periodicity, last_slot);
+            // The beacon window is 122.88 s long and followed by the 3 s beacon guard.
+            assert!(start + SLOT_LEN_MS <= BEACON_RESERVED_MS + 122_880);
+        }
+        assert_eq!(ping_slot_start_ms(0, 7, 0), BEACON_RESERVED_MS);
+        assert_eq!(ping_slot_start_ms(3, 0, 1), BEACON_RESERVED_MS + 35 * SLOT_LEN_MS);
+    }
+
+    #[test]
+    fn missed_beacons_widen_windows_until_lost() {
+        let mut beacon = Beacon::default();
+        assert_eq!(beacon.window_widening_ms(), 0);
+        beacon.lock(1_000_000);
+        let widening = beacon.window_widening_ms();
+        assert!(beacon.miss());
+        assert!(beacon.window_widening_ms() > widening);
+        assert!(matches!(beacon, Beacon::Locked { time: 1_000_128, missed: 1, .. }));
+
+        while beacon.miss() {}
+        assert!(matches!(beacon, Beacon::Searching));
+    }
+}
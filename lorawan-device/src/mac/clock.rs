@@ -0,0 +1,67 @@
+//! Network time obtained with DeviceTimeReq.
+//!
+//! DeviceTimeAns carries the GPS time at the end of the uplink which requested it. The clock keeps
+//! that time along with the local timestamp of the end of the transmission, so the GPS time can be
+//! derived from any later local timestamp.
+
+/// Seconds from the Unix epoch to the GPS epoch (1980-01-06T00:00:00Z).
+pub const GPS_EPOCH_UNIX_SECONDS: u64 = 315_964_800;
+/// Leap seconds between GPS time and UTC (since 2017-01-01).
+pub const GPS_UTC_LEAP_SECONDS: u64 = 18;
+
+/// Device clock synchronized with the network time.
+///
+/// Local timestamps are in milliseconds, in the time base of the device: `Timer::now_ms` for the
+/// async device, the radio timestamps for the non-blocking device.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct DeviceClock {
+    sync: Option<Sync>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+struct Sync {
+    local_ms: u64,
+    gps_ms: u64,
+}
+
+impl DeviceClock {
+    /// Whether a DeviceTimeAns was received.
+    pub fn is_synchronized(&self) -> bool {
+        self.sync.is_some()
+    }
+
+    /// Milliseconds since the GPS epoch at local time `now_ms`, `None` until synchronized or if
+    /// `now_ms` is before the synchronization.
+    pub fn gps_time_ms(&self, now_ms: u64) -> Option<u64> {
+        let sync = self.sync?;
+        Some(sync.gps_ms + now_ms.checked_sub(sync.local_ms)?)
+    }
+
+    /// Milliseconds since the Unix epoch (UTC) at local time `now_ms`, see [`Self::gps_time_ms`].
+    pub fn unix_time_ms(&self, now_ms: u64) -> Option<u64> {
+        let gps_ms = self.gps_time_ms(now_ms)?;
+        Some(gps_ms + (GPS_EPOCH_UNIX_SECONDS - GPS_UTC_LEAP_SECONDS) * 1000)
+    }
+
+    /// Take `gps_ms` as the GPS time at local time `local_ms`.
+    pub(crate) fn synchronize(&mut self, gps_ms: u64, local_ms: u64) {
+        self.sync = Some(Sync { local_ms, gps_ms });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gps_and_unix_time() {
+        let mut clock = DeviceClock::default();
+        assert_eq!(clock.gps_time_ms(1_000), None);
+        clock.synchronize(1_000_000_000_500, 2_000);
+        assert_eq!(clock.gps_time_ms(3_500), Some(1_000_000_002_000));
+        assert_eq!(clock.gps_time_ms(1_000), None);
+        assert_eq!(clock.unix_time_ms(3_500), Some(1_315_964_784_000));
+    }
+}
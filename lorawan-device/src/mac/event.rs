@@ -0,0 +1,108 @@
+//! Changes of the MAC configuration by MAC commands from the network.
+//!
+//! Each MAC command which sets a part of the configuration is reported as a [`MacEvent`] with the
+//! values before and after it was handled, whether the device applied or rejected it. The events
+//! are kept until the application takes them with `take_mac_event`, for telemetry, to know when
+//! to save the state, or to follow ADR in the field.
+use crate::mac::TxParams;
+use lorawan::types::{ChannelMask, DR};
+
+/// Events kept until taken, the oldest are dropped first.
+const MAC_EVENTS_LEN: usize = 8;
+
+/// Value of a setting before and after a MAC command. Both are equal when the command was rejected
+/// or did not change the setting.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    /// Whether the setting changed.
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+/// A MAC command from the network handled by the device. `accepted` is set when the device applied
+/// the command, as answered to the network.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum MacEvent {
+    /// LinkADRReq, or a block of contiguous LinkADRReq. The TX power, as an EIRP in dBm, is the
+    /// region default when `None`.
+    LinkAdr {
+        accepted: bool,
+        data_rate: Change<DR>,
+        tx_power: Change<Option<u8>>,
+        nb_trans: Change<u8>,
+        channel_mask: Change<ChannelMask<9>>,
+    },
+    /// RXParamSetupReq. The RX2 data rate and frequency are the region defaults when `None`.
+    RxParamSetup {
+        accepted: bool,
+        rx1_dr_offset: Change<u8>,
+        rx2_data_rate: Change<Option<DR>>,
+        rx2_frequency: Change<Option<u32>>,
+    },
+    /// RXTimingSetupReq.
+    RxTimingSetup { rx1_delay_ms: Change<u32> },
+    /// DutyCycleReq, for an aggregated duty cycle of `1 / 2^max_duty_cycle`.
+    DutyCycle { max_duty_cycle: Change<u8> },
+    /// TXParamSetupReq.
+    TxParamSetup { tx_params: Change<TxParams> },
+    /// NewChannelReq, a frequency of 0 disables the channel.
+    NewChannel { accepted: bool, index: u8, frequency: u32 },
+    /// DlChannelReq.
+    DlChannel { accepted: bool, index: u8, frequency: u32 },
+    /// PingSlotChannelReq. The frequency is the region default when `None`, the data rate that of
+    /// the beacon.
+    #[cfg(feature = "class-b")]
+    PingSlotChannel {
+        accepted: bool,
+        frequency: Change<Option<u32>>,
+        data_rate: Change<Option<DR>>,
+    },
+    /// BeaconFreqReq. The frequency is the region default when `None`.
+    #[cfg(feature = "class-b")]
+    BeaconFreq { accepted: bool, frequency: Change<Option<u32>> },
+}
+
+/// MAC events not taken by the application yet.
+#[derive(Debug, Default)]
+pub(crate) struct MacEvents {
+    events: heapless::Deque<MacEvent, MAC_EVENTS_LEN>,
+}
+
+impl MacEvents {
+    pub(crate) fn push(&mut self, event: MacEvent) {
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        let _ = self.events.push_back(event);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<MacEvent> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn oldest_events_dropped() {
+        let mut events = MacEvents::default();
+        for delay in 0..=MAC_EVENTS_LEN as u32 {
+            let rx1_delay_ms = Change { before: delay, after: delay + 1 };
+            events.push(MacEvent::RxTimingSetup { rx1_delay_ms });
+        }
+        let first = Change { before: 1, after: 2 };
+        assert_eq!(events.pop(), Some(MacEvent::RxTimingSetup { rx1_delay_ms: first }));
+        assert!(first.changed());
+        assert_eq!(core::iter::from_fn(|| events.pop()).count(), MAC_EVENTS_LEN - 1);
+    }
+}
@@ -0,0 +1,177 @@
+//! Back-off of join requests.
+//!
+//! The aggregated airtime of the join requests of a device is limited from its first join request
+//! on: 36 s per hour during the first hour, 36 s per 10 hours during the next 10 hours, then 8.7 s
+//! per 24 hours. As with the regulatory duty cycle, each join request keeps the next one off for
+//! its time on air divided by the duty cycle of the current phase, which keeps the airtime within
+//! the limit over any of these observation windows.
+use super::Mac;
+use crate::region::DR;
+use rand_core::RngCore;
+
+const HOUR_MS: u64 = 3_600_000;
+const ELEVEN_HOURS_MS: u64 = 11 * HOUR_MS;
+/// Lower bound of the random part of the delay between join requests.
+const MIN_JITTER_MS: u64 = 1_000;
+
+/// Inverse of the join duty cycle `elapsed_ms` after the first join request.
+fn inverse_duty_cycle(elapsed_ms: u64) -> u64 {
+    match elapsed_ms {
+        // 36 s per hour
+        ..HOUR_MS => 100,
+        // 36 s per 10 hours
+        HOUR_MS..ELEVEN_HOURS_MS => 1_000,
+        // 8.64 s per 24 hours, within the 8.7 s allowed
+        _ => 10_000,
+    }
+}
+
+/// Retries of [`join_with_retries`](crate::async_device::Device::join_with_retries).
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct JoinRetryPolicy {
+    /// Join requests sent before giving up, without limit when `None`.
+    pub max_attempts: Option<u32>,
+    /// Upper bound of the random delay before the first join request, which spreads the join
+    /// requests of devices powered up together.
+    pub max_initial_delay_ms: u32,
+    /// Rotate the data rate of successive join requests from the configured data rate down to the
+    /// lowest one. In US915 and AU915 the data rate follows the join channel instead.
+    pub rotate_datarate: bool,
+}
+
+impl Default for JoinRetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: None, max_initial_delay_ms: 10_000, rotate_datarate: true }
+    }
+}
+
+/// Join duty-cycle ledger, from the first join request since the device last joined.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct JoinBackoff {
+    start_ms: Option<u64>,
+    last_ms: u64,
+    off_ms: u64,
+}
+
+impl JoinBackoff {
+    /// Account for a join request of `time_on_air_ms` started at `now_ms`.
+    pub(crate) fn register(&mut self, time_on_air_ms: u64, now_ms: u64) {
+        let start_ms = *self.start_ms.get_or_insert(now_ms);
+        self.last_ms = now_ms;
+        self.off_ms = time_on_air_ms * inverse_duty_cycle(now_ms.saturating_sub(start_ms));
+    }
+
+    /// Time until the join duty cycle allows the next join request.
+    pub(crate) fn remaining_ms(&self, now_ms: u64) -> u64 {
+        // A clock which went backwards (eg: after a reboot) ends the off period.
+        self.off_ms.saturating_sub(now_ms.wrapping_sub(self.last_ms))
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Mac {
+    /// Delay from `now_ms` to the first join request of [`JoinRetryPolicy`]: a random delay, or
+    /// longer while the join or regulatory duty cycle, eg: from join requests of a previous call,
+    /// does not allow it yet.
+    pub(crate) fn join_initial_delay_ms<RNG: RngCore>(
+        &self,
+        rng: &mut RNG,
+        policy: &JoinRetryPolicy,
+        now_ms: u64,
+    ) -> u64 {
+        let jitter_ms = (rng.next_u32() % (policy.max_initial_delay_ms + 1)).into();
+        self.join_wait_ms(now_ms).max(jitter_ms)
+    }
+
+    /// Delay from `now_ms` to the next join request after one which was not accepted: until the
+    /// join and regulatory duty cycles allow it, plus a random part of up to the last off period
+    /// so that devices which started together drift apart.
+    pub(crate) fn join_retry_delay_ms<RNG: RngCore>(&self, rng: &mut RNG, now_ms: u64) -> u64 {
+        self.join_wait_ms(now_ms)
+            + rng.next_u64() % (self.join_backoff.off_ms.max(MIN_JITTER_MS) + 1)
+    }
+
+    /// Time until both the join and regulatory duty cycles allow a join request.
+    fn join_wait_ms(&self, now_ms: u64) -> u64 {
+        self.join_backoff.remaining_ms(now_ms).max(self.time_until_next_tx(now_ms))
+    }
+
+    /// Data rate of join request `attempt` (counted from 0) when rotating down from `dr`, over the
+    /// data rates usable by the region and the radio.
+    pub(crate) fn join_datarate(&self, dr: DR, attempt: u32) -> DR {
+        let dwell_time = self.configuration.tx_params.uplink_dwell_time;
+        let lowest = self.region.min_datarate(DR::_0, dwell_time) as u8;
+        let highest = (dr as u8).max(lowest);
+        let allowed =
+            |dr: &DR| *dr as u8 == lowest || self.region.datarate_allowed(*dr, dwell_time);
+        let rotation = || (lowest..=highest).rev().map(DR::from).filter(allowed);
+        rotation().nth(attempt as usize % rotation().count()).unwrap_or(DR::from(lowest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::region;
+
+    #[test]
+    fn join_duty_cycle_phases() {
+        let mut backoff = JoinBackoff::default();
+        backoff.register(100, 1_000);
+        assert_eq!(backoff.remaining_ms(1_000), 10_000);
+        assert_eq!(backoff.remaining_ms(6_000), 5_000);
+        assert_eq!(backoff.remaining_ms(11_000), 0);
+        backoff.register(100, 1_000 + HOUR_MS);
+        assert_eq!(backoff.remaining_ms(1_000 + HOUR_MS), 100_000);
+        backoff.register(100, 1_000 + ELEVEN_HOURS_MS);
+        assert_eq!(backoff.remaining_ms(1_000 + ELEVEN_HOURS_MS), 1_000_000);
+        backoff.reset();
+        assert_eq!(backoff.remaining_ms(0), 0);
+    }
+
+    #[test]
+    fn join_retry_delay_randomized() {
+        let mut mac = Mac::new(region::Configuration::new(region::Region::US915), 14, 0);
+        mac.join_backoff.register(400, 0);
+        let mut rng = crate::rng::Prng::new(3);
+        let delays: std::vec::Vec<_> =
+            (0..8).map(|_| mac.join_retry_delay_ms(&mut rng, 10_000)).collect();
+        assert!(delays.iter().all(|&delay| (30_000..=70_000).contains(&delay)));
+        assert!(delays.iter().any(|&delay| delay != delays[0]));
+    }
+
+    #[test]
+    fn join_initial_delay_waits_for_duty_cycles() {
+        let mut mac = Mac::new(region::Configuration::new(region::Region::US915), 14, 0);
+        let policy = JoinRetryPolicy { max_initial_delay_ms: 5_000, ..Default::default() };
+        let mut rng = crate::rng::Prng::new(3);
+        assert!(mac.join_initial_delay_ms(&mut rng, &policy, 10_000) <= 5_000);
+        // A join request of a previous call keeps the next one off for 40 s
+        mac.join_backoff.register(400, 0);
+        assert_eq!(mac.join_initial_delay_ms(&mut rng, &policy, 10_000), 30_000);
+        assert!(mac.join_initial_delay_ms(&mut rng, &policy, 40_000) <= 5_000);
+    }
+
+    #[test]
+    fn join_datarate_rotates_down() {
+        let mac = Mac::new(region::Configuration::new(region::Region::EU868), 14, 0);
+        let drs: std::vec::Vec<_> = (0..5).map(|i| mac.join_datarate(DR::_2, i)).collect();
+        assert_eq!(drs, [DR::_2, DR::_1, DR::_0, DR::_2, DR::_1]);
+        assert_eq!(mac.join_datarate(DR::_0, 3), DR::_0);
+        // DR0 and DR1 are not allowed under the uplink dwell time of AS923
+        let mut mac = Mac::new(region::Configuration::new(region::Region::AS923_1), 14, 0);
+        mac.configuration.tx_params.uplink_dwell_time = true;
+        assert_eq!(mac.join_datarate(DR::_3, 2), DR::_3);
+        assert_eq!(mac.join_datarate(DR::_0, 1), DR::_2);
+        // The LR-FHSS data rates DR5 and DR6 of US915 are skipped with a LoRa radio
+        let mut region = region::Configuration::new(region::Region::US915);
+        region.set_modulations(region::SupportedModulations::LORA);
+        let mac = Mac::new(region, 14, 0);
+        let drs: std::vec::Vec<_> = (0..6).map(|i| mac.join_datarate(DR::_6, i)).collect();
+        assert_eq!(drs, [DR::_4, DR::_3, DR::_2, DR::_1, DR::_0, DR::_4]);
+    }
+}
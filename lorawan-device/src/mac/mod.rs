@@ -4,13 +4,18 @@
 
 use crate::{
     AppSKey, Downlink, NwkSKey,
-    radio::{self, RadioBuffer, RfConfig, RxConfig, RxMode},
+    radio::{self, RadioBuffer, RfConfig, RxConfig, RxMode, RxQuality},
     region,
 };
 use heapless::Vec;
 use lora_modulation::BaseBandModulationParams;
+use lorawan::maccommandcreator::{DeviceTimeReqCreator, LinkCheckReqCreator};
 use lorawan::maccommands::SerializableMacCommand;
-use lorawan::parser::DevAddr;
+use lorawan::packet_length::phy::{
+    MHDR_LEN, MIC_LEN,
+    mac::{FPORT_LEN, fhdr::FHDR_MIN_LEN},
+};
+use lorawan::parser::{DevAddr, DevNonce, JoinNonce, PhyPayload, parse};
 use lorawan::types::DR;
 
 pub type FcntDown = u32;
@@ -18,10 +23,26 @@ pub type FcntUp = u32;
 
 mod session;
 use rand_core::RngCore;
-pub use session::{Session, SessionKeys};
+pub use session::{Session, Session1_1, SessionKeys};
 
 mod otaa;
-pub use otaa::NetworkCredentials;
+pub use otaa::{DevNonceStrategy, NetworkCredentials};
+
+mod clock;
+pub use clock::{DeviceClock, GPS_EPOCH_UNIX_SECONDS, GPS_UTC_LEAP_SECONDS};
+
+mod rejoin;
+pub use rejoin::{RejoinParams, RejoinType};
+
+mod join_backoff;
+pub use join_backoff::JoinRetryPolicy;
+
+mod event;
+pub use event::{Change, MacEvent};
+
+mod state;
+pub use state::{DeviceState, STATE_VERSION, StateError};
+pub(crate) use state::{Reader, Writer};
 
 use crate::async_device;
 use crate::nb_device;
@@ -30,6 +51,8 @@ pub(crate) mod uplink;
 
 #[cfg(feature = "certification")]
 pub(crate) mod certification;
+#[cfg(feature = "class-b")]
+pub(crate) mod class_b;
 #[cfg(feature = "multicast")]
 pub(crate) mod multicast;
 
@@ -86,6 +109,95 @@ pub struct Configuration {
     /// When true, uplinks set the FCtrl ADR bit so the network may manage
     /// data rate and TX power via LinkADRReq.
     pub(crate) adr_enabled: bool,
+    /// Aggregated duty cycle of `1 / 2^max_duty_cycle` set by DutyCycleReq, 0 for no limit.
+    pub(crate) max_duty_cycle: u8,
+    pub(crate) tx_params: TxParams,
+    /// Number of transmissions of each unconfirmed uplink set by LinkADRReq.
+    pub(crate) nb_trans: u8,
+    pub(crate) retransmission: RetransmissionPolicy,
+    /// Reported to the network in DevStatusAns.
+    pub(crate) battery_level: BatteryLevel,
+    pub(crate) dev_nonce_strategy: DevNonceStrategy,
+    /// Limit uplinks to the MAC payload sizes which a repeater can relay.
+    pub(crate) repeater_compatible: bool,
+    #[cfg(feature = "class-b")]
+    pub(crate) class_b: class_b::Configuration,
+}
+
+/// Dwell-time and EIRP limits set by TXParamSetupReq (AS923, AU915).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TxParams {
+    /// Uplinks are limited to a dwell time of 400 ms.
+    pub uplink_dwell_time: bool,
+    /// Downlinks are limited to a dwell time of 400 ms.
+    pub downlink_dwell_time: bool,
+    /// Maximum EIRP in dBm, the region default when `None`.
+    pub max_eirp: Option<u8>,
+}
+
+/// Battery status of the device, reported to the network in DevStatusAns.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum BatteryLevel {
+    /// The device is connected to an external power source.
+    ExternalPower,
+    /// Battery level from 1 (empty) to 254 (full), values outside of this range are clamped.
+    Level(u8),
+    /// The device is not able to measure its battery level.
+    #[default]
+    Unknown,
+}
+
+impl BatteryLevel {
+    /// Battery level from a percentage (0 to 100) of the battery capacity.
+    pub fn from_percent(percent: u8) -> Self {
+        Self::Level((1 + percent.min(100) as u16 * 253 / 100) as u8)
+    }
+
+    /// Value of the Battery field of DevStatusAns.
+    pub(crate) fn value(&self) -> u8 {
+        match self {
+            Self::ExternalPower => 0,
+            Self::Level(level) => (*level).clamp(1, 254),
+            Self::Unknown => 255,
+        }
+    }
+}
+
+/// Retransmission of confirmed uplinks which are not acknowledged.
+///
+/// Each retransmission carries the same FCnt and is sent on a newly selected channel, ACK_TIMEOUT
+/// (1 to 3 s, random) after the last receive window of the previous transmission.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct RetransmissionPolicy {
+    /// Retransmissions after the first transmission, none by default.
+    pub retries: u8,
+    /// Step the data rate down every two transmissions, as set out by the regional parameters.
+    pub datarate_step_down: bool,
+}
+
+impl Default for RetransmissionPolicy {
+    fn default() -> Self {
+        Self { retries: 0, datarate_step_down: true }
+    }
+}
+
+/// Answer of the network to LinkCheckReq.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct LinkStatus {
+    /// Link margin in dB of the uplink carrying LinkCheckReq, above the demodulation floor of the
+    /// gateway which received it best.
+    pub margin: u8,
+    /// Number of gateways which received the uplink.
+    pub gateway_count: u8,
+    /// RSSI in dBm of the downlink carrying the answer.
+    pub rssi: i16,
+    /// SNR in dB of the downlink carrying the answer.
+    pub snr: i8,
 }
 
 pub(crate) struct Mac {
@@ -93,12 +205,44 @@ pub(crate) struct Mac {
     pub region: region::Configuration,
     board_eirp: BoardEirp,
     state: State,
+    /// JoinNonce of the last accepted LoRaWAN 1.1 JoinAccept.
+    join_nonce: Option<JoinNonce>,
+    /// DevNonce of the last join request sent with [`DevNonceStrategy::Counter`].
+    dev_nonce: Option<DevNonce>,
+    /// Uplink still to be repeated for NbTrans, or retransmitted until acknowledged.
+    repetition: Option<Repetition>,
+    /// Transmissions of the last uplink.
+    attempts: u8,
+    /// Local time at which the last transmission ended, the reference of DeviceTimeAns.
+    tx_end_ms: Option<u64>,
+    /// Frequency of the last transmission until its outcome goes in the channel statistics.
+    #[cfg(feature = "channel-stats")]
+    tx_frequency: Option<u32>,
+    clock: DeviceClock,
+    /// Answer to the last LinkCheckReq, until taken by the application.
+    link_status: Option<LinkStatus>,
+    events: event::MacEvents,
+    /// Set when the state saved in a [`DeviceState`] changes.
+    state_dirty: bool,
+    rejoin: rejoin::Rejoin,
+    join_backoff: join_backoff::JoinBackoff,
     #[cfg(feature = "certification")]
     certification: certification::Certification,
     #[cfg(feature = "multicast")]
     pub multicast: multicast::Multicast,
 }
 
+/// Copy of the last uplink, which is transmitted again (with the same FCnt) until NbTrans
+/// transmissions (or the retries of a confirmed uplink) are done or a downlink is received.
+struct Repetition {
+    frame: Vec<u8, 256>,
+    fcnt: FcntUp,
+    remaining: u8,
+    confirmed: bool,
+    /// Channel of the last transmission, which the next one avoids.
+    frequency: u32,
+}
+
 struct BoardEirp {
     max_power: u8,
     antenna_gain: i8,
@@ -115,8 +259,28 @@ pub(crate) enum State {
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub enum Error {
     NotJoined,
+    /// The duty cycle does not allow transmitting on any channel yet.
+    DutyCycleRestricted,
+    /// All the DevNonces were used by join requests, the device cannot join anymore with its
+    /// current keys.
+    DevNonceExhausted,
+    /// Rejoin requests need a LoRaWAN 1.1 session and the credentials of the device, and stop
+    /// once their counter is used up.
+    RejoinUnavailable,
+    /// MAC commands were dropped as the room for pending MAC commands was used up: the requested
+    /// one, or answers to a downlink, see `check_dropped_mac_commands` of the devices.
+    MacCommandsDropped,
+    /// The application payload is longer than the `max` bytes allowed in the next uplink, see
+    /// `max_payload_len` of the devices.
+    PayloadTooLarge {
+        max: usize,
+    },
+    /// Listen-before-talk found every channel it tried busy, the uplink was not sent.
+    ChannelBusy,
     #[cfg(feature = "multicast")]
     Multicast(multicast::Error),
+    #[cfg(feature = "class-b")]
+    InvalidPeriodicity,
 }
 
 pub struct SendData<'a> {
@@ -134,6 +298,19 @@ impl Mac {
             board_eirp: BoardEirp { max_power, antenna_gain },
             region,
             state: State::Unjoined,
+            join_nonce: None,
+            dev_nonce: None,
+            repetition: None,
+            attempts: 0,
+            tx_end_ms: None,
+            #[cfg(feature = "channel-stats")]
+            tx_frequency: None,
+            clock: DeviceClock::default(),
+            link_status: None,
+            events: event::MacEvents::default(),
+            state_dirty: false,
+            rejoin: rejoin::Rejoin::default(),
+            join_backoff: join_backoff::JoinBackoff::default(),
             configuration: Configuration {
                 data_rate,
                 rx1_delay: region::constants::RECEIVE_DELAY1,
@@ -144,6 +321,15 @@ impl Mac {
                 rx2_frequency: None,
                 tx_power: None,
                 adr_enabled: true,
+                max_duty_cycle: 0,
+                tx_params: TxParams::default(),
+                nb_trans: 1,
+                retransmission: RetransmissionPolicy::default(),
+                battery_level: BatteryLevel::default(),
+                dev_nonce_strategy: DevNonceStrategy::default(),
+                repeater_compatible: false,
+                #[cfg(feature = "class-b")]
+                class_b: Default::default(),
             },
             #[cfg(feature = "certification")]
             certification: certification::Certification::new(),
@@ -154,29 +340,94 @@ impl Mac {
 
     /// Prepare the radio buffer with transmitting a join request frame and provides the radio
     /// configuration for the transmission along with the RX window configurations bound to it.
+    ///
+    /// `now_ms` is the current time used for duty-cycle accounting, if the caller has a clock.
     pub(crate) fn join_otaa<RNG: RngCore, const N: usize>(
         &mut self,
         rng: &mut RNG,
         credentials: NetworkCredentials,
         buf: &mut RadioBuffer<N>,
-    ) -> (radio::TxConfig, RxWindows, u16) {
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, RxWindows, u16)> {
+        let (mut tx_config, tx_channel) = self.create_tx_config(rng, &Frame::Join, now_ms)?;
+        let dev_nonce = self.next_dev_nonce(rng)?;
+        self.rejoin.reset();
+        self.rejoin.set_credentials(credentials.clone());
         let mut otaa = otaa::Otaa::new(credentials);
-        let dev_nonce = otaa.prepare_buffer::<RNG, N>(rng, buf);
+        let dev_nonce = otaa.prepare_buffer::<N>(dev_nonce, buf);
         self.state = State::Otaa(otaa);
-        let (mut tx_config, tx_channel) =
-            self.region.create_tx_config(rng, self.configuration.data_rate, &Frame::Join);
+        self.repetition = None;
+        self.register_tx(&tx_config, buf, now_ms);
+        if let Some(now_ms) = now_ms {
+            let time_on_air_ms = region::time_on_air_ms(&tx_config, buf.as_ref_for_read().len());
+            self.join_backoff.register(time_on_air_ms, now_ms);
+        }
         tx_config.adjust_power(self.board_eirp.max_power, self.board_eirp.antenna_gain);
-        (tx_config, self.rx_windows(&tx_channel), dev_nonce)
+        Ok((tx_config, self.rx_windows(&tx_channel), dev_nonce))
+    }
+
+    /// DevNonce of the next join request. With [`DevNonceStrategy::Counter`] it is the DevNonce of
+    /// the last join request plus one, which is saved right away.
+    fn next_dev_nonce<RNG: RngCore>(&mut self, rng: &mut RNG) -> Result<DevNonce> {
+        match self.configuration.dev_nonce_strategy {
+            DevNonceStrategy::Counter => {
+                let value = match self.dev_nonce {
+                    Some(last) => last.value().checked_add(1).ok_or(Error::DevNonceExhausted)?,
+                    None => 0,
+                };
+                self.dev_nonce = Some(DevNonce::from_value(value));
+                self.state_dirty = true;
+                Ok(DevNonce::from_value(value))
+            }
+            DevNonceStrategy::Random => Ok(DevNonce::from_value(rng.next_u32() as u16)),
+        }
+    }
+
+    pub(crate) fn dev_nonce(&self) -> Option<DevNonce> {
+        self.dev_nonce
+    }
+
+    /// Restore the DevNonce of the last join request. It never moves backwards.
+    pub(crate) fn set_dev_nonce(&mut self, dev_nonce: DevNonce) {
+        if self.dev_nonce.is_none_or(|last| dev_nonce.value() > last.value()) {
+            self.dev_nonce = Some(dev_nonce);
+        }
     }
 
     /// Join via ABP. This does not transmit a join request frame, but instead sets the session.
     pub(crate) fn join_abp(&mut self, nwkskey: NwkSKey, appskey: AppSKey, devaddr: DevAddr) {
         self.state = State::Joined(Session::new(nwkskey, appskey, devaddr));
+        self.repetition = None;
+        self.state_dirty = true;
     }
 
     /// Join via ABP. This does not transmit a join request frame, but instead sets the session.
     pub(crate) fn set_session(&mut self, session: Session) {
+        if let Some(v1_1) = session.lorawan_1_1() {
+            self.set_join_nonce(v1_1.join_nonce);
+        }
+        self.configuration.tx_params = *session.tx_params();
         self.state = State::Joined(session);
+        self.repetition = None;
+        self.rejoin.reset();
+        self.join_backoff.reset();
+    }
+
+    /// Set the credentials securing rejoin requests, which are otherwise those of the last OTAA
+    /// join, eg: for a session restored from a [`DeviceState`].
+    pub(crate) fn set_rejoin_credentials(&mut self, credentials: NetworkCredentials) {
+        self.rejoin.set_credentials(credentials);
+    }
+
+    pub(crate) fn join_nonce(&self) -> Option<JoinNonce> {
+        self.join_nonce
+    }
+
+    /// Restore the last accepted JoinNonce. It never moves backwards.
+    pub(crate) fn set_join_nonce(&mut self, join_nonce: JoinNonce) {
+        if self.join_nonce.is_none_or(|last| join_nonce.value() > last.value()) {
+            self.join_nonce = Some(join_nonce);
+        }
     }
 
     /// Prepare the radio buffer for transmitting a data frame and provide the radio configuration
@@ -186,16 +437,108 @@ impl Mac {
         rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
         send_data: &SendData<'_>,
+        now_ms: Option<u64>,
     ) -> Result<(radio::TxConfig, RxWindows, FcntUp)> {
-        let fcnt = match &mut self.state {
-            State::Joined(session) => {
-                Ok(session.prepare_buffer::<N>(send_data, buf, &self.configuration, &self.region))
+        self.prepare_uplink(rng, buf, Some(send_data), now_ms)
+    }
+
+    /// Like [`Self::send`], for an unconfirmed uplink without FPort which only carries the queued
+    /// MAC commands.
+    pub(crate) fn send_mac_uplink<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, RxWindows, FcntUp)> {
+        self.prepare_uplink(rng, buf, None, now_ms)
+    }
+
+    /// Whether MAC answers the network waits for, ADRACKReq, or MAC commands overflowing FOpts
+    /// call for an uplink of their own (see [`Self::send_mac_uplink`]).
+    pub(crate) fn mac_uplink_due(&self) -> bool {
+        self.get_session().is_some_and(|session| session.mac_uplink_due())
+    }
+
+    /// Largest application payload of the next uplink: the MAC payload allowed at the data rate it
+    /// goes out at under the dwell time, less the FHDR with the pending FOpts and the FPort. It is
+    /// 0 when the device is not joined.
+    pub(crate) fn max_payload_len(&self) -> usize {
+        let State::Joined(session) = &self.state else {
+            return 0;
+        };
+        let dwell_time = self.configuration.tx_params.uplink_dwell_time;
+        let dr = self.region.data_datarate(self.configuration.data_rate, dwell_time);
+        let max_mac_payload = self.region.get_max_payload_length(
+            dr,
+            self.configuration.repeater_compatible,
+            dwell_time,
+        );
+        (max_mac_payload as usize)
+            .saturating_sub(FHDR_MIN_LEN + session.next_f_opts_len() + FPORT_LEN)
+    }
+
+    /// Fails with [`Error::MacCommandsDropped`] if answers to MAC commands were dropped for lack
+    /// of room since the last call.
+    pub(crate) fn check_dropped_mac_commands(&mut self) -> Result<()> {
+        let dropped = match &mut self.state {
+            State::Joined(session) => session.uplink.take_dropped(),
+            State::Otaa(_) | State::Unjoined => false,
+        };
+        if dropped {
+            Err(Error::MacCommandsDropped)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn prepare_uplink<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        send_data: Option<&SendData<'_>>,
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, RxWindows, FcntUp)> {
+        if !self.is_joined() {
+            return Err(Error::NotJoined);
+        }
+        if let Some(send_data) = send_data {
+            let max = self.max_payload_len();
+            if send_data.data.len() > max {
+                return Err(Error::PayloadTooLarge { max });
             }
+        }
+        // The channel is selected first since LoRaWAN 1.1 covers it in the uplink MIC.
+        let (mut tx_config, tx_channel) = self.create_tx_config(rng, &Frame::Data, now_ms)?;
+        let (fcnt, confirmed) = match &mut self.state {
+            State::Joined(session) => Ok((
+                session.prepare_buffer::<N>(
+                    send_data,
+                    buf,
+                    &self.configuration,
+                    &self.region,
+                    &tx_channel,
+                ),
+                session.confirmed,
+            )),
             State::Otaa(_) => Err(Error::NotJoined),
             State::Unjoined => Err(Error::NotJoined),
         }?;
-        let (mut tx_config, tx_channel) =
-            self.region.create_tx_config(rng, self.configuration.data_rate, &Frame::Data);
+        let remaining = if confirmed {
+            self.configuration.retransmission.retries
+        } else {
+            self.configuration.nb_trans.saturating_sub(1)
+        };
+        self.repetition = (remaining > 0).then(|| Repetition {
+            // The radio buffer never holds more than a 256 byte frame.
+            frame: Vec::from_slice(buf.as_ref_for_read()).unwrap(),
+            fcnt,
+            remaining,
+            confirmed,
+            frequency: tx_config.rf.frequency,
+        });
+        self.attempts = 1;
+        self.rejoin.uplink_sent();
+        self.register_tx(&tx_config, buf, now_ms);
         tx_config.adjust_power(
             self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
             self.board_eirp.antenna_gain,
@@ -203,6 +546,158 @@ impl Mac {
         Ok((tx_config, self.rx_windows(&tx_channel), fcnt))
     }
 
+    /// Delay from the end of the last receive window to the next transmission of the last uplink:
+    /// none for NbTrans repetitions, ACK_TIMEOUT for retransmissions of a confirmed uplink.
+    pub(crate) fn repetition_delay_ms<RNG: RngCore>(&self, rng: &mut RNG) -> Option<u32> {
+        use region::constants::{ACK_TIMEOUT, ACK_TIMEOUT_RAND};
+        let repetition = self.repetition.as_ref()?;
+        Some(match repetition.confirmed {
+            true => ACK_TIMEOUT - ACK_TIMEOUT_RAND + rng.next_u32() % (2 * ACK_TIMEOUT_RAND + 1),
+            false => 0,
+        })
+    }
+
+    /// Transmissions of the last uplink so far, including NbTrans repetitions and
+    /// retransmissions of a confirmed uplink.
+    pub(crate) fn attempts(&self) -> u8 {
+        self.attempts
+    }
+
+    /// Prepare the radio buffer for the next transmission of the last uplink, on a newly selected
+    /// channel, when NbTrans or the retransmission policy asks for it. Returns `None` once all
+    /// transmissions are done or a downlink was received since; the caller then completes with
+    /// `rx2_complete()`.
+    pub(crate) fn repeat_send<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        now_ms: Option<u64>,
+    ) -> Option<Result<(radio::TxConfig, RxWindows, FcntUp)>> {
+        let mut repetition = self.repetition.take()?;
+        // A downlink received in between moved the frame counter on.
+        if self.get_session()?.fcnt_up != repetition.fcnt {
+            return None;
+        }
+        let previous_dr = self.configuration.data_rate;
+        if repetition.confirmed
+            && self.configuration.retransmission.datarate_step_down
+            && self.attempts.is_multiple_of(2)
+        {
+            self.step_down_datarate(repetition.frame.len());
+        }
+        // The channel which missed the acknowledgment is left out of the selection already, as
+        // the previous one.
+        let previous_frequency = Some(repetition.frequency);
+        let (mut tx_config, tx_channel) =
+            match self.create_repeated_tx_config(rng, &Frame::Data, now_ms, previous_frequency) {
+                Ok(config) => config,
+                Err(e) => {
+                    self.configuration.data_rate = previous_dr;
+                    self.repetition = Some(repetition);
+                    return Some(Err(e));
+                }
+            };
+        // The acknowledgment is missed once the retransmission takes place.
+        #[cfg(feature = "channel-stats")]
+        if repetition.confirmed {
+            self.register_missed_ack();
+        }
+        buf.clear();
+        // The frame was copied from a buffer of the same size.
+        buf.extend_from_slice(&repetition.frame).unwrap();
+        if let Some(session) = self.get_session() {
+            session.prepare_repetition(buf, &tx_channel);
+        }
+        self.register_tx(&tx_config, buf, now_ms);
+        tx_config.adjust_power(
+            self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
+            self.board_eirp.antenna_gain,
+        );
+        repetition.remaining -= 1;
+        repetition.frequency = tx_config.rf.frequency;
+        self.attempts = self.attempts.saturating_add(1);
+        let fcnt = repetition.fcnt;
+        if repetition.remaining > 0 {
+            self.repetition = Some(repetition);
+        }
+        Some(Ok((tx_config, self.rx_windows(&tx_channel), fcnt)))
+    }
+
+    /// Lower the data rate by one step for a retransmission, unless a frame of `len` bytes would
+    /// no longer fit.
+    fn step_down_datarate(&mut self, len: usize) {
+        let Some(dr) = session::next_lower_datarate(&self.region, &self.configuration) else {
+            return;
+        };
+        let dwell_time = self.configuration.tx_params.uplink_dwell_time;
+        let fits = self.region.get_datarate(dr as u8).is_some_and(|datarate| {
+            len <= datarate.max_payload_size(dwell_time) as usize + MHDR_LEN + MIC_LEN
+        });
+        if fits {
+            self.configuration.data_rate = dr;
+        }
+    }
+
+    /// Queue DeviceTimeReq for the next uplink.
+    pub(crate) fn request_device_time(&mut self) -> Result<()> {
+        match &mut self.state {
+            State::Joined(session) => {
+                if session.uplink.try_add_mac_command(DeviceTimeReqCreator::new()) {
+                    Ok(())
+                } else {
+                    Err(Error::MacCommandsDropped)
+                }
+            }
+            State::Otaa(_) | State::Unjoined => Err(Error::NotJoined),
+        }
+    }
+
+    /// Queue LinkCheckReq for the next uplink.
+    pub(crate) fn request_link_check(&mut self) -> Result<()> {
+        match &mut self.state {
+            State::Joined(session) => {
+                if session.uplink.try_add_mac_command(LinkCheckReqCreator::new()) {
+                    Ok(())
+                } else {
+                    Err(Error::MacCommandsDropped)
+                }
+            }
+            State::Otaa(_) | State::Unjoined => Err(Error::NotJoined),
+        }
+    }
+
+    pub(crate) fn take_link_status(&mut self) -> Option<LinkStatus> {
+        self.link_status.take()
+    }
+
+    pub(crate) fn take_mac_event(&mut self) -> Option<MacEvent> {
+        self.events.pop()
+    }
+
+    #[cfg(feature = "channel-stats")]
+    pub(crate) fn channel_stats(&self, frequency: u32) -> Option<region::ChannelStats> {
+        self.region.channel_stats.get(frequency)
+    }
+
+    #[cfg(feature = "channel-stats")]
+    pub(crate) fn set_channel_avoidance(&mut self, avoidance: Option<region::ChannelAvoidance>) {
+        self.region.channel_stats.avoidance = avoidance;
+    }
+
+    #[cfg(feature = "channel-stats")]
+    pub(crate) fn clear_channel_stats(&mut self) {
+        self.region.channel_stats.clear();
+    }
+
+    /// Record the local time at which a transmission ended.
+    pub(crate) fn tx_done(&mut self, now_ms: u64) {
+        self.tx_end_ms = Some(now_ms);
+    }
+
+    pub(crate) fn clock(&self) -> &DeviceClock {
+        &self.clock
+    }
+
     pub(crate) fn add_uplink<M: SerializableMacCommand>(&mut self, cmd: M) -> Result<()> {
         let _fcnt = match &mut self.state {
             State::Joined(session) => {
@@ -220,19 +715,23 @@ impl Mac {
         &mut self,
         rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
+        now_ms: Option<u64>,
     ) -> Result<(radio::TxConfig, FcntUp)> {
-        self.multicast.setup_send::<N>(&mut self.state, buf, &self.configuration, &self.region).map(
-            |fcnt_up| {
-                // No RX windows follow this uplink; the caller re-arms the RXC window.
-                let (mut tx_config, _) =
-                    self.region.create_tx_config(rng, self.configuration.data_rate, &Frame::Data);
-                tx_config.adjust_power(
-                    self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
-                    self.board_eirp.antenna_gain,
-                );
-                (tx_config, fcnt_up)
-            },
-        )
+        // No RX windows follow this uplink; the caller re-arms the RXC window.
+        let (mut tx_config, tx_channel) = self.create_tx_config(rng, &Frame::Data, now_ms)?;
+        let fcnt_up = self.multicast.setup_send::<N>(
+            &mut self.state,
+            buf,
+            &self.configuration,
+            &self.region,
+            &tx_channel,
+        )?;
+        self.register_tx(&tx_config, buf, now_ms);
+        tx_config.adjust_power(
+            self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
+            self.board_eirp.antenna_gain,
+        );
+        Ok((tx_config, fcnt_up))
     }
 
     #[cfg(feature = "certification")]
@@ -240,16 +739,149 @@ impl Mac {
         &mut self,
         rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
+        now_ms: Option<u64>,
     ) -> Result<(radio::TxConfig, FcntUp)> {
-        self.certification
-            .setup_send::<N>(&mut self.state, buf, &self.configuration, &self.region)
-            .map(|fcnt_up| {
-                // No RX windows follow this uplink; the caller completes with rx2_complete().
-                let (mut tx_config, _) =
-                    self.region.create_tx_config(rng, self.configuration.data_rate, &Frame::Data);
-                tx_config.adjust_power(self.board_eirp.max_power, self.board_eirp.antenna_gain);
-                (tx_config, fcnt_up)
-            })
+        // No RX windows follow this uplink; the caller completes with rx2_complete().
+        let (mut tx_config, tx_channel) = self.create_tx_config(rng, &Frame::Data, now_ms)?;
+        let fcnt_up = self.certification.setup_send::<N>(
+            &mut self.state,
+            buf,
+            &self.configuration,
+            &self.region,
+            &tx_channel,
+        )?;
+        self.register_tx(&tx_config, buf, now_ms);
+        tx_config.adjust_power(self.board_eirp.max_power, self.board_eirp.antenna_gain);
+        Ok((tx_config, fcnt_up))
+    }
+
+    /// Time until the duty cycle allows the next uplink: a data frame once joined, otherwise a
+    /// join request.
+    pub(crate) fn time_until_next_tx(&self, now_ms: u64) -> u64 {
+        let frame = if self.is_joined() {
+            Frame::Data
+        } else {
+            Frame::Join
+        };
+        self.region.time_until_next_tx(&frame, now_ms)
+    }
+
+    fn create_tx_config<RNG: RngCore>(
+        &mut self,
+        rng: &mut RNG,
+        frame: &Frame,
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, region::TxChannel)> {
+        self.create_repeated_tx_config(rng, frame, now_ms, None)
+    }
+
+    /// Like [`Self::create_tx_config`], avoiding the channel on `previous_frequency` if another
+    /// one is available.
+    fn create_repeated_tx_config<RNG: RngCore>(
+        &mut self,
+        rng: &mut RNG,
+        frame: &Frame,
+        now_ms: Option<u64>,
+        previous_frequency: Option<u32>,
+    ) -> Result<(radio::TxConfig, region::TxChannel)> {
+        let dr = self.configuration.data_rate;
+        self.check_duty_cycle(frame, now_ms)?;
+        let tx_params = &self.configuration.tx_params;
+        Ok(self.region.create_repeated_tx_config(
+            rng,
+            dr,
+            frame,
+            tx_params,
+            now_ms,
+            previous_frequency,
+        ))
+    }
+
+    /// Like [`Self::create_tx_config`], at data rate `dr` instead of the configured one.
+    fn create_tx_config_at<RNG: RngCore>(
+        &mut self,
+        rng: &mut RNG,
+        dr: DR,
+        frame: &Frame,
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, region::TxChannel)> {
+        self.check_duty_cycle(frame, now_ms)?;
+        Ok(self.region.create_tx_config(rng, dr, frame, &self.configuration.tx_params, now_ms))
+    }
+
+    /// Fail if the duty cycle closes all the channels for `frame`, and otherwise forget the
+    /// channels found busy before a new transmission.
+    fn check_duty_cycle(&mut self, frame: &Frame, now_ms: Option<u64>) -> Result<()> {
+        if let Some(now_ms) = now_ms
+            && self.region.time_until_next_tx(frame, now_ms) > 0
+        {
+            return Err(Error::DutyCycleRestricted);
+        }
+        self.region.clear_busy_channels();
+        Ok(())
+    }
+
+    pub(crate) fn get_listen_before_talk(&self) -> Option<region::ListenBeforeTalk> {
+        self.region.get_listen_before_talk()
+    }
+
+    /// Prepare the frame in the radio buffer for another channel, after listen-before-talk found
+    /// the channel of `tx_config` busy. Nothing was transmitted, so the attempt is not accounted
+    /// for in the duty cycle, and the busy channel is skipped until the next uplink.
+    pub(crate) fn channel_busy<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        frame: &Frame,
+        tx_config: &radio::TxConfig,
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, RxWindows)> {
+        let max_duty_cycle = self.configuration.max_duty_cycle;
+        let (dr, tx_params) = (self.configuration.data_rate, self.configuration.tx_params);
+        if !self.region.channel_busy(tx_config, max_duty_cycle, now_ms)
+            || !self.region.has_tx_channel(dr, frame, &tx_params, now_ms)
+        {
+            return Err(Error::ChannelBusy);
+        }
+        let (retry_config, tx_channel) =
+            self.region.create_tx_config(rng, dr, frame, &tx_params, now_ms);
+        // The MIC of a LoRaWAN 1.1 data uplink covers the channel, unlike that of a rejoin request.
+        let data = matches!(parse(buf.as_ref_for_read()), Ok(PhyPayload::Data(_)));
+        if let (true, Some(session)) = (data, self.get_session()) {
+            session.prepare_repetition(buf, &tx_channel);
+            if let Some(repetition) = &mut self.repetition {
+                repetition.frequency = retry_config.rf.frequency;
+            }
+        }
+        self.register_tx(&retry_config, buf, now_ms);
+        // The TX power was already adjusted for the frame.
+        let tx_config = radio::TxConfig { rf: retry_config.rf, ..*tx_config };
+        Ok((tx_config, self.rx_windows(&tx_channel)))
+    }
+
+    fn register_tx<const N: usize>(
+        &mut self,
+        tx_config: &radio::TxConfig,
+        buf: &RadioBuffer<N>,
+        now_ms: Option<u64>,
+    ) {
+        if let Some(now_ms) = now_ms {
+            let len = buf.as_ref_for_read().len();
+            self.region.register_tx(tx_config, len, self.configuration.max_duty_cycle, now_ms);
+        }
+        #[cfg(feature = "channel-stats")]
+        {
+            self.region.channel_stats.register_tx(tx_config.rf.frequency);
+            self.tx_frequency = Some(tx_config.rf.frequency);
+        }
+    }
+
+    /// Account for the last transmission, of a confirmed uplink, not being acknowledged.
+    #[cfg(feature = "channel-stats")]
+    fn register_missed_ack(&mut self) {
+        if let Some(frequency) = self.tx_frequency.take() {
+            self.region.channel_stats.register_missed_ack(frequency, self.tx_end_ms);
+        }
     }
 
     pub(crate) fn get_rx_delay(&self, frame: &Frame, window: &Window) -> u32 {
@@ -275,28 +907,65 @@ impl Mac {
         &mut self,
         buf: &mut RadioBuffer<N>,
         dl: &mut Vec<Downlink, D>,
-        snr: i8,
+        quality: RxQuality,
         rf_config: &RfConfig,
     ) -> Response {
+        if self.is_rejoining() {
+            return self.handle_rejoin_accept::<N>(buf);
+        }
         match &mut self.state {
-            State::Joined(session) => session.handle_rx::<N, D>(
-                &mut self.region,
-                &mut self.configuration,
-                #[cfg(feature = "certification")]
-                &mut self.certification,
-                #[cfg(feature = "multicast")]
-                &mut self.multicast,
-                buf,
-                dl,
-                rf_config.max_payload_len,
-                snr,
-                false,
-            ),
-            State::Otaa(otaa) => {
-                if let Some(session) =
-                    otaa.handle_rx::<N>(&mut self.region, &mut self.configuration, buf)
+            State::Joined(session) => {
+                let response = session.handle_rx::<N, D>(
+                    &mut self.region,
+                    &mut self.configuration,
+                    #[cfg(feature = "certification")]
+                    &mut self.certification,
+                    #[cfg(feature = "multicast")]
+                    &mut self.multicast,
+                    &mut self.events,
+                    buf,
+                    dl,
+                    rf_config.max_payload_len,
+                    quality.snr(),
+                    false,
+                );
+                // A downlink ends the NbTrans repetitions of the uplink.
+                if !matches!(response, Response::NoUpdate) {
+                    self.repetition = None;
+                    self.state_dirty = true;
+                    #[cfg(feature = "channel-stats")]
+                    if let Some(frequency) = self.tx_frequency.take() {
+                        let channel_stats = &mut self.region.channel_stats;
+                        channel_stats.register_answer(frequency, quality.rssi(), quality.snr());
+                    }
+                }
+                if let Some(gps_ms) = session.take_device_time()
+                    && let Some(tx_end_ms) = self.tx_end_ms
                 {
-                    self.state = State::Joined(session);
+                    self.clock.synchronize(gps_ms, tx_end_ms);
+                }
+                if let Some((margin, gateway_count)) = session.take_link_check() {
+                    self.link_status = Some(LinkStatus {
+                        margin,
+                        gateway_count,
+                        rssi: quality.rssi(),
+                        snr: quality.snr(),
+                    });
+                }
+                if let Some(request) = session.take_force_rejoin() {
+                    self.rejoin.force(request);
+                }
+                response
+            }
+            State::Otaa(otaa) => {
+                if let Some(session) = otaa.handle_rx::<N>(
+                    &mut self.region,
+                    &mut self.configuration,
+                    buf,
+                    self.join_nonce,
+                ) {
+                    self.set_session(session);
+                    self.state_dirty = true;
                     Response::JoinSuccess
                 } else {
                     Response::NoUpdate
@@ -306,10 +975,11 @@ impl Mac {
         }
     }
 
-    /// Handles a received RF frame during RXC window. Returns None if unparseable, fails decryption,
-    /// or fails MIC verification. Upon successful data rx, provides Response::DownlinkReceived.
-    /// User must later call `take_downlink()` on the device to get the application data.
-    #[cfg(feature = "class-c")]
+    /// Handles a received RF frame during RXC or ping-slot window. Returns None if unparseable,
+    /// fails decryption, or fails MIC verification. Upon successful data rx, provides
+    /// Response::DownlinkReceived. User must later call `take_downlink()` on the device to get the
+    /// application data.
+    #[cfg(any(feature = "class-b", feature = "class-c"))]
     pub(crate) fn handle_rxc<const N: usize, const D: usize>(
         &mut self,
         buf: &mut RadioBuffer<N>,
@@ -317,31 +987,50 @@ impl Mac {
         snr: i8,
         rf_config: &RfConfig,
     ) -> Result<Response> {
-        match &mut self.state {
-            State::Joined(session) => Ok(session.handle_rx::<N, D>(
+        let response = match &mut self.state {
+            State::Joined(session) => session.handle_rx::<N, D>(
                 &mut self.region,
                 &mut self.configuration,
                 #[cfg(feature = "certification")]
                 &mut self.certification,
                 #[cfg(feature = "multicast")]
                 &mut self.multicast,
+                &mut self.events,
                 buf,
                 dl,
                 rf_config.max_payload_len,
                 snr,
                 true,
-            )),
-            State::Otaa(_) => Err(Error::NotJoined),
-            State::Unjoined => Err(Error::NotJoined),
+            ),
+            State::Otaa(_) => return Err(Error::NotJoined),
+            State::Unjoined => return Err(Error::NotJoined),
+        };
+        if !matches!(response, Response::NoUpdate) {
+            self.state_dirty = true;
         }
+        Ok(response)
     }
 
     pub(crate) fn rx2_complete(&mut self) -> Response {
-        match &mut self.state {
+        if let Some(response) = self.rejoin_rx2_complete() {
+            return response;
+        }
+        let (configuration, fcnt_up) = (self.configuration, self.get_fcnt_up());
+        let response = match &mut self.state {
             State::Joined(session) => session.rx2_complete(&mut self.configuration, &self.region),
             State::Otaa(otaa) => otaa.rx2_complete(),
             State::Unjoined => Response::NoUpdate,
+        };
+        #[cfg(feature = "channel-stats")]
+        if matches!(response, Response::NoAck) {
+            self.register_missed_ack();
         }
+        // The uplink frame counter moves forward once the exchange is over, and ADR backoff may
+        // have changed the data rate or the TX power.
+        if self.get_fcnt_up() != fcnt_up || self.configuration != configuration {
+            self.state_dirty = true;
+        }
+        response
     }
 
     pub(crate) fn get_session_keys(&self) -> Option<SessionKeys> {
@@ -404,7 +1093,9 @@ impl Mac {
                 datarate.bandwidth,
                 self.region.get_coding_rate(),
             ),
-            max_payload_len: datarate.max_mac_payload_size,
+            modulation: datarate.modulation,
+            max_payload_len: datarate
+                .max_payload_size(self.configuration.tx_params.downlink_dwell_time),
         }
     }
 
@@ -428,6 +1119,10 @@ impl Mac {
             self.configuration.rx1_dr_offset,
             &Window::_1,
         );
+        // Data rates unusable under the downlink dwell time raise the RX1 data rate, eg: to DR2
+        // in AS923.
+        let rx1_dr =
+            self.region.min_datarate(rx1_dr, self.configuration.tx_params.downlink_dwell_time);
         RxWindows {
             rx1: self.build_rf_config(tx_channel.rx1_frequency, rx1_dr, tx_channel.dr, &Window::_1),
             rx2: self.rx2_rf_config(tx_channel.dr),
@@ -438,6 +1133,89 @@ impl Mac {
     pub(crate) fn get_rxc_config(&self) -> RxConfig {
         RxConfig { rf: self.rx2_rf_config(self.configuration.data_rate), mode: RxMode::Continuous }
     }
+
+    /// Request ping slots every `2^periodicity` seconds. The PingSlotInfoReq is sent with the next
+    /// uplink and ping slots are opened once the network answered it.
+    #[cfg(feature = "class-b")]
+    pub(crate) fn enable_class_b(&mut self, periodicity: u8) -> Result<()> {
+        let mut cmd = lorawan::maccommandcreator::PingSlotInfoReqCreator::new();
+        cmd.set_periodicity(periodicity).map_err(|_| Error::InvalidPeriodicity)?;
+        match &mut self.state {
+            State::Joined(session) => session.uplink.add_mac_command(cmd),
+            State::Otaa(_) | State::Unjoined => return Err(Error::NotJoined),
+        }
+        self.configuration.class_b.pending_periodicity = Some(periodicity);
+        Ok(())
+    }
+
+    #[cfg(feature = "class-b")]
+    pub(crate) fn disable_class_b(&mut self) {
+        let class_b = &mut self.configuration.class_b;
+        class_b.pending_periodicity = None;
+        class_b.periodicity = None;
+        class_b.beacon = class_b::Beacon::Searching;
+    }
+
+    /// RX configuration for the beacon opening the period at `beacon_time`.
+    #[cfg(feature = "class-b")]
+    pub(crate) fn get_beacon_rx_config(&self, beacon_time: u32, window_ms: u32) -> RxConfig {
+        let frequency = self
+            .configuration
+            .class_b
+            .beacon_frequency
+            .unwrap_or_else(|| self.region.get_beacon_frequency(beacon_time));
+        let rf = self.class_b_rf_config(frequency, self.region.get_beacon_datarate());
+        let len = self.region.get_beacon_layout().frame_len() as u8;
+        RxConfig { rf, mode: RxMode::Beacon { len, ms: window_ms } }
+    }
+
+    /// RX configuration and offset (in slots) of the ping slots during the period opened at
+    /// `beacon_time`. Returns `None` unless joined and the network acknowledged the periodicity.
+    #[cfg(feature = "class-b")]
+    pub(crate) fn get_ping_slot_rx_config(
+        &self,
+        beacon_time: u32,
+        window_ms: u32,
+    ) -> Option<(RxConfig, u16)> {
+        use lorawan::{beacon, default_crypto::DefaultCrypto, keys::AES128};
+
+        let class_b = &self.configuration.class_b;
+        let periodicity = class_b.periodicity?;
+        let devaddr = self.get_session()?.devaddr();
+        let frequency = class_b
+            .ping_slot_frequency
+            .unwrap_or_else(|| self.region.get_ping_slot_frequency(devaddr.value(), beacon_time));
+        let dr = class_b.ping_slot_data_rate.unwrap_or_else(|| self.region.get_beacon_datarate());
+        let rf = self.class_b_rf_config(frequency, dr);
+        let crypto = DefaultCrypto::new(&AES128([0; 16]));
+        let offset = beacon::ping_offset(&crypto, beacon_time, devaddr, periodicity);
+        Some((RxConfig { rf, mode: RxMode::Single { ms: window_ms } }, offset))
+    }
+
+    /// Validates a received beacon and returns its time.
+    #[cfg(feature = "class-b")]
+    pub(crate) fn handle_beacon<const N: usize>(&self, buf: &RadioBuffer<N>) -> Option<u32> {
+        let layout = self.region.get_beacon_layout();
+        lorawan::beacon::Beacon::parse(buf.as_ref_for_read(), layout).ok().map(|b| b.time())
+    }
+
+    #[cfg(feature = "class-b")]
+    fn class_b_rf_config(&self, frequency: u32, dr: DR) -> RfConfig {
+        // Beacon data rates exist in every region and ping-slot data rates are validated when
+        // set by the network.
+        let datarate = self.region.get_datarate(dr as u8).unwrap();
+        RfConfig {
+            frequency,
+            bb: BaseBandModulationParams::new(
+                datarate.spreading_factor,
+                datarate.bandwidth,
+                self.region.get_coding_rate(),
+            ),
+            modulation: datarate.modulation,
+            max_payload_len: datarate
+                .max_payload_size(self.configuration.tx_params.downlink_dwell_time),
+        }
+    }
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
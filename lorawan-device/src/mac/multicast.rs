@@ -1,11 +1,11 @@
 use crate::Downlink;
-use crate::mac::FcntDown;
+use crate::mac::{FcntDown, Reader, StateError, Writer};
 use crate::radio::RadioBuffer;
 use crate::{async_device, mac};
 use core::fmt::Debug;
 use core::ops::RangeInclusive;
 use lorawan::default_crypto::DefaultCrypto;
-use lorawan::keys::McKEKey;
+use lorawan::keys::{McAppSKey, McKEKey, McNetSKey};
 use lorawan::multicast::parse_downlink_multicast_commands;
 pub use lorawan::multicast::{self, Session};
 use lorawan::multicast::{
@@ -30,6 +30,9 @@ pub enum Response {
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub enum Error {}
 
+/// McKEKey and sessions of the multicast groups read from a state snapshot.
+pub(crate) type StateSessions = (Option<McKEKey>, [Option<Session>; multicast::MAX_GROUPS]);
+
 /// The port used for multicast setup message. The messages are "unicast" and encrypted & sent at
 /// the application layer.
 const REMOTE_MULTICAST_SETUP_PORT: u8 = 200;
@@ -119,6 +122,53 @@ impl Multicast {
         self.range.contains(&port)
     }
 
+    /// Write the McKEKey and the sessions of the multicast groups to a state snapshot.
+    pub(crate) fn write_state(&self, w: &mut Writer) {
+        match &self.mc_k_e_key {
+            Some(key) => {
+                w.u8(1);
+                w.bytes(key.as_ref());
+            }
+            None => w.u8(0),
+        }
+        w.u8(self
+            .sessions
+            .iter()
+            .enumerate()
+            .fold(0, |bitmap, (i, session)| bitmap | (session.is_some() as u8) << i));
+        for session in self.sessions.iter().flatten() {
+            w.bytes(session.multicast_addr().as_wire_bytes());
+            w.bytes(session.mc_net_s_key().as_ref());
+            w.bytes(session.mc_app_s_key().as_ref());
+            w.u32(session.fcnt_down);
+            w.u32(session.max_fcnt_down());
+        }
+    }
+
+    pub(crate) fn read_state(r: &mut Reader<'_>) -> Result<StateSessions, StateError> {
+        let mc_k_e_key = match r.u8()? {
+            0 => None,
+            _ => Some(McKEKey::from(r.array::<16>()?)),
+        };
+        let bitmap = r.u8()?;
+        if bitmap >> multicast::MAX_GROUPS != 0 {
+            return Err(StateError::Invalid);
+        }
+        let mut sessions = [None, None, None, None];
+        for (i, session) in sessions.iter_mut().enumerate() {
+            if bitmap & 1 << i != 0 {
+                *session = Some(Session::new(
+                    McAddr::from_wire_bytes(r.array()?),
+                    McNetSKey::from(r.array::<16>()?),
+                    McAppSKey::from(r.array::<16>()?),
+                    r.u32()?,
+                    r.u32()?,
+                ));
+            }
+        }
+        Ok((mc_k_e_key, sessions))
+    }
+
     /// Checks if a given port is the remote multicast setup port
     pub(crate) fn set_remote_setup_port(&mut self, port: u8) {
         self.remote_setup_port = port;
@@ -209,6 +259,7 @@ impl Multicast {
         buf: &mut RadioBuffer<N>,
         configuration: &mac::Configuration,
         region: &crate::region::Configuration,
+        tx_channel: &crate::region::TxChannel,
     ) -> mac::Result<mac::FcntUp> {
         let send_data = mac::SendData {
             fport: self.remote_setup_port,
@@ -217,7 +268,13 @@ impl Multicast {
         };
         match &mut state {
             mac::State::Joined(session) => {
-                let response = session.prepare_buffer::<N>(&send_data, buf, configuration, region);
+                let response = session.prepare_buffer::<N>(
+                    Some(&send_data),
+                    buf,
+                    configuration,
+                    region,
+                    tx_channel,
+                );
                 self.pending_uplinks.clear();
                 Ok(response)
             }
@@ -1,14 +1,28 @@
 use super::{Response, del_to_delay_ms, session::Session};
 use crate::radio::RadioBuffer;
 use crate::region::Configuration;
-use crate::{AppEui, AppKey, DevEui};
+use crate::{AppEui, AppKey, DevEui, NwkKey};
 use lorawan::creator::JoinRequest;
 use lorawan::default_crypto::DefaultCrypto;
-use lorawan::parser::DecryptedJoinAcceptPayload;
-use rand_core::RngCore;
+use lorawan::keys::{AES128, JSIntKey};
+use lorawan::parser::{DecryptedJoinAcceptPayload, JoinNonce, JoinReqType};
 
 pub(crate) type DevNonce = lorawan::parser::DevNonce;
 
+/// How the DevNonce of join requests is chosen.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DevNonceStrategy {
+    /// Increment the DevNonce of the last join request, as required since LoRaWAN 1.0.4. The
+    /// counter has to survive reboots: it is part of the `DeviceState` snapshot, and can be saved
+    /// with `get_dev_nonce` and restored with `set_dev_nonce`.
+    #[default]
+    Counter,
+    /// Draw a random DevNonce, as done by LoRaWAN 1.0.2/1.0.3 devices. Join servers which
+    /// remember the DevNonces in use reject the join requests once they start to collide.
+    Random,
+}
+
 pub(crate) struct Otaa {
     dev_nonce: DevNonce,
     network_credentials: NetworkCredentials,
@@ -19,6 +33,8 @@ pub struct NetworkCredentials {
     deveui: DevEui,
     appeui: AppEui,
     appkey: AppKey,
+    /// LoRaWAN 1.1 network root key; `None` for a LoRaWAN 1.0.x device.
+    nwkkey: Option<NwkKey>,
 }
 
 impl Otaa {
@@ -28,48 +44,68 @@ impl Otaa {
 
     /// Prepare a join request to be sent. This populates the radio buffer with the request to be
     /// sent, and returns the radio config to use for transmitting.
-    pub(crate) fn prepare_buffer<G: RngCore, const N: usize>(
+    pub(crate) fn prepare_buffer<const N: usize>(
         &mut self,
-        rng: &mut G,
+        dev_nonce: DevNonce,
         buf: &mut RadioBuffer<N>,
     ) -> u16 {
-        self.dev_nonce = DevNonce::from_value(rng.next_u32() as u16);
+        self.dev_nonce = dev_nonce;
         buf.clear();
         let request = JoinRequest {
             join_eui: self.network_credentials.appeui.into(),
             dev_eui: self.network_credentials.deveui.into(),
             dev_nonce: self.dev_nonce,
         };
-        let crypto = DefaultCrypto::new(self.network_credentials.appkey.inner());
+        let crypto = DefaultCrypto::new(self.network_credentials.root_key());
         let len = request.build_into(buf.as_mut(), &crypto).unwrap().len();
         buf.set_pos(len);
         self.dev_nonce.value()
     }
 
+    /// Handle a JoinAccept. A LoRaWAN 1.1 JoinAccept is only accepted when its
+    /// JoinNonce is greater than `last_join_nonce`.
     pub(crate) fn handle_rx<const N: usize>(
         &mut self,
         region: &mut Configuration,
         configuration: &mut super::Configuration,
         rx: &mut RadioBuffer<N>,
+        last_join_nonce: Option<JoinNonce>,
     ) -> Option<Session> {
-        if let Ok(decrypt) = DecryptedJoinAcceptPayload::check_mic_and_decrypt_in_place(
-            rx.as_mut_for_read(),
-            &DefaultCrypto::new(self.network_credentials.appkey.inner()),
-        ) {
-            region.process_join_accept(decrypt.c_f_list().as_ref());
-            configuration.rx1_delay = del_to_delay_ms(decrypt.rx_delay());
-            let dl_settings = decrypt.dl_settings();
-            if let Some(rx1_dr_offset) = region.rx1_dr_offset_validate(dl_settings.rx1_dr_offset())
-            {
-                configuration.rx1_dr_offset = rx1_dr_offset;
+        let credentials = &self.network_credentials;
+        let root_crypto = DefaultCrypto::new(credentials.root_key());
+        let decrypt =
+            DecryptedJoinAcceptPayload::decrypt_in_place(rx.as_mut_for_read(), &root_crypto)
+                .ok()?;
+        let session = match &credentials.nwkkey {
+            Some(nwkkey) if decrypt.opt_neg() => {
+                let js_int_key = JSIntKey::derive_from(
+                    &DefaultCrypto::new(nwkkey.inner()),
+                    &credentials.deveui.into(),
+                );
+                if !decrypt.validate_mic_1_1(
+                    &DefaultCrypto::new(js_int_key.inner()),
+                    JoinReqType::JoinRequest,
+                    &credentials.appeui.into(),
+                    &self.dev_nonce,
+                ) {
+                    return None;
+                }
+                // Replay protection: the network increments JoinNonce with every JoinAccept.
+                if last_join_nonce.is_some_and(|last| decrypt.join_nonce().value() <= last.value())
+                {
+                    return None;
+                }
+                Session::derive_new_1_1(&decrypt, self.dev_nonce, credentials, nwkkey)
             }
-            let rx2_data_rate = dl_settings.rx2_data_rate();
-            if region.get_datarate(rx2_data_rate as u8).is_some() {
-                configuration.rx2_data_rate = Some(rx2_data_rate);
+            _ => {
+                if !decrypt.validate_mic(&root_crypto) {
+                    return None;
+                }
+                Session::derive_new(&decrypt, self.dev_nonce, credentials)
             }
-            return Some(Session::derive_new(&decrypt, self.dev_nonce, &self.network_credentials));
-        }
-        None
+        };
+        apply_join_accept(region, configuration, &decrypt);
+        Some(session)
     }
 
     pub(crate) fn rx2_complete(&mut self) -> Response {
@@ -77,9 +113,32 @@ impl Otaa {
     }
 }
 
+/// Apply the channel plan and the receive window settings of a JoinAccept.
+pub(super) fn apply_join_accept(
+    region: &mut Configuration,
+    configuration: &mut super::Configuration,
+    decrypt: &DecryptedJoinAcceptPayload<'_>,
+) {
+    region.process_join_accept(decrypt.c_f_list().as_ref());
+    configuration.rx1_delay = del_to_delay_ms(decrypt.rx_delay());
+    let dl_settings = decrypt.dl_settings();
+    if let Some(rx1_dr_offset) = region.rx1_dr_offset_validate(dl_settings.rx1_dr_offset()) {
+        configuration.rx1_dr_offset = rx1_dr_offset;
+    }
+    let rx2_data_rate = dl_settings.rx2_data_rate();
+    if region.get_datarate(rx2_data_rate as u8).is_some() {
+        configuration.rx2_data_rate = Some(rx2_data_rate);
+    }
+}
+
 impl NetworkCredentials {
     pub fn new(appeui: AppEui, deveui: DevEui, appkey: AppKey) -> Self {
-        Self { deveui, appeui, appkey }
+        Self { deveui, appeui, appkey, nwkkey: None }
+    }
+
+    /// Credentials of a LoRaWAN 1.1 device. `joineui` is the LoRaWAN 1.1 name of the AppEUI.
+    pub fn new_1_1(joineui: AppEui, deveui: DevEui, nwkkey: NwkKey, appkey: AppKey) -> Self {
+        Self { deveui, appeui: joineui, appkey, nwkkey: Some(nwkkey) }
     }
     pub fn appeui(&self) -> &AppEui {
         &self.appeui
@@ -92,4 +151,17 @@ impl NetworkCredentials {
     pub fn appkey(&self) -> &AppKey {
         &self.appkey
     }
+
+    pub fn nwkkey(&self) -> Option<&NwkKey> {
+        self.nwkkey.as_ref()
+    }
+
+    /// The key securing the join procedure: the NwkKey of a LoRaWAN 1.1 device,
+    /// the AppKey otherwise.
+    pub(crate) fn root_key(&self) -> &AES128 {
+        match &self.nwkkey {
+            Some(nwkkey) => nwkkey.inner(),
+            None => self.appkey.inner(),
+        }
+    }
 }
@@ -0,0 +1,316 @@
+//! Rejoin requests of LoRaWAN 1.1 devices.
+//!
+//! A joined device sends rejoin requests to let the network renew its session while the current
+//! one keeps working: type 0 periodically, as set by RejoinParamSetupReq, types 0 and 2 when the
+//! network asks with ForceRejoinReq, and type 1 on the application's initiative to restore a lost
+//! session, eg: after roaming. The session only changes once a JoinAccept answers the request.
+
+use super::{Error, Frame, Mac, Response, Result, RxWindows, State, otaa};
+use crate::radio::{self, RadioBuffer};
+use lorawan::creator::RejoinRequest;
+use lorawan::default_crypto::DefaultCrypto;
+use lorawan::keys::{JSEncKey, JSIntKey};
+use lorawan::parser::{DecryptedJoinAcceptPayload, DevNonce, JoinReqType};
+use lorawan::types::DR;
+use rand_core::RngCore;
+
+/// Base delay between forced rejoin requests, multiplied by `2^period` of ForceRejoinReq.
+const FORCE_REJOIN_PERIOD: u64 = 32_000;
+/// Upper bound of the random delay added between forced rejoin requests.
+const FORCE_REJOIN_RAND: u32 = 32_000;
+
+/// Type of a rejoin request.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum RejoinType {
+    /// Reset of the session and of the radio parameters.
+    Type0,
+    /// Restore a lost session, answered by the join server of the device.
+    Type1,
+    /// New session keys and DevAddr, the radio parameters are kept.
+    Type2,
+}
+
+impl RejoinType {
+    fn join_req_type(self) -> JoinReqType {
+        match self {
+            Self::Type0 => JoinReqType::RejoinType0,
+            Self::Type1 => JoinReqType::RejoinType1,
+            Self::Type2 => JoinReqType::RejoinType2,
+        }
+    }
+}
+
+/// Periodicity of type 0 rejoin requests set by RejoinParamSetupReq.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RejoinParams {
+    pub max_time_n: u8,
+    pub max_count_n: u8,
+}
+
+impl RejoinParams {
+    /// A type 0 rejoin request is sent after `2^(max_count_n + 4)` uplinks.
+    pub fn max_uplinks(&self) -> u32 {
+        1 << (self.max_count_n.min(15) + 4)
+    }
+
+    /// A type 0 rejoin request is sent at least every `2^(max_time_n + 10)` seconds.
+    pub fn max_time_ms(&self) -> u64 {
+        1000 << (self.max_time_n.min(15) + 10)
+    }
+}
+
+/// Rejoin requests asked for with ForceRejoinReq.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub(crate) struct ForceRejoin {
+    pub(crate) rejoin_type: RejoinType,
+    pub(crate) dr: DR,
+    pub(crate) period: u8,
+    pub(crate) max_retries: u8,
+}
+
+/// Forced rejoin requests still to be sent.
+struct Forced {
+    request: ForceRejoin,
+    /// Transmissions left after the next one.
+    remaining: u8,
+    /// Local time of the next transmission, right away when `None`.
+    next_ms: Option<u64>,
+    /// Delay from the end of a transmission to the next one, drawn when it is sent.
+    delay_ms: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Rejoin {
+    /// Credentials of the last OTAA join, which secure the rejoin requests and their answers.
+    credentials: Option<otaa::NetworkCredentials>,
+    /// Type and counter of the rejoin request waiting for a JoinAccept.
+    pending: Option<(RejoinType, u16)>,
+    forced: Option<Forced>,
+    /// Uplinks since the session started or since the last type 0 rejoin request.
+    uplinks: u32,
+    /// Local time at which the periodicity of type 0 rejoin requests was last reset.
+    last_ms: Option<u64>,
+}
+
+impl Rejoin {
+    pub(crate) fn set_credentials(&mut self, credentials: otaa::NetworkCredentials) {
+        self.credentials = Some(credentials);
+    }
+
+    /// Start over with a new session, keeping the credentials.
+    pub(crate) fn reset(&mut self) {
+        *self = Self { credentials: self.credentials.take(), ..Default::default() };
+    }
+
+    /// Count an uplink, which also gives up on a rejoin request left unanswered.
+    pub(crate) fn uplink_sent(&mut self) {
+        self.pending = None;
+        self.uplinks = self.uplinks.saturating_add(1);
+    }
+
+    pub(crate) fn force(&mut self, request: ForceRejoin) {
+        self.forced =
+            Some(Forced { request, remaining: request.max_retries, next_ms: None, delay_ms: 0 });
+    }
+}
+
+impl Mac {
+    /// Rejoin request which is due at local time `now_ms`, if any: one asked for with
+    /// ForceRejoinReq, or a periodic type 0 rejoin request once enabled by RejoinParamSetupReq.
+    pub(crate) fn pending_rejoin(&mut self, now_ms: Option<u64>) -> Option<RejoinType> {
+        let rejoin = &mut self.rejoin;
+        let session = match &self.state {
+            State::Joined(session) => session,
+            State::Otaa(_) | State::Unjoined => return None,
+        };
+        let v1_1 = session.lorawan_1_1()?;
+        if rejoin.pending.is_some() || rejoin.credentials.as_ref()?.nwkkey().is_none() {
+            return None;
+        }
+        if let Some(forced) = &rejoin.forced {
+            let due = match (forced.next_ms, now_ms) {
+                (Some(next_ms), Some(now_ms)) => now_ms >= next_ms,
+                _ => true,
+            };
+            if due {
+                return Some(forced.request.rejoin_type);
+            }
+        }
+        let params = v1_1.rejoin_params()?;
+        let expired = now_ms.is_some_and(|now_ms| {
+            let last_ms = *rejoin.last_ms.get_or_insert(now_ms);
+            now_ms.saturating_sub(last_ms) >= params.max_time_ms()
+        });
+        (expired || rejoin.uplinks >= params.max_uplinks()).then_some(RejoinType::Type0)
+    }
+
+    /// Prepare the radio buffer with a rejoin request and provide the radio configuration for the
+    /// transmission along with the RX window configurations bound to it. The current session is
+    /// kept until a JoinAccept answers the request.
+    pub(crate) fn rejoin<RNG: RngCore, const N: usize>(
+        &mut self,
+        rng: &mut RNG,
+        rejoin_type: RejoinType,
+        buf: &mut RadioBuffer<N>,
+        now_ms: Option<u64>,
+    ) -> Result<(radio::TxConfig, RxWindows, u16)> {
+        let rj_count = self.prepare_rejoin_buffer(rejoin_type, buf)?;
+        // A forced rejoin request is sent at the data rate asked for, if usable.
+        let dwell_time = self.configuration.tx_params.uplink_dwell_time;
+        let dr = match &self.rejoin.forced {
+            Some(forced)
+                if forced.request.rejoin_type == rejoin_type
+                    && self.region.datarate_allowed(forced.request.dr, dwell_time) =>
+            {
+                forced.request.dr
+            }
+            _ => self.configuration.data_rate,
+        };
+        let (mut tx_config, tx_channel) =
+            self.create_tx_config_at(rng, dr, &Frame::Data, now_ms)?;
+
+        if let State::Joined(session) = &mut self.state {
+            session.increment_rj_count(rejoin_type);
+        }
+        self.state_dirty = true;
+        self.rejoin.pending = Some((rejoin_type, rj_count));
+        if rejoin_type == RejoinType::Type0 {
+            self.rejoin.uplinks = 0;
+            self.rejoin.last_ms = now_ms;
+        }
+        if let Some(forced) = &mut self.rejoin.forced
+            && forced.request.rejoin_type == rejoin_type
+        {
+            if forced.remaining == 0 {
+                self.rejoin.forced = None;
+            } else {
+                forced.remaining -= 1;
+                forced.next_ms = None;
+                forced.delay_ms = (FORCE_REJOIN_PERIOD << forced.request.period)
+                    + (rng.next_u32() % FORCE_REJOIN_RAND) as u64;
+            }
+        }
+        self.repetition = None;
+        self.register_tx(&tx_config, buf, now_ms);
+        tx_config.adjust_power(
+            self.configuration.tx_power.unwrap_or(self.board_eirp.max_power),
+            self.board_eirp.antenna_gain,
+        );
+        Ok((tx_config, self.rx_windows(&tx_channel), rj_count))
+    }
+
+    /// Write a rejoin request to the radio buffer, returns the rejoin counter it carries. Types 0
+    /// and 2 are signed with the SNwkSIntKey of the session, type 1 with the JSIntKey.
+    fn prepare_rejoin_buffer<const N: usize>(
+        &self,
+        rejoin_type: RejoinType,
+        buf: &mut RadioBuffer<N>,
+    ) -> Result<u16> {
+        let credentials = self.rejoin.credentials.as_ref().ok_or(Error::RejoinUnavailable)?;
+        let nwkkey = credentials.nwkkey().ok_or(Error::RejoinUnavailable)?;
+        let session = self.get_session().ok_or(Error::NotJoined)?;
+        let v1_1 = session.lorawan_1_1().ok_or(Error::RejoinUnavailable)?;
+        let dev_eui = (*credentials.deveui()).into();
+        let (request, rj_count) = match rejoin_type {
+            RejoinType::Type0 => {
+                let rj_count0 = v1_1.rj_count0();
+                (RejoinRequest::Type0 { net_id: v1_1.net_id(), dev_eui, rj_count0 }, rj_count0)
+            }
+            RejoinType::Type1 => {
+                let join_eui = (*credentials.appeui()).into();
+                let rj_count1 = v1_1.rj_count1();
+                (RejoinRequest::Type1 { join_eui, dev_eui, rj_count1 }, rj_count1)
+            }
+            RejoinType::Type2 => {
+                let rj_count0 = v1_1.rj_count0();
+                (RejoinRequest::Type2 { net_id: v1_1.net_id(), dev_eui, rj_count0 }, rj_count0)
+            }
+        };
+        // The counters must not wrap around for a given set of keys.
+        if rj_count == u16::MAX {
+            return Err(Error::RejoinUnavailable);
+        }
+        buf.clear();
+        let result = match rejoin_type {
+            RejoinType::Type0 | RejoinType::Type2 => {
+                request.build_into(buf.as_mut(), &DefaultCrypto::new(v1_1.s_nwk_s_int_key.inner()))
+            }
+            RejoinType::Type1 => {
+                let nwk_crypto = DefaultCrypto::new(nwkkey.inner());
+                let js_int_key = JSIntKey::derive_from(&nwk_crypto, &dev_eui);
+                request.build_into(buf.as_mut(), &DefaultCrypto::new(js_int_key.inner()))
+            }
+        };
+        // A rejoin request is at most 24 bytes.
+        let len = result.unwrap().len();
+        buf.set_pos(len);
+        Ok(rj_count)
+    }
+
+    pub(crate) fn is_rejoining(&self) -> bool {
+        self.rejoin.pending.is_some()
+    }
+
+    /// Handle the JoinAccept answering a rejoin request: it is encrypted with the JSEncKey, and
+    /// its MIC covers the type and the counter of the request. Other frames are ignored until the
+    /// receive windows of the request are over.
+    pub(crate) fn handle_rejoin_accept<const N: usize>(
+        &mut self,
+        buf: &mut RadioBuffer<N>,
+    ) -> Response {
+        let (Some((rejoin_type, rj_count)), Some(credentials), State::Joined(previous)) =
+            (self.rejoin.pending, &self.rejoin.credentials, &self.state)
+        else {
+            return Response::NoUpdate;
+        };
+        let Some(nwkkey) = credentials.nwkkey() else {
+            return Response::NoUpdate;
+        };
+        let nwk_crypto = DefaultCrypto::new(nwkkey.inner());
+        let dev_eui = (*credentials.deveui()).into();
+        let js_enc_key = JSEncKey::derive_from(&nwk_crypto, &dev_eui);
+        let Ok(decrypt) = DecryptedJoinAcceptPayload::decrypt_in_place(
+            buf.as_mut_for_read(),
+            &DefaultCrypto::new(js_enc_key.inner()),
+        ) else {
+            return Response::NoUpdate;
+        };
+        let js_int_key = JSIntKey::derive_from(&nwk_crypto, &dev_eui);
+        let dev_nonce = DevNonce::from_value(rj_count);
+        if !decrypt.opt_neg()
+            || !decrypt.validate_mic_1_1(
+                &DefaultCrypto::new(js_int_key.inner()),
+                rejoin_type.join_req_type(),
+                &(*credentials.appeui()).into(),
+                &dev_nonce,
+            )
+        {
+            return Response::NoUpdate;
+        }
+        if self.join_nonce.is_some_and(|last| decrypt.join_nonce().value() <= last.value()) {
+            return Response::NoUpdate;
+        }
+        let mut session = super::Session::derive_new_1_1(&decrypt, dev_nonce, credentials, nwkkey);
+        let keep_radio = rejoin_type == RejoinType::Type2;
+        session.carry_over(previous, keep_radio);
+        if !keep_radio {
+            otaa::apply_join_accept(&mut self.region, &mut self.configuration, &decrypt);
+        }
+        self.set_session(session);
+        self.state_dirty = true;
+        Response::JoinSuccess
+    }
+
+    /// Complete the receive windows of a rejoin request without an answer, the session is kept.
+    pub(crate) fn rejoin_rx2_complete(&mut self) -> Option<Response> {
+        self.rejoin.pending.take()?;
+        if let Some(forced) = &mut self.rejoin.forced {
+            forced.next_ms = Some(self.tx_end_ms.unwrap_or(0) + forced.delay_ms);
+        }
+        Some(Response::NoJoinAccept)
+    }
+}
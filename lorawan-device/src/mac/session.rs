@@ -1,32 +1,44 @@
 use super::{
-    FcntUp, Response, SendData,
+    FcntUp, Reader, Response, SendData, StateError, TxParams, Writer,
     otaa::{DevNonce, NetworkCredentials},
+    rejoin::{ForceRejoin, RejoinParams, RejoinType},
     uplink,
 };
 use crate::radio::RadioBuffer;
 use crate::region::constants::{ADR_ACK_DELAY, ADR_ACK_LIMIT, MAX_FCNT_GAP};
-use crate::{AppSKey, Downlink, NwkSKey, region};
+use crate::{AppSKey, Downlink, NwkKey, NwkSKey, region};
 use core::num::NonZeroU8;
 use heapless::Vec;
 use lorawan::creator::{DataFrame, Payload};
+use lorawan::keys::{NetworkSessionCrypto, NwkSEncKey, SNwkSIntKey};
+#[cfg(feature = "class-b")]
+use lorawan::maccommandcreator::{BeaconFreqAnsCreator, PingSlotChannelAnsCreator};
 use lorawan::maccommandcreator::{
-    DevStatusAnsCreator, DlChannelAnsCreator, LinkADRAnsCreator, NewChannelAnsCreator,
-    RXParamSetupAnsCreator, RXTimingSetupAnsCreator,
+    DevStatusAnsCreator, DlChannelAnsCreator, DutyCycleAnsCreator, LinkADRAnsCreator,
+    NewChannelAnsCreator, RXParamSetupAnsCreator, RXTimingSetupAnsCreator,
+    RejoinParamSetupAnsCreator, RekeyIndCreator, TXParamSetupAnsCreator,
 };
 use lorawan::maccommands::DownlinkMacCommand;
 use lorawan::maccommands::{MacCommands, parse_downlink_mac_commands};
 use lorawan::parser::{
     DataFrameType, DecryptedDataPayload, DecryptedJoinAcceptPayload, DevAddr, EncryptedDataPayload,
-    FrmPayload,
+    FrmPayload, JoinNonce, MicContext, NetId,
 };
 use lorawan::{
     default_crypto::DefaultCrypto,
-    packet_length::phy::{MHDR_LEN, MIC_LEN},
+    packet_length::phy::{
+        MHDR_LEN, MIC_LEN,
+        mac::{
+            FPORT_LEN,
+            fhdr::{FHDR_MIN_LEN, FOPTS_MAX_LEN},
+        },
+    },
     types::DR,
 };
 
 #[cfg(feature = "certification")]
 use super::DeviceEvent;
+use super::event::{Change, MacEvent, MacEvents};
 
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -34,6 +46,7 @@ use super::DeviceEvent;
 pub struct Session {
     pub uplink: uplink::Uplink,
     pub confirmed: bool,
+    /// The network session key; the FNwkSIntKey in a LoRaWAN 1.1 session.
     pub nwkskey: NwkSKey,
     pub appskey: AppSKey,
     pub devaddr: DevAddr,
@@ -44,6 +57,23 @@ pub struct Session {
     fcnt_down: Option<u32>,
     /// Uplinks since the last accepted downlink; used for ADRACKReq / ADR backoff.
     pub(crate) adr_ack_cnt: u32,
+    /// LoRaWAN 1.1 state, or `None` for a LoRaWAN 1.0.x session.
+    lorawan_1_1: Option<Session1_1>,
+    /// Limits set by TXParamSetupReq, kept here so they are restored along with the session.
+    tx_params: TxParams,
+    /// GPS time in milliseconds from the last DeviceTimeAns, until taken by the MAC.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    device_time: Option<u64>,
+    /// Margin and gateway count from the last LinkCheckAns, until taken by the MAC.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    link_check: Option<(u8, u8)>,
+    /// Rejoin requests asked for by the last ForceRejoinReq, until taken by the MAC.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    force_rejoin: Option<ForceRejoin>,
+    /// Set when MAC answers the network waits for, ADRACKReq, or MAC commands overflowing FOpts
+    /// call for an uplink of their own; cleared by the next uplink.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mac_uplink_due: bool,
     #[cfg(feature = "certification")]
     /// Whether to override confirmation bit for sent frames
     pub override_confirmed: Option<bool>,
@@ -52,6 +82,93 @@ pub struct Session {
     pub rx_app_cnt: u16,
 }
 
+/// State of a session joined with LoRaWAN 1.1 (OptNeg set in the JoinAccept).
+///
+/// In such a session [`Session::nwkskey`] holds the FNwkSIntKey and
+/// [`Session::fcnt_down`] tracks NFCntDown, the counter of downlinks on FPort 0
+/// or without FPort.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session1_1 {
+    pub s_nwk_s_int_key: SNwkSIntKey,
+    pub nwk_s_enc_key: NwkSEncKey,
+    /// JoinNonce of the JoinAccept which created the session.
+    pub join_nonce: JoinNonce,
+    /// Frame counter of the last accepted application downlink (AFCntDown).
+    a_fcnt_down: Option<u32>,
+    /// Low 16 bits of the last confirmed downlink's counter, acknowledged in
+    /// the MIC of the next uplink (ConfFCnt).
+    conf_fcnt: u16,
+    /// Low 16 bits of the last confirmed uplink's counter, covered by the MIC
+    /// of a downlink acknowledging it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ack_fcnt: u16,
+    /// Set once the network answered RekeyInd with RekeyConf; until then every
+    /// uplink carries a RekeyInd.
+    rekey_confirmed: bool,
+    rj_count0: u16,
+    rj_count1: u16,
+    /// NetID of the network, carried by type 0 and 2 rejoin requests.
+    net_id: NetId,
+    /// Periodicity of type 0 rejoin requests, none until set by RejoinParamSetupReq.
+    rejoin_params: Option<RejoinParams>,
+}
+
+impl Session1_1 {
+    fn new(
+        s_nwk_s_int_key: SNwkSIntKey,
+        nwk_s_enc_key: NwkSEncKey,
+        join_nonce: JoinNonce,
+        net_id: NetId,
+    ) -> Self {
+        Self {
+            s_nwk_s_int_key,
+            nwk_s_enc_key,
+            join_nonce,
+            a_fcnt_down: None,
+            conf_fcnt: 0,
+            ack_fcnt: 0,
+            rekey_confirmed: false,
+            rj_count0: 0,
+            rj_count1: 0,
+            net_id,
+            rejoin_params: None,
+        }
+    }
+
+    /// Frame counter of the last accepted application downlink (AFCntDown), or
+    /// `None` before the first one of the session.
+    pub fn a_fcnt_down(&self) -> Option<u32> {
+        self.a_fcnt_down
+    }
+
+    /// Whether the network confirmed the session with RekeyConf.
+    pub fn rekey_confirmed(&self) -> bool {
+        self.rekey_confirmed
+    }
+
+    /// Number of type 0/2 rejoin requests sent in this session (RJcount0).
+    pub fn rj_count0(&self) -> u16 {
+        self.rj_count0
+    }
+
+    /// Number of type 1 rejoin requests sent in this session (RJcount1).
+    pub fn rj_count1(&self) -> u16 {
+        self.rj_count1
+    }
+
+    /// NetID from the JoinAccept which created the session.
+    pub fn net_id(&self) -> NetId {
+        self.net_id
+    }
+
+    /// Periodicity of type 0 rejoin requests set by the network with RejoinParamSetupReq.
+    pub fn rejoin_params(&self) -> Option<RejoinParams> {
+        self.rejoin_params
+    }
+}
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub struct SessionKeys {
@@ -72,13 +189,40 @@ impl Session {
         devnonce: DevNonce,
         credentials: &NetworkCredentials,
     ) -> Self {
+        // A LoRaWAN 1.1 device falling back to 1.0.x derives both keys from its NwkKey.
+        let root_crypto = DefaultCrypto::new(credentials.root_key());
         Self::new(
-            decrypt.derive_nwkskey(devnonce, &DefaultCrypto::new(credentials.appkey().inner())),
-            decrypt.derive_appskey(devnonce, &DefaultCrypto::new(credentials.appkey().inner())),
+            decrypt.derive_nwkskey(devnonce, &root_crypto),
+            decrypt.derive_appskey(devnonce, &root_crypto),
             decrypt.dev_addr(),
         )
     }
 
+    /// Derive a LoRaWAN 1.1 session from a JoinAccept with OptNeg set.
+    pub(crate) fn derive_new_1_1(
+        decrypt: &DecryptedJoinAcceptPayload<'_>,
+        devnonce: DevNonce,
+        credentials: &NetworkCredentials,
+        nwkkey: &NwkKey,
+    ) -> Self {
+        let join_eui = (*credentials.appeui()).into();
+        let nwk_crypto = DefaultCrypto::new(nwkkey.inner());
+        let app_crypto = DefaultCrypto::new(credentials.appkey().inner());
+        let f_nwk_s_int_key = decrypt.derive_f_nwk_s_int_key(&join_eui, devnonce, &nwk_crypto);
+        let mut session = Self::new(
+            NwkSKey::from(f_nwk_s_int_key.inner().0),
+            decrypt.derive_appskey_1_1(&join_eui, devnonce, &app_crypto),
+            decrypt.dev_addr(),
+        );
+        session.lorawan_1_1 = Some(Session1_1::new(
+            decrypt.derive_s_nwk_s_int_key(&join_eui, devnonce, &nwk_crypto),
+            decrypt.derive_nwk_s_enc_key(&join_eui, devnonce, &nwk_crypto),
+            decrypt.join_nonce(),
+            decrypt.net_id(),
+        ));
+        session
+    }
+
     pub fn new(nwkskey: NwkSKey, appskey: AppSKey, devaddr: DevAddr) -> Self {
         Self {
             nwkskey,
@@ -88,6 +232,12 @@ impl Session {
             fcnt_down: None,
             fcnt_up: 0,
             adr_ack_cnt: 0,
+            lorawan_1_1: None,
+            tx_params: TxParams::default(),
+            device_time: None,
+            link_check: None,
+            force_rejoin: None,
+            mac_uplink_due: false,
             uplink: uplink::Uplink::default(),
 
             #[cfg(feature = "certification")]
@@ -118,11 +268,118 @@ impl Session {
         self.fcnt_down
     }
 
+    /// LoRaWAN 1.1 state, or `None` for a LoRaWAN 1.0.x session.
+    pub fn lorawan_1_1(&self) -> Option<&Session1_1> {
+        self.lorawan_1_1.as_ref()
+    }
+
+    /// Dwell-time and EIRP limits set by the network with TXParamSetupReq.
+    pub fn tx_params(&self) -> &TxParams {
+        &self.tx_params
+    }
+
     pub fn get_session_keys(&self) -> Option<SessionKeys> {
         Some(SessionKeys { nwkskey: self.nwkskey, appskey: self.appskey, devaddr: self.devaddr })
     }
 }
 
+const STATE_FCNT_DOWN: u8 = 0x01;
+const STATE_UPLINK_DWELL_TIME: u8 = 0x02;
+const STATE_DOWNLINK_DWELL_TIME: u8 = 0x04;
+const STATE_LORAWAN_1_1: u8 = 0x08;
+const STATE_A_FCNT_DOWN: u8 = 0x10;
+const STATE_REKEY_CONFIRMED: u8 = 0x20;
+const STATE_REJOIN_PARAMS: u8 = 0x40;
+
+impl Session {
+    /// Write the session to a state snapshot. Answers to MAC commands which are still pending
+    /// are not part of it.
+    pub(crate) fn write_state(&self, w: &mut Writer) {
+        let mut flags = 0;
+        if self.fcnt_down.is_some() {
+            flags |= STATE_FCNT_DOWN;
+        }
+        if self.tx_params.uplink_dwell_time {
+            flags |= STATE_UPLINK_DWELL_TIME;
+        }
+        if self.tx_params.downlink_dwell_time {
+            flags |= STATE_DOWNLINK_DWELL_TIME;
+        }
+        if let Some(v1_1) = &self.lorawan_1_1 {
+            flags |= STATE_LORAWAN_1_1;
+            if v1_1.a_fcnt_down.is_some() {
+                flags |= STATE_A_FCNT_DOWN;
+            }
+            if v1_1.rekey_confirmed {
+                flags |= STATE_REKEY_CONFIRMED;
+            }
+            if v1_1.rejoin_params.is_some() {
+                flags |= STATE_REJOIN_PARAMS;
+            }
+        }
+        w.u8(flags);
+        w.bytes(self.devaddr.as_wire_bytes());
+        w.bytes(self.nwkskey.as_ref());
+        w.bytes(self.appskey.as_ref());
+        w.u32(self.fcnt_up);
+        w.u32(self.fcnt_down.unwrap_or(0));
+        w.u32(self.adr_ack_cnt);
+        w.u8(self.tx_params.max_eirp.unwrap_or(0xFF));
+        if let Some(v1_1) = &self.lorawan_1_1 {
+            w.bytes(v1_1.s_nwk_s_int_key.as_ref());
+            w.bytes(v1_1.nwk_s_enc_key.as_ref());
+            w.bytes(v1_1.join_nonce.as_wire_bytes());
+            w.u32(v1_1.a_fcnt_down.unwrap_or(0));
+            w.u16(v1_1.conf_fcnt);
+            w.u16(v1_1.rj_count0);
+            w.u16(v1_1.rj_count1);
+            w.bytes(v1_1.net_id.as_wire_bytes());
+            if let Some(params) = v1_1.rejoin_params {
+                w.u8(params.max_time_n << 4 | params.max_count_n);
+            }
+        }
+    }
+
+    pub(crate) fn read_state(r: &mut Reader<'_>) -> Result<Self, StateError> {
+        let flags = r.u8()?;
+        let devaddr = DevAddr::from_wire_bytes(r.array()?);
+        let nwkskey = NwkSKey::from(r.array::<16>()?);
+        let appskey = AppSKey::from(r.array::<16>()?);
+        let mut session = Self::new(nwkskey, appskey, devaddr);
+        session.fcnt_up = r.u32()?;
+        let fcnt_down = r.u32()?;
+        session.fcnt_down = (flags & STATE_FCNT_DOWN != 0).then_some(fcnt_down);
+        session.adr_ack_cnt = r.u32()?;
+        session.tx_params = TxParams {
+            uplink_dwell_time: flags & STATE_UPLINK_DWELL_TIME != 0,
+            downlink_dwell_time: flags & STATE_DOWNLINK_DWELL_TIME != 0,
+            max_eirp: Some(r.u8()?).filter(|&eirp| eirp != 0xFF),
+        };
+        if flags & STATE_LORAWAN_1_1 != 0 {
+            let mut v1_1 = Session1_1::new(
+                SNwkSIntKey::from(r.array::<16>()?),
+                NwkSEncKey::from(r.array::<16>()?),
+                JoinNonce::from_wire_bytes(r.array()?),
+                NetId::from_value(0),
+            );
+            let a_fcnt_down = r.u32()?;
+            v1_1.a_fcnt_down = (flags & STATE_A_FCNT_DOWN != 0).then_some(a_fcnt_down);
+            v1_1.conf_fcnt = r.u16()?;
+            v1_1.rekey_confirmed = flags & STATE_REKEY_CONFIRMED != 0;
+            v1_1.rj_count0 = r.u16()?;
+            v1_1.rj_count1 = r.u16()?;
+            v1_1.net_id = NetId::from_wire_bytes(r.array()?);
+            if flags & STATE_REJOIN_PARAMS != 0 {
+                let params = r.u8()?;
+                v1_1.rejoin_params =
+                    Some(RejoinParams { max_time_n: params >> 4, max_count_n: params & 0x0F });
+            }
+            session.lorawan_1_1 = Some(v1_1);
+        }
+        Ok(session)
+    }
+}
+
 impl Session {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn handle_rx<const N: usize, const D: usize>(
@@ -131,6 +388,7 @@ impl Session {
         configuration: &mut super::Configuration,
         #[cfg(feature = "certification")] certification: &mut super::certification::Certification,
         #[cfg(feature = "multicast")] multicast: &mut super::multicast::Multicast,
+        events: &mut MacEvents,
         rx: &mut RadioBuffer<N>,
         dl: &mut Vec<Downlink, D>,
         max_payload_len: u8,
@@ -154,7 +412,7 @@ impl Session {
             // If ignore_mac is false, we're dealing with Class A downlink and
             // therefore can clear uplinks which need to be retained for acknowledgment
             if !ignore_mac {
-                self.uplink.clear_mac_commands(false);
+                self.uplink.clear_sent_mac_commands();
             }
 
             #[cfg(feature = "certification")]
@@ -170,22 +428,68 @@ impl Session {
                 return multicast.handle_rx(dl, bytes).into();
             }
             let confirmed = encrypted_data.is_confirmed();
-            let Some(fcnt) = next_fcnt_down(self.fcnt_down, encrypted_data.fhdr().fcnt()) else {
+            // LoRaWAN 1.1 counts application downlinks (AFCntDown) separately.
+            let app_downlink = matches!(encrypted_data.f_port(), Some(port) if port > 0);
+            let last_fcnt = match &self.lorawan_1_1 {
+                Some(v1_1) if app_downlink => v1_1.a_fcnt_down,
+                _ => self.fcnt_down,
+            };
+            let Some(fcnt) = next_fcnt_down(last_fcnt, encrypted_data.fhdr().fcnt()) else {
                 return Response::NoUpdate;
             };
             let nwk_crypto = DefaultCrypto::new(self.nwkskey.inner());
             let app_crypto = DefaultCrypto::new(self.appskey.inner());
-            if encrypted_data.validate_mic(&nwk_crypto, fcnt) {
-                self.fcnt_down = Some(fcnt);
+            let valid = match &self.lorawan_1_1 {
+                None => encrypted_data.validate_mic(&nwk_crypto, fcnt),
+                Some(v1_1) => {
+                    // A downlink acknowledging our last uplink covers its FCnt in the MIC.
+                    let conf_fcnt = if encrypted_data.fhdr().fctrl().ack() {
+                        v1_1.ack_fcnt
+                    } else {
+                        0
+                    };
+                    encrypted_data.validate_mic_1_1(
+                        &NetworkSessionCrypto {
+                            f_nwk_s_int: &nwk_crypto,
+                            s_nwk_s_int: &DefaultCrypto::new(v1_1.s_nwk_s_int_key.inner()),
+                            nwk_s_enc: &DefaultCrypto::new(v1_1.nwk_s_enc_key.inner()),
+                        },
+                        fcnt,
+                        &MicContext { conf_fcnt, ..Default::default() },
+                    )
+                }
+            };
+            if valid {
+                match &mut self.lorawan_1_1 {
+                    Some(v1_1) => {
+                        if app_downlink {
+                            v1_1.a_fcnt_down = Some(fcnt);
+                        } else {
+                            self.fcnt_down = Some(fcnt);
+                        }
+                        if confirmed {
+                            v1_1.conf_fcnt = fcnt as u16;
+                        }
+                    }
+                    None => self.fcnt_down = Some(fcnt),
+                }
                 // Any accepted downlink confirms connectivity for ADR.
                 self.adr_ack_cnt = 0;
                 // We can safely unwrap here because we already validated the MIC
-                let decrypted = DecryptedDataPayload::decrypt_in_place(
-                    bytes,
-                    Some(&nwk_crypto),
-                    Some(&app_crypto),
-                    fcnt,
-                )
+                let decrypted = match &self.lorawan_1_1 {
+                    None => DecryptedDataPayload::decrypt_in_place(
+                        bytes,
+                        Some(&nwk_crypto),
+                        Some(&app_crypto),
+                        fcnt,
+                    ),
+                    Some(v1_1) => DecryptedDataPayload::decrypt_in_place_1_1(
+                        bytes,
+                        &DefaultCrypto::new(v1_1.nwk_s_enc_key.inner()),
+                        Some(&app_crypto),
+                        fcnt,
+                    ),
+                }
                 .unwrap();
 
                 if !ignore_mac {
@@ -193,6 +497,7 @@ impl Session {
                     self.handle_downlink_macs(
                         configuration,
                         region,
+                        events,
                         parse_downlink_mac_commands(decrypted.fhdr().f_opts()),
                         snr,
                     );
@@ -200,6 +505,7 @@ impl Session {
                         self.handle_downlink_macs(
                             configuration,
                             region,
+                            events,
                             parse_downlink_mac_commands(mac_cmds),
                             snr,
                         );
@@ -273,7 +579,7 @@ impl Session {
         configuration: &mut super::Configuration,
         region: &region::Configuration,
     ) -> Response {
-        // Until we handle NbTrans, there is no case where we should not increment FCntUp.
+        // Repetitions for NbTrans are all done by now, the next uplink uses a new FCntUp.
         if self.fcnt_up == 0xFFFF_FFFF {
             // if the FCnt is used up, the session has expired
             return Response::SessionExpired;
@@ -283,12 +589,18 @@ impl Session {
 
         if configuration.adr_enabled {
             self.adr_ack_cnt = self.adr_ack_cnt.saturating_add(1);
+            // The next uplink sets ADRACKReq, the network is waiting for it to answer.
+            if self.adr_ack_cnt == ADR_ACK_LIMIT as u32
+                && next_lower_datarate(region, configuration).is_some()
+            {
+                self.mac_uplink_due = true;
+            }
             // After ADR_ACK_LIMIT + N*ADR_ACK_DELAY uplinks without a downlink,
             // step down the data rate to try to regain connectivity.
             if self.adr_ack_cnt >= (ADR_ACK_LIMIT + ADR_ACK_DELAY) as u32 {
                 let past_limit = self.adr_ack_cnt - ADR_ACK_LIMIT as u32;
                 if past_limit.is_multiple_of(ADR_ACK_DELAY as u32)
-                    && let Some(dr) = next_lower_datarate(region, configuration.data_rate)
+                    && let Some(dr) = next_lower_datarate(region, configuration)
                 {
                     configuration.data_rate = dr;
                 }
@@ -302,47 +614,81 @@ impl Session {
         }
     }
 
+    /// Prepare an uplink carrying `data`, or only the queued MAC commands, in FOpts and without
+    /// FPort, when `None`.
     pub(crate) fn prepare_buffer<const N: usize>(
         &mut self,
-        data: &SendData<'_>,
+        data: Option<&SendData<'_>>,
         tx_buffer: &mut RadioBuffer<N>,
         configuration: &super::Configuration,
         region: &region::Configuration,
+        tx_channel: &region::TxChannel,
     ) -> FcntUp {
         tx_buffer.clear();
         let fcnt = self.fcnt_up;
         let mut buf = [0u8; 256];
+        self.mac_uplink_due = false;
 
         let ack = self.uplink.confirms_downlink();
         if ack {
             self.uplink.clear_downlink_confirmation();
         }
 
+        if let Some(cmd) = self.rekey_ind() {
+            self.uplink.add_mac_command(cmd);
+        }
+
         let adr = configuration.adr_enabled;
         // ADRACKReq asks the network for a downlink so ADR can keep working.
         // It is not set when already at the lowest usable data rate.
         let adr_ack_req = adr
             && self.adr_ack_cnt >= ADR_ACK_LIMIT as u32
-            && next_lower_datarate(region, configuration.data_rate).is_some();
+            && next_lower_datarate(region, configuration).is_some();
 
-        self.confirmed = data.confirmed;
+        self.confirmed = data.is_some_and(|data| data.confirmed);
         #[cfg(feature = "certification")]
         if let Some(v) = self.override_confirmed {
             self.confirmed = v;
         }
+        if self.confirmed
+            && let Some(v1_1) = &mut self.lorawan_1_1
+        {
+            v1_1.ack_fcnt = fcnt as u16;
+        }
 
         // FPort 0 sends the queued MAC commands as the FRMPayload (encrypted
         // with the NwkSKey) with FOpts left empty; the spec forbids
         // application data on port 0. Any other port piggybacks the queued
-        // commands in FOpts.
-        let (f_opts, payload) = match NonZeroU8::new(data.fport) {
-            Some(f_port) => (self.uplink.mac_commands(), Payload::Data { f_port, data: data.data }),
-            None => {
-                if !data.data.is_empty() {
+        // commands in FOpts. An uplink without application data uses FPort 0
+        // when it carries more commands than FOpts would.
+        let dwell_time = configuration.tx_params.uplink_dwell_time;
+        let max_frm_payload = (tx_channel.datarate.max_payload_size(dwell_time) as usize)
+            .saturating_sub(FHDR_MIN_LEN + FPORT_LEN);
+        let fopts_len = self.uplink.mac_commands_len(FOPTS_MAX_LEN);
+        let port_0_len = self.uplink.mac_commands_len(max_frm_payload);
+        let (f_opts_len, payload_len) = match data {
+            Some(data) if data.fport != 0 => (fopts_len, 0),
+            Some(_) => (0, port_0_len),
+            None if port_0_len > fopts_len => (0, port_0_len),
+            None => (fopts_len, 0),
+        };
+        // Commands which did not fit are sent with an uplink of their own.
+        let sent_len = f_opts_len + payload_len;
+        if sent_len < self.uplink.mac_commands().len() {
+            self.mac_uplink_due = true;
+        }
+        let mac_commands = self.uplink.mac_commands();
+        let f_opts = &mac_commands[..f_opts_len];
+        let payload = match data.map(|data| (NonZeroU8::new(data.fport), data.data)) {
+            Some((Some(f_port), data)) => Payload::Data { f_port, data },
+            Some((None, data)) => {
+                if !data.is_empty() {
                     panic!("Error assembling packet! Data payload with fport 0 not allowed");
                 }
-                (&[][..], Payload::MacCommands(self.uplink.mac_commands()))
+                Payload::MacCommands(&mac_commands[..payload_len])
             }
+            None if payload_len > 0 => Payload::MacCommands(&mac_commands[..payload_len]),
+            None => Payload::None,
         };
         let frame = DataFrame {
             frame_type: if self.confirmed {
@@ -355,27 +701,155 @@ impl Session {
             adr_ack_req,
             ack,
             f_pending: false,
+            #[cfg(feature = "class-b")]
+            class_b: configuration.class_b.is_active(),
+            #[cfg(not(feature = "class-b"))]
+            class_b: false,
             fcnt,
             f_opts,
             payload,
         };
         let nwk_crypto = DefaultCrypto::new(self.nwkskey.inner());
         let app_crypto = DefaultCrypto::new(self.appskey.inner());
-        match frame.build_into(&mut buf, &nwk_crypto, Some(&app_crypto)) {
+        let result = match &self.lorawan_1_1 {
+            None => frame.build_into(&mut buf, &nwk_crypto, Some(&app_crypto)),
+            Some(v1_1) => frame.build_into_1_1(
+                &mut buf,
+                &NetworkSessionCrypto {
+                    f_nwk_s_int: &nwk_crypto,
+                    s_nwk_s_int: &DefaultCrypto::new(v1_1.s_nwk_s_int_key.inner()),
+                    nwk_s_enc: &DefaultCrypto::new(v1_1.nwk_s_enc_key.inner()),
+                },
+                Some(&app_crypto),
+                &MicContext {
+                    conf_fcnt: if ack {
+                        v1_1.conf_fcnt
+                    } else {
+                        0
+                    },
+                    tx_dr: tx_channel.dr as u8,
+                    tx_ch: tx_channel.channel,
+                },
+            ),
+        };
+        match result {
             Ok(packet) => {
                 tx_buffer.clear();
                 tx_buffer.extend_from_slice(packet).unwrap();
             }
             Err(e) => panic!("Error assembling packet! {:?} ", e),
         }
-        self.uplink.clear_mac_commands(true);
+        self.uplink.mac_commands_sent(sent_len);
         fcnt
     }
 
+    /// A LoRaWAN 1.1 device indicates its version with RekeyInd until the
+    /// network answers with RekeyConf.
+    fn rekey_ind(&self) -> Option<RekeyIndCreator> {
+        self.lorawan_1_1.as_ref().filter(|v1_1| !v1_1.rekey_confirmed)?;
+        let mut cmd = RekeyIndCreator::new();
+        // Minor version 1 (LoRaWAN 1.1) is always in range.
+        let _ = cmd.set_minor_version(1);
+        Some(cmd)
+    }
+
+    /// Length of the FOpts of the next uplink with application data.
+    pub(crate) fn next_f_opts_len(&self) -> usize {
+        let mut uplink = self.uplink.clone();
+        if let Some(cmd) = self.rekey_ind() {
+            uplink.add_mac_command(cmd);
+        }
+        uplink.mac_commands_len(FOPTS_MAX_LEN)
+    }
+
+    /// Whether MAC answers the network waits for (RXParamSetupAns, DlChannelAns,
+    /// RXTimingSetupAns), ADRACKReq, or MAC commands overflowing FOpts are due and no uplink was
+    /// sent since.
+    pub(crate) fn mac_uplink_due(&self) -> bool {
+        self.mac_uplink_due
+    }
+
+    /// GPS time in milliseconds received with DeviceTimeAns, if any since the last call.
+    pub(crate) fn take_device_time(&mut self) -> Option<u64> {
+        self.device_time.take()
+    }
+
+    /// Margin and gateway count received with LinkCheckAns, if any since the last call.
+    pub(crate) fn take_link_check(&mut self) -> Option<(u8, u8)> {
+        self.link_check.take()
+    }
+
+    /// Rejoin requests asked for with ForceRejoinReq, if any since the last call.
+    pub(crate) fn take_force_rejoin(&mut self) -> Option<ForceRejoin> {
+        self.force_rejoin.take()
+    }
+
+    /// Count a rejoin request of type `rejoin_type`, whose counter was checked not to be used up.
+    pub(crate) fn increment_rj_count(&mut self, rejoin_type: RejoinType) {
+        if let Some(v1_1) = &mut self.lorawan_1_1 {
+            match rejoin_type {
+                RejoinType::Type0 | RejoinType::Type2 => v1_1.rj_count0 += 1,
+                RejoinType::Type1 => v1_1.rj_count1 += 1,
+            }
+        }
+    }
+
+    /// Take over what outlives a session renewed by a rejoin request from the `previous` one:
+    /// RJcount1, which must not repeat for the keys of the join server, and with `keep_radio` the
+    /// TXParamSetupReq limits and the rejoin periodicity.
+    pub(crate) fn carry_over(&mut self, previous: &Session, keep_radio: bool) {
+        let (Some(v1_1), Some(previous_1_1)) = (&mut self.lorawan_1_1, &previous.lorawan_1_1)
+        else {
+            return;
+        };
+        v1_1.rj_count1 = previous_1_1.rj_count1;
+        if keep_radio {
+            v1_1.rejoin_params = previous_1_1.rejoin_params;
+            self.tx_params = previous.tx_params;
+        }
+    }
+
+    /// Update the MIC of a repeated uplink for the channel and data rate it is now sent on, which
+    /// LoRaWAN 1.1 covers. LoRaWAN 1.0.x uplinks are sent unchanged.
+    pub(crate) fn prepare_repetition<const N: usize>(
+        &self,
+        tx_buffer: &mut RadioBuffer<N>,
+        tx_channel: &region::TxChannel,
+    ) {
+        let Some(v1_1) = &self.lorawan_1_1 else {
+            return;
+        };
+        let frame = tx_buffer.as_mut_for_read();
+        // FCtrl ACK bit of the uplink, the MIC then also covers ConfFCnt.
+        let ack = frame.get(5).is_some_and(|fctrl| fctrl & 0x20 != 0);
+        let result = DataFrame::resign_uplink_1_1(
+            frame,
+            &NetworkSessionCrypto {
+                f_nwk_s_int: &DefaultCrypto::new(self.nwkskey.inner()),
+                s_nwk_s_int: &DefaultCrypto::new(v1_1.s_nwk_s_int_key.inner()),
+                nwk_s_enc: &DefaultCrypto::new(v1_1.nwk_s_enc_key.inner()),
+            },
+            self.fcnt_up,
+            &MicContext {
+                conf_fcnt: if ack {
+                    v1_1.conf_fcnt
+                } else {
+                    0
+                },
+                tx_dr: tx_channel.dr as u8,
+                tx_ch: tx_channel.channel,
+            },
+        );
+        if let Err(e) = result {
+            panic!("Error assembling packet! {:?} ", e);
+        }
+    }
+
     fn handle_downlink_macs(
         &mut self,
         configuration: &mut super::Configuration,
         region: &mut region::Configuration,
+        events: &mut MacEvents,
         cmds: MacCommands<'_, DownlinkMacCommand<'_>>,
         snr: i8,
     ) {
@@ -388,11 +862,12 @@ impl Session {
         while let Some(cmd) = cmd_iter.next() {
             match cmd {
                 DevStatusReq(..) => {
-                    // TODO: Battery information should come from device/application
-                    // Battery: (255 - unable to measure, 1..254 - battery level, 0 - external power source)
-                    // For now we just return dummy value of "255"
+                    // The margin is the SNR of the downlink carrying DevStatusReq, as a 6-bit
+                    // signed value.
                     let mut cmd = DevStatusAnsCreator::new();
-                    let _ = cmd.set_battery(255).set_margin(snr);
+                    cmd.set_battery(configuration.battery_level.value());
+                    // The margin is clamped to its range, so it is always accepted.
+                    let _ = cmd.set_margin(snr.clamp(-32, 31));
                     self.uplink.add_mac_command(cmd);
                 }
                 DlChannelReq(payload) => {
@@ -400,12 +875,15 @@ impl Session {
                         // Regions with fixed channel plan ignore this command
                         continue;
                     }
-                    let (ack_f, ack_c) = region
-                        .channel_dl_update(payload.channel_index(), payload.frequency().value());
+                    let index = payload.channel_index();
+                    let frequency = payload.frequency().value();
+                    let (ack_f, ack_c) = region.channel_dl_update(index, frequency);
+                    events.push(MacEvent::DlChannel { accepted: ack_f && ack_c, index, frequency });
 
                     let mut cmd = DlChannelAnsCreator::new();
                     cmd.set_channel_frequency_ack(ack_f).set_uplink_frequency_exists_ack(ack_c);
                     self.uplink.add_mac_command(cmd);
+                    self.mac_uplink_due = true;
                 }
                 LinkADRReq(payload) => {
                     // Contiguous LinkADRReq commands shall be processed in the
@@ -439,7 +917,8 @@ impl Session {
                     let dr = match payload.data_rate() {
                         DR::_15 => Some(configuration.data_rate),
                         n => {
-                            if region.get_datarate(n as u8).is_some() {
+                            let dwell_time = configuration.tx_params.uplink_dwell_time;
+                            if region.datarate_allowed(n, dwell_time) {
                                 Some(n)
                             } else {
                                 None
@@ -449,16 +928,34 @@ impl Session {
                     // Handle TxPower
                     let pw = match payload.tx_power() {
                         DR::_15 => Some(configuration.tx_power),
-                        p => region.check_tx_power(p as u8),
+                        p => region.check_tx_power(p as u8, configuration.tx_params.max_eirp),
                     };
 
                     let cm_ack = region.channel_mask_validate(&channel_mask, dr);
+                    let before = (
+                        configuration.data_rate,
+                        configuration.tx_power,
+                        configuration.nb_trans,
+                        region.channel_mask_get(),
+                    );
+                    let accepted = cm_ack && dr.is_some() && pw.is_some();
                     if cm_ack && let (Some(dr), Some(pw)) = (dr, pw) {
-                        // TODO: handle nbtrans
+                        // NbTrans of 0 keeps the current value.
+                        let nb_trans = payload.redundancy().number_of_transmissions();
+                        if nb_trans > 0 {
+                            configuration.nb_trans = nb_trans;
+                        }
                         configuration.data_rate = dr;
                         configuration.tx_power = pw;
                         region.channel_mask_set(channel_mask.clone());
                     }
+                    events.push(MacEvent::LinkAdr {
+                        accepted,
+                        data_rate: Change { before: before.0, after: configuration.data_rate },
+                        tx_power: Change { before: before.1, after: configuration.tx_power },
+                        nb_trans: Change { before: before.2, after: configuration.nb_trans },
+                        channel_mask: Change { before: before.3, after: region.channel_mask_get() },
+                    });
                     // Add matching number of LinkADRAns responses
                     for _ in 0..num_adrreq {
                         let mut cmd = LinkADRAnsCreator::new();
@@ -469,24 +966,27 @@ impl Session {
                     }
                     num_adrreq = 0;
                 }
-                LinkCheckAns(..) => {
-                    /* TODO: Payload contents are not consumed/handled
-                     * by MAC layer, instead these might be useful to
-                     * application layer.
-                     * Therefore keep this as a placeholder until a proper
-                     * device <-> mac integration has been implemented.
-                     */
+                LinkCheckAns(payload) => {
+                    self.link_check = Some((payload.margin(), payload.gateway_count()));
+                }
+                DeviceTimeAns(payload) => {
+                    let fraction_ms = payload.nano_seconds() / 1_000_000;
+                    self.device_time = Some(payload.seconds() as u64 * 1000 + fraction_ms as u64);
                 }
                 NewChannelReq(payload) => {
                     if region.has_fixed_channel_plan() {
                         // Regions with fixed channel plan ignore this command
                         continue;
                     }
-                    let (ack_f, ack_d) = region.handle_new_channel(
-                        payload.channel_index(),
-                        payload.frequency().value(),
-                        payload.data_rate_range().ok(),
-                    );
+                    let index = payload.channel_index();
+                    let frequency = payload.frequency().value();
+                    let (ack_f, ack_d) =
+                        region.handle_new_channel(index, frequency, payload.data_rate_range().ok());
+                    events.push(MacEvent::NewChannel {
+                        accepted: ack_f && ack_d,
+                        index,
+                        frequency,
+                    });
 
                     let mut cmd = NewChannelAnsCreator::new();
                     cmd.set_channel_frequency_ack(ack_f).set_data_rate_range_ack(ack_d);
@@ -508,12 +1008,28 @@ impl Session {
                             }
                         }
                     };
+                    let before = *configuration;
                     if freq_ack && let (Some(rx2_dr), Some(rx1_dr_offset)) = (rx2_dr, rx1_dr_offset)
                     {
                         configuration.rx2_data_rate = rx2_dr;
                         configuration.rx2_frequency = Some(freq);
                         configuration.rx1_dr_offset = rx1_dr_offset;
                     }
+                    events.push(MacEvent::RxParamSetup {
+                        accepted: freq_ack && rx1_dr_offset.is_some() && rx2_dr.is_some(),
+                        rx1_dr_offset: Change {
+                            before: before.rx1_dr_offset,
+                            after: configuration.rx1_dr_offset,
+                        },
+                        rx2_data_rate: Change {
+                            before: before.rx2_data_rate,
+                            after: configuration.rx2_data_rate,
+                        },
+                        rx2_frequency: Change {
+                            before: before.rx2_frequency,
+                            after: configuration.rx2_frequency,
+                        },
+                    });
 
                     let mut cmd = RXParamSetupAnsCreator::new();
                     cmd.set_rx1_data_rate_offset_ack(rx1_dr_offset.is_some())
@@ -521,34 +1037,143 @@ impl Session {
                         .set_channel_ack(freq_ack);
 
                     self.uplink.add_mac_command(cmd);
-
-                    // TODO: An end-device that expects to receive Class C
-                    // downlink frames will send an uplink frame as soon
-                    // as possible after receiving a valid RXParamSetupReq
-                    // that modifies RX2 (Frequency or RX2DataRate fields).
+                    // A Class C device sends an uplink as soon as possible after RXParamSetupReq,
+                    // which the async device does from `rxc_listen`.
+                    self.mac_uplink_due = true;
+                }
+                DutyCycleReq(payload) => {
+                    let before = configuration.max_duty_cycle;
+                    configuration.max_duty_cycle = payload.max_duty_cycle_raw();
+                    let after = configuration.max_duty_cycle;
+                    events.push(MacEvent::DutyCycle { max_duty_cycle: Change { before, after } });
+                    self.uplink.add_mac_command(DutyCycleAnsCreator::new());
+                }
+                TXParamSetupReq(payload) => {
+                    if !region.supports_tx_param_setup() {
+                        // Regions without dwell-time limits ignore this command
+                        continue;
+                    }
+                    let tx_params = TxParams {
+                        uplink_dwell_time: payload.uplink_dwell_time(),
+                        downlink_dwell_time: payload.downlink_dwell_time(),
+                        max_eirp: Some(payload.max_eirp()),
+                    };
+                    let before = configuration.tx_params;
+                    configuration.tx_params = tx_params;
+                    self.tx_params = tx_params;
+                    let tx_params = Change { before, after: tx_params };
+                    events.push(MacEvent::TxParamSetup { tx_params });
+                    self.uplink.add_mac_command(TXParamSetupAnsCreator::new());
                 }
                 RXTimingSetupReq(payload) => {
+                    let before = configuration.rx1_delay;
                     configuration.rx1_delay = super::del_to_delay_ms(payload.delay());
+                    let after = configuration.rx1_delay;
+                    events.push(MacEvent::RxTimingSetup { rx1_delay_ms: Change { before, after } });
                     self.uplink.add_mac_command(RXTimingSetupAnsCreator::new());
+                    self.mac_uplink_due = true;
+                }
+                RekeyConf(..) => {
+                    if let Some(v1_1) = &mut self.lorawan_1_1 {
+                        v1_1.rekey_confirmed = true;
+                    }
+                }
+                ForceRejoinReq(payload) => {
+                    if self.lorawan_1_1.is_none() {
+                        continue;
+                    }
+                    // Rejoin types 0 and 1 ask for a type 0 request, 3 to 7 are RFU.
+                    let rejoin_type = match payload.rejoin_type() {
+                        0 | 1 => RejoinType::Type0,
+                        2 => RejoinType::Type2,
+                        _ => continue,
+                    };
+                    self.force_rejoin = Some(ForceRejoin {
+                        rejoin_type,
+                        dr: payload.data_rate(),
+                        period: payload.period(),
+                        max_retries: payload.max_retries(),
+                    });
+                }
+                RejoinParamSetupReq(payload) => {
+                    let Some(v1_1) = &mut self.lorawan_1_1 else {
+                        continue;
+                    };
+                    v1_1.rejoin_params = Some(RejoinParams {
+                        max_time_n: payload.max_time_n(),
+                        max_count_n: payload.max_count_n(),
+                    });
+                    // Rejoin requests are also sent based on time.
+                    let mut cmd = RejoinParamSetupAnsCreator::new();
+                    cmd.set_time_ack(true);
+                    self.uplink.add_mac_command(cmd);
+                }
+                #[cfg(feature = "class-b")]
+                PingSlotInfoAns(..) => {
+                    let class_b = &mut configuration.class_b;
+                    if let Some(periodicity) = class_b.pending_periodicity.take() {
+                        class_b.periodicity = Some(periodicity);
+                    }
+                }
+                #[cfg(feature = "class-b")]
+                PingSlotChannelReq(payload) => {
+                    // A frequency of 0 restores the region default
+                    let freq = payload.frequency().value();
+                    let freq_ack = freq == 0 || region.frequency_valid(freq);
+                    let dr = payload.data_rate();
+                    let dr_ack = region.get_datarate(dr as u8).is_some();
+                    let class_b = &mut configuration.class_b;
+                    let before = (class_b.ping_slot_frequency, class_b.ping_slot_data_rate);
+                    if freq_ack && dr_ack {
+                        class_b.ping_slot_frequency = (freq != 0).then_some(freq);
+                        class_b.ping_slot_data_rate = Some(dr);
+                    }
+                    events.push(MacEvent::PingSlotChannel {
+                        accepted: freq_ack && dr_ack,
+                        frequency: Change { before: before.0, after: class_b.ping_slot_frequency },
+                        data_rate: Change { before: before.1, after: class_b.ping_slot_data_rate },
+                    });
+
+                    let mut cmd = PingSlotChannelAnsCreator::new();
+                    cmd.set_channel_frequency_ack(freq_ack).set_data_rate_ack(dr_ack);
+                    self.uplink.add_mac_command(cmd);
+                }
+                #[cfg(feature = "class-b")]
+                BeaconFreqReq(payload) => {
+                    // A frequency of 0 restores the region default
+                    let freq = payload.frequency().value();
+                    let freq_ack = freq == 0 || region.frequency_valid(freq);
+                    let before = configuration.class_b.beacon_frequency;
+                    if freq_ack {
+                        configuration.class_b.beacon_frequency = (freq != 0).then_some(freq);
+                    }
+                    let after = configuration.class_b.beacon_frequency;
+                    let frequency = Change { before, after };
+                    events.push(MacEvent::BeaconFreq { accepted: freq_ack, frequency });
+
+                    let mut cmd = BeaconFreqAnsCreator::new();
+                    cmd.set_beacon_frequency_ack(freq_ack);
+                    self.uplink.add_mac_command(cmd);
                 }
+                #[cfg(not(feature = "class-b"))]
                 _ => (),
             }
         }
+        // Answers which do not all fit in FOpts are sent with an uplink of their own, on FPort 0.
+        if self.uplink.mac_commands().len() > FOPTS_MAX_LEN {
+            self.mac_uplink_due = true;
+        }
     }
 }
 
-/// Next lower region-supported data rate, if any.
-fn next_lower_datarate(region: &region::Configuration, current: DR) -> Option<DR> {
-    let current = current as u8;
-    if current == 0 {
-        return None;
-    }
-    for candidate in (0..current).rev() {
-        if region.get_datarate(candidate).is_some() {
-            return Some(DR::from(candidate));
-        }
-    }
-    None
+/// Next lower region-supported data rate usable under the uplink dwell time, if any.
+pub(super) fn next_lower_datarate(
+    region: &region::Configuration,
+    configuration: &super::Configuration,
+) -> Option<DR> {
+    let current = configuration.data_rate as u8;
+    let dwell_time = configuration.tx_params.uplink_dwell_time;
+    (0..current).rev().map(DR::from).find(|&dr| region.datarate_allowed(dr, dwell_time))
 }
 
 /// Rebuild the full 32-bit downlink frame counter from the 16-bit value carried
@@ -586,27 +1211,50 @@ fn next_fcnt_down(last: Option<u32>, wire: u16) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use super::next_fcnt_down;
-    use super::{SendData, Session};
-    use crate::mac::Mac;
+    use super::{SendData, Session, Session1_1};
+    use crate::mac::{Error, Mac, NetworkCredentials, RejoinParams, RejoinType, Response};
     use crate::radio::RadioBuffer;
     use crate::region;
-    use crate::{AppSKey, NwkSKey};
-    use lorawan::default_crypto::DefaultCrypto;
-    use lorawan::maccommandcreator::LinkADRAnsCreator;
-    use lorawan::parser::{DecryptedDataPayload, DevAddr, EncryptedDataPayload, FrmPayload};
-    use lorawan::types::DR;
+    use crate::rng::Prng;
+    use crate::{AppEui, AppKey, AppSKey, DevEui, NwkKey, NwkSKey};
+    use core::num::NonZeroU8;
+    use lora_modulation::SpreadingFactor;
+    use lorawan::creator::{DataFrame, JoinAccept, Payload};
+    use lorawan::default_crypto::{DefaultCrypto, DefaultNetworkCrypto};
+    use lorawan::keys::{JSEncKey, JSIntKey, NetworkSessionCrypto, NwkSEncKey, SNwkSIntKey};
+    use lorawan::maccommandcreator::{
+        ForceRejoinReqCreator, LinkADRAnsCreator, RejoinParamSetupReqCreator, RekeyConfCreator,
+    };
+    use lorawan::maccommands::{UplinkMacCommand, parse_uplink_mac_commands};
+    use lorawan::parser::{
+        self, DataFrameType, DecryptedDataPayload, DevAddr, DevNonce, EncryptedDataPayload,
+        FrmPayload, JoinNonce, JoinReqType, MicContext, NetId, PhyPayload,
+    };
+    use lorawan::types::{DLSettings, DR};
 
     fn uplink_fctrl(session: &mut Session, mac: &Mac) -> lorawan::parser::FCtrl {
         let mut tx: RadioBuffer<256> = RadioBuffer::new();
         session.prepare_buffer::<256>(
-            &SendData { data: &[], fport: 1, confirmed: false },
+            Some(&SendData { data: &[], fport: 1, confirmed: false }),
             &mut tx,
             &mac.configuration,
             &mac.region,
+            &tx_channel(mac, 0),
         );
         EncryptedDataPayload::parse(tx.as_mut_for_read()).unwrap().fhdr().fctrl()
     }
 
+    fn tx_channel(mac: &Mac, channel: u8) -> region::TxChannel {
+        let dr = mac.configuration.data_rate;
+        region::TxChannel {
+            datarate: mac.region.get_datarate(dr as u8).unwrap().clone(),
+            dr,
+            channel,
+            frequency: 868_100_000,
+            rx1_frequency: 868_100_000,
+        }
+    }
+
     fn eu868_mac() -> Mac {
         Mac::new(region::Configuration::new(region::Region::EU868), 14, 0)
     }
@@ -636,6 +1284,22 @@ mod tests {
         assert!(!fctrl.adr_ack_req());
     }
 
+    #[test]
+    fn adr_ack_req_calls_for_an_uplink() {
+        let mut mac = eu868_mac();
+        mac.configuration.data_rate = DR::_5;
+        let mut session = session();
+        session.adr_ack_cnt = super::ADR_ACK_LIMIT as u32 - 1;
+        session.rx2_complete(&mut mac.configuration, &mac.region);
+        assert!(session.mac_uplink_due());
+        let fctrl = uplink_fctrl(&mut session, &mac);
+        assert!(fctrl.adr_ack_req());
+        assert!(!session.mac_uplink_due());
+        // Only once, when the limit is reached.
+        session.rx2_complete(&mut mac.configuration, &mac.region);
+        assert!(!session.mac_uplink_due());
+    }
+
     #[test]
     fn adr_backoff_starts_after_ack_limit_and_delay() {
         let mut mac = eu868_mac();
@@ -664,6 +1328,16 @@ mod tests {
         assert!(!fctrl.adr_ack_req());
     }
 
+    #[test]
+    fn adr_backoff_skips_unsupported_modulations() {
+        let mut region = region::Configuration::new(region::Region::EU868);
+        region.set_modulations(region::SupportedModulations { fsk: false, lr_fhss: true });
+        let mut mac = Mac::new(region, 14, 0);
+        mac.configuration.data_rate = DR::_8;
+        // DR7 is FSK
+        assert_eq!(super::next_lower_datarate(&mac.region, &mac.configuration), Some(DR::_6));
+    }
+
     /// FPort 0 sends the queued MAC commands as the FRMPayload (encrypted
     /// with the NwkSKey), with FOpts left empty.
     #[test]
@@ -680,10 +1354,11 @@ mod tests {
         let mut tx: RadioBuffer<256> = RadioBuffer::new();
         let mac = Mac::new(region::Configuration::new(region::Region::EU868), 14, 0);
         session.prepare_buffer::<256>(
-            &SendData { data: &[], fport: 0, confirmed: false },
+            Some(&SendData { data: &[], fport: 0, confirmed: false }),
             &mut tx,
             &mac.configuration,
             &mac.region,
+            &tx_channel(&mac, 0),
         );
 
         let bytes = tx.as_mut_for_read();
@@ -695,6 +1370,299 @@ mod tests {
         assert_eq!(decrypted.frm_payload(), FrmPayload::MacCommands(&expected[..]));
     }
 
+    fn session_1_1() -> Session {
+        let mut session = session();
+        session.lorawan_1_1 = Some(Session1_1::new(
+            SNwkSIntKey::from([3; 16]),
+            NwkSEncKey::from([4; 16]),
+            JoinNonce::from_value(1),
+            NetId::from_value(1),
+        ));
+        session
+    }
+
+    /// Runs `f` with the network session crypto matching [`session_1_1`].
+    fn with_keys_1_1<T>(f: impl FnOnce(&NetworkSessionCrypto<'_, DefaultCrypto>) -> T) -> T {
+        f(&NetworkSessionCrypto {
+            f_nwk_s_int: &DefaultCrypto::new(&[2; 16].into()),
+            s_nwk_s_int: &DefaultCrypto::new(&[3; 16].into()),
+            nwk_s_enc: &DefaultCrypto::new(&[4; 16].into()),
+        })
+    }
+
+    fn uplink_1_1(session: &mut Session, mac: &Mac, channel: u8) -> RadioBuffer<256> {
+        let mut tx: RadioBuffer<256> = RadioBuffer::new();
+        session.prepare_buffer::<256>(
+            Some(&SendData { data: &[1, 2, 3], fport: 1, confirmed: false }),
+            &mut tx,
+            &mac.configuration,
+            &mac.region,
+            &tx_channel(mac, channel),
+        );
+        tx
+    }
+
+    fn uplink_1_1_mac_commands(tx: &mut RadioBuffer<256>) -> std::vec::Vec<u8> {
+        let decrypted = DecryptedDataPayload::decrypt_in_place_1_1(
+            tx.as_mut_for_read(),
+            &DefaultCrypto::new(&[4; 16].into()),
+            Some(&DefaultCrypto::new(&[1; 16].into())),
+            0,
+        )
+        .unwrap();
+        assert_eq!(decrypted.frm_payload(), FrmPayload::Data(&[1, 2, 3]));
+        decrypted.fhdr().f_opts().to_vec()
+    }
+
+    fn downlink_1_1(mac: &mut Mac, fcnt: u32, f_opts: &[u8], f_port: Option<u8>) -> Response {
+        let frame = DataFrame {
+            frame_type: DataFrameType::UnconfirmedDown,
+            dev_addr: DevAddr::from_value(1),
+            fcnt,
+            f_opts,
+            payload: match f_port.and_then(NonZeroU8::new) {
+                Some(f_port) => Payload::Data { f_port, data: &[9] },
+                None => Payload::None,
+            },
+            ..Default::default()
+        };
+        receive_1_1(mac, &frame, &MicContext::default())
+    }
+
+    fn receive_1_1(mac: &mut Mac, frame: &DataFrame<'_>, ctx: &MicContext) -> Response {
+        let mut buf = [0u8; 256];
+        let app_crypto = DefaultCrypto::new(&[1; 16].into());
+        let bytes =
+            with_keys_1_1(|keys| frame.build_into_1_1(&mut buf, keys, Some(&app_crypto), ctx))
+                .unwrap();
+        let mut rx: RadioBuffer<256> = RadioBuffer::new();
+        rx.extend_from_slice(bytes).unwrap();
+        let rf_config = mac.rx_windows(&tx_channel(mac, 0)).rx1;
+        let mut dl = heapless::Vec::<_, 1>::new();
+        mac.handle_rx::<256, 1>(&mut rx, &mut dl, crate::radio::RxQuality::new(0, 0), &rf_config)
+    }
+
+    #[test]
+    fn lorawan_1_1_uplink_mic_covers_tx_channel() {
+        let mac = eu868_mac();
+        let mut tx = uplink_1_1(&mut session_1_1(), &mac, 2);
+
+        let encrypted = EncryptedDataPayload::parse(tx.as_mut_for_read()).unwrap();
+        let ctx = MicContext { conf_fcnt: 0, tx_dr: mac.configuration.data_rate as u8, tx_ch: 2 };
+        with_keys_1_1(|keys| {
+            assert!(encrypted.validate_mic_1_1(keys, 0, &ctx));
+            assert!(!encrypted.validate_mic_1_1(keys, 0, &MicContext { tx_ch: 1, ..ctx }));
+            assert!(!encrypted.validate_mic(keys.f_nwk_s_int, 0));
+        });
+    }
+
+    #[test]
+    fn lorawan_1_1_ack_mic_covers_confirmed_uplink_fcnt() {
+        let mut mac = eu868_mac();
+        let mut session = session_1_1();
+        session.fcnt_up = 7;
+        let mut tx: RadioBuffer<256> = RadioBuffer::new();
+        session.prepare_buffer::<256>(
+            Some(&SendData { data: &[1, 2, 3], fport: 1, confirmed: true }),
+            &mut tx,
+            &mac.configuration,
+            &mac.region,
+            &tx_channel(&mac, 0),
+        );
+        // In Class C the acknowledgment may arrive once RX2 has advanced FCntUp.
+        session.rx2_complete(&mut mac.configuration, &mac.region);
+        assert_eq!(session.fcnt_up, 8);
+        mac.set_session(session);
+
+        let frame = DataFrame {
+            frame_type: DataFrameType::UnconfirmedDown,
+            dev_addr: DevAddr::from_value(1),
+            ack: true,
+            ..Default::default()
+        };
+        let ctx = |conf_fcnt| MicContext { conf_fcnt, ..Default::default() };
+        assert!(matches!(receive_1_1(&mut mac, &frame, &ctx(8)), Response::NoUpdate));
+        assert!(matches!(receive_1_1(&mut mac, &frame, &ctx(7)), Response::DownlinkReceived(0)));
+    }
+
+    #[test]
+    fn lorawan_1_1_rekey_ind_sent_until_rekey_conf() {
+        let mut mac = eu868_mac();
+        let f_opts = uplink_1_1_mac_commands(&mut uplink_1_1(&mut session_1_1(), &mac, 0));
+        match parse_uplink_mac_commands(&f_opts).next() {
+            Some(Ok(UplinkMacCommand::RekeyInd(payload))) => assert_eq!(payload.minor_version(), 1),
+            cmd => panic!("Expected RekeyInd, got {cmd:?}"),
+        }
+
+        mac.set_session(session_1_1());
+        let mut rekey_conf = RekeyConfCreator::new();
+        rekey_conf.set_minor_version(1).unwrap();
+        let response = downlink_1_1(&mut mac, 0, rekey_conf.build(), None);
+        assert!(matches!(response, Response::DownlinkReceived(0)));
+
+        let mut session = mac.get_session().unwrap().clone();
+        assert!(session.lorawan_1_1().unwrap().rekey_confirmed());
+        assert!(uplink_1_1_mac_commands(&mut uplink_1_1(&mut session, &mac, 0)).is_empty());
+    }
+
+    #[test]
+    fn lorawan_1_1_application_downlinks_use_separate_counter() {
+        let mut mac = eu868_mac();
+        mac.set_session(session_1_1());
+
+        assert!(matches!(downlink_1_1(&mut mac, 5, &[], Some(1)), Response::DownlinkReceived(5)));
+        let session = mac.get_session().unwrap();
+        assert_eq!(session.lorawan_1_1().unwrap().a_fcnt_down(), Some(5));
+        assert_eq!(session.fcnt_down(), None);
+
+        // NFCntDown is independent, so a lower counter on FPort 0 is still fresh.
+        assert!(matches!(downlink_1_1(&mut mac, 2, &[], None), Response::DownlinkReceived(2)));
+        assert_eq!(mac.get_session().unwrap().fcnt_down(), Some(2));
+        // A replayed application downlink is dropped.
+        assert!(matches!(downlink_1_1(&mut mac, 5, &[], Some(1)), Response::NoUpdate));
+    }
+
+    fn rejoin_mac() -> Mac {
+        let mut mac = eu868_mac();
+        mac.set_rejoin_credentials(NetworkCredentials::new_1_1(
+            AppEui::from([1; 8]),
+            DevEui::from([3; 8]),
+            NwkKey::from([5; 16]),
+            AppKey::from([6; 16]),
+        ));
+        mac.set_session(session_1_1());
+        mac
+    }
+
+    /// Answer the rejoin request in `request` with a JoinAccept signed for `join_req_type`.
+    fn rejoin_accept(mac: &mut Mac, request: &[u8], join_req_type: JoinReqType) -> Response {
+        let Ok(PhyPayload::RejoinRequest(rejoin)) = parser::parse(request) else {
+            panic!("Did not parse a rejoin request");
+        };
+        let nwk_crypto = DefaultCrypto::new(&[5; 16].into());
+        let js_enc_key = JSEncKey::derive_from(&nwk_crypto, &rejoin.dev_eui());
+        let js_int_key = JSIntKey::derive_from(&nwk_crypto, &rejoin.dev_eui());
+        let accept = JoinAccept {
+            join_nonce: JoinNonce::from_value(5),
+            net_id: NetId::from_value(2),
+            dev_addr: DevAddr::from_value(0x2000),
+            dl_settings: DLSettings::new(0x80),
+            rx_delay: 3,
+            c_f_list: None,
+        };
+        let mut buf = [0; 33];
+        let bytes = accept
+            .build_into_1_1(
+                &mut buf,
+                &DefaultNetworkCrypto::new(js_enc_key.inner()),
+                &DefaultCrypto::new(js_int_key.inner()),
+                join_req_type,
+                &AppEui::from([1; 8]).into(),
+                &DevNonce::from_value(rejoin.rj_count()),
+            )
+            .unwrap();
+        let mut rx: RadioBuffer<256> = RadioBuffer::new();
+        rx.extend_from_slice(bytes).unwrap();
+        let rf_config = mac.rx_windows(&tx_channel(mac, 0)).rx1;
+        let mut dl = heapless::Vec::<_, 1>::new();
+        mac.handle_rx::<256, 1>(&mut rx, &mut dl, crate::radio::RxQuality::new(0, 0), &rf_config)
+    }
+
+    #[test]
+    fn lorawan_1_1_force_rejoin_switches_session() {
+        let mut mac = rejoin_mac();
+        mac.configuration.rx1_delay = 5000;
+        assert_eq!(mac.pending_rejoin(Some(0)), None);
+
+        let mut cmd = ForceRejoinReqCreator::new();
+        cmd.set_rejoin_type(2).unwrap().set_max_retries(1).unwrap().set_data_rate(3).unwrap();
+        assert!(matches!(
+            downlink_1_1(&mut mac, 0, cmd.build(), None),
+            Response::DownlinkReceived(0)
+        ));
+        assert_eq!(mac.pending_rejoin(Some(0)), Some(RejoinType::Type2));
+
+        // Sent at the data rate asked for, and retried 32 s (period 0) to 64 s later.
+        let mut rng = Prng::new(1);
+        let mut buf: RadioBuffer<256> = RadioBuffer::new();
+        let (tx_config, _, rj_count) =
+            mac.rejoin(&mut rng, RejoinType::Type2, &mut buf, None).unwrap();
+        assert_eq!(rj_count, 0);
+        assert_eq!(buf.as_ref_for_read().len(), 19);
+        assert_eq!(tx_config.rf.bb.sf, SpreadingFactor::_9);
+        assert_eq!(mac.pending_rejoin(Some(0)), None);
+        mac.tx_done(1_000);
+        assert!(matches!(mac.rx2_complete(), Response::NoJoinAccept));
+        assert_eq!(mac.get_fcnt_up(), Some(1));
+        assert_eq!(mac.pending_rejoin(Some(32_999)), None);
+        assert_eq!(mac.pending_rejoin(Some(65_000)), Some(RejoinType::Type2));
+
+        let (_, _, rj_count) = mac.rejoin(&mut rng, RejoinType::Type2, &mut buf, None).unwrap();
+        assert_eq!(rj_count, 1);
+        let request = buf.as_ref_for_read().to_vec();
+        // A JoinAccept signed for another type of request is ignored.
+        let response = rejoin_accept(&mut mac, &request, JoinReqType::RejoinType0);
+        assert!(matches!(response, Response::NoUpdate));
+        let response = rejoin_accept(&mut mac, &request, JoinReqType::RejoinType2);
+        assert!(matches!(response, Response::JoinSuccess));
+        let session = mac.get_session().unwrap();
+        assert_eq!(session.devaddr().value(), 0x2000);
+        assert_eq!(session.lorawan_1_1().unwrap().net_id(), NetId::from_value(2));
+        assert_eq!(mac.join_nonce(), Some(JoinNonce::from_value(5)));
+        // A type 2 rejoin keeps the radio parameters.
+        assert_eq!(mac.configuration.rx1_delay, 5000);
+        // The retries are over with the new session.
+        assert_eq!(mac.pending_rejoin(Some(1_000_000)), None);
+    }
+
+    #[test]
+    fn lorawan_1_1_periodic_rejoin() {
+        let mut mac = rejoin_mac();
+        let mut cmd = RejoinParamSetupReqCreator::new();
+        cmd.set_max_time_n(0).unwrap().set_max_count_n(0).unwrap();
+        downlink_1_1(&mut mac, 0, cmd.build(), None);
+        let mut session = mac.get_session().unwrap().clone();
+        let params = session.lorawan_1_1().unwrap().rejoin_params();
+        assert_eq!(params, Some(RejoinParams { max_time_n: 0, max_count_n: 0 }));
+        let f_opts = uplink_1_1_mac_commands(&mut uplink_1_1(&mut session, &mac, 0));
+        let answer = parse_uplink_mac_commands(&f_opts).find_map(|cmd| match cmd {
+            Ok(UplinkMacCommand::RejoinParamSetupAns(payload)) => Some(payload.time_ack()),
+            _ => None,
+        });
+        assert_eq!(answer, Some(true));
+
+        // Every 16 uplinks and 1024 s.
+        assert_eq!(mac.pending_rejoin(Some(0)), None);
+        let mut rng = Prng::new(1);
+        let mut buf: RadioBuffer<256> = RadioBuffer::new();
+        let data = SendData { data: &[], fport: 1, confirmed: false };
+        for _ in 0..16 {
+            mac.send(&mut rng, &mut buf, &data, None).unwrap();
+            mac.rx2_complete();
+        }
+        assert_eq!(mac.pending_rejoin(Some(0)), Some(RejoinType::Type0));
+        mac.rejoin(&mut rng, RejoinType::Type0, &mut buf, Some(1_000)).unwrap();
+        assert!(matches!(mac.rx2_complete(), Response::NoJoinAccept));
+        assert_eq!(mac.get_session().unwrap().lorawan_1_1().unwrap().rj_count0(), 1);
+        assert_eq!(mac.pending_rejoin(Some(1_024_999)), None);
+        assert_eq!(mac.pending_rejoin(Some(1_025_000)), Some(RejoinType::Type0));
+    }
+
+    #[test]
+    fn rejoin_needs_lorawan_1_1_session_and_credentials() {
+        let mut rng = Prng::new(1);
+        let mut buf: RadioBuffer<256> = RadioBuffer::new();
+        let mut mac = eu868_mac();
+        mac.set_session(session_1_1());
+        let result = mac.rejoin(&mut rng, RejoinType::Type1, &mut buf, None);
+        assert!(matches!(result, Err(Error::RejoinUnavailable)));
+
+        let mut mac = rejoin_mac();
+        mac.set_session(session());
+        let result = mac.rejoin(&mut rng, RejoinType::Type0, &mut buf, None);
+        assert!(matches!(result, Err(Error::RejoinUnavailable)));
+    }
+
     #[test]
     fn first_downlink_taken_at_face_value() {
         // Before any downlink is seen, the wire value is accepted as-is even
@@ -750,4 +1718,31 @@ mod tests {
         // rather than wrapping to a smaller value.
         assert_eq!(next_fcnt_down(Some(0xFFFF_FFFE), 0), None);
     }
+
+    #[test]
+    fn state_round_trip_1_1() {
+        let mut session = session_1_1();
+        session.fcnt_down = Some(0x1_0002);
+        session.tx_params.max_eirp = Some(16);
+        let v1_1 = session.lorawan_1_1.as_mut().unwrap();
+        v1_1.a_fcnt_down = Some(5);
+        v1_1.conf_fcnt = 5;
+        v1_1.rekey_confirmed = true;
+        v1_1.rj_count1 = 2;
+        let mut mac = eu868_mac();
+        mac.set_session(session);
+        let state = mac.snapshot_state();
+
+        let mut restored = eu868_mac();
+        restored.restore_state(&state).unwrap();
+        assert_eq!(restored.snapshot_state(), state);
+        let session = restored.get_session().unwrap();
+        assert_eq!(session.fcnt_down(), Some(0x1_0002));
+        assert_eq!(session.tx_params().max_eirp, Some(16));
+        let v1_1 = session.lorawan_1_1().unwrap();
+        assert_eq!(v1_1.a_fcnt_down(), Some(5));
+        assert!(v1_1.rekey_confirmed());
+        assert_eq!(v1_1.rj_count1(), 2);
+        assert_eq!(restored.join_nonce(), Some(JoinNonce::from_value(1)));
+    }
 }
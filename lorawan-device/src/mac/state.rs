@@ -0,0 +1,437 @@
+//! Snapshot of the state which has to survive a reboot for the device to stay in sync with the
+//! network: the session and its frame counters, the configuration set by MAC commands, the channel
+//! plan and mask, and the multicast sessions.
+//!
+//! The snapshot is a compact binary encoding, little-endian, versioned by its first byte:
+//!
+//! | Section       | Content                                                                 |
+//! |---------------|-------------------------------------------------------------------------|
+//! | Header        | version, region, flags, last DevNonce and 1.1 JoinNonce                 |
+//! | Configuration | data rate, RX1 delay and DR offset, RX2 parameters, TX power, NbTrans...|
+//! | Class B       | ping-slot frequency and data rate, beacon frequency                     |
+//! | Session       | keys, DevAddr, frame counters, TXParamSetupReq limits, 1.1 state        |
+//! | Channels      | channel mask and the channels of a dynamic plan (with a session only)   |
+//! | Multicast     | McKEKey and the sessions of the multicast groups                        |
+//!
+//! Optional values are either flagged or use a value which is invalid otherwise (`0xFF` for a
+//! data rate or a TX power, 0 for a frequency). Sections of disabled features are written empty and
+//! skipped when read, so that a snapshot does not depend on the features of the build.
+
+use super::{Configuration, Mac, Session, State};
+use crate::region::Region;
+use heapless::Vec;
+use lorawan::parser::{DevNonce, JoinNonce};
+use lorawan::types::DR;
+
+/// Version of the encoding written by [`DeviceState`].
+pub const STATE_VERSION: u8 = 2;
+
+const MAX_LEN: usize = 512;
+
+const FLAG_SESSION: u8 = 0x01;
+const FLAG_JOIN_NONCE: u8 = 0x02;
+const FLAG_DEV_NONCE: u8 = 0x04;
+
+/// Error restoring a [`DeviceState`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum StateError {
+    /// The snapshot was written with another version of the encoding.
+    UnsupportedVersion(u8),
+    /// The snapshot was taken on a device configured for another region.
+    RegionMismatch,
+    /// The snapshot is truncated or holds invalid values.
+    Invalid,
+}
+
+/// Versioned binary snapshot of the persistent state of a device, to be stored in non-volatile
+/// memory and restored after a reboot.
+///
+/// Take it with `snapshot_state` when `take_state_dirty` reports a change, and restore it with
+/// `restore_state` on a device created for the same region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceState {
+    bytes: Vec<u8, MAX_LEN>,
+}
+
+impl DeviceState {
+    /// Upper bound of the length of a snapshot.
+    pub const MAX_LEN: usize = MAX_LEN;
+
+    /// The encoded snapshot.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Load a snapshot read back from non-volatile memory. Only the length and the version are
+    /// checked here, the content is validated when it is restored.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StateError> {
+        let version = *bytes.first().ok_or(StateError::Invalid)?;
+        if version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+        Ok(Self { bytes: Vec::from_slice(bytes).map_err(|_| StateError::Invalid)? })
+    }
+}
+
+impl AsRef<[u8]> for DeviceState {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+pub(crate) struct Writer {
+    bytes: Vec<u8, MAX_LEN>,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        // MAX_LEN covers the largest state: a 1.1 session, 16 channels and 4 multicast groups.
+        self.bytes.extend_from_slice(bytes).unwrap();
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn array<const L: usize>(&mut self) -> Result<[u8; L], StateError> {
+        let (head, tail) = self.bytes.split_first_chunk().ok_or(StateError::Invalid)?;
+        self.bytes = tail;
+        Ok(*head)
+    }
+
+    #[cfg(any(not(feature = "multicast"), not(feature = "class-b")))]
+    fn skip(&mut self, len: usize) -> Result<(), StateError> {
+        self.bytes = self.bytes.get(len..).ok_or(StateError::Invalid)?;
+        Ok(())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    /// A data rate, `None` when encoded as `0xFF`.
+    pub(crate) fn datarate(&mut self) -> Result<Option<DR>, StateError> {
+        match self.u8()? {
+            0xFF => Ok(None),
+            dr @ 0..=15 => Ok(Some(DR::from(dr))),
+            _ => Err(StateError::Invalid),
+        }
+    }
+}
+
+/// Custom regions share an id, a snapshot is not checked against the custom region it is
+/// restored with.
+fn region_id(region: Option<Region>) -> u8 {
+    let Some(region) = region else {
+        return 15;
+    };
+    match region {
+        #[cfg(feature = "region-as923-1")]
+        Region::AS923_1 => 0,
+        #[cfg(feature = "region-as923-2")]
+        Region::AS923_2 => 1,
+        #[cfg(feature = "region-as923-3")]
+        Region::AS923_3 => 2,
+        #[cfg(feature = "region-as923-4")]
+        Region::AS923_4 => 3,
+        #[cfg(feature = "region-au915")]
+        Region::AU915 => 4,
+        #[cfg(feature = "region-eu868")]
+        Region::EU868 => 5,
+        #[cfg(feature = "region-eu433")]
+        Region::EU433 => 6,
+        #[cfg(feature = "region-in865")]
+        Region::IN865 => 7,
+        #[cfg(feature = "region-us915")]
+        Region::US915 => 8,
+        #[cfg(feature = "region-kr920")]
+        Region::KR920 => 9,
+        #[cfg(feature = "region-cn470")]
+        Region::CN470_20A => 10,
+        #[cfg(feature = "region-cn470")]
+        Region::CN470_20B => 11,
+        #[cfg(feature = "region-cn470")]
+        Region::CN470_26A => 12,
+        #[cfg(feature = "region-cn470")]
+        Region::CN470_26B => 13,
+        #[cfg(feature = "region-ru864")]
+        Region::RU864 => 14,
+    }
+}
+
+impl Configuration {
+    fn write_state(&self, w: &mut Writer) {
+        w.u8(self.data_rate as u8);
+        w.u8((self.rx1_delay / 1000) as u8);
+        w.u8(self.rx1_dr_offset);
+        w.u8(self.rx2_data_rate.map_or(0xFF, |dr| dr as u8));
+        w.u32(self.rx2_frequency.unwrap_or(0));
+        w.u8(self.tx_power.unwrap_or(0xFF));
+        w.u8(self.max_duty_cycle);
+        w.u8(self.nb_trans);
+        w.u8(self.adr_enabled as u8);
+        #[cfg(feature = "class-b")]
+        {
+            w.u32(self.class_b.ping_slot_frequency.unwrap_or(0));
+            w.u8(self.class_b.ping_slot_data_rate.map_or(0xFF, |dr| dr as u8));
+            w.u32(self.class_b.beacon_frequency.unwrap_or(0));
+        }
+        #[cfg(not(feature = "class-b"))]
+        w.bytes(&[0, 0, 0, 0, 0xFF, 0, 0, 0, 0]);
+    }
+
+    fn read_state(&mut self, r: &mut Reader<'_>) -> Result<(), StateError> {
+        self.data_rate = r.datarate()?.ok_or(StateError::Invalid)?;
+        self.rx1_delay = super::del_to_delay_ms(r.u8()?);
+        self.rx1_dr_offset = r.u8()?;
+        self.rx2_data_rate = r.datarate()?;
+        self.rx2_frequency = Some(r.u32()?).filter(|&f| f != 0);
+        self.tx_power = Some(r.u8()?).filter(|&p| p != 0xFF);
+        self.max_duty_cycle = r.u8()?;
+        self.nb_trans = r.u8()?.max(1);
+        self.adr_enabled = r.u8()? != 0;
+        #[cfg(feature = "class-b")]
+        {
+            self.class_b.ping_slot_frequency = Some(r.u32()?).filter(|&f| f != 0);
+            self.class_b.ping_slot_data_rate = r.datarate()?;
+            self.class_b.beacon_frequency = Some(r.u32()?).filter(|&f| f != 0);
+        }
+        #[cfg(not(feature = "class-b"))]
+        r.skip(9)?;
+        Ok(())
+    }
+}
+
+impl Mac {
+    pub(crate) fn snapshot_state(&self) -> DeviceState {
+        let mut w = Writer::new();
+        let session = self.get_session();
+        let mut flags = 0;
+        if session.is_some() {
+            flags |= FLAG_SESSION;
+        }
+        if self.join_nonce.is_some() {
+            flags |= FLAG_JOIN_NONCE;
+        }
+        if self.dev_nonce.is_some() {
+            flags |= FLAG_DEV_NONCE;
+        }
+        w.u8(STATE_VERSION);
+        w.u8(region_id(self.region.get_current_region()));
+        w.u8(flags);
+        if let Some(dev_nonce) = self.dev_nonce {
+            w.bytes(dev_nonce.as_wire_bytes());
+        }
+        if let Some(join_nonce) = self.join_nonce {
+            w.bytes(join_nonce.as_wire_bytes());
+        }
+        self.configuration.write_state(&mut w);
+        if let Some(session) = session {
+            session.write_state(&mut w);
+            self.region.write_state(&mut w);
+        }
+        #[cfg(feature = "multicast")]
+        self.multicast.write_state(&mut w);
+        #[cfg(not(feature = "multicast"))]
+        w.bytes(&[0, 0]);
+        DeviceState { bytes: w.bytes }
+    }
+
+    /// Restore a snapshot, which replaces the current session. Nothing is changed if the snapshot
+    /// is invalid.
+    pub(crate) fn restore_state(&mut self, state: &DeviceState) -> Result<(), StateError> {
+        let mut r = Reader::new(state.as_bytes());
+        let version = r.u8()?;
+        if version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+        if r.u8()? != region_id(self.region.get_current_region()) {
+            return Err(StateError::RegionMismatch);
+        }
+        let flags = r.u8()?;
+        let dev_nonce = match flags & FLAG_DEV_NONCE {
+            0 => None,
+            _ => Some(DevNonce::from_wire_bytes(r.array()?)),
+        };
+        let join_nonce = match flags & FLAG_JOIN_NONCE {
+            0 => None,
+            _ => Some(JoinNonce::from_wire_bytes(r.array()?)),
+        };
+        let mut configuration = self.configuration;
+        configuration.read_state(&mut r)?;
+        let mut region = self.region.clone();
+        let session = match flags & FLAG_SESSION {
+            0 => None,
+            _ => {
+                let session = Session::read_state(&mut r)?;
+                region.read_state(&mut r)?;
+                Some(session)
+            }
+        };
+        #[cfg(feature = "multicast")]
+        let (mc_k_e_key, mc_sessions) = super::multicast::Multicast::read_state(&mut r)?;
+        #[cfg(not(feature = "multicast"))]
+        skip_multicast(&mut r)?;
+        if !r.bytes.is_empty() {
+            return Err(StateError::Invalid);
+        }
+
+        self.configuration = configuration;
+        self.region = region;
+        #[cfg(feature = "multicast")]
+        {
+            self.multicast.mc_k_e_key = mc_k_e_key;
+            self.multicast.sessions = mc_sessions;
+        }
+        if let Some(dev_nonce) = dev_nonce {
+            self.set_dev_nonce(dev_nonce);
+        }
+        if let Some(join_nonce) = join_nonce {
+            self.set_join_nonce(join_nonce);
+        }
+        match session {
+            Some(session) => self.set_session(session),
+            None => {
+                self.state = State::Unjoined;
+                self.repetition = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the state saved by [`Self::snapshot_state`] changed since the last call.
+    pub(crate) fn take_state_dirty(&mut self) -> bool {
+        core::mem::take(&mut self.state_dirty)
+    }
+}
+
+/// Skip the multicast section of a snapshot taken with the `multicast` feature.
+#[cfg(not(feature = "multicast"))]
+fn skip_multicast(r: &mut Reader<'_>) -> Result<(), StateError> {
+    const MULTICAST_KEY_LEN: usize = 16;
+    // McAddr, McNetSKey, McAppSKey and the frame counters
+    const MULTICAST_GROUP_LEN: usize = 4 + 2 * 16 + 2 * 4;
+    if r.u8()? != 0 {
+        r.skip(MULTICAST_KEY_LEN)?;
+    }
+    let groups = r.u8()?;
+    r.skip(groups.count_ones() as usize * MULTICAST_GROUP_LEN)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::region;
+    use crate::{AppSKey, NwkSKey};
+    use lorawan::parser::DevAddr;
+    use lorawan::types::DataRateRange;
+
+    fn joined_mac(region: Region) -> Mac {
+        let mut mac = Mac::new(region::Configuration::new(region), 14, 0);
+        let mut session =
+            Session::new(NwkSKey::from([2; 16]), AppSKey::from([1; 16]), DevAddr::from_value(1));
+        session.fcnt_up = 42;
+        session.adr_ack_cnt = 3;
+        mac.set_session(session);
+        mac
+    }
+
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn round_trip() {
+        let mut mac = joined_mac(Region::EU868);
+        mac.configuration.data_rate = DR::_3;
+        mac.configuration.rx1_delay = 5000;
+        mac.configuration.rx2_data_rate = Some(DR::_2);
+        mac.configuration.rx2_frequency = Some(869_525_000);
+        mac.configuration.tx_power = Some(2);
+        mac.configuration.nb_trans = 2;
+        mac.region.handle_new_channel(
+            3,
+            867_100_000,
+            Some(DataRateRange::new_range(DR::_0, DR::_5)),
+        );
+        mac.region.channel_dl_update(3, 868_500_000);
+        mac.set_join_nonce(JoinNonce::from_value(7));
+        mac.set_dev_nonce(DevNonce::from_value(300));
+        let state = mac.snapshot_state();
+        assert!(state.as_bytes().len() <= DeviceState::MAX_LEN);
+
+        let mut restored = Mac::new(region::Configuration::new(Region::EU868), 14, 0);
+        let state = DeviceState::from_bytes(state.as_bytes()).unwrap();
+        restored.restore_state(&state).unwrap();
+        assert_eq!(restored.snapshot_state(), state);
+        assert_eq!(restored.configuration, mac.configuration);
+        assert_eq!(restored.get_fcnt_up(), Some(42));
+        assert_eq!(restored.join_nonce(), Some(JoinNonce::from_value(7)));
+        assert_eq!(restored.dev_nonce(), Some(DevNonce::from_value(300)));
+        assert_eq!(restored.region.channel_mask_get(), mac.region.channel_mask_get());
+    }
+
+    #[test]
+    #[cfg(all(feature = "region-eu868", feature = "class-b"))]
+    fn round_trip_class_b() {
+        let mut mac = joined_mac(Region::EU868);
+        mac.configuration.class_b.ping_slot_frequency = Some(869_525_000);
+        mac.configuration.class_b.ping_slot_data_rate = Some(DR::_3);
+        mac.configuration.class_b.beacon_frequency = Some(869_100_000);
+        let state = mac.snapshot_state();
+
+        let mut restored = Mac::new(region::Configuration::new(Region::EU868), 14, 0);
+        restored.restore_state(&state).unwrap();
+        assert_eq!(restored.configuration.class_b, mac.configuration.class_b);
+    }
+
+    #[test]
+    #[cfg(all(feature = "region-eu868", feature = "region-us915"))]
+    fn invalid_snapshots_are_rejected() {
+        let state = joined_mac(Region::EU868).snapshot_state();
+        let mut mac = Mac::new(region::Configuration::new(Region::US915), 14, 0);
+        assert_eq!(mac.restore_state(&state), Err(StateError::RegionMismatch));
+
+        let mut mac = Mac::new(region::Configuration::new(Region::EU868), 14, 0);
+        let truncated = &state.as_bytes()[..state.as_bytes().len() - 1];
+        let truncated = DeviceState::from_bytes(truncated).unwrap();
+        assert_eq!(mac.restore_state(&truncated), Err(StateError::Invalid));
+        assert!(!mac.is_joined());
+
+        let mut bytes = [0; DeviceState::MAX_LEN];
+        bytes[..state.as_bytes().len()].copy_from_slice(state.as_bytes());
+        bytes[0] = STATE_VERSION + 1;
+        assert_eq!(
+            DeviceState::from_bytes(&bytes[..state.as_bytes().len()]),
+            Err(StateError::UnsupportedVersion(STATE_VERSION + 1))
+        );
+    }
+}
@@ -1,14 +1,21 @@
 use lorawan::maccommands::parse_uplink_mac_commands;
 use lorawan::maccommands::{SerializableMacCommand, UplinkMacCommand};
-use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
 
 #[cfg(feature = "serde")]
 mod serde;
 
+/// Room for pending MAC commands: those which fit in FOpts, and those overflowing them which are
+/// sent as the FRMPayload of an uplink on FPort 0.
+pub(crate) const MAC_COMMANDS_MAX_LEN: usize = 128;
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Uplink {
-    pending: heapless::Vec<u8, FOPTS_MAX_LEN>,
+    pending: heapless::Vec<u8, MAC_COMMANDS_MAX_LEN>,
+    /// Length of the answers at the start of `pending` which were already sent and are retained
+    /// until a downlink is received.
+    retained_len: usize,
     confirmed: bool,
+    dropped: bool,
 }
 
 impl Uplink {
@@ -21,32 +28,63 @@ impl Uplink {
     pub fn confirms_downlink(&self) -> bool {
         self.confirmed
     }
+    /// Add a MAC command, recording that it was dropped when there is no room left for it.
     pub fn add_mac_command<M: SerializableMacCommand>(&mut self, cmd: M) {
+        if !self.try_add_mac_command(cmd) {
+            self.dropped = true;
+        }
+    }
+    /// Add a MAC command, returning whether there was room left for it. Unlike
+    /// [`Self::add_mac_command`], a dropped command is left to the caller to report.
+    pub fn try_add_mac_command<M: SerializableMacCommand>(&mut self, cmd: M) -> bool {
         // Check that there's still enough room for MAC commands
-        if self.pending.len() + cmd.payload_len() < FOPTS_MAX_LEN {
+        if self.pending.len() + 1 + cmd.payload_len() <= MAC_COMMANDS_MAX_LEN {
             let _ = self.pending.push(cmd.cid());
             self.pending.extend_from_slice(cmd.payload_bytes()).unwrap();
+            true
+        } else {
+            false
         }
     }
-    pub fn clear_mac_commands(&mut self, retain_acks: bool) {
-        // Certain commands have to be retained until their acknowledgment is confirmed
-        if retain_acks {
-            use UplinkMacCommand::*;
-            let mut data: heapless::Vec<u8, FOPTS_MAX_LEN> = heapless::Vec::new();
-            let _: heapless::Vec<_, FOPTS_MAX_LEN> = parse_uplink_mac_commands(&self.pending)
-                .filter_map(Result::ok)
-                .filter(|cmd| {
-                    matches!(cmd, DlChannelAns(_) | RXParamSetupAns(_) | RXTimingSetupAns(_))
-                })
-                .map(|c| {
-                    let _ = data.push(c.cid());
-                    data.extend_from_slice(c.payload_bytes()).unwrap();
-                })
-                .collect();
-            self.pending = data;
-        } else {
-            self.pending.clear();
+    /// Whether MAC commands were dropped for lack of room since the last call.
+    pub fn take_dropped(&mut self) -> bool {
+        core::mem::take(&mut self.dropped)
+    }
+    /// Length of the pending MAC commands which fit whole in `max_len` bytes, in their order.
+    pub fn mac_commands_len(&self, max_len: usize) -> usize {
+        let mut len = 0;
+        for cmd in parse_uplink_mac_commands(&self.pending) {
+            let Ok(cmd) = cmd else { break };
+            let cmd_len = 1 + cmd.payload_bytes().len();
+            if len + cmd_len > max_len {
+                break;
+            }
+            len += cmd_len;
+        }
+        len
+    }
+    /// Drop the first `len` bytes of pending MAC commands once they were sent, except for the
+    /// commands which have to be retained until their acknowledgment is confirmed by a downlink.
+    pub fn mac_commands_sent(&mut self, len: usize) {
+        use UplinkMacCommand::*;
+        let mut data: heapless::Vec<u8, MAC_COMMANDS_MAX_LEN> = heapless::Vec::new();
+        for cmd in parse_uplink_mac_commands(&self.pending[..len]).filter_map(Result::ok) {
+            if matches!(cmd, DlChannelAns(_) | RXParamSetupAns(_) | RXTimingSetupAns(_)) {
+                let _ = data.push(cmd.cid());
+                data.extend_from_slice(cmd.payload_bytes()).unwrap();
+            }
         }
+        self.retained_len = data.len();
+        // The retained commands are part of the sent ones, so the rest always fits.
+        data.extend_from_slice(&self.pending[len..]).unwrap();
+        self.pending = data;
+    }
+    /// Drop the retained answers once a downlink confirms their reception. Commands which were
+    /// not sent yet are kept.
+    pub fn clear_sent_mac_commands(&mut self) {
+        let retained_len = core::mem::take(&mut self.retained_len);
+        self.pending.rotate_left(retained_len);
+        self.pending.truncate(self.pending.len() - retained_len);
     }
     pub fn mac_commands(&self) -> &[u8] {
         &self.pending
@@ -68,8 +106,11 @@ impl defmt::Format for Uplink {
 #[cfg(test)]
 mod test {
     use super::*;
-    use lorawan::maccommands::LinkADRAnsCreator;
     use lorawan::maccommands::parse_uplink_mac_commands;
+    use lorawan::maccommands::{
+        DevStatusAnsCreator, LinkADRAnsCreator, NewChannelAnsCreator, RXTimingSetupAnsCreator,
+    };
+    use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
     #[test]
     fn two_link_adr_ans() {
         let mut uplink = Uplink::default();
@@ -80,4 +121,41 @@ mod test {
         assert!(matches!(mac_commands.next().unwrap().unwrap(), UplinkMacCommand::LinkADRAns(_)));
         assert!(mac_commands.next().is_none());
     }
+
+    #[test]
+    fn overflow_kept_until_sent() {
+        let mut uplink = Uplink::default();
+        uplink.add_mac_command(RXTimingSetupAnsCreator::new());
+        for _ in 0..10 {
+            uplink.add_mac_command(NewChannelAnsCreator::new());
+        }
+        assert_eq!(uplink.mac_commands().len(), 21);
+        assert_eq!(uplink.mac_commands_len(FOPTS_MAX_LEN), 15);
+        assert_eq!(uplink.mac_commands_len(14), 13);
+
+        // RXTimingSetupAns is retained until a downlink, the commands which were not sent are
+        // kept after it
+        uplink.mac_commands_sent(15);
+        assert_eq!(uplink.mac_commands(), [8, 7, 0, 7, 0, 7, 0]);
+        uplink.clear_sent_mac_commands();
+        assert_eq!(uplink.mac_commands(), [7, 0, 7, 0, 7, 0]);
+        uplink.clear_sent_mac_commands();
+        assert_eq!(uplink.mac_commands().len(), 6);
+    }
+
+    #[test]
+    fn dropped_when_full() {
+        let mut uplink = Uplink::default();
+        for _ in 0..MAC_COMMANDS_MAX_LEN / 3 {
+            uplink.add_mac_command(DevStatusAnsCreator::new());
+        }
+        assert!(!uplink.take_dropped());
+        uplink.add_mac_command(DevStatusAnsCreator::new());
+        assert!(uplink.take_dropped());
+        assert!(!uplink.take_dropped());
+        assert_eq!(uplink.mac_commands().len(), MAC_COMMANDS_MAX_LEN / 3 * 3);
+        // A command whose drop is reported to the caller does not record it
+        assert!(!uplink.try_add_mac_command(DevStatusAnsCreator::new()));
+        assert!(!uplink.take_dropped());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::mac::uplink::Uplink;
+use crate::mac::uplink::{MAC_COMMANDS_MAX_LEN, Uplink};
 use lorawan::packet_length::phy::mac::fhdr::FOPTS_MAX_LEN;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -6,12 +6,16 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 impl Serialize for Uplink {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("Uplink", 3)?;
+        let mut state = serializer.serialize_struct("Uplink", 4)?;
         state.serialize_field("confirmed", &self.confirmed)?;
-        state.serialize_field("pending_len", &(self.pending.len() as u8))?;
+        // MAC commands overflowing FOpts follow as a sequence of their own, keeping the format
+        // of the first FOPTS_MAX_LEN bytes.
+        let (pending, overflow) = self.pending.split_at(self.pending.len().min(FOPTS_MAX_LEN));
+        state.serialize_field("pending_len", &(pending.len() as u8))?;
         let mut full_array = [0u8; FOPTS_MAX_LEN];
-        full_array[..self.pending.len()].copy_from_slice(&self.pending);
+        full_array[..pending.len()].copy_from_slice(pending);
         state.serialize_field("pending_data", &full_array)?;
+        state.serialize_field("overflow", overflow)?;
         state.end()
     }
 }
@@ -31,6 +35,7 @@ impl<'de> Deserialize<'de> for Uplink {
             Confirmed,
             PendingLen,
             PendingData,
+            Overflow,
         }
 
         struct UplinkVisitor;
@@ -49,6 +54,7 @@ impl<'de> Deserialize<'de> for Uplink {
                 let mut confirmed: Option<bool> = None;
                 let mut pending_len: Option<u8> = None;
                 let mut pending_data: Option<[u8; FOPTS_MAX_LEN]> = None;
+                let mut overflow: Option<heapless::Vec<u8, MAC_COMMANDS_MAX_LEN>> = None;
 
                 while let Some(key) = map.next_key::<Field>()? {
                     match key {
@@ -70,6 +76,12 @@ impl<'de> Deserialize<'de> for Uplink {
                             }
                             pending_data = Some(map.next_value()?);
                         }
+                        Field::Overflow => {
+                            if overflow.is_some() {
+                                return Err(de::Error::duplicate_field("overflow"));
+                            }
+                            overflow = Some(map.next_value()?);
+                        }
                     }
                 }
 
@@ -87,14 +99,20 @@ impl<'de> Deserialize<'de> for Uplink {
                 pending
                     .extend_from_slice(&pending_data[..pending_len as usize])
                     .map_err(|_| de::Error::custom("failed to create heapless::Vec"))?;
+                // Absent from the data of earlier versions.
+                if let Some(overflow) = overflow {
+                    pending
+                        .extend_from_slice(&overflow)
+                        .map_err(|_| de::Error::custom("overflow exceeds maximum size"))?;
+                }
 
-                Ok(Uplink { pending, confirmed })
+                Ok(Uplink { pending, confirmed, ..Default::default() })
             }
         }
 
         deserializer.deserialize_struct(
             "Uplink",
-            &["confirmed", "pending_len", "pending_data"],
+            &["confirmed", "pending_len", "pending_data", "overflow"],
             UplinkVisitor,
         )
     }
@@ -136,4 +154,22 @@ mod tests {
         assert!(!decoded.confirms_downlink());
         assert_eq!(decoded.mac_commands(), &max_data);
     }
+
+    #[test]
+    fn test_serde_overflow() {
+        let mut uplink = Uplink::default();
+        let data: std::vec::Vec<u8> = (0..40).collect();
+        uplink.pending.extend_from_slice(&data).unwrap();
+
+        let json = serde_json::to_string(&uplink).unwrap();
+        let decoded: Uplink = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.mac_commands(), &data[..]);
+
+        // Without the overflow, as written by earlier versions
+        let json =
+            r#"{"confirmed":true,"pending_len":2,"pending_data":[3,7,0,0,0,0,0,0,0,0,0,0,0,0,0]}"#;
+        let decoded: Uplink = serde_json::from_str(json).unwrap();
+        assert!(decoded.confirms_downlink());
+        assert_eq!(decoded.mac_commands(), &[3, 7]);
+    }
 }
@@ -28,7 +28,8 @@ where
     R: PhyRxTx + Timings,
     RNG: RngCore,
 {
-    pub fn new(region: region::Configuration, radio: R, rng: RNG) -> Device<R, RNG, N, D> {
+    pub fn new(mut region: region::Configuration, radio: R, rng: RNG) -> Device<R, RNG, N, D> {
+        region.set_modulations(R::MODULATIONS);
         Device {
             state: State::default(),
             shared: Shared {
@@ -46,6 +47,9 @@ where
             JoinMode::OTAA { deveui, appeui, appkey } => {
                 self.handle_event(Event::Join(NetworkCredentials::new(appeui, deveui, appkey)))
             }
+            JoinMode::OTAA1_1 { deveui, joineui, nwkkey, appkey } => self.handle_event(
+                Event::Join(NetworkCredentials::new_1_1(joineui, deveui, nwkkey, appkey)),
+            ),
             JoinMode::ABP { devaddr, appskey, nwkskey } => {
                 self.shared.mac.join_abp(nwkskey, appskey, devaddr);
                 Ok(Response::JoinSuccess)
@@ -53,6 +57,25 @@ where
         }
     }
 
+    /// Send a rejoin request of a LoRaWAN 1.1 device. It completes with
+    /// [`Response::JoinSuccess`] once the device switched to the new session, downlinks not taken
+    /// yet are kept, or with [`Response::NoJoinAccept`] and the current session goes on.
+    pub fn rejoin(&mut self, rejoin_type: mac::RejoinType) -> Result<Response, Error<R>> {
+        self.handle_event(Event::Rejoin(rejoin_type))
+    }
+
+    /// Rejoin request which is due at `now_ms`, in the time base of the radio timestamps, see
+    /// [`async_device::Device::pending_rejoin`].
+    pub fn pending_rejoin(&mut self, now_ms: u64) -> Option<mac::RejoinType> {
+        self.shared.mac.pending_rejoin(Some(now_ms))
+    }
+
+    /// Set the credentials securing rejoin requests, needed when the session was restored
+    /// instead of joined by this device with LoRaWAN 1.1 OTAA.
+    pub fn set_rejoin_credentials(&mut self, credentials: NetworkCredentials) {
+        self.shared.mac.set_rejoin_credentials(credentials);
+    }
+
     pub fn get_radio(&mut self) -> &mut R {
         &mut self.shared.radio
     }
@@ -78,6 +101,82 @@ where
         }
     }
 
+    /// Ask the network for its time with DeviceTimeReq on the next uplink.
+    pub fn request_device_time(&mut self) -> Result<(), Error<R>> {
+        Ok(self.shared.mac.request_device_time()?)
+    }
+
+    /// Set the battery level reported when the network asks for the device status with
+    /// DevStatusReq. It is [`mac::BatteryLevel::Unknown`] until set, and should be updated as the
+    /// application measures it.
+    pub fn set_battery_level(&mut self, level: mac::BatteryLevel) {
+        self.shared.mac.configuration.battery_level = level;
+    }
+
+    /// Largest application payload which `send` accepts for the next uplink, given the current
+    /// data rate, the dwell time, and the MAC commands which go along in FOpts. Larger payloads
+    /// fail with [`mac::Error::PayloadTooLarge`]. It is 0 when the device is not joined.
+    pub fn max_payload_len(&self) -> usize {
+        self.shared.mac.max_payload_len()
+    }
+
+    /// Limit uplinks to the payload sizes which LoRaWAN repeaters can relay. Disabled by default.
+    pub fn set_repeater_compatible(&mut self, enabled: bool) {
+        self.shared.mac.configuration.repeater_compatible = enabled;
+    }
+
+    /// Ask the network for a link check with LinkCheckReq on the next uplink. The answer is
+    /// available with [`Self::take_link_status`].
+    pub fn request_link_check(&mut self) -> Result<(), Error<R>> {
+        Ok(self.shared.mac.request_link_check()?)
+    }
+
+    /// Take the answer to the last link check, if it was received.
+    pub fn take_link_status(&mut self) -> Option<mac::LinkStatus> {
+        self.shared.mac.take_link_status()
+    }
+
+    /// Fails with `mac::Error::MacCommandsDropped` if answers to MAC commands were dropped since
+    /// the last call, as the room for pending MAC commands was used up, typically by a large burst
+    /// of MAC commands in a downlink. The response to the uplink which received that downlink is
+    /// not affected.
+    pub fn check_dropped_mac_commands(&mut self) -> Result<(), Error<R>> {
+        Ok(self.shared.mac.check_dropped_mac_commands()?)
+    }
+
+    /// Take the oldest change of the MAC configuration by the network which was not taken yet.
+    /// Up to 8 events are kept, the oldest ones are dropped first.
+    pub fn take_mac_event(&mut self) -> Option<mac::MacEvent> {
+        self.shared.mac.take_mac_event()
+    }
+
+    /// The device clock, synchronized by DeviceTimeAns. Its local time base is the one of the
+    /// timestamps provided by the radio with `TxDone`.
+    pub fn get_clock(&self) -> &mac::DeviceClock {
+        self.shared.mac.clock()
+    }
+
+    /// Milliseconds until the regulatory duty cycle, and the aggregated duty cycle set by the
+    /// network with DutyCycleReq, allow the next uplink (a join request while not joined), from
+    /// the current time of the radio.
+    ///
+    /// While it is not 0, `send` and `join` fail with [`mac::Error::DutyCycleRestricted`].
+    pub fn time_until_next_tx(&self) -> u64 {
+        self.shared.mac.time_until_next_tx(self.shared.radio.now_ms().into())
+    }
+
+    /// Set how confirmed uplinks which are not acknowledged are retransmitted. By default they
+    /// are not, and the uplink completes with [`Response::NoAck`] after the first transmission.
+    pub fn set_retransmission_policy(&mut self, policy: mac::RetransmissionPolicy) {
+        self.shared.mac.configuration.retransmission = policy;
+    }
+
+    /// Transmissions used by the last uplink, including NbTrans repetitions and retransmissions
+    /// of a confirmed uplink.
+    pub fn send_attempts(&self) -> u8 {
+        self.shared.mac.attempts()
+    }
+
     pub fn ready_to_send_data(&self) -> bool {
         matches!(&self.state, State::Idle(_)) && self.shared.mac.is_joined()
     }
@@ -86,6 +185,19 @@ where
         self.handle_event(Event::SendDataRequest(SendData { data, fport, confirmed }))
     }
 
+    /// Whether the MAC answers the network waits for (RXParamSetupAns, DlChannelAns,
+    /// RXTimingSetupAns), ADRACKReq, or MAC commands overflowing FOpts call for an uplink of their
+    /// own. Unlike the async device, the non-blocking device does not send it by itself: the
+    /// application checks this after each downlink and calls [`Self::send_mac_uplink`].
+    pub fn mac_uplink_due(&self) -> bool {
+        self.shared.mac.mac_uplink_due()
+    }
+
+    /// Send an unconfirmed uplink without FPort which only carries the pending MAC commands.
+    pub fn send_mac_uplink(&mut self) -> Result<Response, Error<R>> {
+        self.handle_event(Event::SendMacUplink)
+    }
+
     pub fn get_fcnt_up(&self) -> Option<u32> {
         self.shared.mac.get_fcnt_up()
     }
@@ -98,6 +210,50 @@ where
         self.shared.mac.set_session(s)
     }
 
+    /// JoinNonce of the last accepted LoRaWAN 1.1 JoinAccept, to be kept in non-volatile memory.
+    pub fn get_join_nonce(&self) -> Option<JoinNonce> {
+        self.shared.mac.join_nonce()
+    }
+
+    /// Restore the JoinNonce of the last accepted LoRaWAN 1.1 JoinAccept.
+    pub fn set_join_nonce(&mut self, join_nonce: JoinNonce) {
+        self.shared.mac.set_join_nonce(join_nonce)
+    }
+
+    /// DevNonce of the last join request, to be kept in non-volatile memory with the default
+    /// [`mac::DevNonceStrategy::Counter`].
+    pub fn get_dev_nonce(&self) -> Option<DevNonce> {
+        self.shared.mac.dev_nonce()
+    }
+
+    /// Restore the DevNonce of the last join request.
+    pub fn set_dev_nonce(&mut self, dev_nonce: DevNonce) {
+        self.shared.mac.set_dev_nonce(dev_nonce)
+    }
+
+    /// Set how the DevNonce of join requests is chosen, a counter by default.
+    pub fn set_dev_nonce_strategy(&mut self, strategy: mac::DevNonceStrategy) {
+        self.shared.mac.configuration.dev_nonce_strategy = strategy;
+    }
+
+    /// Snapshot of the session, the configuration and channel plan set by the network, and the
+    /// multicast sessions, to be kept in non-volatile memory.
+    pub fn snapshot_state(&self) -> mac::DeviceState {
+        self.shared.mac.snapshot_state()
+    }
+
+    /// Restore a snapshot taken with [`Self::snapshot_state`], eg: after a reboot, while the device
+    /// is idle. The device must be configured for the same region.
+    pub fn restore_state(&mut self, state: &mac::DeviceState) -> Result<(), mac::StateError> {
+        self.shared.mac.restore_state(state)
+    }
+
+    /// Whether the state saved by [`Self::snapshot_state`] changed since the last call, see
+    /// [`async_device::Device::take_state_dirty`].
+    pub fn take_state_dirty(&mut self) -> bool {
+        self.shared.mac.take_state_dirty()
+    }
+
     pub fn get_session_keys(&self) -> Option<mac::SessionKeys> {
         self.shared.mac.get_session_keys()
     }
@@ -161,7 +317,10 @@ where
     R: PhyRxTx,
 {
     Join(NetworkCredentials),
+    Rejoin(mac::RejoinType),
     SendDataRequest(SendData<'a>),
+    /// Uplink without FPort for the pending MAC commands, see [`Device::send_mac_uplink`].
+    SendMacUplink,
     RadioEvent(radio::Event<'a, R>),
     TimeoutFired,
 }
@@ -173,7 +332,9 @@ where
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let event = match self {
             Event::Join(_) => "Join",
+            Event::Rejoin(_) => "Rejoin",
             Event::SendDataRequest(_) => "SendDataRequest",
+            Event::SendMacUplink => "SendMacUplink",
             Event::RadioEvent(_) => "RadioEvent",
             Event::TimeoutFired => "TimeoutFired",
         };
@@ -40,8 +40,16 @@ pub trait PhyRxTx {
     /// the value of maximum power will be used as an upper bound.
     const MAX_RADIO_POWER: u8;
 
+    /// Modulations the radio supports besides LoRa. Data rates of other modulations are refused
+    /// in LinkADRReq, and skipped by the data rate backoff and the join data rate rotation.
+    const MODULATIONS: SupportedModulations = SupportedModulations::LORA;
+
     fn get_mut_radio(&mut self) -> &mut Self;
 
+    /// Current time in milliseconds, in the time base of the `TxDone` timestamps and of the
+    /// timeouts requested by the device. The device enforces the duty cycle with it.
+    fn now_ms(&self) -> TimestampMs;
+
     // we require mutability so we may decrypt in place
     fn get_received_packet(&mut self) -> &mut [u8];
     fn handle_event(&mut self, event: Event<'_, Self>) -> Result<Response<Self>, Self::PhyError>
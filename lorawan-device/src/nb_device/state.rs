@@ -37,6 +37,10 @@ O
 └──────────╫─╫───┘         ║   ║               ║                    ║
 else(Ready)║ ╚═════════════╝   ║               ║                    ║
            ╚═══════════════════╝               ╚════════════════════╝
+
+When NbTrans or the retransmission policy asks for another transmission of the uplink, the RX2
+Timeout sends it right away (SendData path), or after ACK_TIMEOUT or once the duty cycle allows it
+(WaitingForRetransmission, which moves on to the SendData path on Timeout).
  */
 use super::super::*;
 use super::{
@@ -51,6 +55,7 @@ pub enum State {
     SendingData(SendingData),
     WaitingForRxWindow(WaitingForRxWindow),
     WaitingForRx(WaitingForRx),
+    WaitingForRetransmission(WaitingForRetransmission),
 }
 
 macro_rules! into_state {
@@ -65,7 +70,7 @@ macro_rules! into_state {
     )*};
 }
 
-into_state!(Idle, SendingData, WaitingForRxWindow, WaitingForRx);
+into_state!(Idle, SendingData, WaitingForRxWindow, WaitingForRx, WaitingForRetransmission);
 
 impl Default for State {
     fn default() -> Self {
@@ -91,6 +96,9 @@ pub enum Error {
     TxRequestDuringTx,
     NewSessionWhileWaitingForRx,
     SendDataWhileWaitingForRx,
+    RadioEventWhileWaitingForRetransmission,
+    NewSessionWhileWaitingForRetransmission,
+    SendDataWhileWaitingForRetransmission,
     BufferTooSmall,
     UnexpectedRadioResponse,
 }
@@ -120,7 +128,12 @@ impl State {
             State::Idle(s) => s.handle_event::<R, RNG, N>(mac, radio, rng, buf, event),
             State::SendingData(s) => s.handle_event::<R, N>(mac, radio, event),
             State::WaitingForRxWindow(s) => s.handle_event::<R, N>(mac, radio, event),
-            State::WaitingForRx(s) => s.handle_event::<R, N, D>(mac, radio, buf, event, dl),
+            State::WaitingForRx(s) => {
+                s.handle_event::<R, RNG, N, D>(mac, radio, rng, buf, event, dl)
+            }
+            State::WaitingForRetransmission(s) => {
+                s.handle_event::<R, RNG, N>(mac, radio, rng, buf, event)
+            }
         }
     }
 }
@@ -142,23 +155,33 @@ impl Idle {
             EarlyReturn(Result<Response, super::Error<R>>),
         }
 
+        let now = Some(radio.now_ms().into());
         let response = match event {
             // tolerate unexpected timeout
-            Event::Join(creds) => {
-                let (tx_config, rx_windows, dev_nonce) = mac.join_otaa::<RNG, N>(rng, creds, buf);
-                IntermediateResponse::RadioTx((
+            Event::Join(creds) => match mac.join_otaa::<RNG, N>(rng, creds, buf, now) {
+                Err(e) => IntermediateResponse::EarlyReturn(Err(e.into())),
+                Ok((tx_config, rx_windows, dev_nonce)) => IntermediateResponse::RadioTx((
                     Frame::Join,
                     tx_config,
                     rx_windows,
                     dev_nonce as u32,
-                ))
-            }
+                )),
+            },
+            Event::Rejoin(rejoin_type) => match mac.rejoin::<RNG, N>(rng, rejoin_type, buf, now) {
+                Err(e) => IntermediateResponse::EarlyReturn(Err(e.into())),
+                Ok((tx_config, rx_windows, rj_count)) => IntermediateResponse::RadioTx((
+                    Frame::Join,
+                    tx_config,
+                    rx_windows,
+                    rj_count as u32,
+                )),
+            },
             Event::TimeoutFired => IntermediateResponse::EarlyReturn(Ok(Response::NoUpdate)),
             Event::RadioEvent(_radio_event) => {
                 IntermediateResponse::EarlyReturn(Err(Error::RadioEventWhileIdle.into()))
             }
             Event::SendDataRequest(send_data) => {
-                let tx_config = mac.send::<RNG, N>(rng, buf, &send_data);
+                let tx_config = mac.send::<RNG, N>(rng, buf, &send_data, now);
                 match tx_config {
                     Err(e) => IntermediateResponse::EarlyReturn(Err(e.into())),
                     Ok((tx_config, rx_windows, fcnt_up)) => {
@@ -166,33 +189,51 @@ impl Idle {
                     }
                 }
             }
+            Event::SendMacUplink => match mac.send_mac_uplink::<RNG, N>(rng, buf, now) {
+                Err(e) => IntermediateResponse::EarlyReturn(Err(e.into())),
+                Ok((tx_config, rx_windows, fcnt_up)) => {
+                    IntermediateResponse::RadioTx((Frame::Data, tx_config, rx_windows, fcnt_up))
+                }
+            },
         };
         match response {
             IntermediateResponse::EarlyReturn(response) => (State::Idle(self), response),
             IntermediateResponse::RadioTx((frame, tx_config, rx_windows, fcnt_up)) => {
-                let event: radio::Event<'_, R> =
-                    radio::Event::TxRequest(tx_config, buf.as_ref_for_read());
-                match radio.handle_event(event) {
-                    Ok(response) => {
-                        match response {
-                            // intermediate state where we wait for Join to complete sending
-                            // allows for asynchronous sending
-                            radio::Response::Txing => (
-                                State::SendingData(SendingData { frame, rx_windows }),
-                                Ok(Response::UplinkSending(fcnt_up)),
-                            ),
-                            // directly jump to waiting for RxWindow
-                            // allows for synchronous sending
-                            radio::Response::TxDone(ms) => {
-                                data_rxwindow1_timeout::<R, N>(frame, rx_windows, mac, radio, ms)
-                            }
-                            _ => (State::Idle(self), Err(Error::UnexpectedRadioResponse.into())),
-                        }
-                    }
-                    Err(e) => (State::Idle(self), Err(super::Error::Radio(e))),
+                transmit::<R, N>(frame, tx_config, rx_windows, fcnt_up, mac, radio, buf)
+            }
+        }
+    }
+}
+
+fn transmit<R: radio::PhyRxTx + Timings, const N: usize>(
+    frame: Frame,
+    tx_config: radio::TxConfig,
+    rx_windows: RxWindows,
+    fcnt_up: u32,
+    mac: &mut Mac,
+    radio: &mut R,
+    buf: &mut RadioBuffer<N>,
+) -> (State, Result<Response, super::Error<R>>) {
+    let event: radio::Event<'_, R> = radio::Event::TxRequest(tx_config, buf.as_ref_for_read());
+    match radio.handle_event(event) {
+        Ok(response) => {
+            match response {
+                // intermediate state where we wait for Join to complete sending
+                // allows for asynchronous sending
+                radio::Response::Txing => (
+                    State::SendingData(SendingData { frame, rx_windows }),
+                    Ok(Response::UplinkSending(fcnt_up)),
+                ),
+                // directly jump to waiting for RxWindow
+                // allows for synchronous sending
+                radio::Response::TxDone(ms) => {
+                    mac.tx_done(ms.into());
+                    data_rxwindow1_timeout::<R, N>(frame, rx_windows, mac, radio, ms)
                 }
+                _ => (State::Idle(Idle), Err(Error::UnexpectedRadioResponse.into())),
             }
         }
+        Err(e) => (State::Idle(Idle), Err(super::Error::Radio(e))),
     }
 }
 
@@ -217,13 +258,16 @@ impl SendingData {
                     Ok(response) => {
                         match response {
                             // expect a complete transmit
-                            radio::Response::TxDone(ms) => data_rxwindow1_timeout::<R, N>(
-                                self.frame,
-                                self.rx_windows,
-                                mac,
-                                radio,
-                                ms,
-                            ),
+                            radio::Response::TxDone(ms) => {
+                                mac.tx_done(ms.into());
+                                data_rxwindow1_timeout::<R, N>(
+                                    self.frame,
+                                    self.rx_windows,
+                                    mac,
+                                    radio,
+                                    ms,
+                                )
+                            }
                             // anything other than TxComplete is unexpected
                             _ => {
                                 panic!("SendingData: Unexpected radio response");
@@ -236,9 +280,10 @@ impl SendingData {
             // tolerate unexpected timeout
             Event::TimeoutFired => (State::SendingData(self), Ok(Response::NoUpdate)),
             // anything other than a RadioEvent is unexpected
-            Event::Join(_) | Event::SendDataRequest(_) => {
-                (self.into(), Err(Error::TxRequestDuringTx.into()))
-            }
+            Event::Join(_)
+            | Event::Rejoin(_)
+            | Event::SendDataRequest(_)
+            | Event::SendMacUplink => (self.into(), Err(Error::TxRequestDuringTx.into())),
         }
     }
 }
@@ -296,11 +341,11 @@ impl WaitingForRxWindow {
                 State::WaitingForRxWindow(self),
                 Err(Error::RadioEventWhileWaitingForRxWindow.into()),
             ),
-            Event::Join(_) => (
+            Event::Join(_) | Event::Rejoin(_) => (
                 State::WaitingForRxWindow(self),
                 Err(Error::NewSessionWhileWaitingForRxWindow.into()),
             ),
-            Event::SendDataRequest(_) => (
+            Event::SendDataRequest(_) | Event::SendMacUplink => (
                 State::WaitingForRxWindow(self),
                 Err(Error::SendDataWhileWaitingForRxWindow.into()),
             ),
@@ -317,10 +362,16 @@ pub struct WaitingForRx {
 }
 
 impl WaitingForRx {
-    pub(crate) fn handle_event<R: radio::PhyRxTx + Timings, const N: usize, const D: usize>(
+    pub(crate) fn handle_event<
+        R: radio::PhyRxTx + Timings,
+        RNG: RngCore,
+        const N: usize,
+        const D: usize,
+    >(
         self,
         mac: &mut Mac,
         radio: &mut R,
+        rng: &mut RNG,
         buf: &mut RadioBuffer<N>,
         event: Event<'_, R>,
         dl: &mut Vec<Downlink, D>,
@@ -342,7 +393,7 @@ impl WaitingForRx {
                                     Err(Error::BufferTooSmall.into()),
                                 );
                             }
-                            match mac.handle_rx::<N, D>(buf, dl, quality.snr(), &self.rf_config) {
+                            match mac.handle_rx::<N, D>(buf, dl, quality, &self.rf_config) {
                                 // NoUpdate can occur when a stray radio packet is received. Maintain state
                                 mac::Response::NoUpdate => {
                                     (State::WaitingForRx(self), Ok(Response::NoUpdate))
@@ -376,23 +427,90 @@ impl WaitingForRx {
                             Ok(Response::TimeoutRequest(t2)),
                         )
                     }
-                    // Timeout during second RxWindow leads to giving up
-                    Rx::_2(_) => {
-                        let response = mac.rx2_complete();
-                        (State::Idle(Idle), Ok(response.into()))
-                    }
+                    // Timeout during second RxWindow leads to repeating the uplink for NbTrans,
+                    // retransmitting it after ACK_TIMEOUT, or giving up
+                    Rx::_2(_) => match mac.repetition_delay_ms(rng) {
+                        Some(delay) => {
+                            repeat_after::<R, RNG, N>(self.frame, delay, mac, radio, rng, buf)
+                        }
+                        None => {
+                            let response = mac.rx2_complete();
+                            (State::Idle(Idle), Ok(response.into()))
+                        }
+                    },
                 }
             }
-            Event::Join(_) => {
+            Event::Join(_) | Event::Rejoin(_) => {
                 (State::WaitingForRx(self), Err(Error::NewSessionWhileWaitingForRx.into()))
             }
-            Event::SendDataRequest(_) => {
+            Event::SendDataRequest(_) | Event::SendMacUplink => {
                 (State::WaitingForRx(self), Err(Error::SendDataWhileWaitingForRx.into()))
             }
         }
     }
 }
 
+#[derive(Copy, Clone)]
+pub struct WaitingForRetransmission {
+    frame: Frame,
+}
+
+impl WaitingForRetransmission {
+    pub(crate) fn handle_event<R: radio::PhyRxTx + Timings, RNG: RngCore, const N: usize>(
+        self,
+        mac: &mut Mac,
+        radio: &mut R,
+        rng: &mut RNG,
+        buf: &mut RadioBuffer<N>,
+        event: Event<'_, R>,
+    ) -> (State, Result<Response, super::Error<R>>) {
+        match event {
+            // ACK_TIMEOUT elapsed, or the duty cycle allows the transmission
+            Event::TimeoutFired => repeat_after::<R, RNG, N>(self.frame, 0, mac, radio, rng, buf),
+            Event::RadioEvent(_) => (
+                State::WaitingForRetransmission(self),
+                Err(Error::RadioEventWhileWaitingForRetransmission.into()),
+            ),
+            Event::Join(_) | Event::Rejoin(_) => (
+                State::WaitingForRetransmission(self),
+                Err(Error::NewSessionWhileWaitingForRetransmission.into()),
+            ),
+            Event::SendDataRequest(_) | Event::SendMacUplink => (
+                State::WaitingForRetransmission(self),
+                Err(Error::SendDataWhileWaitingForRetransmission.into()),
+            ),
+        }
+    }
+}
+
+/// Transmit the last uplink again once `delay_ms` elapsed and the duty cycle allows it, or
+/// complete it if it no longer needs to be.
+fn repeat_after<R: radio::PhyRxTx + Timings, RNG: RngCore, const N: usize>(
+    frame: Frame,
+    delay_ms: u32,
+    mac: &mut Mac,
+    radio: &mut R,
+    rng: &mut RNG,
+    buf: &mut RadioBuffer<N>,
+) -> (State, Result<Response, super::Error<R>>) {
+    let now_ms = radio.now_ms();
+    let wait_ms = mac.time_until_next_tx(now_ms.into()).max(delay_ms.into());
+    if wait_ms > 0 {
+        let t = now_ms.wrapping_add(wait_ms.try_into().unwrap_or(u32::MAX));
+        return (
+            State::WaitingForRetransmission(WaitingForRetransmission { frame }),
+            Ok(Response::TimeoutRequest(t)),
+        );
+    }
+    match mac.repeat_send::<RNG, N>(rng, buf, Some(now_ms.into())) {
+        Some(Ok((tx_config, rx_windows, fcnt_up))) => {
+            transmit::<R, N>(frame, tx_config, rx_windows, fcnt_up, mac, radio, buf)
+        }
+        Some(Err(e)) => (State::Idle(Idle), Err(e.into())),
+        None => (State::Idle(Idle), Ok(mac.rx2_complete().into())),
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 enum Rx {
     _1(u32),
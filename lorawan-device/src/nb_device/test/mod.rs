@@ -128,3 +128,162 @@ fn test_link_adr_ans() {
     let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
     assert!(matches!(response, Response::DownlinkReceived(1)));
 }
+
+#[test]
+fn test_unconfirmed_uplink_nb_trans() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    device.shared.mac.configuration.nb_trans = 2;
+    let response = device.send(&[0; 1], 1, false).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(device.get_radio().take_last_uplink().unwrap().fcnt(), 0);
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    // Nothing received in RX2, the uplink is sent again with the same FCnt
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(device.get_radio().take_last_uplink().unwrap().fcnt(), 0);
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::RxComplete));
+    assert_eq!(device.get_session().unwrap().fcnt_up, 1);
+}
+
+#[test]
+fn test_state_snapshot_restore() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    assert!(device.take_state_dirty());
+    device.send(&[0; 1], 1, false).unwrap();
+    device.get_radio().take_last_uplink().unwrap();
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    assert!(!device.take_state_dirty());
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::RxComplete));
+    assert!(device.take_state_dirty());
+    let state = device.snapshot_state();
+
+    let mut restored = test_device();
+    restored.restore_state(&state).unwrap();
+    assert!(restored.ready_to_send_data());
+    assert_eq!(restored.get_fcnt_up(), Some(1));
+    assert_eq!(restored.get_session_keys().unwrap().devaddr, get_dev_addr());
+}
+
+#[test]
+fn test_confirmed_uplink_retransmission() {
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    device.set_retransmission_policy(crate::mac::RetransmissionPolicy {
+        retries: 1,
+        ..Default::default()
+    });
+    let response = device.send(&[0; 1], 1, true).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert_eq!(device.get_radio().take_last_uplink().unwrap().fcnt(), 0);
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    // ACK_TIMEOUT of 1 to 3 s after the end of RX2
+    device.get_radio().set_now_ms(2100);
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    let Response::TimeoutRequest(t @ 3100..=5100) = response else { panic!("{response:?}") };
+    assert!(!device.ready_to_send_data());
+    device.get_radio().set_now_ms(t);
+    let response = device.handle_event(Event::TimeoutFired).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(timeout) if timeout == t + 1000));
+    assert_eq!(device.get_radio().take_last_uplink().unwrap().fcnt(), 0);
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    assert!(matches!(response, Response::NoAck));
+    assert_eq!(device.send_attempts(), 2);
+}
+
+#[test]
+#[cfg(feature = "region-eu868")]
+fn test_duty_cycle() {
+    let mut device = test_device_with_region(region::Region::EU868);
+    device.join(get_abp_credentials()).unwrap();
+    device.shared.mac.configuration.nb_trans = 2;
+    device.send(&[0; 1], 1, false).unwrap();
+    device.get_radio().take_last_uplink().unwrap();
+    for _ in 0..3 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+    // The 1% duty cycle of the band holds the repetition back
+    device.get_radio().set_now_ms(2100);
+    let wait_ms = device.time_until_next_tx();
+    assert!(wait_ms > 0);
+    let response = device.handle_event(Event::TimeoutFired).unwrap(); // end Rx2
+    let t = 2100 + wait_ms as u32;
+    assert!(matches!(response, Response::TimeoutRequest(timeout) if timeout == t));
+    assert!(device.get_radio().take_last_uplink().is_none());
+    device.get_radio().set_now_ms(t);
+    let response = device.handle_event(Event::TimeoutFired).unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(_)));
+    assert_eq!(device.get_radio().take_last_uplink().unwrap().fcnt(), 0);
+    for _ in 0..4 {
+        device.handle_event(Event::TimeoutFired).unwrap();
+    }
+
+    // The next uplink waits for the duty cycle as well
+    assert!(matches!(
+        device.send(&[0; 1], 1, false),
+        Err(crate::nb_device::Error::Mac(crate::mac::Error::DutyCycleRestricted))
+    ));
+    let now_ms = t + device.time_until_next_tx() as u32;
+    device.get_radio().set_now_ms(now_ms);
+    assert_eq!(device.time_until_next_tx(), 0);
+    device.send(&[0; 1], 1, false).unwrap();
+    assert_eq!(device.get_radio().take_last_uplink().unwrap().fcnt(), 1);
+}
+
+/// Answer the uplink with RXParamSetupReq in FOpts.
+fn handle_rx_param_setup_req(
+    _uplink: Option<Uplink>,
+    _config: crate::radio::RfConfig,
+    rx_buffer: &mut [u8],
+) -> usize {
+    use lorawan::creator::DataFrame;
+    use lorawan::parser::DataFrameType;
+    // RX2 on 923.3 MHz at DR8
+    let f_opts = [0x05, 0x08, 0x68, 0xe2, 0x8c];
+    let frame = DataFrame {
+        frame_type: DataFrameType::UnconfirmedDown,
+        dev_addr: get_dev_addr(),
+        fcnt: 1,
+        f_opts: &f_opts,
+        ..Default::default()
+    };
+    frame.build_into(rx_buffer, &get_crypto(), Some(&get_crypto())).unwrap().len()
+}
+
+#[test]
+fn test_mac_uplink_answers_rx_param_setup_req() {
+    use lorawan::maccommands::{UplinkMacCommand, parse_uplink_mac_commands};
+    let mut device = test_device();
+    device.join(get_abp_credentials()).unwrap();
+    device.send(&[0; 1], 1, false).unwrap();
+    device.handle_event(Event::TimeoutFired).unwrap(); // begin Rx1
+    device.get_radio().set_rxtx_handler(handle_rx_param_setup_req);
+    let response = device.handle_event(Event::RadioEvent(radio::Event::Phy(()))).unwrap();
+    assert!(matches!(response, Response::DownlinkReceived(1)));
+    assert!(device.mac_uplink_due());
+
+    // The answer goes in an uplink of its own, without FPort
+    let response = device.send_mac_uplink().unwrap();
+    assert!(matches!(response, Response::TimeoutRequest(1000)));
+    assert!(!device.mac_uplink_due());
+    let uplink = device.get_radio().take_last_uplink().unwrap();
+    assert_eq!(uplink.f_port(), None);
+    let f_opts = uplink.f_opts();
+    let mut cmds = parse_uplink_mac_commands(&f_opts);
+    assert!(matches!(cmds.next(), Some(Ok(UplinkMacCommand::RXParamSetupAns(_)))));
+}
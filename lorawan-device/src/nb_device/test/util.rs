@@ -9,7 +9,11 @@ use crate::test_util::*;
 use region::{Configuration, Region};
 
 pub fn test_device() -> Device<TestRadio, rand_core::OsRng, 255> {
-    Device::new(Configuration::new(Region::US915), TestRadio::default(), rand::rngs::OsRng)
+    test_device_with_region(Region::US915)
+}
+
+pub fn test_device_with_region(region: Region) -> Device<TestRadio, rand_core::OsRng, 255> {
+    Device::new(Configuration::new(region), TestRadio::default(), rand::rngs::OsRng)
 }
 
 #[derive(Debug)]
@@ -19,12 +23,22 @@ pub struct TestRadio {
     rxtx_handler: Option<RxTxHandler>,
     buffer: [u8; 256],
     buffer_index: usize,
+    now_ms: u32,
 }
 
 impl TestRadio {
     pub fn set_rxtx_handler(&mut self, handler: RxTxHandler) {
         self.rxtx_handler = Some(handler);
     }
+
+    pub fn take_last_uplink(&mut self) -> Option<Uplink> {
+        self.last_uplink.take()
+    }
+
+    /// Set the time of the clock, which does not move on its own.
+    pub fn set_now_ms(&mut self, now_ms: u32) {
+        self.now_ms = now_ms;
+    }
 }
 
 impl Default for TestRadio {
@@ -35,6 +49,7 @@ impl Default for TestRadio {
             rxtx_handler: None,
             buffer: [0; 256],
             buffer_index: 0,
+            now_ms: 0,
         }
     }
 }
@@ -51,6 +66,9 @@ impl PhyRxTx for TestRadio {
     fn get_mut_radio(&mut self) -> &mut Self {
         self
     }
+    fn now_ms(&self) -> u32 {
+        self.now_ms
+    }
     fn get_received_packet(&mut self) -> &mut [u8] {
         &mut self.buffer[..self.buffer_index]
     }
@@ -67,7 +85,7 @@ impl PhyRxTx for TestRadio {
                 }
                 self.last_uplink =
                     Some(Uplink::new(buf, config).map_err(|_| "error creating uplink")?);
-                return Ok(Response::TxDone(0));
+                return Ok(Response::TxDone(self.now_ms));
             }
             Event::RxRequest(rf_config) => {
                 self.current_config = Some(rf_config);
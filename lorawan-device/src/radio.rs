@@ -4,10 +4,104 @@ pub use lora_modulation::BaseBandModulationParams;
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct RfConfig {
     pub frequency: u32,
+    /// LoRa modulation parameters, which only apply when `modulation` is [`Modulation::LoRa`].
     pub bb: BaseBandModulationParams,
+    pub modulation: Modulation,
     pub max_payload_len: u8,
 }
 
+/// Modulation of a data rate. Most data rates are LoRa, but some regions also define FSK and
+/// LR-FHSS data rates, the latter for uplinks only.
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modulation {
+    LoRa,
+    Fsk(FskParams),
+    LrFhss(LrFhssParams),
+}
+
+/// Modulations which a radio is able to use besides LoRa, as declared by `PhyRxTx::MODULATIONS`.
+/// The device refuses the data rates of the other modulations.
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupportedModulations {
+    /// FSK, to transmit and receive.
+    pub fsk: bool,
+    /// LR-FHSS, to transmit.
+    pub lr_fhss: bool,
+}
+
+impl SupportedModulations {
+    /// LoRa only.
+    pub const LORA: Self = Self { fsk: false, lr_fhss: false };
+    /// LoRa, FSK and LR-FHSS.
+    pub const ALL: Self = Self { fsk: true, lr_fhss: true };
+
+    pub fn supports(&self, modulation: &Modulation) -> bool {
+        match modulation {
+            Modulation::LoRa => true,
+            Modulation::Fsk(_) => self.fsk,
+            Modulation::LrFhss(_) => self.lr_fhss,
+        }
+    }
+}
+
+/// (G)FSK modulation parameters.
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FskParams {
+    pub bitrate: u32,
+    pub frequency_deviation: u32,
+}
+
+/// LR-FHSS modulation parameters, as defined by the LoRaWAN Regional Parameters.
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LrFhssParams {
+    pub coding_rate: LrFhssCodingRate,
+    /// Occupied channel width.
+    pub bandwidth: LrFhssBandwidth,
+    /// Spacing of the hopping grid.
+    pub grid: LrFhssGrid,
+}
+
+impl LrFhssParams {
+    /// Number of header replicas sent before the payload.
+    pub fn header_count(&self) -> u8 {
+        match self.coding_rate {
+            LrFhssCodingRate::_1_3 => 3,
+            LrFhssCodingRate::_2_3 => 2,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LrFhssCodingRate {
+    _1_3,
+    _2_3,
+}
+
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LrFhssBandwidth {
+    /// 136.719 kHz
+    _137KHz,
+    /// 335.938 kHz
+    _336KHz,
+    /// 1.523 MHz
+    _1523KHz,
+}
+
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LrFhssGrid {
+    /// 3.906 kHz
+    _3_9KHz,
+    /// 25.391 kHz
+    _25_4KHz,
+}
+
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RxMode {
@@ -17,6 +111,12 @@ pub enum RxMode {
     Single {
         ms: u32,
     },
+    /// Single shot receive of a Class B beacon of `len` bytes, which is sent with a 10 symbol
+    /// preamble, an implicit header, no CRC and non-inverted IQ. `ms` is the same as for `Single`.
+    Beacon {
+        len: u8,
+        ms: u32,
+    },
 }
 
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -0,0 +1,186 @@
+//! Uplink statistics per channel, and avoidance of channels which fail repeatedly.
+//!
+//! A transmission fails when a confirmed uplink is not acknowledged or listen-before-talk finds the
+//! channel busy, and succeeds when a downlink answers it. With a [`ChannelAvoidance`] policy, a
+//! channel which failed too many times in a row is left out of the channel selection for a while,
+//! as long as other channels enabled by the network are available.
+
+/// Channels with statistics, the least recently used one is replaced by a new channel.
+const MAX_CHANNELS: usize = 16;
+
+/// Uplink statistics of a channel.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ChannelStats {
+    /// Transmissions on the channel, including repetitions and retransmissions.
+    pub transmissions: u32,
+    /// Transmissions answered by a downlink.
+    pub answered: u32,
+    /// Transmissions of confirmed uplinks which were not acknowledged.
+    pub missed_acks: u32,
+    /// Transmissions given up because listen-before-talk found the channel busy.
+    pub busy: u32,
+    /// Failures since the last transmission answered.
+    pub consecutive_failures: u8,
+    /// RSSI in dBm of the last downlink answering an uplink on the channel.
+    pub last_rssi: Option<i16>,
+    /// SNR in dB of the last downlink answering an uplink on the channel.
+    pub last_snr: Option<i8>,
+}
+
+impl ChannelStats {
+    /// Share in percent of the transmissions expecting an answer which got one.
+    pub fn ack_rate_percent(&self) -> Option<u8> {
+        let expected = self.answered + self.missed_acks;
+        (expected > 0).then(|| (self.answered as u64 * 100 / expected as u64) as u8)
+    }
+}
+
+/// Avoidance of channels which failed `max_consecutive_failures` times in a row, for `avoid_ms`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ChannelAvoidance {
+    pub max_consecutive_failures: u8,
+    pub avoid_ms: u32,
+}
+
+impl Default for ChannelAvoidance {
+    fn default() -> Self {
+        Self { max_consecutive_failures: 3, avoid_ms: 600_000 }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    frequency: u32,
+    stats: ChannelStats,
+    /// Time from which a channel which is avoided may be used again.
+    avoid_until_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ChannelStatsTable {
+    /// Ordered from the least to the most recently used channel.
+    entries: heapless::Vec<Entry, MAX_CHANNELS>,
+    pub(crate) avoidance: Option<ChannelAvoidance>,
+}
+
+impl ChannelStatsTable {
+    pub(crate) fn get(&self, frequency: u32) -> Option<ChannelStats> {
+        self.entries.iter().find(|entry| entry.frequency == frequency).map(|entry| entry.stats)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Whether the channel on `frequency` is left out of the channel selection at `now_ms`.
+    pub(crate) fn avoided(&self, frequency: u32, now_ms: u64) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.frequency == frequency && entry.avoid_until_ms.is_some_and(|until| now_ms < until)
+        })
+    }
+
+    pub(crate) fn register_tx(&mut self, frequency: u32) {
+        let entry = self.entry(frequency);
+        entry.stats.transmissions = entry.stats.transmissions.saturating_add(1);
+    }
+
+    pub(crate) fn register_answer(&mut self, frequency: u32, rssi: i16, snr: i8) {
+        let entry = self.entry(frequency);
+        entry.stats.answered = entry.stats.answered.saturating_add(1);
+        entry.stats.consecutive_failures = 0;
+        entry.stats.last_rssi = Some(rssi);
+        entry.stats.last_snr = Some(snr);
+        entry.avoid_until_ms = None;
+    }
+
+    /// Account for a missed acknowledgment of a transmission which ended at `now_ms`, if known.
+    pub(crate) fn register_missed_ack(&mut self, frequency: u32, now_ms: Option<u64>) {
+        let entry = self.entry(frequency);
+        entry.stats.missed_acks = entry.stats.missed_acks.saturating_add(1);
+        self.register_failure(frequency, now_ms);
+    }
+
+    fn register_failure(&mut self, frequency: u32, now_ms: Option<u64>) {
+        let avoidance = self.avoidance;
+        let entry = self.entry(frequency);
+        entry.stats.consecutive_failures = entry.stats.consecutive_failures.saturating_add(1);
+        if let (Some(avoidance), Some(now_ms)) = (avoidance, now_ms)
+            && entry.stats.consecutive_failures >= avoidance.max_consecutive_failures
+        {
+            entry.avoid_until_ms = Some(now_ms + avoidance.avoid_ms as u64);
+        }
+    }
+
+    /// Account for a transmission given up at `now_ms`, if known, as listen-before-talk found the
+    /// channel busy.
+    pub(crate) fn register_busy(&mut self, frequency: u32, now_ms: Option<u64>) {
+        let entry = self.entry(frequency);
+        entry.stats.transmissions = entry.stats.transmissions.saturating_sub(1);
+        entry.stats.busy = entry.stats.busy.saturating_add(1);
+        self.register_failure(frequency, now_ms);
+    }
+
+    /// Entry of the channel on `frequency`, moved to the end as the most recently used one.
+    fn entry(&mut self, frequency: u32) -> &mut Entry {
+        let entry = match self.entries.iter().position(|entry| entry.frequency == frequency) {
+            Some(index) => self.entries.remove(index),
+            None => {
+                if self.entries.is_full() {
+                    self.entries.remove(0);
+                }
+                Entry { frequency, stats: ChannelStats::default(), avoid_until_ms: None }
+            }
+        };
+        // There is room for the entry, which was just removed or made room for.
+        let _ = self.entries.push(entry);
+        self.entries.last_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn failing_channel_avoided() {
+        let mut table = ChannelStatsTable {
+            avoidance: Some(ChannelAvoidance::default()),
+            ..Default::default()
+        };
+        for _ in 0..3 {
+            table.register_tx(868_100_000);
+            assert!(!table.avoided(868_100_000, 1_000));
+            table.register_missed_ack(868_100_000, Some(1_000));
+        }
+        assert!(table.avoided(868_100_000, 1_000));
+        assert!(table.avoided(868_100_000, 600_999));
+        assert!(!table.avoided(868_100_000, 601_000));
+        assert!(!table.avoided(868_300_000, 1_000));
+
+        table.register_answer(868_100_000, -90, 5);
+        assert!(!table.avoided(868_100_000, 1_000));
+        let stats = table.get(868_100_000).unwrap();
+        assert_eq!(stats.transmissions, 3);
+        assert_eq!(stats.missed_acks, 3);
+        assert_eq!(stats.consecutive_failures, 0);
+        assert_eq!(stats.ack_rate_percent(), Some(25));
+        assert_eq!(stats.last_rssi, Some(-90));
+    }
+
+    #[test]
+    fn least_recently_used_channel_replaced() {
+        let mut table = ChannelStatsTable::default();
+        for channel in 0..=MAX_CHANNELS as u32 {
+            table.register_tx(902_300_000 + channel * 200_000);
+        }
+        assert!(table.get(902_300_000).is_none());
+        assert!(table.get(902_500_000).is_some());
+        // Without a policy, failing channels are not avoided
+        for _ in 0..5 {
+            table.register_missed_ack(902_500_000, Some(0));
+        }
+        assert!(!table.avoided(902_500_000, 0));
+    }
+}
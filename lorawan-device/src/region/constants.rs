@@ -8,10 +8,12 @@ pub(crate) const JOIN_ACCEPT_DELAY2: u32 = 6000;
 pub(crate) const MAX_FCNT_GAP: usize = 16384;
 pub(crate) const ADR_ACK_LIMIT: usize = 64;
 pub(crate) const ADR_ACK_DELAY: usize = 32;
-pub(crate) const ACK_TIMEOUT: usize = 2; // random delay between 1 and 3 seconds
+pub(crate) const ACK_TIMEOUT: u32 = 2000; // random delay between 1 and 3 seconds
+pub(crate) const ACK_TIMEOUT_RAND: u32 = 1000;
 
-// Although there are 16 possible slots, last one is not defined as Datarate
-pub(crate) const NUM_DATARATES: u8 = 15;
+/// Number of data rates a region may define: although there are 16 possible slots, the last one is
+/// not defined as a data rate.
+pub const NUM_DATARATES: u8 = 15;
 pub(crate) const NUM_CHANNELS_DYNAMIC: u8 = 16;
 
 pub(crate) const DEFAULT_BANDWIDTH: Bandwidth = Bandwidth::_125KHz;
@@ -0,0 +1,111 @@
+//! Regions defined outside of this crate, eg: for a private ISM band or an experimental plan.
+use super::{DR, Datarate, ListenBeforeTalk, NUM_DATARATES};
+use lorawan::beacon::BeaconLayout;
+
+/// A region with a dynamic channel plan, as EU868, defined outside of this crate.
+///
+/// The device joins on the [default channels](Self::default_channels), and the network may define
+/// up to 16 channels in total with the CFList of the join-accept or NewChannelReq. Duty-cycle
+/// limits are not enforced, use
+/// [`Configuration::set_listen_before_talk`](super::Configuration::set_listen_before_talk) or
+/// [`listen_before_talk`](Self::listen_before_talk) to sense the channel before each uplink.
+///
+/// # Example
+///
+/// ```
+/// use lorawan_device::region::{Bandwidth, Configuration, CustomRegion, Datarate, NUM_DATARATES};
+/// use lorawan_device::region::{DR, SpreadingFactor};
+///
+/// struct Private915;
+///
+/// static DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = {
+///     let mut datarates = [const { None }; NUM_DATARATES as usize];
+///     datarates[0] = Some(Datarate::new(SpreadingFactor::_10, Bandwidth::_125KHz, 11));
+///     datarates[1] = Some(Datarate::new(SpreadingFactor::_7, Bandwidth::_125KHz, 242));
+///     datarates
+/// };
+///
+/// impl CustomRegion for Private915 {
+///     fn datarates(&self) -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+///         &DATARATES
+///     }
+///     fn default_channels(&self) -> &'static [u32] {
+///         &[916_100_000, 916_300_000]
+///     }
+///     fn frequency_valid(&self, frequency: u32) -> bool {
+///         (916_000_000..=917_000_000).contains(&frequency)
+///     }
+///     fn tx_power(&self, tx_power: u8) -> Option<u8> {
+///         (tx_power <= 5).then(|| 20 - 2 * tx_power)
+///     }
+///     fn rx1_datarate(&self, tx_dr: DR, rx1_dr_offset: u8) -> DR {
+///         tx_dr.offset_sub(rx1_dr_offset)
+///     }
+///     fn max_rx1_dr_offset(&self) -> u8 {
+///         1
+///     }
+///     fn rx2_frequency(&self) -> u32 {
+///         916_900_000
+///     }
+/// }
+///
+/// let configuration = Configuration::custom(&Private915);
+/// ```
+pub trait CustomRegion: Sync {
+    /// Data rates DR0 to DR14, `None` for those which are not supported.
+    fn datarates(&self) -> &'static [Option<Datarate>; NUM_DATARATES as usize];
+
+    /// Frequencies of the one to three default channels, which are used to join.
+    fn default_channels(&self) -> &'static [u32];
+
+    /// Whether channels may be set on `frequency` by the network.
+    fn frequency_valid(&self, frequency: u32) -> bool;
+
+    /// EIRP in dBm of the TXPower index `tx_power`, `None` for unsupported indices. Index 0 is
+    /// the maximum EIRP of the region.
+    fn tx_power(&self, tx_power: u8) -> Option<u8>;
+
+    /// Data rate of RX1 for an uplink sent with `tx_dr`.
+    fn rx1_datarate(&self, tx_dr: DR, rx1_dr_offset: u8) -> DR;
+
+    /// Highest RX1DROffset accepted from the network.
+    fn max_rx1_dr_offset(&self) -> u8;
+
+    /// Default frequency of RX2.
+    fn rx2_frequency(&self) -> u32;
+
+    /// Default data rate of RX2.
+    fn rx2_datarate(&self) -> DR {
+        DR::_0
+    }
+
+    /// Listen-before-talk required by the regional regulations before each uplink.
+    fn listen_before_talk(&self) -> Option<ListenBeforeTalk> {
+        None
+    }
+
+    /// Whether the region implements TXParamSetupReq, ie: dwell-time and EIRP limits.
+    fn supports_tx_param_setup(&self) -> bool {
+        false
+    }
+
+    /// Size of the RFU fields framing the Class B beacon.
+    fn beacon_layout(&self) -> BeaconLayout {
+        BeaconLayout::SHORT
+    }
+
+    /// Data rate of the Class B beacon, also used by default for ping slots.
+    fn beacon_datarate(&self) -> DR {
+        DR::_3
+    }
+
+    /// Frequency of the beacon transmitted at `beacon_time` (GPS seconds).
+    fn beacon_frequency(&self, _beacon_time: u32) -> u32 {
+        self.rx2_frequency()
+    }
+
+    /// Default ping slot frequency during the beacon period opened at `beacon_time`.
+    fn ping_slot_frequency(&self, _dev_addr: u32, beacon_time: u32) -> u32 {
+        self.beacon_frequency(beacon_time)
+    }
+}
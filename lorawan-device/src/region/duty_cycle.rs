@@ -0,0 +1,135 @@
+//! Regulatory duty-cycle accounting.
+//!
+//! Each transmission keeps its sub-band closed for `time on air / duty cycle` counted from the
+//! start of the transmission, which keeps the airtime spent in the band within its limit over
+//! any observation window. The network may further restrict the aggregated duty cycle of the
+//! device with DutyCycleReq, which is accounted the same way across all bands.
+
+/// Most sub-bands defined by a region (EU868).
+pub(crate) const MAX_BANDS: usize = 6;
+
+/// A sub-band with its own duty-cycle limit, spanning `min_frequency..max_frequency`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Band {
+    min_frequency: u32,
+    max_frequency: u32,
+    /// Inverse of the duty cycle, eg: 100 for 1%.
+    inverse_duty_cycle: u16,
+}
+
+impl Band {
+    #[cfg(any(feature = "region-eu433", feature = "region-eu868", feature = "region-ru864"))]
+    pub(crate) const fn new(
+        min_frequency: u32,
+        max_frequency: u32,
+        inverse_duty_cycle: u16,
+    ) -> Self {
+        Self { min_frequency, max_frequency, inverse_duty_cycle }
+    }
+
+    fn contains(&self, frequency: u32) -> bool {
+        (self.min_frequency..self.max_frequency).contains(&frequency)
+    }
+}
+
+/// Index of the band which `frequency` belongs to, if any.
+pub(crate) fn band_index(bands: &[Band], frequency: u32) -> Option<usize> {
+    bands.iter().position(|band| band.contains(frequency))
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct OffPeriod {
+    start_ms: u64,
+    len_ms: u64,
+}
+
+impl OffPeriod {
+    fn remaining_ms(&self, now_ms: u64) -> u64 {
+        // A clock which went backwards (eg: after a reboot) reopens the band.
+        self.len_ms.saturating_sub(now_ms.wrapping_sub(self.start_ms))
+    }
+}
+
+/// Time-on-air ledger of the last transmission in each band.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DutyCycle {
+    bands: [OffPeriod; MAX_BANDS],
+    aggregated: OffPeriod,
+}
+
+impl DutyCycle {
+    /// Time until band `index` may be used again.
+    pub(crate) fn band_remaining_ms(&self, index: usize, now_ms: u64) -> u64 {
+        self.bands[index].remaining_ms(now_ms)
+    }
+
+    /// Time until the aggregated duty cycle set by the network allows transmitting again.
+    pub(crate) fn aggregated_remaining_ms(&self, now_ms: u64) -> u64 {
+        self.aggregated.remaining_ms(now_ms)
+    }
+
+    /// Account for a transmission of `time_on_air_ms` started at `now_ms` in band `index` (if
+    /// the frequency belongs to one), with an aggregated duty cycle of `1 / 2^max_duty_cycle`.
+    pub(crate) fn register(
+        &mut self,
+        band: Option<(usize, &Band)>,
+        max_duty_cycle: u8,
+        time_on_air_ms: u64,
+        now_ms: u64,
+    ) {
+        if let Some((index, band)) = band {
+            self.bands[index] = OffPeriod {
+                start_ms: now_ms,
+                len_ms: time_on_air_ms * band.inverse_duty_cycle as u64,
+            };
+        }
+        // MaxDCycle of 0 means no limitation besides the regional one.
+        if max_duty_cycle > 0 {
+            self.aggregated =
+                OffPeriod { start_ms: now_ms, len_ms: time_on_air_ms << (max_duty_cycle & 0x0f) };
+        }
+    }
+
+    /// Cancel the accounting of a transmission which did not take place, eg: on a channel found
+    /// busy by listen-before-talk. Its band and the aggregated duty cycle were open before, as
+    /// they must be for a transmission.
+    pub(crate) fn unregister(&mut self, band: Option<usize>, max_duty_cycle: u8) {
+        if let Some(index) = band {
+            self.bands[index] = OffPeriod::default();
+        }
+        if max_duty_cycle > 0 {
+            self.aggregated = OffPeriod::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BANDS: [Band; 2] =
+        [Band::new(868_000_000, 868_600_000, 100), Band::new(869_400_000, 869_650_000, 10)];
+
+    #[test]
+    fn band_closed_for_off_period() {
+        let mut ledger = DutyCycle::default();
+        let band = band_index(&BANDS, 868_100_000).unwrap();
+        ledger.register(Some((band, &BANDS[band])), 0, 50, 1_000);
+        assert_eq!(ledger.band_remaining_ms(band, 1_000), 5_000);
+        assert_eq!(ledger.band_remaining_ms(band, 5_000), 1_000);
+        assert_eq!(ledger.band_remaining_ms(band, 6_000), 0);
+        // Other bands stay open
+        assert_eq!(ledger.band_remaining_ms(1, 1_000), 0);
+        assert_eq!(ledger.aggregated_remaining_ms(1_000), 0);
+        assert_eq!(band_index(&BANDS, 868_700_000), None);
+    }
+
+    #[test]
+    fn aggregated_duty_cycle() {
+        let mut ledger = DutyCycle::default();
+        // 1 / 2^4 aggregated duty cycle, outside of any band
+        ledger.register(None, 4, 100, 0);
+        assert_eq!(ledger.aggregated_remaining_ms(0), 1_600);
+        assert_eq!(ledger.aggregated_remaining_ms(1_600), 0);
+    }
+}
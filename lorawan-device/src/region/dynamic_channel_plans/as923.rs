@@ -21,6 +21,8 @@ pub struct AS923Region<const DEFAULT_RX2: u32, const O: u32>;
 impl<const DEFAULT_RX2: u32, const OFFSET: u32> ChannelRegion for AS923Region<DEFAULT_RX2, OFFSET> {
     const DEFAULT_RX2_FREQ: u32 = DEFAULT_RX2;
     const MAX_RX1_DR_OFFSET: u8 = 7;
+    const SUPPORTS_TX_PARAM_SETUP: bool = true;
+    const LISTEN_BEFORE_TALK: Option<ListenBeforeTalk> = Some(ListenBeforeTalk::ARIB_STD_T108);
 
     fn datarates() -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
         &DATARATES
@@ -32,6 +34,15 @@ impl<const DEFAULT_RX2: u32, const OFFSET: u32> ChannelRegion for AS923Region<DE
             _ => None,
         }
     }
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::SHORT;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_3;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        923_400_000 - OFFSET
+    }
 }
 
 fn as924_generic_freq_check(f: u32) -> bool {
@@ -58,8 +69,8 @@ impl<const DEFAULT_RX2: u32, const OFFSET: u32> DynamicChannelRegion
     const NUM_JOIN_CHANNELS: u8 = 2;
 
     fn get_rx_datarate(tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR {
-        // TODO: Handle DwellTime, current values correspond to Dwelltime = 0
-        // In case DwellTime is 1, minimum data rate is DR::_2
+        // These values correspond to DownlinkDwellTime = 0, the MAC raises the RX1 data rate
+        // to DR2 when the downlink dwell time is enforced.
         match window {
             Window::_1 => match tx_dr {
                 DR::_0 | DR::_1 | DR::_2 | DR::_3 | DR::_4 | DR::_5 | DR::_6 | DR::_7 => {
@@ -86,13 +97,14 @@ impl<const DEFAULT_RX2: u32, const OFFSET: u32> DynamicChannelRegion
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
     }),
@@ -100,6 +112,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
     }),
@@ -107,6 +120,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 19,
     }),
@@ -114,6 +128,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 61,
     }),
@@ -121,6 +136,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 133,
     }),
@@ -128,6 +144,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -135,6 +152,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_250KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
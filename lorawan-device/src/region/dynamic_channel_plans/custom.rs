@@ -0,0 +1,99 @@
+/// Dynamic channel plan of a [`CustomRegion`] defined outside of this crate.
+use super::*;
+
+pub(crate) type Custom = DynamicChannelPlan<CustomPlanRegion>;
+
+#[derive(Clone, Copy)]
+pub(crate) struct CustomPlanRegion(&'static dyn CustomRegion);
+
+fn any_frequency(_f: u32) -> bool {
+    true
+}
+
+impl DynamicChannelPlan<CustomPlanRegion> {
+    pub fn new_custom(region: &'static dyn CustomRegion) -> Self {
+        let num_join_channels = region.default_channels().len();
+        // Join channels are sampled with two random bits, as in the built-in regions.
+        assert!(
+            (1..=3).contains(&num_join_channels),
+            "a custom region must have one to three default channels"
+        );
+        Self::with_region(CustomPlanRegion(region), any_frequency)
+    }
+}
+
+impl PlanRegion for CustomPlanRegion {
+    fn num_join_channels(&self) -> u8 {
+        self.0.default_channels().len() as u8
+    }
+
+    /// The default channels support all the data rates of the region.
+    fn init_channels(&self, channels: &mut ChannelPlan) {
+        let datarates = self.0.datarates();
+        let dr_min = datarates.iter().position(Option::is_some).unwrap_or(0);
+        let dr_max = datarates.iter().rposition(Option::is_some).unwrap_or(0);
+        for (channel, &frequency) in channels.iter_mut().zip(self.0.default_channels()) {
+            *channel =
+                Some(Channel::new(frequency, DR::from(dr_min as u8), DR::from(dr_max as u8)));
+        }
+    }
+
+    fn datarates(&self) -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+        self.0.datarates()
+    }
+
+    fn duty_cycle_bands(&self) -> &'static [Band] {
+        &[]
+    }
+
+    fn listen_before_talk(&self) -> Option<ListenBeforeTalk> {
+        self.0.listen_before_talk()
+    }
+
+    fn rx2_frequency(&self) -> u32 {
+        self.0.rx2_frequency()
+    }
+
+    fn get_rx_datarate(&self, tx_datarate: DR, rx1_dr_offset: u8, window: &Window) -> DR {
+        match window {
+            Window::_1 => self.0.rx1_datarate(tx_datarate, rx1_dr_offset),
+            Window::_2 => self.0.rx2_datarate(),
+        }
+    }
+
+    fn tx_power_adjust(&self, pw: u8) -> Option<u8> {
+        self.0.tx_power(pw)
+    }
+
+    fn supports_tx_param_setup(&self) -> bool {
+        self.0.supports_tx_param_setup()
+    }
+
+    fn max_rx1_dr_offset(&self) -> u8 {
+        self.0.max_rx1_dr_offset()
+    }
+
+    fn frequency_valid(&self, freq: u32) -> bool {
+        self.0.frequency_valid(freq)
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_layout(&self) -> BeaconLayout {
+        self.0.beacon_layout()
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_datarate(&self) -> DR {
+        self.0.beacon_datarate()
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(&self, beacon_time: u32) -> u32 {
+        self.0.beacon_frequency(beacon_time)
+    }
+
+    #[cfg(feature = "class-b")]
+    fn ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32 {
+        self.0.ping_slot_frequency(dev_addr, beacon_time)
+    }
+}
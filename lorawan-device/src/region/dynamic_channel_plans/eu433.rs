@@ -9,6 +9,9 @@ use super::*;
 
 const MAX_EIRP: u8 = 16;
 
+/// The 433.05..434.79 MHz ISM band.
+const BANDS: [Band; 1] = [Band::new(433_050_000, 434_790_001, 100)];
+
 pub(crate) type EU433 = DynamicChannelPlan<EU433Region>;
 
 #[derive(Default, Clone)]
@@ -39,6 +42,20 @@ impl ChannelRegion for EU433Region {
             _ => None,
         }
     }
+
+    fn duty_cycle_bands() -> &'static [Band] {
+        &BANDS
+    }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::SHORT;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_3;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        434_665_000
+    }
 }
 
 impl DynamicChannelRegion for EU433Region {
@@ -66,13 +83,14 @@ impl DynamicChannelRegion for EU433Region {
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
     }),
@@ -80,6 +98,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
     }),
@@ -87,6 +106,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 19,
     }),
@@ -94,6 +114,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 61,
     }),
@@ -101,6 +122,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 133,
     }),
@@ -108,6 +130,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -115,6 +138,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_250KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -5,11 +5,21 @@
 /// 2. DR0 to DR7
 /// 3. DR0 to DR11 (all data rates implemented)
 ///
-/// Current status: DR0..DR5 (minimum set is supported)
+/// Current status: DR0..DR11 (all data rates implemented)
 use super::*;
 
 const MAX_EIRP: u8 = 16;
 
+/// ETSI EN 300 220 sub-bands used by LoRaWAN.
+const BANDS: [Band; 6] = [
+    Band::new(863_000_000, 865_000_000, 1000),
+    Band::new(865_000_000, 868_000_000, 100),
+    Band::new(868_000_000, 868_600_000, 100),
+    Band::new(868_700_000, 869_200_000, 1000),
+    Band::new(869_400_000, 869_650_000, 10),
+    Band::new(869_700_000, 870_000_000, 100),
+];
+
 pub(crate) type EU868 = DynamicChannelPlan<EU868Region>;
 
 #[derive(Default, Clone)]
@@ -40,6 +50,20 @@ impl ChannelRegion for EU868Region {
             _ => None,
         }
     }
+
+    fn duty_cycle_bands() -> &'static [Band] {
+        &BANDS
+    }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::SHORT;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_3;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        869_525_000
+    }
 }
 
 impl DynamicChannelRegion for EU868Region {
@@ -69,13 +93,14 @@ impl DynamicChannelRegion for EU868Region {
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
     }),
@@ -83,6 +108,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
     }),
@@ -90,6 +116,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
     }),
@@ -97,6 +124,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 123,
     }),
@@ -104,6 +132,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -111,31 +140,61 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
-    None,
-    /*
-    // TODO: DR6: Can be enabled once DR7 is implemented
+    // DR6
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_250KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
-    */
-    // TODO: DR7: FSK: 50 kbps
-    None,
-    // TODO: DR8: LR-FHSS CR1/3: 137 kHz BW
-    None,
-    // TODO: DR9: LR-FHSS CR2/3: 137 kHz BW
-    None,
-    // TODO: DR10: LR-FHSS CR1/3: 336 kHz BW
-    None,
-    // TODO: DR11: LR-FHSS CR2/3: 336 kHz BW
-    None,
+    // DR7: FSK 50 kbps
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::Fsk(FSK_50KBPS),
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR8: LR-FHSS CR1/3: 137 kHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_1_3, LrFhssBandwidth::_137KHz),
+    // DR9: LR-FHSS CR2/3: 137 kHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_2_3, LrFhssBandwidth::_137KHz),
+    // DR10: LR-FHSS CR1/3: 336 kHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_1_3, LrFhssBandwidth::_336KHz),
+    // DR11: LR-FHSS CR2/3: 336 kHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_2_3, LrFhssBandwidth::_336KHz),
     // DR12..DR14: RFU
     None,
     None,
     None,
 ];
+
+const FSK_50KBPS: FskParams = FskParams { bitrate: 50_000, frequency_deviation: 25_000 };
+
+/// LR-FHSS data rates use the 3.9 kHz grid, and carry less payload with the more robust coding
+/// rate.
+const fn lr_fhss_datarate(
+    coding_rate: LrFhssCodingRate,
+    bandwidth: LrFhssBandwidth,
+) -> Option<Datarate> {
+    let max_mac_payload_size = match coding_rate {
+        LrFhssCodingRate::_1_3 => 58,
+        LrFhssCodingRate::_2_3 => 123,
+    };
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LrFhss(LrFhssParams {
+            coding_rate,
+            bandwidth,
+            grid: LrFhssGrid::_3_9KHz,
+        }),
+        max_mac_payload_size,
+        max_mac_payload_size_with_dwell_time: max_mac_payload_size,
+    })
+}
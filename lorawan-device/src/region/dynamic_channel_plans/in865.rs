@@ -38,6 +38,16 @@ impl ChannelRegion for IN865Region {
             _ => None,
         }
     }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::MEDIUM;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_4;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        866_550_000
+    }
 }
 
 impl DynamicChannelRegion for IN865Region {
@@ -88,13 +98,14 @@ impl DynamicChannelRegion for IN865Region {
     }
 }
 
-use super::{Bandwidth, Datarate, SpreadingFactor};
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
     }),
@@ -102,6 +113,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
     }),
@@ -109,6 +121,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 59,
     }),
@@ -116,6 +129,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 123,
     }),
@@ -123,6 +137,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -130,6 +145,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -0,0 +1,148 @@
+/// KR920 region support (920.9..923.3 MHz)
+///
+/// KR920-923 end-devices SHALL support DR0 to DR5, and sense the channel with listen-before-talk
+/// before each transmission.
+///
+/// Current status: DR0..DR5 is supported
+use super::*;
+
+const MAX_EIRP: u8 = 14;
+
+pub(crate) type KR920 = DynamicChannelPlan<KR920Region>;
+
+#[derive(Default, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct KR920Region;
+
+fn kr920_freq_check(f: u32) -> bool {
+    (920_900_000..=923_300_000).contains(&f)
+}
+
+impl<R: DynamicChannelRegion> DynamicChannelPlan<R> {
+    pub fn new_kr920() -> Self {
+        Self::new(kr920_freq_check)
+    }
+}
+
+impl ChannelRegion for KR920Region {
+    const DEFAULT_RX2_FREQ: u32 = 921_900_000;
+    const MAX_RX1_DR_OFFSET: u8 = 5;
+    /// Carrier sense required by the Korean regulations.
+    const LISTEN_BEFORE_TALK: Option<ListenBeforeTalk> =
+        Some(ListenBeforeTalk { threshold_dbm: -65, duration_us: 5_000 });
+
+    fn datarates() -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+        &DATARATES
+    }
+
+    fn tx_power_adjust(pw: u8) -> Option<u8> {
+        match pw {
+            0..=7 => Some(MAX_EIRP - (2 * pw)),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::SHORT;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_3;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        923_100_000
+    }
+}
+
+impl DynamicChannelRegion for KR920Region {
+    const NUM_JOIN_CHANNELS: u8 = 3;
+
+    fn get_rx_datarate(tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR {
+        match window {
+            Window::_1 => match tx_dr {
+                DR::_0 | DR::_1 | DR::_2 | DR::_3 | DR::_4 | DR::_5 => {
+                    tx_dr.offset_sub(rx1_dr_offset)
+                }
+                DR::_6
+                | DR::_7
+                | DR::_8
+                | DR::_9
+                | DR::_10
+                | DR::_11
+                | DR::_12
+                | DR::_13
+                | DR::_14
+                | DR::_15 => DR::_0,
+            },
+            Window::_2 => DR::_0,
+        }
+    }
+
+    fn init_channels(channels: &mut ChannelPlan) {
+        channels[0] = Some(Channel::new(922_100_000, DR::_0, DR::_5));
+        channels[1] = Some(Channel::new(922_300_000, DR::_0, DR::_5));
+        channels[2] = Some(Channel::new(922_500_000, DR::_0, DR::_5));
+    }
+}
+
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
+
+pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
+    // DR0
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_12,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 59,
+        max_mac_payload_size_with_dwell_time: 59,
+    }),
+    // DR1
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_11,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 59,
+        max_mac_payload_size_with_dwell_time: 59,
+    }),
+    // DR2
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_10,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 59,
+        max_mac_payload_size_with_dwell_time: 59,
+    }),
+    // DR3
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_9,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 123,
+        max_mac_payload_size_with_dwell_time: 123,
+    }),
+    // DR4
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_8,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR5
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR6..DR14: RFU
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+];
@@ -1,5 +1,4 @@
 use super::*;
-use core::marker::PhantomData;
 use lorawan::types::DataRateRange;
 
 #[cfg(any(
@@ -9,12 +8,18 @@ use lorawan::types::DataRateRange;
     feature = "region-as923-4"
 ))]
 mod as923;
+#[cfg(feature = "region-custom")]
+mod custom;
 #[cfg(feature = "region-eu433")]
 pub(crate) mod eu433;
 #[cfg(feature = "region-eu868")]
 pub(crate) mod eu868;
 #[cfg(feature = "region-in865")]
 mod in865;
+#[cfg(feature = "region-kr920")]
+mod kr920;
+#[cfg(feature = "region-ru864")]
+mod ru864;
 
 #[cfg(feature = "region-as923-1")]
 pub(crate) use as923::AS923_1;
@@ -24,12 +29,18 @@ pub(crate) use as923::AS923_2;
 pub(crate) use as923::AS923_3;
 #[cfg(feature = "region-as923-4")]
 pub(crate) use as923::AS923_4;
+#[cfg(feature = "region-custom")]
+pub(crate) use custom::Custom;
 #[cfg(feature = "region-eu433")]
 pub(crate) use eu433::EU433;
 #[cfg(feature = "region-eu868")]
 pub(crate) use eu868::EU868;
 #[cfg(feature = "region-in865")]
 pub(crate) use in865::IN865;
+#[cfg(feature = "region-kr920")]
+pub(crate) use kr920::KR920;
+#[cfg(feature = "region-ru864")]
+pub(crate) use ru864::RU864;
 
 #[derive(Clone, Copy)]
 pub(crate) struct Channel {
@@ -60,24 +71,36 @@ impl Channel {
 type ChannelPlan = [Option<Channel>; NUM_CHANNELS_DYNAMIC as usize];
 
 #[derive(Clone)]
-pub(crate) struct DynamicChannelPlan<R: DynamicChannelRegion> {
+pub(crate) struct DynamicChannelPlan<R: PlanRegion> {
     channels: ChannelPlan,
     channel_mask: ChannelMask<9>,
-    _dynamic_channel_region: PhantomData<R>,
+    region: R,
     frequency_valid: fn(u32) -> bool,
 }
 
 impl<R: DynamicChannelRegion> DynamicChannelPlan<R> {
+    #[cfg(any(
+        feature = "region-as923-1",
+        feature = "region-as923-2",
+        feature = "region-as923-3",
+        feature = "region-as923-4",
+        feature = "region-eu433",
+        feature = "region-eu868",
+        feature = "region-in865",
+        feature = "region-kr920",
+        feature = "region-ru864"
+    ))]
     fn new(freq_fn: fn(u32) -> bool) -> Self {
+        Self::with_region(R::default(), freq_fn)
+    }
+}
+
+impl<R: PlanRegion> DynamicChannelPlan<R> {
+    fn with_region(region: R, freq_fn: fn(u32) -> bool) -> Self {
         let mut channels = [None; NUM_CHANNELS_DYNAMIC as usize];
-        R::init_channels(&mut channels);
+        region.init_channels(&mut channels);
 
-        Self {
-            channels,
-            channel_mask: Default::default(),
-            _dynamic_channel_region: Default::default(),
-            frequency_valid: freq_fn,
-        }
+        Self { channels, channel_mask: Default::default(), region, frequency_valid: freq_fn }
     }
 
     fn get_random_in_range<RNG: RngCore>(&self, rng: &mut RNG) -> usize {
@@ -93,19 +116,106 @@ impl<R: DynamicChannelRegion> DynamicChannelPlan<R> {
         };
         (rng.next_u32() as usize) & cm
     }
-
-    pub fn get_max_payload_length(datarate: DR, repeater_compatible: bool, dwell_time: bool) -> u8 {
-        R::get_max_payload_length(datarate, repeater_compatible, dwell_time)
-    }
 }
 
-pub(crate) trait DynamicChannelRegion: ChannelRegion {
+pub(crate) trait DynamicChannelRegion: ChannelRegion + Clone + Default {
     const NUM_JOIN_CHANNELS: u8;
     fn init_channels(channels: &mut ChannelPlan);
     fn get_rx_datarate(tx_datarate: DR, rx1_dr_offset: u8, window: &Window) -> DR;
 }
 
-impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
+/// Parameters of the region of a [`DynamicChannelPlan`]: a built-in [`DynamicChannelRegion`], or
+/// a [`CustomRegion`] defined outside of this crate.
+pub(crate) trait PlanRegion: Clone {
+    fn num_join_channels(&self) -> u8;
+    fn init_channels(&self, channels: &mut ChannelPlan);
+    fn datarates(&self) -> &'static [Option<Datarate>; NUM_DATARATES as usize];
+    fn duty_cycle_bands(&self) -> &'static [Band];
+    fn listen_before_talk(&self) -> Option<ListenBeforeTalk>;
+    fn rx2_frequency(&self) -> u32;
+    fn get_rx_datarate(&self, tx_datarate: DR, rx1_dr_offset: u8, window: &Window) -> DR;
+    fn tx_power_adjust(&self, pw: u8) -> Option<u8>;
+    fn supports_tx_param_setup(&self) -> bool;
+    fn max_rx1_dr_offset(&self) -> u8;
+
+    /// Checked on top of the frequency range given to the channel plan.
+    fn frequency_valid(&self, _freq: u32) -> bool {
+        true
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_layout(&self) -> BeaconLayout;
+    #[cfg(feature = "class-b")]
+    fn beacon_datarate(&self) -> DR;
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(&self, beacon_time: u32) -> u32;
+    #[cfg(feature = "class-b")]
+    fn ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32;
+}
+
+impl<R: DynamicChannelRegion> PlanRegion for R {
+    fn num_join_channels(&self) -> u8 {
+        R::NUM_JOIN_CHANNELS
+    }
+
+    fn init_channels(&self, channels: &mut ChannelPlan) {
+        <R as DynamicChannelRegion>::init_channels(channels)
+    }
+
+    fn datarates(&self) -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+        <R as ChannelRegion>::datarates()
+    }
+
+    fn duty_cycle_bands(&self) -> &'static [Band] {
+        <R as ChannelRegion>::duty_cycle_bands()
+    }
+
+    fn listen_before_talk(&self) -> Option<ListenBeforeTalk> {
+        R::LISTEN_BEFORE_TALK
+    }
+
+    fn rx2_frequency(&self) -> u32 {
+        R::DEFAULT_RX2_FREQ
+    }
+
+    fn get_rx_datarate(&self, tx_datarate: DR, rx1_dr_offset: u8, window: &Window) -> DR {
+        <R as DynamicChannelRegion>::get_rx_datarate(tx_datarate, rx1_dr_offset, window)
+    }
+
+    fn tx_power_adjust(&self, pw: u8) -> Option<u8> {
+        <R as ChannelRegion>::tx_power_adjust(pw)
+    }
+
+    fn supports_tx_param_setup(&self) -> bool {
+        R::SUPPORTS_TX_PARAM_SETUP
+    }
+
+    fn max_rx1_dr_offset(&self) -> u8 {
+        R::MAX_RX1_DR_OFFSET
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_layout(&self) -> BeaconLayout {
+        R::BEACON_LAYOUT
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_datarate(&self) -> DR {
+        R::BEACON_DR
+    }
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(&self, beacon_time: u32) -> u32 {
+        <R as ChannelRegion>::beacon_frequency(beacon_time)
+    }
+
+    #[cfg(feature = "class-b")]
+    fn ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32 {
+        <R as ChannelRegion>::ping_slot_frequency(dev_addr, beacon_time)
+    }
+}
+
+impl<R: PlanRegion> RegionHandler for DynamicChannelPlan<R> {
     fn process_join_accept(&mut self, c_f_list: Option<&CfList>) {
         match c_f_list {
             // Type 0
@@ -113,7 +223,7 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
                 // CfList of Type 0 may contain up to 5 frequencies, which define
                 // channels J to (J+4). Data rates for these channels is DR0..=DR5
                 for (n, freq) in cf_list.iter().enumerate() {
-                    let index = R::NUM_JOIN_CHANNELS as usize + n;
+                    let index = self.region.num_join_channels() as usize + n;
                     let value = freq.hz();
                     // unused channels are set to 0
                     if value == 0 {
@@ -192,7 +302,7 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
     }
 
     fn get_datarate(&self, dr: u8) -> Option<&Datarate> {
-        R::datarates()[dr as usize].as_ref()
+        self.region.datarates()[dr as usize].as_ref()
     }
 
     fn select_tx_channel<RNG: RngCore>(
@@ -200,21 +310,26 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
         rng: &mut RNG,
         datarate: DR,
         frame: &Frame,
+        available: &impl Fn(u32) -> bool,
     ) -> TxChannel {
         match frame {
             Frame::Join => {
                 // There are at most 3 join channels in dynamic regions,
                 // keep sampling until we get a valid channel.
+                // SAFETY: Join channels SHALL be always present
+                let channel = |index: u8| self.channels[index as usize].unwrap();
                 let mut index = (rng.next_u32() & 0b11) as u8;
-                while index >= R::NUM_JOIN_CHANNELS {
+                while index >= self.region.num_join_channels()
+                    || !available(channel(index).ul_frequency())
+                {
                     index = (rng.next_u32() & 0b11) as u8;
                 }
 
-                // SAFETY: Join channels SHALL be always present
-                let channel = self.channels[index as usize].unwrap();
+                let channel = channel(index);
                 TxChannel {
-                    datarate: R::datarates()[datarate as usize].clone().unwrap(),
+                    datarate: self.region.datarates()[datarate as usize].clone().unwrap(),
                     dr: datarate,
+                    channel: index,
                     frequency: channel.ul_frequency(),
                     rx1_frequency: channel.rx1_frequency(),
                 }
@@ -224,10 +339,12 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
                 loop {
                     if self.channel_mask.is_enabled(channel).unwrap()
                         && let Some(ch) = self.channels[channel]
+                        && available(ch.ul_frequency())
                     {
                         return TxChannel {
-                            datarate: R::datarates()[datarate as usize].clone().unwrap(),
+                            datarate: self.region.datarates()[datarate as usize].clone().unwrap(),
                             dr: datarate,
+                            channel: channel as u8,
                             frequency: ch.ul_frequency(),
                             rx1_frequency: ch.rx1_frequency(),
                         };
@@ -238,26 +355,105 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
         }
     }
 
+    fn has_tx_channel(
+        &self,
+        _datarate: DR,
+        frame: &Frame,
+        available: &impl Fn(u32) -> bool,
+    ) -> bool {
+        let channels = match frame {
+            Frame::Join => &self.channels[..self.region.num_join_channels() as usize],
+            Frame::Data => &self.channels[..],
+        };
+        channels.iter().enumerate().any(|(i, channel)| {
+            (matches!(frame, Frame::Join) || self.channel_mask.is_enabled(i).unwrap())
+                && channel.is_some_and(|ch| available(ch.ul_frequency()))
+        })
+    }
+
+    fn tx_wait_ms(&self, frame: &Frame, wait_ms: &impl Fn(u32) -> u64) -> u64 {
+        let channels = match frame {
+            Frame::Join => &self.channels[..self.region.num_join_channels() as usize],
+            Frame::Data => &self.channels[..],
+        };
+        channels
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                matches!(frame, Frame::Join) || self.channel_mask.is_enabled(*i).unwrap()
+            })
+            .filter_map(|(_, channel)| channel.map(|ch| wait_ms(ch.ul_frequency())))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn get_duty_cycle_bands(&self) -> &'static [Band] {
+        self.region.duty_cycle_bands()
+    }
+
+    fn get_listen_before_talk(&self) -> Option<ListenBeforeTalk> {
+        self.region.listen_before_talk()
+    }
+
     fn get_rx2_frequency(&self) -> u32 {
-        R::DEFAULT_RX2_FREQ
+        self.region.rx2_frequency()
     }
 
     fn get_rx_datarate(&self, tx_datarate: DR, rx1_dr_offset: u8, window: &Window) -> DR {
-        R::get_rx_datarate(tx_datarate, rx1_dr_offset, window)
+        self.region.get_rx_datarate(tx_datarate, rx1_dr_offset, window)
     }
 
     fn check_tx_power(&self, tx_power: u8) -> Option<u8> {
-        R::tx_power_adjust(tx_power)
+        self.region.tx_power_adjust(tx_power)
+    }
+
+    fn supports_tx_param_setup(&self) -> bool {
+        self.region.supports_tx_param_setup()
     }
 
     fn frequency_valid(&self, freq: u32) -> bool {
-        (self.frequency_valid)(freq)
+        (self.frequency_valid)(freq) && self.region.frequency_valid(freq)
     }
 
     fn has_fixed_channel_plan(&self) -> bool {
         false
     }
 
+    fn write_channels(&self, w: &mut Writer) {
+        let set = self.channels.iter().enumerate().filter_map(|(i, ch)| ch.map(|ch| (i, ch)));
+        w.u16(set.clone().fold(0, |bitmap, (i, _)| bitmap | 1 << i));
+        for (_, channel) in set {
+            w.u32(channel.frequency);
+            w.u8(channel._datarates.raw_value());
+            w.u32(channel.dl_frequency.unwrap_or(0));
+        }
+    }
+
+    fn read_channels(&mut self, r: &mut Reader<'_>) -> Result<(), StateError> {
+        let bitmap = r.u16()?;
+        let mut channels: ChannelPlan = [None; NUM_CHANNELS_DYNAMIC as usize];
+        for (i, channel) in channels.iter_mut().enumerate() {
+            if bitmap & 1 << i == 0 {
+                continue;
+            }
+            let frequency = r.u32()?;
+            let datarates = DataRateRange::new(r.u8()?).map_err(|_| StateError::Invalid)?;
+            let dl_frequency = Some(r.u32()?).filter(|&f| f != 0);
+            if !self.frequency_valid(frequency)
+                || dl_frequency.is_some_and(|f| !self.frequency_valid(f))
+            {
+                return Err(StateError::Invalid);
+            }
+            *channel = Some(Channel { frequency, _datarates: datarates, dl_frequency });
+        }
+        // The join channels are always defined.
+        if channels[..self.region.num_join_channels() as usize].iter().any(Option::is_none) {
+            return Err(StateError::Invalid);
+        }
+        self.channels = channels;
+        Ok(())
+    }
+
     /// Update channel's downlink frequency for RX1 slot
     fn channel_dl_update(&mut self, index: u8, freq: u32) -> (bool, bool) {
         let freq_valid = self.frequency_valid(freq);
@@ -291,7 +487,7 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
         dr: Option<DataRateRange>,
     ) -> (bool, bool) {
         // Join channels are readonly - these cannot be modified!
-        if index < R::NUM_JOIN_CHANNELS {
+        if index < self.region.num_join_channels() {
             return (false, false);
         }
         // A crafted NewChannelReq can carry any index in 0..=255; reject anything
@@ -314,7 +510,7 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
             // unchecked range would index past it. Reject anything out of range.
             let dr_supported = r.max_data_rate() < NUM_DATARATES
                 && (r.min_data_rate()..=r.max_data_rate())
-                    .all(|c| (R::datarates()[c as usize]).is_some());
+                    .all(|c| (self.region.datarates()[c as usize]).is_some());
 
             if freq_valid && dr_supported {
                 self.channels[index as usize] = Some(Channel::new_with_dr(freq, r));
@@ -326,12 +522,32 @@ impl<R: DynamicChannelRegion> RegionHandler for DynamicChannelPlan<R> {
     }
 
     fn rx1_dr_offset_validate(&self, value: u8) -> Option<u8> {
-        if value <= R::MAX_RX1_DR_OFFSET {
+        if value <= self.region.max_rx1_dr_offset() {
             Some(value)
         } else {
             None
         }
     }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_layout(&self) -> BeaconLayout {
+        self.region.beacon_layout()
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_datarate(&self) -> DR {
+        self.region.beacon_datarate()
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_frequency(&self, beacon_time: u32) -> u32 {
+        self.region.beacon_frequency(beacon_time)
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32 {
+        self.region.ping_slot_frequency(dev_addr, beacon_time)
+    }
 }
 
 #[cfg(all(test, feature = "region-eu868"))]
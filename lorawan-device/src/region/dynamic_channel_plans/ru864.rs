@@ -0,0 +1,164 @@
+/// RU864 region support (864..870 MHz)
+///
+/// RU864-870 end-devices SHALL support DR0 to DR5, and may support DR6 and DR7.
+///
+/// Current status: DR0..DR7 (all data rates implemented)
+use super::*;
+
+const MAX_EIRP: u8 = 16;
+
+/// Sub-bands of the Russian regulations used by LoRaWAN.
+const BANDS: [Band; 4] = [
+    Band::new(864_000_000, 865_000_000, 1000),
+    Band::new(866_000_000, 868_000_000, 100),
+    Band::new(868_700_000, 869_200_000, 100),
+    Band::new(869_400_000, 869_650_000, 10),
+];
+
+pub(crate) type RU864 = DynamicChannelPlan<RU864Region>;
+
+#[derive(Default, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct RU864Region;
+
+fn ru864_freq_check(f: u32) -> bool {
+    (864_000_000..=870_000_000).contains(&f)
+}
+
+impl<R: DynamicChannelRegion> DynamicChannelPlan<R> {
+    pub fn new_ru864() -> Self {
+        Self::new(ru864_freq_check)
+    }
+}
+
+impl ChannelRegion for RU864Region {
+    const DEFAULT_RX2_FREQ: u32 = 869_100_000;
+    const MAX_RX1_DR_OFFSET: u8 = 5;
+
+    fn datarates() -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+        &DATARATES
+    }
+
+    fn tx_power_adjust(pw: u8) -> Option<u8> {
+        match pw {
+            0..=7 => Some(MAX_EIRP - (2 * pw)),
+            _ => None,
+        }
+    }
+
+    fn duty_cycle_bands() -> &'static [Band] {
+        &BANDS
+    }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::SHORT;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_3;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        869_100_000
+    }
+}
+
+impl DynamicChannelRegion for RU864Region {
+    const NUM_JOIN_CHANNELS: u8 = 2;
+
+    fn get_rx_datarate(tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR {
+        match window {
+            Window::_1 => match tx_dr {
+                DR::_0 | DR::_1 | DR::_2 | DR::_3 | DR::_4 | DR::_5 | DR::_6 | DR::_7 => {
+                    tx_dr.offset_sub(rx1_dr_offset)
+                }
+                DR::_8 | DR::_9 | DR::_10 | DR::_11 | DR::_12 | DR::_13 | DR::_14 | DR::_15 => {
+                    DR::_0
+                }
+            },
+            Window::_2 => DR::_0,
+        }
+    }
+
+    fn init_channels(channels: &mut ChannelPlan) {
+        channels[0] = Some(Channel::new(868_900_000, DR::_0, DR::_5));
+        channels[1] = Some(Channel::new(869_100_000, DR::_0, DR::_5));
+    }
+}
+
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
+
+pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
+    // DR0
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_12,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 59,
+        max_mac_payload_size_with_dwell_time: 59,
+    }),
+    // DR1
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_11,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 59,
+        max_mac_payload_size_with_dwell_time: 59,
+    }),
+    // DR2
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_10,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 59,
+        max_mac_payload_size_with_dwell_time: 59,
+    }),
+    // DR3
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_9,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 123,
+        max_mac_payload_size_with_dwell_time: 123,
+    }),
+    // DR4
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_8,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR5
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR6
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_250KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR7: FSK 50 kbps
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::Fsk(FSK_50KBPS),
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR8..DR14: RFU
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+];
+
+const FSK_50KBPS: FskParams = FskParams { bitrate: 50_000, frequency_deviation: 25_000 };
@@ -1,10 +1,14 @@
-use super::{Bandwidth, Datarate, NUM_DATARATES, SpreadingFactor};
+use super::{
+    Bandwidth, Datarate, LrFhssCodingRate, Modulation, NUM_DATARATES, SpreadingFactor,
+    lr_fhss_datarate,
+};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
     }),
@@ -12,6 +16,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 0,
     }),
@@ -19,6 +24,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 59,
         max_mac_payload_size_with_dwell_time: 19,
     }),
@@ -26,6 +32,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 123,
         max_mac_payload_size_with_dwell_time: 61,
     }),
@@ -33,6 +40,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 133,
     }),
@@ -40,6 +48,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -47,15 +56,17 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
-    // TODO: DR7: LR-FHSS CR1/3: 1.523 MHz BW
-    None,
+    // DR7: LR-FHSS CR1/3: 1.523 MHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_1_3, 58),
     // DR8
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 61,
         max_mac_payload_size_with_dwell_time: 61,
     }),
@@ -63,6 +74,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 137,
         max_mac_payload_size_with_dwell_time: 137,
     }),
@@ -70,6 +82,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -77,6 +90,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -84,6 +98,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -91,6 +106,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
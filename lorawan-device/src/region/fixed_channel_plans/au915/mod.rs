@@ -4,8 +4,10 @@
 /// 1. DR0 to DR6 and DR8 to DR13 (minimum set supported for certification)
 /// 2. DR0 to DR13 (all data rates implemented)
 ///
-/// Current status: DR7 is unimplemented (LR-FHSS)
+/// Current status: DR0..DR13 (all data rates implemented)
 use super::*;
+#[cfg(feature = "class-b")]
+use lorawan::beacon::BEACON_PERIOD;
 
 mod frequencies;
 use frequencies::*;
@@ -61,6 +63,7 @@ pub(crate) struct AU915Region;
 impl ChannelRegion for AU915Region {
     const DEFAULT_RX2_FREQ: u32 = 923_300_000;
     const MAX_RX1_DR_OFFSET: u8 = 5;
+    const SUPPORTS_TX_PARAM_SETUP: bool = true;
 
     fn datarates() -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
         &DATARATES
@@ -72,14 +75,31 @@ impl ChannelRegion for AU915Region {
             _ => None,
         }
     }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::LONG;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_8;
+
+    /// Beacons hop over the downlink channels, one beacon period each.
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(beacon_time: u32) -> u32 {
+        DOWNLINK_CHANNEL_MAP[(beacon_time / BEACON_PERIOD) as usize % 8]
+    }
+
+    #[cfg(feature = "class-b")]
+    fn ping_slot_frequency(dev_addr: u32, beacon_time: u32) -> u32 {
+        let channel = dev_addr.wrapping_add(beacon_time / BEACON_PERIOD) % 8;
+        DOWNLINK_CHANNEL_MAP[channel as usize]
+    }
 }
 
 impl FixedChannelRegion for AU915Region {
-    fn uplink_channels() -> &'static [u32; 72] {
-        &UPLINK_CHANNEL_MAP
+    fn uplink_frequency(channel: u8) -> u32 {
+        UPLINK_CHANNEL_MAP[channel as usize]
     }
-    fn downlink_channels() -> &'static [u32; 8] {
-        &DOWNLINK_CHANNEL_MAP
+    fn rx1_frequency(channel: u8) -> u32 {
+        DOWNLINK_CHANNEL_MAP[(channel % 8) as usize]
     }
     fn get_rx_datarate(tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR {
         match window {
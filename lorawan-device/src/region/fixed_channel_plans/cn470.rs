@@ -0,0 +1,233 @@
+/// CN470 region support (470..510 MHz)
+///
+/// CN470-510 end-devices SHALL support DR1 to DR5, on one of four channel plans: the 20 MHz
+/// antenna plans A and B, with 64 uplink channels in two groups of 32, and the 26 MHz antenna
+/// plans A and B, with 48 uplink channels. The plan is selected with the [`Region`] variant.
+///
+/// Current status: DR1..DR5 is supported, the Class B beacon and ping slots use the RX2
+/// frequency of the plan.
+use super::*;
+
+const MAX_EIRP: u8 = 19;
+
+const PLAN_20MHZ_A: u8 = 0;
+const PLAN_20MHZ_B: u8 = 1;
+const PLAN_26MHZ_A: u8 = 2;
+const PLAN_26MHZ_B: u8 = 3;
+
+pub(crate) type CN470_20A = FixedChannelPlan<CN470Region<PLAN_20MHZ_A>>;
+pub(crate) type CN470_20B = FixedChannelPlan<CN470Region<PLAN_20MHZ_B>>;
+pub(crate) type CN470_26A = FixedChannelPlan<CN470Region<PLAN_26MHZ_A>>;
+pub(crate) type CN470_26B = FixedChannelPlan<CN470Region<PLAN_26MHZ_B>>;
+
+#[derive(Default, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub(crate) struct CN470Region<const PLAN: u8>;
+
+fn cn470_freq_check(f: u32) -> bool {
+    (470_000_000..=510_000_000).contains(&f)
+}
+
+impl<F: FixedChannelRegion> FixedChannelPlan<F> {
+    pub fn new_cn470() -> Self {
+        Self::new(cn470_freq_check)
+    }
+}
+
+impl<const PLAN: u8> CN470Region<PLAN> {
+    const fn is_20mhz() -> bool {
+        matches!(PLAN, PLAN_20MHZ_A | PLAN_20MHZ_B)
+    }
+
+    const fn num_channels() -> u8 {
+        if Self::is_20mhz() {
+            64
+        } else {
+            48
+        }
+    }
+}
+
+impl<const PLAN: u8> ChannelRegion for CN470Region<PLAN> {
+    const DEFAULT_RX2_FREQ: u32 = match PLAN {
+        PLAN_20MHZ_A => 485_300_000,
+        PLAN_20MHZ_B => 486_900_000,
+        PLAN_26MHZ_A => 492_500_000,
+        _ => 502_500_000,
+    };
+    const MAX_RX1_DR_OFFSET: u8 = 5;
+
+    fn datarates() -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+        &DATARATES
+    }
+
+    fn tx_power_adjust(pw: u8) -> Option<u8> {
+        match pw {
+            0..=7 => Some(MAX_EIRP - (2 * pw)),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout = BeaconLayout::CN470;
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR = DR::_2;
+
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(_beacon_time: u32) -> u32 {
+        Self::DEFAULT_RX2_FREQ
+    }
+}
+
+impl<const PLAN: u8> FixedChannelRegion for CN470Region<PLAN> {
+    const DEFAULT_DATARATE: DR = DR::_1;
+
+    /// Channels are 200 kHz apart. The 20 MHz plans have two groups of 32 channels, 0-31 and
+    /// 32-63.
+    fn uplink_frequency(channel: u8) -> u32 {
+        let channel = channel as u32;
+        match PLAN {
+            PLAN_20MHZ_A if channel < 32 => 470_300_000 + channel * 200_000,
+            PLAN_20MHZ_A => 503_500_000 + (channel - 32) * 200_000,
+            PLAN_20MHZ_B if channel < 32 => 476_900_000 + channel * 200_000,
+            PLAN_20MHZ_B => 496_900_000 + (channel - 32) * 200_000,
+            PLAN_26MHZ_A => 470_300_000 + channel * 200_000,
+            _ => 480_300_000 + channel * 200_000,
+        }
+    }
+
+    /// The 20 MHz plan A answers each group of 32 channels on its own 32 downlink channels, and
+    /// plan B on the uplink channel. The 26 MHz plans have 24 downlink channels, shared by
+    /// uplink channels `n` and `n + 24`.
+    fn rx1_frequency(channel: u8) -> u32 {
+        let channel = channel as u32;
+        match PLAN {
+            PLAN_20MHZ_A if channel < 32 => 483_900_000 + channel * 200_000,
+            PLAN_20MHZ_A => 490_300_000 + (channel - 32) * 200_000,
+            PLAN_20MHZ_B => Self::uplink_frequency(channel as u8),
+            PLAN_26MHZ_A => 490_100_000 + (channel % 24) * 200_000,
+            _ => 500_100_000 + (channel % 24) * 200_000,
+        }
+    }
+
+    fn data_channels(_bandwidth: Bandwidth) -> Range<u8> {
+        0..Self::num_channels()
+    }
+
+    fn join_channels() -> Option<&'static [u8]> {
+        Some(match PLAN {
+            PLAN_20MHZ_A => &[3, 11, 19, 27, 35, 43, 51, 59],
+            PLAN_20MHZ_B => &[15, 47],
+            _ => &[0, 10, 20, 30, 40],
+        })
+    }
+
+    fn join_datarate(_channel: u8, datarate: DR) -> DR {
+        match datarate {
+            DR::_1 | DR::_2 | DR::_3 | DR::_4 | DR::_5 => datarate,
+            _ => DR::_1,
+        }
+    }
+
+    fn get_rx_datarate(tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR {
+        match window {
+            Window::_1 => match tx_dr {
+                DR::_1 | DR::_2 | DR::_3 | DR::_4 | DR::_5 => {
+                    let dr = tx_dr.offset_sub(rx1_dr_offset);
+                    if dr == DR::_0 {
+                        DR::_1
+                    } else {
+                        dr
+                    }
+                }
+                _ => DR::_1,
+            },
+            Window::_2 => DR::_1,
+        }
+    }
+
+    /// ChMaskCntl 0 to 5 select a bank of 16 channels, 6 enables all channels.
+    fn channel_mask_update(
+        channel_mask: &mut ChannelMask<9>,
+        ch_mask_ctl: u8,
+        ch_mask: ChannelMask<2>,
+    ) -> Option<()> {
+        match ch_mask_ctl {
+            0..=5 if ch_mask_ctl * 16 < Self::num_channels() => {
+                let base_index = ch_mask_ctl as usize * 2;
+                channel_mask.set_bank(base_index, ch_mask.get_index(0));
+                channel_mask.set_bank(base_index + 1, ch_mask.get_index(1));
+            }
+            6 => {
+                for bank in 0..Self::num_channels() as usize / 8 {
+                    channel_mask.set_bank(bank, 0xFF);
+                }
+            }
+            // Banks beyond the channels of the plan, and RFU
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn channel_mask_validate(channel_mask: &ChannelMask<9>, _datarate: &Datarate) -> bool {
+        (0..Self::num_channels() as usize).any(|i| channel_mask.is_enabled(i).unwrap())
+    }
+}
+
+use super::{Bandwidth, Datarate, Modulation, SpreadingFactor};
+
+pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
+    // DR0: not used for uplinks
+    None,
+    // DR1
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_11,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 31,
+        max_mac_payload_size_with_dwell_time: 31,
+    }),
+    // DR2
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_10,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 94,
+        max_mac_payload_size_with_dwell_time: 94,
+    }),
+    // DR3
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_9,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 192,
+        max_mac_payload_size_with_dwell_time: 192,
+    }),
+    // DR4
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_8,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR5
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_7,
+        bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
+        max_mac_payload_size: 250,
+        max_mac_payload_size_with_dwell_time: 250,
+    }),
+    // DR6 (SF7 at 500 kHz) and DR7 (FSK): unimplemented
+    None,
+    None,
+    // DR8..DR14: RFU
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+];
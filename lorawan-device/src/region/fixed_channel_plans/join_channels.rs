@@ -24,6 +24,12 @@ impl JoinChannels {
             && self.num_retries != 0
     }
 
+    /// First channel of the preferred subband, as long as data frames are sent on it.
+    pub(crate) fn biased_channel(&self) -> Option<u8> {
+        let subband = self.preferred_subband.filter(|_| self.has_bias_and_not_exhausted())?;
+        Some((subband as usize - 1) as u8 * 8)
+    }
+
     /// The first data channel will always be some random channel (possibly the same as previous)
     /// of the preferred subband. Returns None if there is no preferred subband.
     pub(crate) fn first_data_channel(&mut self, rng: &mut impl RngCore) -> Option<u8> {
@@ -42,6 +48,7 @@ impl JoinChannels {
         }
     }
 
+    #[cfg(any(feature = "region-us915", feature = "region-au915"))]
     pub(crate) fn set_join_bias(&mut self, subband: Subband, max_retries: usize) {
         self.preferred_subband = Some(subband);
         self.max_retries = max_retries;
@@ -162,6 +169,7 @@ impl AvailableChannels {
 /// This macro implements public functions relating to a fixed plan region. This is preferred to a
 /// trait implementation because the user does not have to worry about importing the trait to make
 /// use of these functions.
+#[cfg(any(feature = "region-us915", feature = "region-au915"))]
 macro_rules! impl_join_bias {
     ($region:ident) => {
         impl $region {
@@ -217,6 +225,7 @@ mod test {
     use crate::{
         AppEui, AppKey, DevEui, NetworkCredentials,
         mac::{Mac, SendData},
+        radio::RxQuality,
         test_util::{Uplink, get_key, handle_join_request},
     };
     use heapless::Vec;
@@ -311,7 +320,7 @@ mod test {
         let mut checked_fat_bank = false;
         for _ in 0..9 {
             let (tx_config, rx_windows, _) =
-                mac.join_otaa::<_, 255>(&mut rng, credentials.clone(), &mut buf);
+                mac.join_otaa::<_, 255>(&mut rng, credentials.clone(), &mut buf, None).unwrap();
             if tx_config.rf.bb.bw == Bandwidth::_500KHz {
                 // Join on the fat bank is forced to DR4 (SF8/500kHz)...
                 assert_eq!(tx_config.rf.bb.sf, SpreadingFactor::_8);
@@ -341,15 +350,18 @@ mod test {
         let mut mac = Mac::new(us915.into(), 21, 2);
 
         let mut buf: RadioBuffer<255> = RadioBuffer::new();
-        let (tx_config, rx_windows, _dev_nonce) = mac.join_otaa::<_, 255>(
-            &mut rand::rngs::OsRng,
-            NetworkCredentials::new(
-                AppEui::from([0x0; 8]),
-                DevEui::from([0x0; 8]),
-                AppKey::from(get_key()),
-            ),
-            &mut buf,
-        );
+        let (tx_config, rx_windows, _dev_nonce) = mac
+            .join_otaa::<_, 255>(
+                &mut rand::rngs::OsRng,
+                NetworkCredentials::new(
+                    AppEui::from([0x0; 8]),
+                    DevEui::from([0x0; 8]),
+                    AppKey::from(get_key()),
+                ),
+                &mut buf,
+                None,
+            )
+            .unwrap();
         // Confirm that the join request occurs on our subband
         assert!(
             tx_config.rf.frequency >= 903_900_000,
@@ -371,7 +383,12 @@ mod test {
         buf.clear();
         buf.extend_from_slice(&rx_buf[..len]).unwrap();
 
-        let response = mac.handle_rx::<255, 3>(&mut buf, &mut downlinks, 0, &rx_windows.rx1);
+        let response = mac.handle_rx::<255, 3>(
+            &mut buf,
+            &mut downlinks,
+            RxQuality::new(0, 0),
+            &rx_windows.rx1,
+        );
         if let Response::JoinSuccess = response {
         } else {
             panic!("Did not receive join success");
@@ -381,6 +398,7 @@ mod test {
                 &mut rand::rngs::OsRng,
                 &mut buf,
                 &SendData { fport: 1, data: &[0x0; 1], confirmed: false },
+                None,
             )
             .unwrap();
         // Confirm that the first data frame occurs on our subband
@@ -403,15 +421,18 @@ mod test {
         let mut mac = Mac::new(us915.into(), 21, 2);
 
         let mut buf: RadioBuffer<255> = RadioBuffer::new();
-        let (tx_config, rx_windows, _dev_nonce) = mac.join_otaa::<_, 255>(
-            &mut rand::rngs::OsRng,
-            NetworkCredentials::new(
-                AppEui::from([0x0; 8]),
-                DevEui::from([0x0; 8]),
-                AppKey::from(get_key()),
-            ),
-            &mut buf,
-        );
+        let (tx_config, rx_windows, _dev_nonce) = mac
+            .join_otaa::<_, 255>(
+                &mut rand::rngs::OsRng,
+                NetworkCredentials::new(
+                    AppEui::from([0x0; 8]),
+                    DevEui::from([0x0; 8]),
+                    AppKey::from(get_key()),
+                ),
+                &mut buf,
+                None,
+            )
+            .unwrap();
         // Confirm that the join request occurs on our subband
         assert!(
             tx_config.rf.frequency >= 903_900_000,
@@ -432,17 +453,29 @@ mod test {
         let len = handle_join_request::<0>(Some(uplink), tx_config.rf, &mut rx_buf);
         buf.clear();
         buf.extend_from_slice(&rx_buf[..len]).unwrap();
-        let response = mac.handle_rx::<255, 3>(&mut buf, &mut downlinks, 0, &rx_windows.rx1);
+        let response = mac.handle_rx::<255, 3>(
+            &mut buf,
+            &mut downlinks,
+            RxQuality::new(0, 0),
+            &rx_windows.rx1,
+        );
         if let Response::JoinSuccess = response {
         } else {
             panic!("Did not receive JoinSuccess")
         }
+        // Data frames go out at the join data rate while the bias lasts, which bounds the payload
+        mac.configuration.data_rate = DR::_3;
+        assert_eq!(mac.max_payload_len(), 11);
+        let send_data = SendData { fport: 1, data: &[0x0; 12], confirmed: false };
+        let response = mac.send::<_, 255>(&mut rand::rngs::OsRng, &mut buf, &send_data, None);
+        assert!(matches!(response, Err(crate::mac::Error::PayloadTooLarge { max: 11 })));
         for _ in 0..8 {
             let (tx_config, _rx_windows, _fcnt) = mac
                 .send::<_, 255>(
                     &mut rand::rngs::OsRng,
                     &mut buf,
                     &SendData { fport: 1, data: &[0x0; 1], confirmed: false },
+                    None,
                 )
                 .unwrap();
             // Confirm that the first data frame occurs on our subband
@@ -1,5 +1,6 @@
 use super::*;
 use core::marker::PhantomData;
+use core::ops::Range;
 use lorawan::maccommands::ChannelMask;
 
 mod join_channels;
@@ -7,11 +8,15 @@ use join_channels::JoinChannels;
 
 #[cfg(feature = "region-au915")]
 mod au915;
+#[cfg(feature = "region-cn470")]
+mod cn470;
 #[cfg(feature = "region-us915")]
 mod us915;
 
 #[cfg(feature = "region-au915")]
 pub use au915::AU915;
+#[cfg(feature = "region-cn470")]
+pub(crate) use cn470::{CN470_20A, CN470_20B, CN470_26A, CN470_26B};
 #[cfg(feature = "region-us915")]
 pub use us915::US915;
 
@@ -57,29 +62,19 @@ impl<F: FixedChannelRegion> FixedChannelPlan<F> {
         }
     }
 
-    pub fn set_125k_channels(
-        &self,
-        channel_mask: &mut ChannelMask<9>,
-        enabled: bool,
-        extra_mask: ChannelMask<2>,
-    ) {
-        let mask = if enabled {
-            0xFF
-        } else {
-            0x00
+    /// Channel of the next join request, at random among the join channels of the region if it
+    /// has some, avoiding the previous one.
+    fn next_join_channel<RNG: RngCore>(&mut self, rng: &mut RNG) -> u8 {
+        let Some(channels) = F::join_channels() else {
+            return self.join_channels.get_next_channel(rng);
         };
-        channel_mask.set_bank(0, mask);
-        channel_mask.set_bank(1, mask);
-        channel_mask.set_bank(2, mask);
-        channel_mask.set_bank(3, mask);
-        channel_mask.set_bank(4, mask);
-        channel_mask.set_bank(5, mask);
-        channel_mask.set_bank(6, mask);
-        channel_mask.set_bank(7, mask);
-
-        channel_mask.set_bank(8, extra_mask.get_index(0));
-        // Bank 9 is not (yet) used for frequencies
-        // channel_mask.set_bank(9, extra_mask.get_index(1));
+        let previous = self.join_channels.previous_channel;
+        let mut channel = channels[rng.next_u32() as usize % channels.len()];
+        while channels.len() > 1 && channel == previous {
+            channel = channels[rng.next_u32() as usize % channels.len()];
+        }
+        self.join_channels.previous_channel = channel;
+        channel
     }
 
     #[allow(unused)]
@@ -88,30 +83,79 @@ impl<F: FixedChannelRegion> FixedChannelPlan<F> {
     }
 }
 
+fn set_125k_channels(channel_mask: &mut ChannelMask<9>, enabled: bool, extra_mask: ChannelMask<2>) {
+    let mask = if enabled {
+        0xFF
+    } else {
+        0x00
+    };
+    channel_mask.set_bank(0, mask);
+    channel_mask.set_bank(1, mask);
+    channel_mask.set_bank(2, mask);
+    channel_mask.set_bank(3, mask);
+    channel_mask.set_bank(4, mask);
+    channel_mask.set_bank(5, mask);
+    channel_mask.set_bank(6, mask);
+    channel_mask.set_bank(7, mask);
+
+    channel_mask.set_bank(8, extra_mask.get_index(0));
+    // Bank 9 is not (yet) used for frequencies
+    // channel_mask.set_bank(9, extra_mask.get_index(1));
+}
+
+/// LR-FHSS data rates of US915 and AU915 hop over 1.523 MHz operating channels on the 25.4 kHz
+/// grid. These are centered on the 500 kHz channels 64 to 71, which carry the data rate here.
+#[cfg(any(feature = "region-us915", feature = "region-au915"))]
+const fn lr_fhss_datarate(
+    coding_rate: LrFhssCodingRate,
+    max_mac_payload_size: u8,
+) -> Option<Datarate> {
+    Some(Datarate {
+        spreading_factor: SpreadingFactor::_8,
+        bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LrFhss(LrFhssParams {
+            coding_rate,
+            bandwidth: LrFhssBandwidth::_1523KHz,
+            grid: LrFhssGrid::_25_4KHz,
+        }),
+        max_mac_payload_size,
+        max_mac_payload_size_with_dwell_time: max_mac_payload_size,
+    })
+}
+
+/// A fixed channel plan. The defaults describe the 64 + 8 channels of US915 and AU915: 64 channels
+/// of 125 kHz, 8 of 500 kHz, and joins walking the subbands with [`JoinChannels`].
 pub(crate) trait FixedChannelRegion: ChannelRegion {
-    fn uplink_channels() -> &'static [u32; 72];
-    fn downlink_channels() -> &'static [u32; 8];
+    const DEFAULT_DATARATE: DR = DR::_0;
+
+    fn uplink_frequency(channel: u8) -> u32;
+    fn rx1_frequency(channel: u8) -> u32;
     fn get_rx_datarate(tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR;
-}
 
-impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
-    fn process_join_accept(&mut self, c_f_list: Option<&CfList>) {
-        if let Some(CfList::FixedChannel(channel_mask)) = c_f_list {
-            self.channel_mask_set(channel_mask.clone());
+    /// Channels usable at `bandwidth`.
+    fn data_channels(bandwidth: Bandwidth) -> Range<u8> {
+        match bandwidth {
+            Bandwidth::_500KHz => 64..72,
+            _ => 0..64,
         }
     }
 
-    fn channel_mask_get(&self) -> ChannelMask<9> {
-        self.channel_mask.clone()
+    /// Channels reserved for join requests, picked at random. Without them, join requests walk
+    /// the subbands.
+    fn join_channels() -> Option<&'static [u8]> {
+        None
     }
 
-    fn channel_mask_set(&mut self, channel_mask: ChannelMask<9>) {
-        self.join_channels.reset();
-        self.channel_mask = channel_mask;
+    /// Data rate of a join request on `channel`, for a device set to `datarate`.
+    fn join_datarate(channel: u8, _datarate: DR) -> DR {
+        if channel < 64 {
+            DR::_0
+        } else {
+            DR::_4
+        }
     }
 
     fn channel_mask_update(
-        &self,
         channel_mask: &mut ChannelMask<9>,
         ch_mask_ctl: u8,
         ch_mask: ChannelMask<2>,
@@ -138,10 +182,10 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
                 channel_mask.set_bank(8, blocks);
             }
             6 => {
-                self.set_125k_channels(channel_mask, true, ch_mask);
+                set_125k_channels(channel_mask, true, ch_mask);
             }
             7 => {
-                self.set_125k_channels(channel_mask, false, ch_mask);
+                set_125k_channels(channel_mask, false, ch_mask);
             }
             _ => {
                 // RFU
@@ -151,18 +195,48 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
         Some(())
     }
 
+    fn channel_mask_validate(channel_mask: &ChannelMask<9>, datarate: &Datarate) -> bool {
+        match datarate.bandwidth {
+            Bandwidth::_500KHz => (64..=71).any(|i| channel_mask.is_enabled(i).unwrap()),
+            Bandwidth::_125KHz => {
+                // Check that at least two channels are enabled
+                (0..64).filter(|&i| channel_mask.is_enabled(i).unwrap()).take(2).count() == 2
+            }
+            _ => true,
+        }
+    }
+}
+
+impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
+    fn process_join_accept(&mut self, c_f_list: Option<&CfList>) {
+        if let Some(CfList::FixedChannel(channel_mask)) = c_f_list {
+            self.channel_mask_set(channel_mask.clone());
+        }
+    }
+
+    fn channel_mask_get(&self) -> ChannelMask<9> {
+        self.channel_mask.clone()
+    }
+
+    fn channel_mask_set(&mut self, channel_mask: ChannelMask<9>) {
+        self.join_channels.reset();
+        self.channel_mask = channel_mask;
+    }
+
+    fn channel_mask_update(
+        &self,
+        channel_mask: &mut ChannelMask<9>,
+        ch_mask_ctl: u8,
+        ch_mask: ChannelMask<2>,
+    ) -> Option<()> {
+        F::channel_mask_update(channel_mask, ch_mask_ctl, ch_mask)
+    }
+
     fn channel_mask_validate(&self, channel_mask: &ChannelMask<9>, dr: Option<DR>) -> bool {
         if let Some(dr) = dr
             && let Some(dr) = &F::datarates()[dr as usize]
         {
-            return match dr.bandwidth {
-                Bandwidth::_500KHz => (64..=71).any(|i| channel_mask.is_enabled(i).unwrap()),
-                Bandwidth::_125KHz => {
-                    // Check that at least two channels are enabled
-                    (0..64).filter(|&i| channel_mask.is_enabled(i).unwrap()).take(2).count() == 2
-                }
-                _ => true,
-            };
+            return F::channel_mask_validate(channel_mask, dr);
         }
         false
     }
@@ -171,67 +245,76 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
         F::datarates()[dr as usize].as_ref()
     }
 
+    fn get_default_datarate(&self) -> DR {
+        F::DEFAULT_DATARATE
+    }
+
+    fn data_datarate(&self, datarate: DR) -> DR {
+        // Data frames are sent like join requests while the join bias lasts, see
+        // `select_tx_channel`.
+        match self.join_channels.biased_channel() {
+            Some(channel) => F::join_datarate(channel, datarate),
+            None => datarate,
+        }
+    }
+
     fn select_tx_channel<RNG: RngCore>(
         &mut self,
         rng: &mut RNG,
         datarate: DR,
         frame: &Frame,
+        available: &impl Fn(u32) -> bool,
     ) -> TxChannel {
+        // Fixed channel plans have no duty-cycle bands, `available` only leaves out the channels
+        // avoided for their failures, once the join bias is over.
         let (dr, channel) = match frame {
             Frame::Join => {
-                let channel = self.join_channels.get_next_channel(rng);
-                let dr = if channel < 64 {
-                    DR::_0
-                } else {
-                    DR::_4
-                };
-                (dr, channel)
+                let channel = self.next_join_channel(rng);
+                (F::join_datarate(channel, datarate), channel)
             }
             Frame::Data => {
+                let bandwidth = F::datarates()[datarate as usize].as_ref().unwrap().bandwidth;
                 // The join bias gets reset after receiving CFList in Join Frame
                 // or ChannelMask in the LinkADRReq in Data Frame.
                 // If it has not been reset yet, we continue to use the bias for the data frames.
                 // We hope to acquire ChannelMask via LinkADRReq.
                 if self.join_channels.has_bias_and_not_exhausted() {
                     let channel = self.join_channels.get_next_channel(rng);
-                    let dr = if channel < 64 {
-                        DR::_0
-                    } else {
-                        DR::_4
-                    };
-                    (dr, channel)
+                    (F::join_datarate(channel, datarate), channel)
                 // Alternatively, we will ask JoinChannel logic to determine a channel from the
                 // subband that  the join succeeded on.
                 } else if let Some(channel) = self.join_channels.first_data_channel(rng) {
-                    (datarate, channel)
-                } else {
-                    // For the data frame, the datarate impacts which channel sets we can choose
-                    // from. If the datarate bandwidth is 500 kHz, we must use
-                    // channels 64..=71. Else, we must use 0-63
-                    let bandwidth = F::datarates()[datarate as usize].as_ref().unwrap().bandwidth;
+                    // Data rates of the 500 kHz channels (and the LR-FHSS operating channels
+                    // centered on them) use the one of the subband.
                     if bandwidth == Bandwidth::_500KHz {
-                        let mut channel = (rng.next_u32() & 0b111) as u8;
-                        // keep selecting a random channel until we find one that is enabled
-                        while !self.channel_mask.is_enabled((channel + 64).into()).unwrap() {
-                            channel = (rng.next_u32() & 0b111) as u8;
-                        }
-                        (datarate, 64 + channel)
+                        (datarate, 64 + channel / 8)
                     } else {
-                        let mut channel = (rng.next_u32() & 0b111111) as u8;
-                        // keep selecting a random channel until we find one that is enabled
-                        while !self.channel_mask.is_enabled(channel.into()).unwrap() {
-                            channel = (rng.next_u32() & 0b111111) as u8;
-                        }
                         (datarate, channel)
                     }
+                } else {
+                    // For the data frame, the datarate impacts which channel sets we can choose
+                    // from, eg: in US915 channels 64..=71 at 500 kHz and 0-63 otherwise.
+                    let usable = |channel: u8| {
+                        self.channel_mask.is_enabled(channel.into()).unwrap()
+                            && available(F::uplink_frequency(channel))
+                    };
+                    let channels = F::data_channels(bandwidth);
+                    let len = (channels.end - channels.start) as u32;
+                    let mut channel = channels.start + (rng.next_u32() % len) as u8;
+                    // keep selecting a random channel until we find one that is enabled
+                    while !usable(channel) {
+                        channel = channels.start + (rng.next_u32() % len) as u8;
+                    }
+                    (datarate, channel)
                 }
             }
         };
         TxChannel {
             datarate: F::datarates()[dr as usize].clone().unwrap(),
             dr,
-            frequency: F::uplink_channels()[channel as usize],
-            rx1_frequency: F::downlink_channels()[(channel % 8) as usize],
+            channel,
+            frequency: F::uplink_frequency(channel),
+            rx1_frequency: F::rx1_frequency(channel),
         }
     }
 
@@ -239,6 +322,36 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
         F::DEFAULT_RX2_FREQ
     }
 
+    fn has_tx_channel(
+        &self,
+        datarate: DR,
+        frame: &Frame,
+        available: &impl Fn(u32) -> bool,
+    ) -> bool {
+        if matches!(frame, Frame::Join) {
+            return true;
+        }
+        let Some(datarate) = F::datarates()[datarate as usize].as_ref() else {
+            return false;
+        };
+        F::data_channels(datarate.bandwidth).any(|channel| {
+            self.channel_mask.is_enabled(channel.into()).unwrap()
+                && available(F::uplink_frequency(channel))
+        })
+    }
+
+    fn tx_wait_ms(&self, _frame: &Frame, _wait_ms: &impl Fn(u32) -> u64) -> u64 {
+        0
+    }
+
+    fn get_duty_cycle_bands(&self) -> &'static [Band] {
+        F::duty_cycle_bands()
+    }
+
+    fn get_listen_before_talk(&self) -> Option<ListenBeforeTalk> {
+        F::LISTEN_BEFORE_TALK
+    }
+
     fn get_rx_datarate(&self, tx_dr: DR, rx1_dr_offset: u8, window: &Window) -> DR {
         F::get_rx_datarate(tx_dr, rx1_dr_offset, window)
     }
@@ -247,6 +360,10 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
         F::tx_power_adjust(tx_power)
     }
 
+    fn supports_tx_param_setup(&self) -> bool {
+        F::SUPPORTS_TX_PARAM_SETUP
+    }
+
     fn frequency_valid(&self, freq: u32) -> bool {
         (self.frequency_valid)(freq)
     }
@@ -270,4 +387,24 @@ impl<F: FixedChannelRegion> RegionHandler for FixedChannelPlan<F> {
             None
         }
     }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_layout(&self) -> BeaconLayout {
+        F::BEACON_LAYOUT
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_datarate(&self) -> DR {
+        F::BEACON_DR
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_frequency(&self, beacon_time: u32) -> u32 {
+        F::beacon_frequency(beacon_time)
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32 {
+        F::ping_slot_frequency(dev_addr, beacon_time)
+    }
 }
@@ -1,10 +1,14 @@
-use super::{Bandwidth, Datarate, NUM_DATARATES, SpreadingFactor};
+use super::{
+    Bandwidth, Datarate, LrFhssCodingRate, Modulation, NUM_DATARATES, SpreadingFactor,
+    lr_fhss_datarate,
+};
 
 pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     // DR0
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 19,
         max_mac_payload_size_with_dwell_time: 19,
     }),
@@ -12,6 +16,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 61,
         max_mac_payload_size_with_dwell_time: 61,
     }),
@@ -19,6 +24,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 133,
         max_mac_payload_size_with_dwell_time: 133,
     }),
@@ -26,6 +32,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_125KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -33,19 +40,21 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
-    // TODO: DR5: LR-FHSS CR1/3: 1.523 MHz BW
-    None,
-    // TODO: DR6: LR-FHSS CR2/3: 1.523 MHz BW
-    None,
+    // DR5: LR-FHSS CR1/3: 1.523 MHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_1_3, 58),
+    // DR6: LR-FHSS CR2/3: 1.523 MHz BW
+    lr_fhss_datarate(LrFhssCodingRate::_2_3, 133),
     // DR7: RFU
     None,
     // DR8
     Some(Datarate {
         spreading_factor: SpreadingFactor::_12,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 61,
         max_mac_payload_size_with_dwell_time: 61,
     }),
@@ -53,6 +62,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_11,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 137,
         max_mac_payload_size_with_dwell_time: 137,
     }),
@@ -60,6 +70,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_10,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -67,6 +78,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_9,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -74,6 +86,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_8,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
@@ -81,6 +94,7 @@ pub(crate) const DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = [
     Some(Datarate {
         spreading_factor: SpreadingFactor::_7,
         bandwidth: Bandwidth::_500KHz,
+        modulation: Modulation::LoRa,
         max_mac_payload_size: 250,
         max_mac_payload_size_with_dwell_time: 250,
     }),
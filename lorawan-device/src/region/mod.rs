@@ -1,15 +1,34 @@
 //! LoRaWAN device region definitions (eg: EU868, US915, etc).
-use lora_modulation::{Bandwidth, BaseBandModulationParams, CodingRate, SpreadingFactor};
+pub use lora_modulation::{Bandwidth, SpreadingFactor};
+use lora_modulation::{BaseBandModulationParams, CodingRate};
 use lorawan::{
     parser::CfList,
     types::{ChannelMask, DataRateRange},
 };
 use rand_core::RngCore;
 
-use crate::mac::{Frame, Window};
+#[cfg(feature = "class-b")]
+use lorawan::beacon::BeaconLayout;
+
+use crate::mac::{Frame, Reader, StateError, TxParams, Window, Writer};
 pub(crate) mod constants;
 pub(crate) use crate::radio::*;
+pub use crate::radio::{
+    FskParams, LrFhssBandwidth, LrFhssCodingRate, LrFhssGrid, LrFhssParams, Modulation,
+    SupportedModulations,
+};
+pub use constants::NUM_DATARATES;
 use constants::*;
+#[cfg(feature = "region-custom")]
+mod custom;
+#[cfg(feature = "region-custom")]
+pub use custom::CustomRegion;
+mod duty_cycle;
+use duty_cycle::{Band, DutyCycle, band_index};
+#[cfg(feature = "channel-stats")]
+mod channel_stats;
+#[cfg(feature = "channel-stats")]
+pub use channel_stats::{ChannelAvoidance, ChannelStats};
 // For backward compatibility
 pub use lorawan::types::DR;
 
@@ -21,8 +40,12 @@ pub use lorawan::types::DR;
     feature = "region-eu433",
     feature = "region-eu868",
     feature = "region-in865",
+    feature = "region-kr920",
+    feature = "region-ru864",
     feature = "region-au915",
-    feature = "region-us915"
+    feature = "region-cn470",
+    feature = "region-us915",
+    feature = "region-custom"
 )))]
 compile_error!("You must enable at least one region! eg: `region-eu868`, `region-us915`...");
 
@@ -33,7 +56,10 @@ compile_error!("You must enable at least one region! eg: `region-eu868`, `region
     feature = "region-as923-4",
     feature = "region-eu433",
     feature = "region-eu868",
-    feature = "region-in865"
+    feature = "region-in865",
+    feature = "region-kr920",
+    feature = "region-ru864",
+    feature = "region-custom"
 ))]
 mod dynamic_channel_plans;
 #[cfg(feature = "region-as923-1")]
@@ -44,14 +70,20 @@ pub(crate) use dynamic_channel_plans::AS923_2;
 pub(crate) use dynamic_channel_plans::AS923_3;
 #[cfg(feature = "region-as923-4")]
 pub(crate) use dynamic_channel_plans::AS923_4;
+#[cfg(feature = "region-custom")]
+pub(crate) use dynamic_channel_plans::Custom;
 #[cfg(feature = "region-eu433")]
 pub(crate) use dynamic_channel_plans::EU433;
 #[cfg(feature = "region-eu868")]
 pub(crate) use dynamic_channel_plans::EU868;
 #[cfg(feature = "region-in865")]
 pub(crate) use dynamic_channel_plans::IN865;
+#[cfg(feature = "region-kr920")]
+pub(crate) use dynamic_channel_plans::KR920;
+#[cfg(feature = "region-ru864")]
+pub(crate) use dynamic_channel_plans::RU864;
 
-#[cfg(any(feature = "region-us915", feature = "region-au915"))]
+#[cfg(any(feature = "region-us915", feature = "region-au915", feature = "region-cn470"))]
 mod fixed_channel_plans;
 #[cfg(feature = "region-au915")]
 pub use fixed_channel_plans::AU915;
@@ -59,30 +91,68 @@ pub use fixed_channel_plans::AU915;
 pub use fixed_channel_plans::Subband;
 #[cfg(feature = "region-us915")]
 pub use fixed_channel_plans::US915;
+#[cfg(feature = "region-cn470")]
+pub(crate) use fixed_channel_plans::{CN470_20A, CN470_20B, CN470_26A, CN470_26B};
 
 pub(crate) trait ChannelRegion {
     const MAX_RX1_DR_OFFSET: u8;
     const DEFAULT_RX2_FREQ: u32;
+    /// Whether the region implements TXParamSetupReq, ie: dwell-time and EIRP limits.
+    const SUPPORTS_TX_PARAM_SETUP: bool = false;
 
     fn datarates() -> &'static [Option<Datarate>; NUM_DATARATES as usize];
 
+    #[allow(unused)]
     fn get_max_payload_length(datarate: DR, repeater_compatible: bool, dwell_time: bool) -> u8 {
         let Some(Some(dr)) = Self::datarates().get(datarate as usize) else {
             return 0;
         };
-        let max_size = if dwell_time {
-            dr.max_mac_payload_size_with_dwell_time
-        } else {
-            dr.max_mac_payload_size
-        };
-        if repeater_compatible && max_size > 230 {
-            230
-        } else {
-            max_size
-        }
+        dr.max_payload_length(repeater_compatible, dwell_time)
     }
 
     fn tx_power_adjust(pw: u8) -> Option<u8>;
+
+    /// Sub-bands with a regulatory duty-cycle limit, eg: the ETSI sub-bands of EU868.
+    fn duty_cycle_bands() -> &'static [Band] {
+        &[]
+    }
+
+    /// Listen-before-talk required by the regional regulations before each uplink.
+    const LISTEN_BEFORE_TALK: Option<ListenBeforeTalk> = None;
+
+    /// Size of the RFU fields framing the Class B beacon.
+    #[cfg(feature = "class-b")]
+    const BEACON_LAYOUT: BeaconLayout;
+    /// Data rate of the Class B beacon, also used by default for ping slots.
+    #[cfg(feature = "class-b")]
+    const BEACON_DR: DR;
+
+    /// Frequency of the beacon transmitted at `beacon_time` (GPS seconds).
+    #[cfg(feature = "class-b")]
+    fn beacon_frequency(beacon_time: u32) -> u32;
+
+    /// Default ping slot frequency during the beacon period opened at `beacon_time`.
+    #[cfg(feature = "class-b")]
+    fn ping_slot_frequency(_dev_addr: u32, beacon_time: u32) -> u32 {
+        Self::beacon_frequency(beacon_time)
+    }
+}
+
+/// Channels found busy by listen-before-talk for one uplink, after which it is given up.
+const MAX_BUSY_CHANNELS: usize = 8;
+
+/// Listen-before-talk before each uplink: the channel is used if the RSSI stays below
+/// `threshold_dbm` for `duration_us`, otherwise another channel is selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ListenBeforeTalk {
+    pub threshold_dbm: i16,
+    pub duration_us: u32,
+}
+
+impl ListenBeforeTalk {
+    /// Carrier sense required by ARIB STD-T108 in Japan, the default of AS923.
+    pub const ARIB_STD_T108: Self = Self { threshold_dbm: -80, duration_us: 5_000 };
 }
 
 #[derive(Clone)]
@@ -90,15 +160,28 @@ pub(crate) trait ChannelRegion {
 ///
 /// Generally constructed using the [`Region`] enum, unless You need to do region-specific
 /// fine-tuning, like for example [`US915`] or [`AU915`].
+/// A region defined outside of this crate, with `CustomRegion` (`region-custom` feature), is used
+/// with `Configuration::custom`.
 pub struct Configuration {
     state: State,
+    duty_cycle: DutyCycle,
+    listen_before_talk: Option<ListenBeforeTalk>,
+    /// Modulations of the radio, data rates of the others are not used.
+    modulations: SupportedModulations,
+    /// Channels found busy by listen-before-talk, skipped until the next uplink.
+    busy_channels: heapless::Vec<u32, MAX_BUSY_CHANNELS>,
+    #[cfg(feature = "channel-stats")]
+    pub(crate) channel_stats: channel_stats::ChannelStatsTable,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-/// Regions supported by this crate: AS923_1, AS923_2, AS923_3, AS923_4, AU915, EU868, EU433, IN865, US915.
+/// Regions supported by this crate: AS923_1, AS923_2, AS923_3, AS923_4, AU915, CN470_20A, CN470_20B,
+/// CN470_26A, CN470_26B, EU868, EU433, IN865, KR920, RU864, US915.
 ///
 /// Each region is individually feature-gated (eg: `region-eu868`), however, by default, all regions are enabled.
+/// The four CN470 channel plans (20 MHz and 26 MHz antennas, plans A and B) share the
+/// `region-cn470` feature.
 ///
 pub enum Region {
     #[cfg(feature = "region-as923-1")]
@@ -111,17 +194,31 @@ pub enum Region {
     AS923_4,
     #[cfg(feature = "region-au915")]
     AU915,
+    #[cfg(feature = "region-cn470")]
+    CN470_20A,
+    #[cfg(feature = "region-cn470")]
+    CN470_20B,
+    #[cfg(feature = "region-cn470")]
+    CN470_26A,
+    #[cfg(feature = "region-cn470")]
+    CN470_26B,
     #[cfg(feature = "region-eu868")]
     EU868,
     #[cfg(feature = "region-eu433")]
     EU433,
     #[cfg(feature = "region-in865")]
     IN865,
+    #[cfg(feature = "region-kr920")]
+    KR920,
+    #[cfg(feature = "region-ru864")]
+    RU864,
     #[cfg(feature = "region-us915")]
     US915,
 }
 
 #[derive(Clone)]
+// The channel table of a custom region is held inline, as the crate does not allocate.
+#[cfg_attr(feature = "region-custom", allow(clippy::large_enum_variant))]
 enum State {
     #[cfg(feature = "region-as923-1")]
     AS923_1(AS923_1),
@@ -133,14 +230,28 @@ enum State {
     AS923_4(AS923_4),
     #[cfg(feature = "region-au915")]
     AU915(AU915),
+    #[cfg(feature = "region-cn470")]
+    CN470_20A(CN470_20A),
+    #[cfg(feature = "region-cn470")]
+    CN470_20B(CN470_20B),
+    #[cfg(feature = "region-cn470")]
+    CN470_26A(CN470_26A),
+    #[cfg(feature = "region-cn470")]
+    CN470_26B(CN470_26B),
     #[cfg(feature = "region-eu868")]
     EU868(EU868),
     #[cfg(feature = "region-eu433")]
     EU433(EU433),
     #[cfg(feature = "region-in865")]
     IN865(IN865),
+    #[cfg(feature = "region-kr920")]
+    KR920(KR920),
+    #[cfg(feature = "region-ru864")]
+    RU864(RU864),
     #[cfg(feature = "region-us915")]
     US915(US915),
+    #[cfg(feature = "region-custom")]
+    Custom(Custom),
 }
 
 impl State {
@@ -156,52 +267,130 @@ impl State {
             Region::AS923_4 => State::AS923_4(AS923_4::new_as924_4()),
             #[cfg(feature = "region-au915")]
             Region::AU915 => State::AU915(AU915::default()),
+            #[cfg(feature = "region-cn470")]
+            Region::CN470_20A => State::CN470_20A(CN470_20A::new_cn470()),
+            #[cfg(feature = "region-cn470")]
+            Region::CN470_20B => State::CN470_20B(CN470_20B::new_cn470()),
+            #[cfg(feature = "region-cn470")]
+            Region::CN470_26A => State::CN470_26A(CN470_26A::new_cn470()),
+            #[cfg(feature = "region-cn470")]
+            Region::CN470_26B => State::CN470_26B(CN470_26B::new_cn470()),
             #[cfg(feature = "region-eu868")]
             Region::EU868 => State::EU868(EU868::new_eu868()),
             #[cfg(feature = "region-eu433")]
             Region::EU433 => State::EU433(EU433::new_eu433()),
             #[cfg(feature = "region-in865")]
             Region::IN865 => State::IN865(IN865::new_in865()),
+            #[cfg(feature = "region-kr920")]
+            Region::KR920 => State::KR920(KR920::new_kr920()),
+            #[cfg(feature = "region-ru864")]
+            Region::RU864 => State::RU864(RU864::new_ru864()),
             #[cfg(feature = "region-us915")]
             Region::US915 => State::US915(US915::default()),
         }
     }
 
+    /// The built-in region, `None` for a [`CustomRegion`].
     #[allow(dead_code)]
-    pub fn region(&self) -> Region {
+    pub fn region(&self) -> Option<Region> {
         match self {
             #[cfg(feature = "region-as923-1")]
-            Self::AS923_1(_) => Region::AS923_1,
+            Self::AS923_1(_) => Some(Region::AS923_1),
             #[cfg(feature = "region-as923-2")]
-            Self::AS923_2(_) => Region::AS923_2,
+            Self::AS923_2(_) => Some(Region::AS923_2),
             #[cfg(feature = "region-as923-3")]
-            Self::AS923_3(_) => Region::AS923_3,
+            Self::AS923_3(_) => Some(Region::AS923_3),
             #[cfg(feature = "region-as923-4")]
-            Self::AS923_4(_) => Region::AS923_4,
+            Self::AS923_4(_) => Some(Region::AS923_4),
             #[cfg(feature = "region-au915")]
-            Self::AU915(_) => Region::AU915,
+            Self::AU915(_) => Some(Region::AU915),
+            #[cfg(feature = "region-cn470")]
+            Self::CN470_20A(_) => Some(Region::CN470_20A),
+            #[cfg(feature = "region-cn470")]
+            Self::CN470_20B(_) => Some(Region::CN470_20B),
+            #[cfg(feature = "region-cn470")]
+            Self::CN470_26A(_) => Some(Region::CN470_26A),
+            #[cfg(feature = "region-cn470")]
+            Self::CN470_26B(_) => Some(Region::CN470_26B),
             #[cfg(feature = "region-eu433")]
-            Self::EU433(_) => Region::EU433,
+            Self::EU433(_) => Some(Region::EU433),
             #[cfg(feature = "region-eu868")]
-            Self::EU868(_) => Region::EU868,
+            Self::EU868(_) => Some(Region::EU868),
             #[cfg(feature = "region-in865")]
-            Self::IN865(_) => Region::IN865,
+            Self::IN865(_) => Some(Region::IN865),
+            #[cfg(feature = "region-kr920")]
+            Self::KR920(_) => Some(Region::KR920),
+            #[cfg(feature = "region-ru864")]
+            Self::RU864(_) => Some(Region::RU864),
             #[cfg(feature = "region-us915")]
-            Self::US915(_) => Region::US915,
+            Self::US915(_) => Some(Region::US915),
+            #[cfg(feature = "region-custom")]
+            Self::Custom(_) => None,
         }
     }
 }
 
-/// This datarate type is used internally for defining [`Bandwidth`]/[`SpreadingFactor`] per
-/// region.
+/// This datarate type is used for defining [`Bandwidth`]/[`SpreadingFactor`] per region, and is
+/// constructed with [`Datarate::new`] for a `CustomRegion`. FSK and LR-FHSS data rates leave
+/// the spreading factor unused, and their bandwidth is that of the channels they are sent on.
 #[derive(Debug, Clone)]
-pub(crate) struct Datarate {
+pub struct Datarate {
     pub(crate) bandwidth: Bandwidth,
     pub(crate) spreading_factor: SpreadingFactor,
+    pub(crate) modulation: Modulation,
     pub(crate) max_mac_payload_size: u8,
     max_mac_payload_size_with_dwell_time: u8,
 }
 
+impl Datarate {
+    /// A LoRa data rate carrying up to `max_mac_payload_size` bytes, with or without dwell time.
+    pub const fn new(
+        spreading_factor: SpreadingFactor,
+        bandwidth: Bandwidth,
+        max_mac_payload_size: u8,
+    ) -> Self {
+        Self {
+            bandwidth,
+            spreading_factor,
+            modulation: Modulation::LoRa,
+            max_mac_payload_size,
+            max_mac_payload_size_with_dwell_time: max_mac_payload_size,
+        }
+    }
+
+    /// Send with `modulation` rather than LoRa, eg: FSK.
+    pub const fn with_modulation(mut self, modulation: Modulation) -> Self {
+        self.modulation = modulation;
+        self
+    }
+
+    /// Limit the MAC payload size to `max_mac_payload_size` under the 400 ms dwell time set by
+    /// TXParamSetupReq, 0 if the data rate is unusable then.
+    pub const fn with_dwell_time(mut self, max_mac_payload_size: u8) -> Self {
+        self.max_mac_payload_size_with_dwell_time = max_mac_payload_size;
+        self
+    }
+
+    /// Maximum MAC payload size, which is 0 if the data rate is unusable under the dwell time.
+    pub(crate) fn max_payload_size(&self, dwell_time: bool) -> u8 {
+        if dwell_time {
+            self.max_mac_payload_size_with_dwell_time
+        } else {
+            self.max_mac_payload_size
+        }
+    }
+
+    /// Maximum MAC payload size, limited to 230 bytes when repeater compatible.
+    fn max_payload_length(&self, repeater_compatible: bool, dwell_time: bool) -> u8 {
+        let max_size = self.max_payload_size(dwell_time);
+        if repeater_compatible && max_size > 230 {
+            230
+        } else {
+            max_size
+        }
+    }
+}
+
 /// The result of TX channel selection. Carries the DR actually used for the uplink (which may
 /// differ from the requested DR, eg: fixed-plan join frames force DR0/DR4 by channel) and the
 /// RX1 frequency paired with the selected channel, so RX windows can be derived from the
@@ -209,6 +398,8 @@ pub(crate) struct Datarate {
 pub(crate) struct TxChannel {
     pub(crate) datarate: Datarate,
     pub(crate) dr: DR,
+    /// Index of the selected channel, as covered by the LoRaWAN 1.1 uplink MIC.
+    pub(crate) channel: u8,
     pub(crate) frequency: u32,
     pub(crate) rx1_frequency: u32,
 }
@@ -225,14 +416,28 @@ macro_rules! mut_region_dispatch {
         State::AS923_4(state) => state.$t(),
         #[cfg(feature = "region-au915")]
         State::AU915(state) => state.0.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20A(state) => state.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20B(state) => state.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26A(state) => state.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26B(state) => state.$t(),
         #[cfg(feature = "region-eu868")]
         State::EU868(state) => state.$t(),
         #[cfg(feature = "region-eu433")]
         State::EU433(state) => state.$t(),
         #[cfg(feature = "region-in865")]
         State::IN865(state) => state.$t(),
+        #[cfg(feature = "region-kr920")]
+        State::KR920(state) => state.$t(),
+        #[cfg(feature = "region-ru864")]
+        State::RU864(state) => state.$t(),
         #[cfg(feature = "region-us915")]
         State::US915(state) => state.0.$t(),
+        #[cfg(feature = "region-custom")]
+        State::Custom(state) => state.$t(),
     }
   };
   ($s:expr, $t:tt, $($arg:tt)*) => {
@@ -247,14 +452,28 @@ macro_rules! mut_region_dispatch {
         State::AS923_4(state) => state.$t($($arg)*),
         #[cfg(feature = "region-au915")]
         State::AU915(state) => state.0.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20A(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20B(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26A(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26B(state) => state.$t($($arg)*),
         #[cfg(feature = "region-eu868")]
         State::EU868(state) => state.$t($($arg)*),
         #[cfg(feature = "region-eu433")]
         State::EU433(state) => state.$t($($arg)*),
         #[cfg(feature = "region-in865")]
         State::IN865(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-kr920")]
+        State::KR920(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-ru864")]
+        State::RU864(state) => state.$t($($arg)*),
         #[cfg(feature = "region-us915")]
         State::US915(state) => state.0.$t($($arg)*),
+        #[cfg(feature = "region-custom")]
+        State::Custom(state) => state.$t($($arg)*),
     }
   };
 }
@@ -272,14 +491,28 @@ macro_rules! region_dispatch {
         State::AS923_4(state) => state.$t(),
         #[cfg(feature = "region-au915")]
         State::AU915(state) => state.0.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20A(state) => state.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20B(state) => state.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26A(state) => state.$t(),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26B(state) => state.$t(),
         #[cfg(feature = "region-eu868")]
         State::EU868(state) => state.$t(),
         #[cfg(feature = "region-eu433")]
         State::EU433(state) => state.$t(),
         #[cfg(feature = "region-in865")]
         State::IN865(state) => state.$t(),
+        #[cfg(feature = "region-kr920")]
+        State::KR920(state) => state.$t(),
+        #[cfg(feature = "region-ru864")]
+        State::RU864(state) => state.$t(),
         #[cfg(feature = "region-us915")]
         State::US915(state) => state.0.$t(),
+        #[cfg(feature = "region-custom")]
+        State::Custom(state) => state.$t(),
     }
   };
   ($s:expr, $t:tt, $($arg:tt)*) => {
@@ -294,63 +527,69 @@ macro_rules! region_dispatch {
         State::AS923_4(state) => state.$t($($arg)*),
         #[cfg(feature = "region-au915")]
         State::AU915(state) => state.0.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20A(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_20B(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26A(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-cn470")]
+        State::CN470_26B(state) => state.$t($($arg)*),
         #[cfg(feature = "region-eu868")]
         State::EU868(state) => state.$t($($arg)*),
         #[cfg(feature = "region-eu433")]
         State::EU433(state) => state.$t($($arg)*),
         #[cfg(feature = "region-in865")]
         State::IN865(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-kr920")]
+        State::KR920(state) => state.$t($($arg)*),
+        #[cfg(feature = "region-ru864")]
+        State::RU864(state) => state.$t($($arg)*),
         #[cfg(feature = "region-us915")]
         State::US915(state) => state.0.$t($($arg)*),
+        #[cfg(feature = "region-custom")]
+        State::Custom(state) => state.$t($($arg)*),
     }
   };
 }
 
-macro_rules! region_static_dispatch {
-  ($s:expr, $t:tt) => {
-      match &$s.state {
-        #[cfg(feature = "region-as923-1")]
-        State::AS923_1(_) => dynamic_channel_plans::AS923_1::$t(),
-        #[cfg(feature = "region-as923-2")]
-        State::AS923_2(_) => dynamic_channel_plans::AS923_2::$t(),
-        #[cfg(feature = "region-as923-3")]
-        State::AS923_3(_) => dynamic_channel_plans::AS923_3::$t(),
-        #[cfg(feature = "region-as923-4")]
-        State::AS923_4(_) => dynamic_channel_plans::AS923_4::$t(),
-        #[cfg(feature = "region-au915")]
-        State::AU915(_) => fixed_channel_plans::AU915::$t(),
-        #[cfg(feature = "region-eu868")]
-        State::EU868(_) => dynamic_channel_plans::EU868::$t(),
-        #[cfg(feature = "region-eu433")]
-        State::EU433(_) => dynamic_channel_plans::EU433::$t(),
-        #[cfg(feature = "region-in865")]
-        State::IN865(_) => dynamic_channel_plans::IN865::$t(),
-        #[cfg(feature = "region-us915")]
-        State::US915(_) => fixed_channel_plans::US915::$t(),
-    }
-  };
-  ($s:expr, $t:tt, $($arg:tt)*) => {
-      match &$s.state {
-        #[cfg(feature = "region-as923-1")]
-        State::AS923_1(_) => dynamic_channel_plans::AS923_1::$t($($arg)*),
-        #[cfg(feature = "region-as923-2")]
-        State::AS923_2(_) => dynamic_channel_plans::AS923_2::$t($($arg)*),
-        #[cfg(feature = "region-as923-3")]
-        State::AS923_3(_) => dynamic_channel_plans::AS923_3::$t($($arg)*),
-        #[cfg(feature = "region-as923-4")]
-        State::AS923_4(_) => dynamic_channel_plans::AS923_4::$t($($arg)*),
-        #[cfg(feature = "region-au915")]
-        State::AU915(_) => fixed_channel_plans::AU915::$t($($arg)*),
-        #[cfg(feature = "region-eu868")]
-        State::EU868(_) => dynamic_channel_plans::EU868::$t($($arg)*),
-        #[cfg(feature = "region-eu433")]
-        State::EU433(_) => dynamic_channel_plans::EU433::$t($($arg)*),
-        #[cfg(feature = "region-in865")]
-        State::IN865(_) => dynamic_channel_plans::IN865::$t($($arg)*),
-        #[cfg(feature = "region-us915")]
-        State::US915(_) => fixed_channel_plans::US915::$t($($arg)*),
+/// Whether `frequency` is usable for an uplink: not found busy by listen-before-talk, and with
+/// `now_ms`, in a sub-band which its duty cycle leaves open.
+fn channel_available(
+    duty_cycle: &DutyCycle,
+    bands: &[Band],
+    busy_channels: &[u32],
+    frequency: u32,
+    now_ms: Option<u64>,
+) -> bool {
+    let open = match (now_ms, band_index(bands, frequency)) {
+        (Some(now_ms), Some(band)) => duty_cycle.band_remaining_ms(band, now_ms) == 0,
+        _ => true,
+    };
+    open && !busy_channels.contains(&frequency)
+}
+
+/// Time on air in milliseconds of an uplink of `len` bytes sent with `tx_config`.
+pub(crate) fn time_on_air_ms(tx_config: &TxConfig, len: usize) -> u64 {
+    let len = len as u64;
+    match tx_config.rf.modulation {
+        Modulation::LoRa => {
+            tx_config.rf.bb.time_on_air_us(Some(8), true, len as u8).div_ceil(1000) as u64
+        }
+        // 5 bytes of preamble, 3 of sync word, the length byte and a 2 byte CRC
+        Modulation::Fsk(fsk) => ((len + 11) * 8 * 1000).div_ceil(fsk.bitrate as u64),
+        // Each header replica lasts 233.472 ms and each fragment of 48 coded bits 102.4 ms. The
+        // payload is followed by a 2 byte CRC and 6 tail bits before coding.
+        Modulation::LrFhss(lr_fhss) => {
+            let bits = (len + 2) * 8 + 6;
+            let coded_bits = match lr_fhss.coding_rate {
+                LrFhssCodingRate::_1_3 => bits * 3,
+                LrFhssCodingRate::_2_3 => (bits * 3).div_ceil(2),
+            };
+            let us = lr_fhss.header_count() as u64 * 233_472 + coded_bits.div_ceil(48) * 102_400;
+            us.div_ceil(1000)
+        }
     }
-  };
 }
 
 impl Configuration {
@@ -358,8 +597,43 @@ impl Configuration {
         Configuration::with_state(State::new(region))
     }
 
+    /// Configuration for a region defined outside of this crate.
+    ///
+    /// # Panics
+    ///
+    /// If the region does not have one to three [default
+    /// channels](CustomRegion::default_channels).
+    #[cfg(feature = "region-custom")]
+    pub fn custom(region: &'static dyn CustomRegion) -> Configuration {
+        Configuration::with_state(State::Custom(Custom::new_custom(region)))
+    }
+
     fn with_state(state: State) -> Configuration {
-        Configuration { state }
+        let mut configuration = Configuration {
+            state,
+            duty_cycle: DutyCycle::default(),
+            listen_before_talk: None,
+            modulations: SupportedModulations::ALL,
+            busy_channels: heapless::Vec::new(),
+            #[cfg(feature = "channel-stats")]
+            channel_stats: Default::default(),
+        };
+        configuration.listen_before_talk = region_dispatch!(configuration, get_listen_before_talk);
+        configuration
+    }
+
+    /// Override the listen-before-talk required by the region, eg: to disable it for AS923
+    /// outside of Japan, or to sense the channel before each uplink in another region.
+    pub fn set_listen_before_talk(&mut self, listen_before_talk: Option<ListenBeforeTalk>) {
+        self.listen_before_talk = listen_before_talk;
+    }
+
+    pub(crate) fn get_listen_before_talk(&self) -> Option<ListenBeforeTalk> {
+        self.listen_before_talk
+    }
+
+    pub(crate) fn set_modulations(&mut self, modulations: SupportedModulations) {
+        self.modulations = modulations;
     }
 
     pub fn get_max_payload_length(
@@ -368,25 +642,115 @@ impl Configuration {
         repeater_compatible: bool,
         dwell_time: bool,
     ) -> u8 {
-        region_static_dispatch!(
-            self,
-            get_max_payload_length,
-            datarate,
-            repeater_compatible,
-            dwell_time
-        )
+        let Some(dr) = self.get_datarate(datarate as u8) else {
+            return 0;
+        };
+        dr.max_payload_length(repeater_compatible, dwell_time)
     }
 
+    /// Select a channel for an uplink and provide its TX configuration. With `now_ms`, channels
+    /// whose sub-band is still closed by its duty cycle are skipped, so the caller must first
+    /// check that [`time_until_next_tx`](Self::time_until_next_tx) is 0.
     pub(crate) fn create_tx_config<RNG: RngCore>(
         &mut self,
         rng: &mut RNG,
         datarate: DR,
         frame: &Frame,
+        tx_params: &TxParams,
+        now_ms: Option<u64>,
+    ) -> (TxConfig, TxChannel) {
+        self.create_repeated_tx_config(rng, datarate, frame, tx_params, now_ms, None)
+    }
+
+    /// Like [`Self::create_tx_config`], for another transmission of an uplink last sent on
+    /// `previous_frequency`, which is only selected again if no other channel is available.
+    pub(crate) fn create_repeated_tx_config<RNG: RngCore>(
+        &mut self,
+        rng: &mut RNG,
+        datarate: DR,
+        frame: &Frame,
+        tx_params: &TxParams,
+        now_ms: Option<u64>,
+        previous_frequency: Option<u32>,
     ) -> (TxConfig, TxChannel) {
-        let tx_channel = self.select_tx_channel(rng, datarate, frame);
-        let tx_config = TxConfig {
+        let bands = self.get_duty_cycle_bands();
+        let (duty_cycle, busy_channels) = (&self.duty_cycle, &self.busy_channels);
+        let available =
+            |frequency| channel_available(duty_cycle, bands, busy_channels, frequency, now_ms);
+        let datarate = self.min_datarate(datarate, tx_params.uplink_dwell_time);
+        // Channels which failed repeatedly are avoided as long as others are available.
+        #[cfg(feature = "channel-stats")]
+        let channel_stats = &self.channel_stats;
+        #[cfg(feature = "channel-stats")]
+        let preferred = |frequency| {
+            available(frequency)
+                && now_ms.is_none_or(|now_ms| !channel_stats.avoided(frequency, now_ms))
+        };
+        #[cfg(not(feature = "channel-stats"))]
+        let preferred = available;
+        let other = |frequency| preferred(frequency) && Some(frequency) != previous_frequency;
+        if previous_frequency.is_some()
+            && region_dispatch!(self, has_tx_channel, datarate, frame, &other)
+        {
+            let tx_channel =
+                mut_region_dispatch!(self, select_tx_channel, rng, datarate, frame, &other);
+            return (self.tx_config(tx_params, &tx_channel), tx_channel);
+        }
+        if region_dispatch!(self, has_tx_channel, datarate, frame, &preferred) {
+            let tx_channel =
+                mut_region_dispatch!(self, select_tx_channel, rng, datarate, frame, &preferred);
+            return (self.tx_config(tx_params, &tx_channel), tx_channel);
+        }
+        let tx_channel =
+            mut_region_dispatch!(self, select_tx_channel, rng, datarate, frame, &available);
+        (self.tx_config(tx_params, &tx_channel), tx_channel)
+    }
+
+    /// Whether [`Self::create_tx_config`] has a channel to select which is neither busy nor closed
+    /// by its duty cycle.
+    pub(crate) fn has_tx_channel(
+        &self,
+        datarate: DR,
+        frame: &Frame,
+        tx_params: &TxParams,
+        now_ms: Option<u64>,
+    ) -> bool {
+        let bands = self.get_duty_cycle_bands();
+        let available = |frequency| {
+            channel_available(&self.duty_cycle, bands, &self.busy_channels, frequency, now_ms)
+        };
+        let datarate = self.min_datarate(datarate, tx_params.uplink_dwell_time);
+        region_dispatch!(self, has_tx_channel, datarate, frame, &available)
+    }
+
+    /// Skip the channel of `tx_config`, found busy by listen-before-talk, until the next uplink,
+    /// and cancel the accounting of the transmission which did not take place. Returns `false`
+    /// once too many channels were found busy.
+    pub(crate) fn channel_busy(
+        &mut self,
+        tx_config: &TxConfig,
+        max_duty_cycle: u8,
+        now_ms: Option<u64>,
+    ) -> bool {
+        let frequency = tx_config.rf.frequency;
+        if now_ms.is_some() {
+            let band = band_index(self.get_duty_cycle_bands(), frequency);
+            self.duty_cycle.unregister(band, max_duty_cycle);
+        }
+        #[cfg(feature = "channel-stats")]
+        self.channel_stats.register_busy(frequency, now_ms);
+        self.busy_channels.push(frequency).is_ok()
+    }
+
+    /// Forget the channels found busy, before a new uplink.
+    pub(crate) fn clear_busy_channels(&mut self) {
+        self.busy_channels.clear();
+    }
+
+    fn tx_config(&self, tx_params: &TxParams, tx_channel: &TxChannel) -> TxConfig {
+        TxConfig {
             // We can do this safely, as default output power will be positive
-            pw: self.check_tx_power(0).unwrap().unwrap() as i8,
+            pw: self.check_tx_power(0, tx_params.max_eirp).unwrap().unwrap() as i8,
             rf: RfConfig {
                 frequency: tx_channel.frequency,
                 bb: BaseBandModulationParams::new(
@@ -394,27 +758,82 @@ impl Configuration {
                     tx_channel.datarate.bandwidth,
                     self.get_coding_rate(),
                 ),
-                max_payload_len: tx_channel.datarate.max_mac_payload_size,
+                modulation: tx_channel.datarate.modulation,
+                max_payload_len: tx_channel.datarate.max_payload_size(tx_params.uplink_dwell_time),
             },
-        };
-        (tx_config, tx_channel)
+        }
     }
 
     pub(crate) fn get_datarate(&self, dr: u8) -> Option<&Datarate> {
         region_dispatch!(self, get_datarate, dr)
     }
 
-    pub(crate) fn check_tx_power(&self, tx_power: u8) -> Option<Option<u8>> {
-        region_dispatch!(self, check_tx_power, tx_power).map(Some)
+    /// Whether `dr` is supported, by the region and the radio, and usable under the dwell time.
+    pub(crate) fn datarate_allowed(&self, dr: DR, dwell_time: bool) -> bool {
+        self.get_datarate(dr as u8).is_some_and(|d| {
+            self.modulations.supports(&d.modulation) && d.max_payload_size(dwell_time) > 0
+        })
     }
 
-    fn select_tx_channel<RNG: RngCore>(
+    /// Data rate at which the next data frame goes out for a device set to `dr`: the lowest one
+    /// usable under the dwell time, or in US915 and AU915 the one of the join requests while the
+    /// join bias lasts.
+    pub(crate) fn data_datarate(&self, dr: DR, dwell_time: bool) -> DR {
+        let dr = self.min_datarate(dr, dwell_time);
+        region_dispatch!(self, data_datarate, dr)
+    }
+
+    /// Lowest data rate from `dr` upwards which is usable under the dwell time.
+    pub(crate) fn min_datarate(&self, dr: DR, dwell_time: bool) -> DR {
+        (dr as u8..NUM_DATARATES)
+            .map(DR::from)
+            .find(|&dr| self.datarate_allowed(dr, dwell_time))
+            .unwrap_or(dr)
+    }
+
+    /// EIRP in dBm of the TXPower index `tx_power`, counted down from `max_eirp` (as set by
+    /// TXParamSetupReq) or from the region's default maximum EIRP.
+    pub(crate) fn check_tx_power(&self, tx_power: u8, max_eirp: Option<u8>) -> Option<Option<u8>> {
+        let eirp = region_dispatch!(self, check_tx_power, tx_power)?;
+        let eirp = match max_eirp {
+            Some(max_eirp) => {
+                let default_max_eirp = region_dispatch!(self, check_tx_power, 0)?;
+                (eirp + max_eirp).saturating_sub(default_max_eirp)
+            }
+            None => eirp,
+        };
+        Some(Some(eirp))
+    }
+
+    pub(crate) fn supports_tx_param_setup(&self) -> bool {
+        region_dispatch!(self, supports_tx_param_setup)
+    }
+
+    /// Time until an uplink of type `frame` is allowed by the duty cycle on at least one channel.
+    pub(crate) fn time_until_next_tx(&self, frame: &Frame, now_ms: u64) -> u64 {
+        let bands = self.get_duty_cycle_bands();
+        let wait_ms = |frequency| {
+            band_index(bands, frequency).map_or(0, |i| self.duty_cycle.band_remaining_ms(i, now_ms))
+        };
+        let channel_wait_ms = region_dispatch!(self, tx_wait_ms, frame, &wait_ms);
+        channel_wait_ms.max(self.duty_cycle.aggregated_remaining_ms(now_ms))
+    }
+
+    /// Account for the airtime of an uplink of `len` bytes started at `now_ms`.
+    pub(crate) fn register_tx(
         &mut self,
-        rng: &mut RNG,
-        datarate: DR,
-        frame: &Frame,
-    ) -> TxChannel {
-        mut_region_dispatch!(self, select_tx_channel, rng, datarate, frame)
+        tx_config: &TxConfig,
+        len: usize,
+        max_duty_cycle: u8,
+        now_ms: u64,
+    ) {
+        let bands = self.get_duty_cycle_bands();
+        let band = band_index(bands, tx_config.rf.frequency).map(|i| (i, &bands[i]));
+        self.duty_cycle.register(band, max_duty_cycle, time_on_air_ms(tx_config, len), now_ms);
+    }
+
+    fn get_duty_cycle_bands(&self) -> &'static [Band] {
+        region_dispatch!(self, get_duty_cycle_bands)
     }
 
     pub(crate) fn process_join_accept(&mut self, c_f_list: Option<&CfList>) {
@@ -467,7 +886,7 @@ impl Configuration {
     }
 
     #[allow(dead_code)]
-    pub(crate) fn get_current_region(&self) -> super::region::Region {
+    pub(crate) fn get_current_region(&self) -> Option<Region> {
         self.state.region()
     }
 
@@ -491,8 +910,55 @@ impl Configuration {
     pub(crate) fn rx1_dr_offset_validate(&self, value: u8) -> Option<u8> {
         region_dispatch!(self, rx1_dr_offset_validate, value)
     }
+
+    /// Write the channel mask and the channels set by the network to a state snapshot.
+    pub(crate) fn write_state(&self, w: &mut Writer) {
+        w.bytes(self.channel_mask_get().as_ref());
+        region_dispatch!(self, write_channels, w)
+    }
+
+    pub(crate) fn read_state(&mut self, r: &mut Reader<'_>) -> Result<(), StateError> {
+        let channel_mask = ChannelMask::new_from_raw(&r.array::<9>()?);
+        mut_region_dispatch!(self, read_channels, r)?;
+        self.channel_mask_set(channel_mask);
+        Ok(())
+    }
+
+    #[cfg(feature = "class-b")]
+    pub(crate) fn get_beacon_layout(&self) -> BeaconLayout {
+        region_dispatch!(self, get_beacon_layout)
+    }
+
+    #[cfg(feature = "class-b")]
+    pub(crate) fn get_beacon_datarate(&self) -> DR {
+        region_dispatch!(self, get_beacon_datarate)
+    }
+
+    #[cfg(feature = "class-b")]
+    pub(crate) fn get_beacon_frequency(&self, beacon_time: u32) -> u32 {
+        region_dispatch!(self, get_beacon_frequency, beacon_time)
+    }
+
+    #[cfg(feature = "class-b")]
+    pub(crate) fn get_ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32 {
+        region_dispatch!(self, get_ping_slot_frequency, dev_addr, beacon_time)
+    }
 }
 
+#[cfg(any(
+    feature = "region-as923-1",
+    feature = "region-as923-2",
+    feature = "region-as923-3",
+    feature = "region-as923-4",
+    feature = "region-eu433",
+    feature = "region-eu868",
+    feature = "region-in865",
+    feature = "region-kr920",
+    feature = "region-ru864",
+    feature = "region-au915",
+    feature = "region-cn470",
+    feature = "region-us915"
+))]
 macro_rules! from_region {
     ($r:tt) => {
         impl From<$r> for Configuration {
@@ -513,8 +979,20 @@ from_region!(AS923_3);
 from_region!(AS923_4);
 #[cfg(feature = "region-in865")]
 from_region!(IN865);
+#[cfg(feature = "region-kr920")]
+from_region!(KR920);
+#[cfg(feature = "region-ru864")]
+from_region!(RU864);
 #[cfg(feature = "region-au915")]
 from_region!(AU915);
+#[cfg(feature = "region-cn470")]
+from_region!(CN470_20A);
+#[cfg(feature = "region-cn470")]
+from_region!(CN470_20B);
+#[cfg(feature = "region-cn470")]
+from_region!(CN470_26A);
+#[cfg(feature = "region-cn470")]
+from_region!(CN470_26B);
 #[cfg(feature = "region-eu868")]
 from_region!(EU868);
 #[cfg(feature = "region-eu433")]
@@ -553,13 +1031,31 @@ pub(crate) trait RegionHandler {
         DR::_0
     }
 
+    /// Data rate of the data frames of a device set to `datarate`.
+    fn data_datarate(&self, datarate: DR) -> DR {
+        datarate
+    }
+
+    /// Select a channel for which `available` holds.
     fn select_tx_channel<RNG: RngCore>(
         &mut self,
         rng: &mut RNG,
         datarate: DR,
         frame: &Frame,
+        available: &impl Fn(u32) -> bool,
     ) -> TxChannel;
 
+    /// Whether [`Self::select_tx_channel`] may select a channel for which `available` holds.
+    fn has_tx_channel(&self, datarate: DR, frame: &Frame, available: &impl Fn(u32) -> bool)
+    -> bool;
+
+    /// Smallest `wait_ms` among the channels usable for `frame`.
+    fn tx_wait_ms(&self, frame: &Frame, wait_ms: &impl Fn(u32) -> u64) -> u64;
+
+    fn get_duty_cycle_bands(&self) -> &'static [Band];
+
+    fn get_listen_before_talk(&self) -> Option<ListenBeforeTalk>;
+
     fn get_rx_datarate(&self, datarate: DR, rx1_dr_offset: u8, window: &Window) -> DR;
     fn get_rx2_frequency(&self) -> u32;
     fn get_coding_rate(&self) -> CodingRate {
@@ -568,6 +1064,8 @@ pub(crate) trait RegionHandler {
 
     fn check_tx_power(&self, tx_power: u8) -> Option<u8>;
 
+    fn supports_tx_param_setup(&self) -> bool;
+
     fn frequency_valid(&self, freq: u32) -> bool;
 
     /// Whether region supports modifying channel plan
@@ -575,6 +1073,28 @@ pub(crate) trait RegionHandler {
     fn has_fixed_channel_plan(&self) -> bool;
 
     fn rx1_dr_offset_validate(&self, value: u8) -> Option<u8>;
+
+    /// Write the channels of a dynamic channel plan to a state snapshot, as a bitmap of the
+    /// channels which are set followed by their frequencies and data rates.
+    fn write_channels(&self, w: &mut Writer) {
+        w.u16(0);
+    }
+
+    fn read_channels(&mut self, r: &mut Reader<'_>) -> Result<(), StateError> {
+        match r.u16()? {
+            0 => Ok(()),
+            _ => Err(StateError::Invalid),
+        }
+    }
+
+    #[cfg(feature = "class-b")]
+    fn get_beacon_layout(&self) -> BeaconLayout;
+    #[cfg(feature = "class-b")]
+    fn get_beacon_datarate(&self) -> DR;
+    #[cfg(feature = "class-b")]
+    fn get_beacon_frequency(&self, beacon_time: u32) -> u32;
+    #[cfg(feature = "class-b")]
+    fn get_ping_slot_frequency(&self, dev_addr: u32, beacon_time: u32) -> u32;
 }
 
 #[cfg(test)]
@@ -632,6 +1152,85 @@ mod tests {
         assert!(!r.frequency_valid(872_000_000));
     }
 
+    #[test]
+    #[cfg(feature = "region-as923-1")]
+    fn test_as923_tx_params() {
+        let mut r = Configuration::new(Region::AS923_1);
+        let mut rng = rand::rngs::OsRng;
+        let tx_params =
+            TxParams { uplink_dwell_time: true, downlink_dwell_time: true, max_eirp: Some(14) };
+
+        assert!(r.datarate_allowed(DR::_0, false));
+        assert!(!r.datarate_allowed(DR::_0, true));
+        assert_eq!(r.min_datarate(DR::_0, true), DR::_2);
+        assert_eq!(r.min_datarate(DR::_3, true), DR::_3);
+
+        // TXPower indices count down from MaxEIRP
+        assert_eq!(r.check_tx_power(0, None), Some(Some(16)));
+        assert_eq!(r.check_tx_power(1, Some(14)), Some(Some(12)));
+        assert_eq!(r.check_tx_power(7, Some(8)), Some(Some(0)));
+
+        let (tx_config, tx_channel) =
+            r.create_tx_config(&mut rng, DR::_0, &Frame::Data, &tx_params, None);
+        assert_eq!(tx_channel.dr, DR::_2);
+        assert_eq!(tx_config.pw, 14);
+        assert_eq!(tx_config.rf.max_payload_len, 19);
+    }
+
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn test_eu868_duty_cycle_skips_closed_band() {
+        let mut r = Configuration::new(Region::EU868);
+        // Add a channel in the 10% sub-band next to the default 1% one
+        r.handle_new_channel(3, 869_525_000, Some(DataRateRange::new_range(DR::_0, DR::_5)));
+        let mut rng = rand::rngs::OsRng;
+
+        let (tx_config, _) =
+            r.create_tx_config(&mut rng, DR::_5, &Frame::Join, &TxParams::default(), Some(0));
+        assert!((868_000_000..868_600_000).contains(&tx_config.rf.frequency));
+        r.register_tx(&tx_config, 20, 0, 0);
+        assert!(r.time_until_next_tx(&Frame::Join, 0) > 0);
+        assert_eq!(r.time_until_next_tx(&Frame::Data, 0), 0);
+        for _ in 0..10 {
+            let (tx_config, _) =
+                r.create_tx_config(&mut rng, DR::_5, &Frame::Data, &TxParams::default(), Some(0));
+            assert_eq!(tx_config.rf.frequency, 869_525_000);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn test_eu868_repetition_changes_channel() {
+        let mut r = Configuration::new(Region::EU868);
+        let mut rng = rand::rngs::OsRng;
+        let tx_params = TxParams::default();
+        for _ in 0..10 {
+            let (tx_config, _) =
+                r.create_tx_config(&mut rng, DR::_5, &Frame::Data, &tx_params, None);
+            let previous = Some(tx_config.rf.frequency);
+            let (repeated, _) = r.create_repeated_tx_config(
+                &mut rng,
+                DR::_5,
+                &Frame::Data,
+                &tx_params,
+                None,
+                previous,
+            );
+            assert_ne!(Some(repeated.rf.frequency), previous);
+        }
+        // The previous channel is used again when it is the only one enabled
+        r.channel_mask_set(ChannelMask::new(&[0x01, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap());
+        let (repeated, _) = r.create_repeated_tx_config(
+            &mut rng,
+            DR::_5,
+            &Frame::Data,
+            &tx_params,
+            None,
+            Some(868_100_000),
+        );
+        assert_eq!(repeated.rf.frequency, 868_100_000);
+    }
+
     #[test]
     #[cfg(feature = "region-eu868")]
     fn test_rx1_dr_offset_eu868() {
@@ -644,6 +1243,49 @@ mod tests {
         assert_eq!(r.get_rx_datarate(DR::_12, 0, &Window::_1), DR::_0);
     }
 
+    #[test]
+    #[cfg(feature = "region-eu868")]
+    fn test_eu868_high_datarates() {
+        let mut r = Configuration::new(Region::EU868);
+        let mut rng = rand::rngs::OsRng;
+        let mut tx_config =
+            |dr| r.create_tx_config(&mut rng, dr, &Frame::Data, &TxParams::default(), None).0;
+
+        let dr6 = tx_config(DR::_6);
+        assert_eq!(dr6.rf.modulation, Modulation::LoRa);
+        assert_eq!(dr6.rf.bb.bw, Bandwidth::_250KHz);
+        assert_eq!(dr6.rf.bb.sf, SpreadingFactor::_7);
+
+        let dr7 = tx_config(DR::_7);
+        assert_eq!(
+            dr7.rf.modulation,
+            Modulation::Fsk(FskParams { bitrate: 50_000, frequency_deviation: 25_000 })
+        );
+        assert_eq!(time_on_air_ms(&dr7, 23), 6);
+
+        // LR-FHSS: 3 headers and 13 fragments at CR1/3, 2 headers and 7 fragments at CR2/3
+        for (dr, coding_rate, bandwidth, max_payload_len, toa_ms) in [
+            (DR::_8, LrFhssCodingRate::_1_3, LrFhssBandwidth::_137KHz, 58, 2032),
+            (DR::_9, LrFhssCodingRate::_2_3, LrFhssBandwidth::_137KHz, 123, 1184),
+            (DR::_10, LrFhssCodingRate::_1_3, LrFhssBandwidth::_336KHz, 58, 2032),
+            (DR::_11, LrFhssCodingRate::_2_3, LrFhssBandwidth::_336KHz, 123, 1184),
+        ] {
+            let tx_config = tx_config(dr);
+            assert_eq!(
+                tx_config.rf.modulation,
+                Modulation::LrFhss(LrFhssParams {
+                    coding_rate,
+                    bandwidth,
+                    grid: LrFhssGrid::_3_9KHz
+                })
+            );
+            assert_eq!(tx_config.rf.max_payload_len, max_payload_len);
+            assert_eq!(time_on_air_ms(&tx_config, 23), toa_ms);
+        }
+        assert!(r.datarate_allowed(DR::_11, false));
+        assert!(!r.datarate_allowed(DR::_12, false));
+    }
+
     #[test]
     #[cfg(feature = "region-in865")]
     fn test_rx1_dr_offset_in865() {
@@ -671,6 +1313,199 @@ mod tests {
         assert_eq!(r.get_rx_datarate(DR::_7, 7, &Window::_1), DR::_7);
     }
 
+    #[test]
+    #[cfg(feature = "region-kr920")]
+    fn test_rx1_dr_offset_kr920() {
+        let r = Configuration::new(Region::KR920);
+        assert_eq!(r.get_rx_datarate(DR::_0, 5, &Window::_1), DR::_0);
+        assert_eq!(r.get_rx_datarate(DR::_3, 1, &Window::_1), DR::_2);
+        assert_eq!(r.get_rx_datarate(DR::_5, 0, &Window::_1), DR::_5);
+        assert_eq!(r.get_rx_datarate(DR::_5, 5, &Window::_1), DR::_0);
+        // No DR6 and above in this region
+        assert_eq!(r.get_rx_datarate(DR::_6, 0, &Window::_1), DR::_0);
+        assert_eq!(r.get_rx_datarate(DR::_5, 0, &Window::_2), DR::_0);
+        assert_eq!(r.rx1_dr_offset_validate(5), Some(5));
+        assert_eq!(r.rx1_dr_offset_validate(6), None);
+    }
+
+    #[test]
+    #[cfg(feature = "region-kr920")]
+    fn test_kr920_channel_plan() {
+        let mut r = Configuration::new(Region::KR920);
+        let mut rng = rand::rngs::OsRng;
+        assert_eq!(r.get_rx2_frequency(), 921_900_000);
+        assert!(r.frequency_valid(920_900_000));
+        assert!(r.frequency_valid(923_300_000));
+        assert!(!r.frequency_valid(923_300_001));
+        assert_eq!(
+            r.get_listen_before_talk(),
+            Some(ListenBeforeTalk { threshold_dbm: -65, duration_us: 5_000 })
+        );
+        // TXPower indices count down from MaxEIRP
+        assert_eq!(r.check_tx_power(0, None), Some(Some(14)));
+        assert_eq!(r.check_tx_power(7, None), Some(Some(0)));
+        assert_eq!(r.check_tx_power(8, None), None);
+
+        assert_eq!(r.get_max_payload_length(DR::_2, false, false), 59);
+        assert_eq!(r.get_max_payload_length(DR::_5, true, false), 230);
+        for _ in 0..10 {
+            let (tx_config, _) =
+                r.create_tx_config(&mut rng, DR::_0, &Frame::Join, &TxParams::default(), None);
+            assert!([922_100_000, 922_300_000, 922_500_000].contains(&tx_config.rf.frequency));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "region-ru864")]
+    fn test_rx1_dr_offset_ru864() {
+        let r = Configuration::new(Region::RU864);
+        assert_eq!(r.get_rx_datarate(DR::_5, 0, &Window::_1), DR::_5);
+        assert_eq!(r.get_rx_datarate(DR::_5, 5, &Window::_1), DR::_0);
+        assert_eq!(r.get_rx_datarate(DR::_2, 3, &Window::_1), DR::_0);
+        assert_eq!(r.get_rx_datarate(DR::_5, 0, &Window::_2), DR::_0);
+        // Invalid DR should return DR::_0
+        assert_eq!(r.get_rx_datarate(DR::_12, 0, &Window::_1), DR::_0);
+    }
+
+    #[test]
+    #[cfg(feature = "region-ru864")]
+    fn test_ru864_channel_plan() {
+        let mut r = Configuration::new(Region::RU864);
+        let mut rng = rand::rngs::OsRng;
+        assert_eq!(r.get_rx2_frequency(), 869_100_000);
+        assert!(r.frequency_valid(864_000_000));
+        assert!(r.frequency_valid(870_000_000));
+        assert!(!r.frequency_valid(863_900_000));
+        // TXPower indices count down from MaxEIRP
+        assert_eq!(r.check_tx_power(0, None), Some(Some(16)));
+        assert_eq!(r.check_tx_power(7, None), Some(Some(2)));
+        assert_eq!(r.check_tx_power(8, None), None);
+        assert_eq!(r.get_listen_before_talk(), None);
+
+        let (tx_config, _) =
+            r.create_tx_config(&mut rng, DR::_0, &Frame::Join, &TxParams::default(), Some(0));
+        let frequency = tx_config.rf.frequency;
+        assert!([868_900_000, 869_100_000].contains(&frequency));
+        // Both default channels share the 868.7-869.2 MHz sub-band and its 1% duty cycle
+        r.register_tx(&tx_config, 20, 0, 0);
+        let wait_ms = r.time_until_next_tx(&Frame::Data, 0);
+        assert!(wait_ms > 0);
+        assert_eq!(r.time_until_next_tx(&Frame::Join, 0), wait_ms);
+        assert_eq!(r.time_until_next_tx(&Frame::Data, wait_ms), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "region-cn470")]
+    fn test_rx1_dr_offset_cn470() {
+        let r = Configuration::new(Region::CN470_20A);
+        assert_eq!(r.get_default_datarate(), DR::_1);
+        assert_eq!(r.get_rx_datarate(DR::_5, 0, &Window::_1), DR::_5);
+        assert_eq!(r.get_rx_datarate(DR::_5, 3, &Window::_1), DR::_2);
+        assert_eq!(r.get_rx_datarate(DR::_5, 5, &Window::_1), DR::_1);
+        assert_eq!(r.get_rx_datarate(DR::_1, 1, &Window::_1), DR::_1);
+        assert_eq!(r.get_rx_datarate(DR::_5, 0, &Window::_2), DR::_1);
+        // Invalid DR should return DR::_1
+        assert_eq!(r.get_rx_datarate(DR::_12, 0, &Window::_1), DR::_1);
+        assert!(r.rx1_dr_offset_validate(5).is_some());
+        assert!(r.rx1_dr_offset_validate(6).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "region-cn470")]
+    fn test_cn470_channel_plans() {
+        let mut rng = rand::rngs::OsRng;
+        // (plan, RX2 frequency, join channel frequencies, uplink channel with its RX1 frequency)
+        let plans = [
+            (
+                Region::CN470_20A,
+                485_300_000,
+                &[
+                    470_900_000,
+                    472_500_000,
+                    474_100_000,
+                    475_700_000,
+                    504_100_000,
+                    505_700_000,
+                    507_300_000,
+                    508_900_000,
+                ][..],
+                (504_100_000, 490_900_000),
+            ),
+            (
+                Region::CN470_20B,
+                486_900_000,
+                &[479_900_000, 499_900_000][..],
+                (499_900_000, 499_900_000),
+            ),
+            (
+                Region::CN470_26A,
+                492_500_000,
+                &[470_300_000, 472_300_000, 474_300_000, 476_300_000, 478_300_000][..],
+                (478_300_000, 493_300_000),
+            ),
+            (
+                Region::CN470_26B,
+                502_500_000,
+                &[480_300_000, 482_300_000, 484_300_000, 486_300_000, 488_300_000][..],
+                (488_300_000, 503_300_000),
+            ),
+        ];
+        for (region, rx2_frequency, join_frequencies, (uplink, rx1)) in plans {
+            let mut r = Configuration::new(region);
+            assert_eq!(r.get_rx2_frequency(), rx2_frequency);
+            assert!(r.frequency_valid(470_000_000));
+            assert!(!r.frequency_valid(510_000_001));
+            assert_eq!(r.check_tx_power(0, None), Some(Some(19)));
+            assert_eq!(r.check_tx_power(8, None), None);
+            let mut seen = [false; 8];
+            for _ in 0..100 {
+                let (tx_config, tx_channel) =
+                    r.create_tx_config(&mut rng, DR::_2, &Frame::Join, &TxParams::default(), None);
+                let index = join_frequencies.iter().position(|&f| f == tx_config.rf.frequency);
+                seen[index.unwrap()] = true;
+                assert_eq!(tx_channel.dr, DR::_2);
+                if tx_config.rf.frequency == uplink {
+                    assert_eq!(tx_channel.rx1_frequency, rx1);
+                }
+            }
+            assert!(seen[..join_frequencies.len()].iter().all(|&seen| seen));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "region-cn470")]
+    fn test_cn470_channel_mask_banks() {
+        let mut r = Configuration::new(Region::CN470_26A);
+        let mut rng = rand::rngs::OsRng;
+        let mut mask = ChannelMask::<9>::default();
+        // Only channel 33 left in the 48 channels of the 26 MHz plans
+        for ch_mask_ctl in 0..=2 {
+            let bits = if ch_mask_ctl == 2 {
+                [0x02, 0x00]
+            } else {
+                [0x00, 0x00]
+            };
+            r.channel_mask_update(&mut mask, ch_mask_ctl, ChannelMask::<2>::new(&bits).unwrap())
+                .unwrap();
+        }
+        // Bank 3 is beyond the channels of the plan, 7 is RFU
+        let all = ChannelMask::<2>::new(&[0xFF, 0xFF]).unwrap();
+        assert!(r.channel_mask_update(&mut mask.clone(), 3, all.clone()).is_none());
+        assert!(r.channel_mask_update(&mut mask.clone(), 7, all.clone()).is_none());
+        assert!(r.channel_mask_validate(&mask, Some(DR::_3)));
+        assert!(!r.channel_mask_validate(&mask, Some(DR::_0)));
+        r.channel_mask_set(mask.clone());
+        for _ in 0..10 {
+            let (tx_config, tx_channel) =
+                r.create_tx_config(&mut rng, DR::_3, &Frame::Data, &TxParams::default(), None);
+            assert_eq!(tx_config.rf.frequency, 476_900_000);
+            assert_eq!(tx_channel.rx1_frequency, 491_900_000);
+        }
+        // ChMaskCntl 6 enables all channels
+        r.channel_mask_update(&mut mask, 6, ChannelMask::<2>::new(&[0x00, 0x00]).unwrap()).unwrap();
+        assert!((0..48).all(|ch| mask.is_enabled(ch).unwrap()));
+    }
+
     #[test]
     #[cfg(feature = "region-au915")]
     fn test_fixed_au915_frequency_range() {
@@ -770,4 +1605,140 @@ mod tests {
         // Invalid DR should return DR::_8
         assert_eq!(r.get_rx_datarate(DR::_12, 0, &Window::_1), DR::_8);
     }
+
+    #[test]
+    #[cfg(feature = "region-us915")]
+    fn test_us915_lr_fhss_datarates() {
+        let mut r = Configuration::new(Region::US915);
+        let mut rng = rand::rngs::OsRng;
+        for (dr, coding_rate, max_payload_len) in
+            [(DR::_5, LrFhssCodingRate::_1_3, 58), (DR::_6, LrFhssCodingRate::_2_3, 133)]
+        {
+            let params = LrFhssParams {
+                coding_rate,
+                bandwidth: LrFhssBandwidth::_1523KHz,
+                grid: LrFhssGrid::_25_4KHz,
+            };
+            for _ in 0..10 {
+                let (tx_config, tx_channel) =
+                    r.create_tx_config(&mut rng, dr, &Frame::Data, &TxParams::default(), None);
+                assert_eq!(tx_config.rf.modulation, Modulation::LrFhss(params));
+                assert_eq!(tx_config.rf.max_payload_len, max_payload_len);
+                // The operating channels are centered on the 500 kHz channels
+                assert!((64..72).contains(&tx_channel.channel));
+                assert_eq!((tx_config.rf.frequency - 903_000_000) % 1_600_000, 0);
+            }
+        }
+
+        // LR-FHSS needs one of the operating channels
+        let mut mask = ChannelMask::<9>::default();
+        r.channel_mask_update(&mut mask, 6, ChannelMask::<2>::new(&[0x00, 0x00]).unwrap()).unwrap();
+        assert!(r.channel_mask_validate(&mask, Some(DR::_0)));
+        assert!(!r.channel_mask_validate(&mask, Some(DR::_5)));
+        r.channel_mask_update(&mut mask, 7, ChannelMask::<2>::new(&[0x01, 0x00]).unwrap()).unwrap();
+        assert!(r.channel_mask_validate(&mask, Some(DR::_6)));
+    }
+
+    #[test]
+    #[cfg(feature = "region-au915")]
+    fn test_au915_lr_fhss_datarate() {
+        let mut r = Configuration::new(Region::AU915);
+        let mut rng = rand::rngs::OsRng;
+        let (tx_config, tx_channel) =
+            r.create_tx_config(&mut rng, DR::_7, &Frame::Data, &TxParams::default(), None);
+        assert_eq!(
+            tx_config.rf.modulation,
+            Modulation::LrFhss(LrFhssParams {
+                coding_rate: LrFhssCodingRate::_1_3,
+                bandwidth: LrFhssBandwidth::_1523KHz,
+                grid: LrFhssGrid::_25_4KHz,
+            })
+        );
+        assert_eq!(tx_config.rf.max_payload_len, 58);
+        assert!((64..72).contains(&tx_channel.channel));
+    }
+
+    #[cfg(feature = "region-custom")]
+    struct Private916;
+
+    #[cfg(feature = "region-custom")]
+    static PRIVATE916_DATARATES: [Option<Datarate>; NUM_DATARATES as usize] = {
+        let mut datarates = [const { None }; NUM_DATARATES as usize];
+        datarates[0] = Some(Datarate::new(SpreadingFactor::_10, Bandwidth::_125KHz, 11));
+        datarates[1] = Some(Datarate::new(SpreadingFactor::_9, Bandwidth::_125KHz, 53));
+        datarates[2] =
+            Some(Datarate::new(SpreadingFactor::_7, Bandwidth::_125KHz, 242).with_dwell_time(0));
+        datarates
+    };
+
+    #[cfg(feature = "region-custom")]
+    impl CustomRegion for Private916 {
+        fn datarates(&self) -> &'static [Option<Datarate>; NUM_DATARATES as usize] {
+            &PRIVATE916_DATARATES
+        }
+
+        fn default_channels(&self) -> &'static [u32] {
+            &[916_100_000, 916_300_000]
+        }
+
+        fn frequency_valid(&self, frequency: u32) -> bool {
+            (916_000_000..=917_000_000).contains(&frequency)
+        }
+
+        fn tx_power(&self, tx_power: u8) -> Option<u8> {
+            (tx_power <= 5).then(|| 20 - 2 * tx_power)
+        }
+
+        fn rx1_datarate(&self, tx_dr: DR, rx1_dr_offset: u8) -> DR {
+            tx_dr.offset_sub(rx1_dr_offset)
+        }
+
+        fn max_rx1_dr_offset(&self) -> u8 {
+            1
+        }
+
+        fn rx2_frequency(&self) -> u32 {
+            916_900_000
+        }
+
+        fn rx2_datarate(&self) -> DR {
+            DR::_1
+        }
+    }
+
+    #[cfg(feature = "region-custom")]
+    #[test]
+    fn test_custom_region() {
+        let mut r = Configuration::custom(&Private916);
+        let mut rng = rand::rngs::OsRng;
+        assert_eq!(r.get_current_region(), None);
+        assert!(!r.has_fixed_channel_plan());
+        assert_eq!(r.get_rx2_frequency(), 916_900_000);
+        assert_eq!(r.get_rx_datarate(DR::_2, 1, &Window::_1), DR::_1);
+        assert_eq!(r.get_rx_datarate(DR::_2, 1, &Window::_2), DR::_1);
+        assert_eq!(r.rx1_dr_offset_validate(1), Some(1));
+        assert_eq!(r.rx1_dr_offset_validate(2), None);
+        assert_eq!(r.check_tx_power(0, None), Some(Some(20)));
+        assert_eq!(r.check_tx_power(6, None), None);
+        assert_eq!(r.get_max_payload_length(DR::_2, true, false), 230);
+        assert_eq!(r.get_max_payload_length(DR::_3, false, false), 0);
+        assert!(!r.datarate_allowed(DR::_2, true));
+
+        for _ in 0..10 {
+            let (tx_config, tx_channel) =
+                r.create_tx_config(&mut rng, DR::_0, &Frame::Join, &TxParams::default(), None);
+            assert!([916_100_000, 916_300_000].contains(&tx_config.rf.frequency));
+            assert_eq!(tx_config.rf.bb.sf, SpreadingFactor::_10);
+            assert!(tx_channel.channel < 2);
+        }
+
+        // Channels may only be set within the frequency range of the region
+        let dr = Some(DataRateRange::new_range(DR::_0, DR::_2));
+        assert_eq!(r.handle_new_channel(2, 916_500_000, dr), (true, true));
+        assert_eq!(r.handle_new_channel(3, 918_000_000, dr), (false, true));
+        assert_eq!(r.handle_new_channel(3, 916_700_000, dr), (true, true));
+        // DR3 is not defined
+        let dr = Some(DataRateRange::new_range(DR::_0, DR::_3));
+        assert_eq!(r.handle_new_channel(4, 916_700_000, dr), (true, false));
+    }
 }
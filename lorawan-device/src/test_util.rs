@@ -2,12 +2,13 @@ use super::*;
 use core::num::NonZeroU8;
 use lorawan::creator::{DataFrame, JoinAccept, Payload};
 use lorawan::default_crypto::{DefaultCrypto, DefaultNetworkCrypto};
+use lorawan::keys::{JSEncKey, JSIntKey};
 use lorawan::maccommandcreator::LinkADRReqCreator;
 use lorawan::maccommands::UplinkMacCommand;
 use lorawan::maccommands::parse_uplink_mac_commands;
 use lorawan::parser::{
     self, DataFrameType, DecryptedDataPayload, DecryptedJoinAcceptPayload, DevAddr, JoinNonce,
-    NetId, PhyPayload,
+    JoinReqType, NetId, PhyPayload,
 };
 use lorawan::types::{ChannelMask, DLSettings};
 use mac::Session;
@@ -26,7 +27,6 @@ use std::{
 #[derive(Debug, Clone)]
 pub struct Uplink {
     data: Vec<u8>,
-    #[allow(unused)]
     tx_config: TxConfig,
 }
 
@@ -43,6 +43,56 @@ impl Uplink {
     pub fn data_mut(&mut self) -> &mut [u8] {
         &mut self.data
     }
+
+    pub fn tx_config(&self) -> &TxConfig {
+        &self.tx_config
+    }
+
+    /// FCnt of a data uplink, as sent on the wire.
+    pub fn fcnt(&self) -> u16 {
+        match parser::parse(&self.data) {
+            Ok(PhyPayload::Data(data)) => data.fhdr().fcnt(),
+            _ => panic!("Did not decode PhyPayload::Data!"),
+        }
+    }
+
+    /// FPort of a data uplink, `None` when it has no FRMPayload.
+    pub fn f_port(&self) -> Option<u8> {
+        match parser::parse(&self.data) {
+            Ok(PhyPayload::Data(data)) => data.f_port(),
+            _ => panic!("Did not decode PhyPayload::Data!"),
+        }
+    }
+
+    /// FOpts of a LoRaWAN 1.0 data uplink.
+    pub fn f_opts(&self) -> Vec<u8> {
+        match parser::parse(&self.data) {
+            Ok(PhyPayload::Data(data)) => data.fhdr().f_opts().to_vec(),
+            _ => panic!("Did not decode PhyPayload::Data!"),
+        }
+    }
+
+    /// MAC commands in the FRMPayload of a LoRaWAN 1.0 data uplink on FPort 0.
+    pub fn port_0_mac_commands(&self) -> Vec<u8> {
+        let mut data = self.data.clone();
+        let fcnt = self.fcnt().into();
+        let crypto = get_crypto();
+        match DecryptedDataPayload::decrypt_in_place(&mut data, Some(&crypto), None, fcnt)
+            .unwrap()
+            .frm_payload()
+        {
+            parser::FrmPayload::MacCommands(cmds) => cmds.to_vec(),
+            _ => panic!("Did not decode MAC commands!"),
+        }
+    }
+
+    /// DevNonce of a join request.
+    pub fn dev_nonce(&self) -> u16 {
+        match parser::parse(&self.data) {
+            Ok(PhyPayload::JoinRequest(join_request)) => join_request.dev_nonce().value(),
+            _ => panic!("Did not decode PhyPayload::JoinRequest!"),
+        }
+    }
 }
 
 /// Test functions shared by async_device and no_async_device tests
@@ -71,6 +121,20 @@ pub fn get_otaa_credentials() -> JoinMode {
     }
 }
 
+/// NwkKey of the LoRaWAN 1.1 test device; the AppKey is [`get_key`].
+pub fn get_nwk_key() -> [u8; 16] {
+    [2; 16]
+}
+
+pub fn get_otaa_1_1_credentials() -> JoinMode {
+    JoinMode::OTAA1_1 {
+        deveui: DevEui::from([0; 8]),
+        joineui: AppEui::from([0; 8]),
+        nwkkey: NwkKey::from(get_nwk_key()),
+        appkey: AppKey::from(get_key()),
+    }
+}
+
 pub fn get_abp_credentials() -> JoinMode {
     JoinMode::ABP {
         devaddr: get_dev_addr(),
@@ -142,6 +206,82 @@ pub fn handle_join_request_with_dl_settings<const I: usize, const DL_SETTINGS: u
     }
 }
 
+/// Handle join request from a LoRaWAN 1.1 device and respond with a JoinAccept which has OptNeg
+/// set and carries the given JoinNonce.
+pub fn handle_join_request_1_1<const JOIN_NONCE: u32>(
+    uplink: Option<Uplink>,
+    _config: RfConfig,
+    rx_buffer: &mut [u8],
+) -> usize {
+    if let Some(mut uplink) = uplink {
+        if let Ok(PhyPayload::JoinRequest(join_request)) = parser::parse(uplink.data_mut()) {
+            let nwk_crypto = DefaultCrypto::new(&get_nwk_key().into());
+            assert!(join_request.validate_mic(&nwk_crypto));
+            let js_int_key = JSIntKey::derive_from(&nwk_crypto, &join_request.dev_eui());
+            let accept = JoinAccept {
+                join_nonce: JoinNonce::from_value(JOIN_NONCE),
+                net_id: NetId::from_wire_bytes([1; 3]),
+                dev_addr: get_dev_addr(),
+                dl_settings: DLSettings::new(0x80),
+                rx_delay: 0,
+                c_f_list: None,
+            };
+            accept
+                .build_into_1_1(
+                    rx_buffer,
+                    &DefaultNetworkCrypto::new(&get_nwk_key().into()),
+                    &DefaultCrypto::new(js_int_key.inner()),
+                    JoinReqType::JoinRequest,
+                    &join_request.join_eui(),
+                    &join_request.dev_nonce(),
+                )
+                .unwrap()
+                .len()
+        } else {
+            panic!("Did not parse join request from uplink");
+        }
+    } else {
+        panic!("No uplink passed to handle_join_request_1_1");
+    }
+}
+
+/// Answer a type 1 rejoin request of the LoRaWAN 1.1 test device with a JoinAccept.
+pub fn handle_rejoin_request_1_1<const JOIN_NONCE: u32>(
+    uplink: Option<Uplink>,
+    _config: RfConfig,
+    rx_buffer: &mut [u8],
+) -> usize {
+    let Some(mut uplink) = uplink else {
+        panic!("No uplink passed to handle_rejoin_request_1_1");
+    };
+    let Ok(PhyPayload::RejoinRequest(rejoin)) = parser::parse(uplink.data_mut()) else {
+        panic!("Did not parse rejoin request from uplink");
+    };
+    let nwk_crypto = DefaultCrypto::new(&get_nwk_key().into());
+    let js_int_key = JSIntKey::derive_from(&nwk_crypto, &rejoin.dev_eui());
+    let js_enc_key = JSEncKey::derive_from(&nwk_crypto, &rejoin.dev_eui());
+    assert!(rejoin.validate_mic(&DefaultCrypto::new(js_int_key.inner())));
+    let accept = JoinAccept {
+        join_nonce: JoinNonce::from_value(JOIN_NONCE),
+        net_id: NetId::from_wire_bytes([1; 3]),
+        dev_addr: DevAddr::from_value(0x2000),
+        dl_settings: DLSettings::new(0x80),
+        rx_delay: 0,
+        c_f_list: None,
+    };
+    accept
+        .build_into_1_1(
+            rx_buffer,
+            &DefaultNetworkCrypto::new(js_enc_key.inner()),
+            &DefaultCrypto::new(js_int_key.inner()),
+            rejoin.rejoin_type(),
+            &rejoin.join_eui().unwrap(),
+            &parser::DevNonce::from_value(rejoin.rj_count()),
+        )
+        .unwrap()
+        .len()
+}
+
 /// Handle an uplink and respond with two LinkAdrReq on Port 0
 pub fn handle_data_uplink_with_link_adr_req<const FCNT_UP: u16, const FCNT_DOWN: u32>(
     uplink: Option<Uplink>,
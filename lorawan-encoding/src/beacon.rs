@@ -0,0 +1,218 @@
+//! Class B beacon frames and ping-slot scheduling.
+//!
+//! A beacon carries the GPS time of its transmission (protected by its own CRC) followed by an
+//! optional gateway-specific field (protected by a second CRC). The amount of RFU padding around
+//! these fields depends on the region, which is described by a [`BeaconLayout`].
+use crate::keys::Crypto;
+use crate::parser::DevAddr;
+
+/// Beacon period in seconds.
+pub const BEACON_PERIOD: u32 = 128;
+
+/// Length of the gateway-specific field (InfoDesc and 6 bytes of Info).
+const GW_SPECIFIC_LEN: usize = 7;
+const TIME_LEN: usize = 4;
+const CRC_LEN: usize = 2;
+
+/// Largest beacon defined by the regional parameters (US915/AU915).
+pub const MAX_BEACON_LEN: usize = 23;
+
+/// Errors from parsing a beacon frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Error {
+    /// The buffer length does not match the beacon layout.
+    InvalidLength,
+    /// The CRC protecting the network common part does not match.
+    InvalidCrc,
+}
+
+/// Region-specific sizes of the RFU fields framing a beacon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BeaconLayout {
+    rfu1_len: usize,
+    rfu2_len: usize,
+}
+
+impl BeaconLayout {
+    /// Layout of 17-byte beacons (EU868, EU433, AS923, KR920, RU864).
+    pub const SHORT: Self = Self { rfu1_len: 2, rfu2_len: 0 };
+    /// Layout of 19-byte beacons (IN865).
+    pub const MEDIUM: Self = Self { rfu1_len: 1, rfu2_len: 3 };
+    /// Layout of CN470 beacons, 19 bytes as well but with the RFU padding placed otherwise.
+    pub const CN470: Self = Self { rfu1_len: 3, rfu2_len: 1 };
+    /// Layout of 23-byte beacons (US915, AU915).
+    pub const LONG: Self = Self { rfu1_len: 5, rfu2_len: 3 };
+
+    /// Total length of a beacon frame in bytes.
+    pub const fn frame_len(&self) -> usize {
+        self.rfu1_len + TIME_LEN + CRC_LEN + GW_SPECIFIC_LEN + self.rfu2_len + CRC_LEN
+    }
+
+    const fn common_len(&self) -> usize {
+        self.rfu1_len + TIME_LEN
+    }
+
+    const fn gw_specific_offset(&self) -> usize {
+        self.common_len() + CRC_LEN
+    }
+}
+
+/// Gateway-specific part of a beacon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct GwSpecific {
+    /// Describes the content of `info`, eg: 0..=2 for the GPS coordinates of antenna 1..=3.
+    pub info_desc: u8,
+    /// Gateway information, eg: latitude and longitude (3 bytes each, little endian).
+    pub info: [u8; 6],
+}
+
+/// A beacon frame whose network common part has been validated.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Beacon<'a> {
+    bytes: &'a [u8],
+    layout: BeaconLayout,
+}
+
+impl<'a> Beacon<'a> {
+    /// Parses a beacon, validating its length and the CRC of the network common part.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lorawan::beacon::{Beacon, BeaconLayout};
+    /// let bytes = [
+    ///     0x00, 0x00, 0x00, 0x00, 0x02, 0xcc, 0xa2, 0x7e, 0x00, 0x01, 0x20, 0x00, 0x00, 0x81,
+    ///     0x03, 0xde, 0x55,
+    /// ];
+    /// let beacon = Beacon::parse(&bytes, BeaconLayout::SHORT).unwrap();
+    /// assert_eq!(beacon.time(), 0xcc02_0000);
+    /// ```
+    pub fn parse(bytes: &'a [u8], layout: BeaconLayout) -> Result<Self, Error> {
+        if bytes.len() != layout.frame_len() {
+            return Err(Error::InvalidLength);
+        }
+        let common = layout.common_len();
+        if crc16(&bytes[..common]) != read_crc(&bytes[common..]) {
+            return Err(Error::InvalidCrc);
+        }
+        Ok(Self { bytes, layout })
+    }
+
+    /// GPS time of the beacon transmission, in seconds modulo 2^32.
+    pub fn time(&self) -> u32 {
+        let offset = self.layout.rfu1_len;
+        u32::from_le_bytes(self.bytes[offset..offset + TIME_LEN].try_into().unwrap())
+    }
+
+    /// The gateway-specific part, or `None` if its CRC does not match.
+    pub fn gw_specific(&self) -> Option<GwSpecific> {
+        let start = self.layout.gw_specific_offset();
+        let end = self.bytes.len() - CRC_LEN;
+        if crc16(&self.bytes[start..end]) != read_crc(&self.bytes[end..]) {
+            return None;
+        }
+        Some(GwSpecific {
+            info_desc: self.bytes[start],
+            info: self.bytes[start + 1..start + GW_SPECIFIC_LEN].try_into().unwrap(),
+        })
+    }
+}
+
+/// BeaconCreator serves for creating beacon frames, eg: for testing Class B end-devices.
+///
+/// # Examples
+///
+/// ```
+/// use lorawan::beacon::{Beacon, BeaconCreator, BeaconLayout, GwSpecific};
+/// let mut creator = BeaconCreator::new(BeaconLayout::LONG);
+/// let bytes = creator
+///     .set_time(1_234_567_808)
+///     .set_gw_specific(GwSpecific { info_desc: 0, info: [1, 2, 3, 4, 5, 6] })
+///     .build();
+/// let beacon = Beacon::parse(bytes, BeaconLayout::LONG).unwrap();
+/// assert_eq!(beacon.time(), 1_234_567_808);
+/// ```
+pub struct BeaconCreator {
+    data: [u8; MAX_BEACON_LEN],
+    layout: BeaconLayout,
+}
+
+impl BeaconCreator {
+    pub fn new(layout: BeaconLayout) -> Self {
+        Self { data: [0; MAX_BEACON_LEN], layout }
+    }
+
+    /// Sets the GPS time of the beacon, in seconds.
+    pub fn set_time(&mut self, time: u32) -> &mut Self {
+        let offset = self.layout.rfu1_len;
+        self.data[offset..offset + TIME_LEN].copy_from_slice(&time.to_le_bytes());
+        self
+    }
+
+    pub fn set_gw_specific(&mut self, gw_specific: GwSpecific) -> &mut Self {
+        let start = self.layout.gw_specific_offset();
+        self.data[start] = gw_specific.info_desc;
+        self.data[start + 1..start + GW_SPECIFIC_LEN].copy_from_slice(&gw_specific.info);
+        self
+    }
+
+    /// Computes both CRCs and returns the beacon frame.
+    pub fn build(&mut self) -> &[u8] {
+        let len = self.layout.frame_len();
+        let common = self.layout.common_len();
+        let crc = crc16(&self.data[..common]);
+        self.data[common..common + CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+        let start = self.layout.gw_specific_offset();
+        let crc = crc16(&self.data[start..len - CRC_LEN]);
+        self.data[len - CRC_LEN..len].copy_from_slice(&crc.to_le_bytes());
+        &self.data[..len]
+    }
+}
+
+/// Number of 30 ms slots between two ping slots for the given periodicity (0..=7), ie: the
+/// `pingPeriod` of the Class B specification.
+pub const fn ping_period(periodicity: u8) -> u16 {
+    1 << (5 + (periodicity & 0x07))
+}
+
+/// Computes the pseudo-random offset of the first ping slot of a beacon period, in 30 ms slots
+/// since the end of the beacon reserved interval.
+///
+/// `crypto` must be keyed with the all-zero AES key; `beacon_time` is the time carried by the
+/// beacon opening the period.
+pub fn ping_offset<F: Crypto>(
+    crypto: &F,
+    beacon_time: u32,
+    dev_addr: &DevAddr,
+    periodicity: u8,
+) -> u16 {
+    let mut block = [0u8; 16];
+    block[..4].copy_from_slice(&beacon_time.to_le_bytes());
+    block[4..8].copy_from_slice(dev_addr.as_wire_bytes());
+    crypto.encrypt_block(&mut block);
+    u16::from_le_bytes([block[0], block[1]]) % ping_period(periodicity)
+}
+
+fn read_crc(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+/// CRC-16/XMODEM (polynomial 0x1021, initial value 0) as used for beacon fields.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
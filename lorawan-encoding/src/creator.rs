@@ -17,16 +17,18 @@
 
 use core::num::NonZeroU8;
 
-use crate::keys::{Crypto, NetworkCrypto};
+use crate::keys::{Crypto, NetworkCrypto, NetworkSessionCrypto};
 use crate::packet_length::phy::join::{
-    JOIN_ACCEPT_LEN, JOIN_ACCEPT_WITH_CFLIST_LEN, JOIN_REQUEST_LEN,
+    JOIN_ACCEPT_LEN, JOIN_ACCEPT_WITH_CFLIST_LEN, JOIN_REQUEST_LEN, REJOIN_REQUEST_0_2_LEN,
+    REJOIN_REQUEST_1_LEN,
 };
-use crate::packet_length::phy::{MHDR_LEN, MIC_LEN};
+use crate::packet_length::phy::{MHDR_LEN, MIC_LEN, PHY_PAYLOAD_MIN_LEN};
 use crate::securityhelpers;
 use crate::types::DLSettings;
 
 use crate::parser::{
-    CfList, DataFrameType, DevAddr, DevEui, DevNonce, Error, JoinEui, JoinNonce, NetId,
+    CfList, DataFrameType, DevAddr, DevEui, DevNonce, Error, JoinEui, JoinNonce, JoinReqType,
+    MicContext, NetId,
 };
 
 fn write_mic(out: &mut [u8], crypto: &dyn Crypto) {
@@ -64,6 +66,67 @@ impl JoinRequest {
     }
 }
 
+/// A LoRaWAN 1.1 rejoin request, ready to build.
+///
+/// Each type carries the fields and the counter it is defined with, so a type 1
+/// request without a JoinEUI is unrepresentable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejoinRequest {
+    /// Sent periodically or when asked by ForceRejoinReq, to hand the device
+    /// over to another network or to rekey it.
+    Type0 { net_id: NetId, dev_eui: DevEui, rj_count0: u16 },
+    /// Restores a lost session context, like a JoinRequest.
+    Type1 { join_eui: JoinEui, dev_eui: DevEui, rj_count1: u16 },
+    /// Asked by ForceRejoinReq to rekey the device or change its DevAddr,
+    /// keeping its radio parameters.
+    Type2 { net_id: NetId, dev_eui: DevEui, rj_count0: u16 },
+}
+
+impl RejoinRequest {
+    /// The request type, which also enters the MIC of the JoinAccept answering it.
+    pub fn join_req_type(&self) -> JoinReqType {
+        match self {
+            Self::Type0 { .. } => JoinReqType::RejoinType0,
+            Self::Type1 { .. } => JoinReqType::RejoinType1,
+            Self::Type2 { .. } => JoinReqType::RejoinType2,
+        }
+    }
+
+    /// Writes the frame into the front of `buf` with the MIC set, returning
+    /// the built bytes.
+    ///
+    /// `crypto` must be bound to the SNwkSIntKey for types 0 and 2, to the
+    /// JSIntKey for type 1.
+    pub fn build_into<'a, C: Crypto>(
+        &self,
+        buf: &'a mut [u8],
+        crypto: &C,
+    ) -> Result<&'a [u8], Error> {
+        let len = match self {
+            Self::Type1 { .. } => REJOIN_REQUEST_1_LEN,
+            Self::Type0 { .. } | Self::Type2 { .. } => REJOIN_REQUEST_0_2_LEN,
+        };
+        let out = buf.get_mut(..len).ok_or(Error::BufferTooShort)?;
+        out[0] = 0xc0;
+        out[1] = self.join_req_type().wire_value();
+        match self {
+            Self::Type0 { net_id, dev_eui, rj_count0: rj_count }
+            | Self::Type2 { net_id, dev_eui, rj_count0: rj_count } => {
+                out[2..5].copy_from_slice(net_id.as_wire_bytes());
+                out[5..13].copy_from_slice(dev_eui.as_wire_bytes());
+                out[13..15].copy_from_slice(&rj_count.to_le_bytes());
+            }
+            Self::Type1 { join_eui, dev_eui, rj_count1 } => {
+                out[2..10].copy_from_slice(join_eui.as_wire_bytes());
+                out[10..18].copy_from_slice(dev_eui.as_wire_bytes());
+                out[18..20].copy_from_slice(&rj_count1.to_le_bytes());
+            }
+        }
+        write_mic(out, crypto);
+        Ok(out)
+    }
+}
+
 /// A JoinAccept, ready to build.
 ///
 /// Reuses the owned [`CfList`] that parsing produces, so a network server can
@@ -93,6 +156,42 @@ impl JoinAccept {
         buf: &'a mut [u8],
         crypto: &C,
     ) -> Result<&'a [u8], Error> {
+        let out = self.write_fields(buf)?;
+        write_mic(out, crypto);
+        Self::encrypt(out, crypto);
+        Ok(out)
+    }
+
+    /// Like [`Self::build_into`], with the LoRaWAN 1.1 MIC used when
+    /// `dl_settings` has the OptNeg bit set.
+    ///
+    /// `crypto` must be bound to the NwkKey (JSEncKey when answering a
+    /// rejoin), `js_int_crypto` to the JSIntKey; `join_eui` and `dev_nonce`
+    /// are those of the request being answered.
+    pub fn build_into_1_1<'a, C: NetworkCrypto, I: Crypto>(
+        &self,
+        buf: &'a mut [u8],
+        crypto: &C,
+        js_int_crypto: &I,
+        join_req_type: JoinReqType,
+        join_eui: &JoinEui,
+        dev_nonce: &DevNonce,
+    ) -> Result<&'a [u8], Error> {
+        let out = self.write_fields(buf)?;
+        let mic_offset = out.len() - MIC_LEN;
+        let mic = securityhelpers::calculate_join_accept_mic_1_1(
+            &out[..mic_offset],
+            js_int_crypto,
+            join_req_type.wire_value(),
+            join_eui,
+            dev_nonce,
+        );
+        out[mic_offset..].copy_from_slice(&mic.0);
+        Self::encrypt(out, crypto);
+        Ok(out)
+    }
+
+    fn write_fields<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut [u8], Error> {
         let len = if self.c_f_list.is_some() {
             JOIN_ACCEPT_WITH_CFLIST_LEN
         } else {
@@ -119,14 +218,16 @@ impl JoinAccept {
                 out[28] = 1; // CFList type
             }
         }
-        write_mic(out, crypto);
+        Ok(out)
+    }
+
+    fn encrypt<C: NetworkCrypto>(out: &mut [u8], crypto: &C) {
         // The server encrypts by running AES in *decrypt* mode so that the
         // device can decrypt with the cheaper encrypt mode. MHDR stays clear;
         // the MIC is inside the encrypted region.
         for block in out[MHDR_LEN..].chunks_exact_mut(16) {
             crypto.decrypt_block(block);
         }
-        Ok(out)
     }
 }
 
@@ -160,6 +261,8 @@ pub struct DataFrame<'a> {
     pub ack: bool,
     /// More downlink pending (FCtrl bit 4); written on downlinks only.
     pub f_pending: bool,
+    /// Class B enabled (FCtrl bit 4); written on uplinks only.
+    pub class_b: bool,
     /// The full 32-bit frame counter. The low 16 bits go on the wire; the
     /// rest participates in encryption and the MIC.
     pub fcnt: u32,
@@ -177,6 +280,7 @@ impl Default for DataFrame<'_> {
             adr_ack_req: false,
             ack: false,
             f_pending: false,
+            class_b: false,
             fcnt: 0,
             f_opts: &[],
             payload: Payload::None,
@@ -208,6 +312,9 @@ impl DataFrame<'_> {
         if self.f_pending && !self.frame_type.is_uplink() {
             b |= 0x10;
         }
+        if self.class_b && self.frame_type.is_uplink() {
+            b |= 0x10;
+        }
         b
     }
 
@@ -223,6 +330,85 @@ impl DataFrame<'_> {
         nwk_crypto: &C,
         app_crypto: Option<&C>,
     ) -> Result<&'a [u8], Error> {
+        let out = self.write_encrypted(buf, nwk_crypto, app_crypto)?;
+        let mic_offset = out.len() - MIC_LEN;
+        let mic = securityhelpers::calculate_data_mic(&out[..mic_offset], nwk_crypto, self.fcnt);
+        out[mic_offset..].copy_from_slice(&mic.0);
+        Ok(out)
+    }
+
+    /// Writes the frame with LoRaWAN 1.1 security into the front of `buf`,
+    /// returning the built bytes.
+    ///
+    /// FOpts and FPort-0 payloads are encrypted with the NwkSEncKey, and the
+    /// MIC covers `ctx`: uplinks are signed with both integrity keys,
+    /// downlinks with the SNwkSIntKey. `app_crypto` is as for
+    /// [`Self::build_into`].
+    pub fn build_into_1_1<'a, C: Crypto>(
+        &self,
+        buf: &'a mut [u8],
+        keys: &NetworkSessionCrypto<'_, C>,
+        app_crypto: Option<&C>,
+        ctx: &MicContext,
+    ) -> Result<&'a [u8], Error> {
+        let out = self.write_encrypted(buf, keys.nwk_s_enc, app_crypto)?;
+        if !self.f_opts.is_empty() {
+            securityhelpers::encrypt_f_opts(out, self.f_opts.len(), self.fcnt, keys.nwk_s_enc);
+        }
+        let mic_offset = out.len() - MIC_LEN;
+        let mic = if self.frame_type.is_uplink() {
+            securityhelpers::calculate_uplink_mic_1_1(
+                &out[..mic_offset],
+                keys.f_nwk_s_int,
+                keys.s_nwk_s_int,
+                self.fcnt,
+                ctx,
+            )
+        } else {
+            securityhelpers::calculate_downlink_mic_1_1(
+                &out[..mic_offset],
+                keys.s_nwk_s_int,
+                self.fcnt,
+                ctx,
+            )
+        };
+        out[mic_offset..].copy_from_slice(&mic.0);
+        Ok(out)
+    }
+
+    /// Recomputes the MIC of an uplink built by [`Self::build_into_1_1`] for
+    /// another `ctx`, eg: when the frame is repeated on another channel.
+    ///
+    /// `fcnt` is the full frame counter the frame was built with.
+    pub fn resign_uplink_1_1<C: Crypto>(
+        frame: &mut [u8],
+        keys: &NetworkSessionCrypto<'_, C>,
+        fcnt: u32,
+        ctx: &MicContext,
+    ) -> Result<(), Error> {
+        if frame.len() < PHY_PAYLOAD_MIN_LEN {
+            return Err(Error::TooShort);
+        }
+        let mic_offset = frame.len() - MIC_LEN;
+        let mic = securityhelpers::calculate_uplink_mic_1_1(
+            &frame[..mic_offset],
+            keys.f_nwk_s_int,
+            keys.s_nwk_s_int,
+            fcnt,
+            ctx,
+        );
+        frame[mic_offset..].copy_from_slice(&mic.0);
+        Ok(())
+    }
+
+    /// Writes the frame with its FRMPayload encrypted, leaving the MIC to the
+    /// caller.
+    fn write_encrypted<'a, C: Crypto>(
+        &self,
+        buf: &'a mut [u8],
+        nwk_crypto: &C,
+        app_crypto: Option<&C>,
+    ) -> Result<&'a mut [u8], Error> {
         if self.f_opts.len() > 15 {
             return Err(Error::FOptsTooLong);
         }
@@ -266,9 +452,6 @@ impl DataFrame<'_> {
                 enc_crypto,
             );
         }
-        let mic_offset = total - MIC_LEN;
-        let mic = securityhelpers::calculate_data_mic(&out[..mic_offset], nwk_crypto, self.fcnt);
-        out[mic_offset..].copy_from_slice(&mic.0);
         Ok(out)
     }
 }
@@ -1,6 +1,6 @@
 //! Implement types for dealing with LoRaWAN keys and required
 //! cryptography entities.
-use super::parser::McAddr;
+use super::parser::{DevEui as WireDevEui, McAddr};
 
 macro_rules! lorawan_key {
     (
@@ -82,6 +82,77 @@ lorawan_key!(
 #[deprecated(since = "0.9.1", note = "Please use `NwkSKey` instead")]
 pub type NewSKey = NwkSKey;
 
+lorawan_key!(
+    /// The [`NwkKey`] is the LoRaWAN 1.1 network root key (AES-128) specific to the end-device.
+    ///
+    /// It derives the network session keys and the join server lifetime keys. In LoRaWAN 1.0.x
+    /// the [`AppKey`] serves both purposes instead.
+    pub struct NwkKey(AES128);
+);
+
+lorawan_key!(
+    /// The LoRaWAN 1.1 forwarding network session integrity key ([`FNwkSIntKey`]).
+    ///
+    /// Computes half of the MIC of every uplink; the only integrity key of 1.0.x uplinks.
+    pub struct FNwkSIntKey(AES128);
+);
+
+lorawan_key!(
+    /// The LoRaWAN 1.1 serving network session integrity key ([`SNwkSIntKey`]).
+    ///
+    /// Computes the other half of the uplink MIC and the full MIC of downlinks.
+    pub struct SNwkSIntKey(AES128);
+);
+
+lorawan_key!(
+    /// The LoRaWAN 1.1 network session encryption key ([`NwkSEncKey`]).
+    ///
+    /// Encrypts MAC commands, both in FOpts and in an FPort 0 FRMPayload.
+    pub struct NwkSEncKey(AES128);
+);
+
+lorawan_key!(
+    /// The LoRaWAN 1.1 join server integrity key ([`JSIntKey`]), a lifetime key derived from the
+    /// [`NwkKey`].
+    ///
+    /// Computes the MIC of JoinAccepts sent with the OptNeg bit set.
+    pub struct JSIntKey(AES128);
+);
+
+lorawan_key!(
+    /// The LoRaWAN 1.1 join server encryption key ([`JSEncKey`]), a lifetime key derived from the
+    /// [`NwkKey`].
+    ///
+    /// Encrypts JoinAccepts that answer a rejoin request.
+    pub struct JSEncKey(AES128);
+);
+
+impl JSIntKey {
+    /// JSIntKey = aes128_encrypt(NwkKey, 0x06 | DevEUI | pad16)
+    ///
+    /// `crypto` must be bound to the NwkKey.
+    pub fn derive_from<C: Crypto>(crypto: &C, dev_eui: &WireDevEui) -> Self {
+        JSIntKey(derive_lifetime_key(crypto, 0x06, dev_eui))
+    }
+}
+
+impl JSEncKey {
+    /// JSEncKey = aes128_encrypt(NwkKey, 0x05 | DevEUI | pad16)
+    ///
+    /// `crypto` must be bound to the NwkKey.
+    pub fn derive_from<C: Crypto>(crypto: &C, dev_eui: &WireDevEui) -> Self {
+        JSEncKey(derive_lifetime_key(crypto, 0x05, dev_eui))
+    }
+}
+
+fn derive_lifetime_key<C: Crypto>(crypto: &C, first_byte: u8, dev_eui: &WireDevEui) -> AES128 {
+    let mut bytes: [u8; 16] = [0; 16];
+    bytes[0] = first_byte;
+    bytes[1..9].copy_from_slice(dev_eui.as_wire_bytes());
+    crypto.encrypt_block(&mut bytes);
+    AES128(bytes)
+}
+
 lorawan_key!(
     pub struct McKey(AES128);
 );
@@ -331,6 +402,19 @@ pub trait NetworkCrypto: Crypto {
     fn decrypt_block(&self, block: &mut [u8]);
 }
 
+/// The LoRaWAN 1.1 network session keys of a data frame, each bound to its [`Crypto`].
+///
+/// A LoRaWAN 1.0.x session maps onto this by binding all three to the NwkSKey.
+#[derive(Debug, Clone, Copy)]
+pub struct NetworkSessionCrypto<'a, C> {
+    /// Bound to the FNwkSIntKey.
+    pub f_nwk_s_int: &'a C,
+    /// Bound to the SNwkSIntKey.
+    pub s_nwk_s_int: &'a C,
+    /// Bound to the NwkSEncKey.
+    pub nwk_s_enc: &'a C,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
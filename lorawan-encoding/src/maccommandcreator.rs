@@ -11,6 +11,12 @@ pub enum Error {
     MaxDutyCycleOutOfRange,
     MaxEirpOutOfRange,
     NanoSecondsOutOfRange,
+    MinorVersionOutOfRange,
+    PeriodicityOutOfRange,
+    RejoinTypeOutOfRange,
+    PeriodOutOfRange,
+    MaxRetriesOutOfRange,
+    RejoinParamOutOfRange,
     BufferTooShort,
 }
 
@@ -588,6 +594,44 @@ impl DlChannelAnsCreator {
     }
 }
 
+#[doc(inline)]
+pub use crate::maccommands::RekeyIndCreator;
+
+impl RekeyIndCreator {
+    /// Sets the LoRaWAN minor version of the RekeyInd to the provided value.
+    ///
+    /// # Argument
+    ///
+    /// * minor_version - 1 for LoRaWAN 1.1, other values are RFU.
+    pub fn set_minor_version(&mut self, minor_version: u8) -> Result<&mut Self, Error> {
+        if minor_version > 0x0f {
+            return Err(Error::MinorVersionOutOfRange);
+        }
+        self.data[1] = minor_version;
+
+        Ok(self)
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::RekeyConfCreator;
+
+impl RekeyConfCreator {
+    /// Sets the LoRaWAN minor version of the RekeyConf to the provided value.
+    ///
+    /// # Argument
+    ///
+    /// * minor_version - 1 for LoRaWAN 1.1, other values are RFU.
+    pub fn set_minor_version(&mut self, minor_version: u8) -> Result<&mut Self, Error> {
+        if minor_version > 0x0f {
+            return Err(Error::MinorVersionOutOfRange);
+        }
+        self.data[1] = minor_version;
+
+        Ok(self)
+    }
+}
+
 #[doc(inline)]
 pub use crate::maccommands::DeviceTimeAnsCreator;
 #[doc(inline)]
@@ -607,6 +651,193 @@ impl DeviceTimeAnsCreator {
     }
 }
 
+#[doc(inline)]
+pub use crate::maccommands::ForceRejoinReqCreator;
+
+impl ForceRejoinReqCreator {
+    /// Sets the period of the ForceRejoinReq: rejoin requests are retransmitted every
+    /// `32 * 2^period` seconds plus a random delay of up to 32 seconds (0..=7).
+    pub fn set_period(&mut self, period: u8) -> Result<&mut Self, Error> {
+        if period > 0x07 {
+            return Err(Error::PeriodOutOfRange);
+        }
+        self.data[2] = (self.data[2] & !0x38) | (period << 3);
+
+        Ok(self)
+    }
+
+    /// Sets the retransmissions of the rejoin request after the first transmission (0..=7).
+    pub fn set_max_retries(&mut self, max_retries: u8) -> Result<&mut Self, Error> {
+        if max_retries > 0x07 {
+            return Err(Error::MaxRetriesOutOfRange);
+        }
+        self.data[2] = (self.data[2] & !0x07) | max_retries;
+
+        Ok(self)
+    }
+
+    /// Sets the type of the rejoin request: 0 or 1 for type 0, 2 for type 2 (0..=7, values above
+    /// 2 are RFU).
+    pub fn set_rejoin_type(&mut self, rejoin_type: u8) -> Result<&mut Self, Error> {
+        if rejoin_type > 0x07 {
+            return Err(Error::RejoinTypeOutOfRange);
+        }
+        self.data[1] = (self.data[1] & !0x70) | (rejoin_type << 4);
+
+        Ok(self)
+    }
+
+    /// Sets the data rate of the rejoin request.
+    pub fn set_data_rate(&mut self, data_rate: u8) -> Result<&mut Self, Error> {
+        if data_rate > 0x0f {
+            return Err(Error::InvalidDataRate);
+        }
+        self.data[1] = (self.data[1] & !0x0f) | data_rate;
+
+        Ok(self)
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::RejoinParamSetupReqCreator;
+
+impl RejoinParamSetupReqCreator {
+    /// Sets MaxTimeN: type 0 rejoin requests are sent at least every `2^(max_time_n + 10)`
+    /// seconds (0..=15).
+    pub fn set_max_time_n(&mut self, max_time_n: u8) -> Result<&mut Self, Error> {
+        if max_time_n > 0x0f {
+            return Err(Error::RejoinParamOutOfRange);
+        }
+        self.data[1] = (self.data[1] & 0x0f) | (max_time_n << 4);
+
+        Ok(self)
+    }
+
+    /// Sets MaxCountN: type 0 rejoin requests are sent at least every `2^(max_count_n + 4)`
+    /// uplinks (0..=15).
+    pub fn set_max_count_n(&mut self, max_count_n: u8) -> Result<&mut Self, Error> {
+        if max_count_n > 0x0f {
+            return Err(Error::RejoinParamOutOfRange);
+        }
+        self.data[1] = (self.data[1] & 0xf0) | max_count_n;
+
+        Ok(self)
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::RejoinParamSetupAnsCreator;
+
+impl RejoinParamSetupAnsCreator {
+    /// Sets whether the device also sends rejoin requests based on time.
+    pub fn set_time_ack(&mut self, ack: bool) -> &mut Self {
+        self.data[1] = ack as u8;
+
+        self
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::PingSlotInfoReqCreator;
+
+impl PingSlotInfoReqCreator {
+    /// Sets the ping slot periodicity of the PingSlotInfoReq to the provided value.
+    ///
+    /// # Argument
+    ///
+    /// * periodicity - ping slots are opened every `2^periodicity` seconds (0..=7).
+    pub fn set_periodicity(&mut self, periodicity: u8) -> Result<&mut Self, Error> {
+        if periodicity > 0x07 {
+            return Err(Error::PeriodicityOutOfRange);
+        }
+        self.data[1] = periodicity;
+
+        Ok(self)
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::PingSlotInfoAnsCreator;
+
+#[doc(inline)]
+pub use crate::maccommands::PingSlotChannelReqCreator;
+
+impl PingSlotChannelReqCreator {
+    pub fn set_frequency<'a, T: Into<Frequency<'a>>>(&mut self, frequency: T) -> &mut Self {
+        let converted = frequency.into();
+        self.data[1..4].copy_from_slice(converted.as_ref());
+
+        self
+    }
+
+    pub fn set_data_rate(&mut self, data_rate: u8) -> Result<&mut Self, Error> {
+        if data_rate > 0x0f {
+            return Err(Error::InvalidDataRate);
+        }
+        self.data[4] = data_rate;
+
+        Ok(self)
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::PingSlotChannelAnsCreator;
+
+impl PingSlotChannelAnsCreator {
+    /// Sets the channel frequency acknowledgement of the PingSlotChannelAns to the provided value.
+    ///
+    /// # Argument
+    ///
+    /// * ack - true when channel frequency was acceptable or false otherwise.
+    pub fn set_channel_frequency_ack(&mut self, ack: bool) -> &mut Self {
+        self.data[1] &= 0xfe;
+        self.data[1] |= ack as u8;
+
+        self
+    }
+
+    /// Sets the data rate acknowledgement of the PingSlotChannelAns to the provided value.
+    ///
+    /// # Argument
+    ///
+    /// * ack - true when data rate was acceptable or false otherwise.
+    pub fn set_data_rate_ack(&mut self, ack: bool) -> &mut Self {
+        self.data[1] &= 0xfd;
+        self.data[1] |= (ack as u8) << 1;
+
+        self
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::BeaconFreqReqCreator;
+
+impl BeaconFreqReqCreator {
+    pub fn set_frequency<'a, T: Into<Frequency<'a>>>(&mut self, frequency: T) -> &mut Self {
+        let converted = frequency.into();
+        self.data[1..4].copy_from_slice(converted.as_ref());
+
+        self
+    }
+}
+
+#[doc(inline)]
+pub use crate::maccommands::BeaconFreqAnsCreator;
+
+impl BeaconFreqAnsCreator {
+    /// Sets the beacon frequency acknowledgement of the BeaconFreqAns to the provided value.
+    ///
+    /// # Argument
+    ///
+    /// * ack - true when beacon frequency was acceptable or false otherwise.
+    pub fn set_beacon_frequency_ack(&mut self, ack: bool) -> &mut Self {
+        self.data[1] &= 0xfe;
+        self.data[1] |= ack as u8;
+
+        self
+    }
+}
+
 pub fn build_mac_commands<T: AsMut<[u8]>>(
     cmds: &[&dyn SerializableMacCommand],
     mut out: T,
@@ -89,10 +89,37 @@ pub enum DownlinkMacCommand<'a> {
     #[cmd(cid = 0x0A, len = 4)]
     DlChannelReq(DlChannelReqPayload<'a>),
 
+    // LoRaWAN 1.1+ commands
+    /// RekeyConf payload handling (LoRaWAN 1.1+)
+    #[cmd(cid = 0x0B, len = 1)]
+    RekeyConf(RekeyConfPayload<'a>),
+
     // LoRaWAN 1.0.3+ commands
     /// DeviceTimeAns payload handling (LoRaWAN 1.0.3+)
     #[cmd(cid = 0x0D, len = 5)]
     DeviceTimeAns(DeviceTimeAnsPayload<'a>),
+
+    // LoRaWAN 1.1+ commands
+    /// ForceRejoinReq payload handling (LoRaWAN 1.1+)
+    #[cmd(cid = 0x0E, len = 2)]
+    ForceRejoinReq(ForceRejoinReqPayload<'a>),
+
+    /// RejoinParamSetupReq payload handling (LoRaWAN 1.1+)
+    #[cmd(cid = 0x0F, len = 1)]
+    RejoinParamSetupReq(RejoinParamSetupReqPayload<'a>),
+
+    // Class B commands (LoRaWAN 1.0.3+)
+    /// PingSlotInfoAns payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x10, len = 0)]
+    PingSlotInfoAns(PingSlotInfoAnsPayload),
+
+    /// PingSlotChannelReq payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x11, len = 4)]
+    PingSlotChannelReq(PingSlotChannelReqPayload<'a>),
+
+    /// BeaconFreqReq payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x13, len = 3)]
+    BeaconFreqReq(BeaconFreqReqPayload<'a>),
 }
 
 #[derive(Debug, PartialEq, CommandHandler)]
@@ -137,10 +164,33 @@ pub enum UplinkMacCommand<'a> {
     #[cmd(cid = 0x0A, len = 1)]
     DlChannelAns(DlChannelAnsPayload<'a>),
 
+    // LoRaWAN 1.1+ commands
+    /// RekeyInd payload handling (LoRaWAN 1.1+)
+    #[cmd(cid = 0x0B, len = 1)]
+    RekeyInd(RekeyIndPayload<'a>),
+
     // 1.0.3+
     /// DeviceTimeReq payload handling (LoRaWAN 1.0.3+)
     #[cmd(cid = 0x0D, len = 0)]
     DeviceTimeReq(DeviceTimeReqPayload),
+
+    // LoRaWAN 1.1+ commands
+    /// RejoinParamSetupAns payload handling (LoRaWAN 1.1+)
+    #[cmd(cid = 0x0F, len = 1)]
+    RejoinParamSetupAns(RejoinParamSetupAnsPayload<'a>),
+
+    // Class B commands (LoRaWAN 1.0.3+)
+    /// PingSlotInfoReq payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x10, len = 1)]
+    PingSlotInfoReq(PingSlotInfoReqPayload<'a>),
+
+    /// PingSlotChannelAns payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x11, len = 1)]
+    PingSlotChannelAns(PingSlotChannelAnsPayload<'a>),
+
+    /// BeaconFreqAns payload handling (LoRaWAN 1.0.3+)
+    #[cmd(cid = 0x13, len = 1)]
+    BeaconFreqAns(BeaconFreqAnsPayload<'a>),
 }
 
 macro_rules! create_ack_fn {
@@ -502,12 +552,127 @@ impl DlChannelAnsPayload<'_> {
     }
 }
 
+impl RekeyIndPayload<'_> {
+    /// The LoRaWAN minor version of the end-device (1 for LoRaWAN 1.1).
+    pub fn minor_version(&self) -> u8 {
+        self.0[0] & 0x0f
+    }
+}
+
+impl RekeyConfPayload<'_> {
+    /// The LoRaWAN minor version agreed by the network server (1 for LoRaWAN 1.1).
+    pub fn minor_version(&self) -> u8 {
+        self.0[0] & 0x0f
+    }
+}
+
 impl DeviceTimeAnsPayload<'_> {
+    /// Seconds since the GPS epoch at the end of the uplink which carried DeviceTimeReq.
     pub fn seconds(&self) -> u32 {
-        u32::from_le_bytes([self.0[3], self.0[2], self.0[1], self.0[0]])
+        u32::from_le_bytes([self.0[0], self.0[1], self.0[2], self.0[3]])
     }
     //raw value in 1/256 seconds
     pub fn nano_seconds(&self) -> u32 {
         (self.0[4] as u32) * 3906250
     }
 }
+
+impl ForceRejoinReqPayload<'_> {
+    fn value(&self) -> u16 {
+        u16::from_le_bytes([self.0[0], self.0[1]])
+    }
+
+    /// Delay between retransmissions of the rejoin request: `32 * 2^period` seconds plus a
+    /// random delay of up to 32 seconds.
+    pub fn period(&self) -> u8 {
+        ((self.value() >> 11) & 0x07) as u8
+    }
+
+    /// Retransmissions of the rejoin request after the first transmission.
+    pub fn max_retries(&self) -> u8 {
+        ((self.value() >> 8) & 0x07) as u8
+    }
+
+    /// Type of the rejoin request to send: 0 or 1 for type 0, 2 for type 2, other values are RFU.
+    pub fn rejoin_type(&self) -> u8 {
+        ((self.value() >> 4) & 0x07) as u8
+    }
+
+    /// Data rate of the rejoin request.
+    pub fn data_rate(&self) -> DR {
+        DR::from((self.value() & 0x0f) as u8)
+    }
+}
+
+impl RejoinParamSetupReqPayload<'_> {
+    /// Type 0 rejoin requests are sent at least every `2^(max_time_n + 10)` seconds.
+    pub fn max_time_n(&self) -> u8 {
+        self.0[0] >> 4
+    }
+
+    /// Type 0 rejoin requests are sent at least every `2^(max_count_n + 4)` uplinks.
+    pub fn max_count_n(&self) -> u8 {
+        self.0[0] & 0x0f
+    }
+}
+
+impl RejoinParamSetupAnsPayload<'_> {
+    create_ack_fn!(
+        /// Whether the device also sends rejoin requests based on time.
+        time_ack,
+        0
+    );
+}
+
+impl PingSlotInfoReqPayload<'_> {
+    /// Ping slot periodicity: the device opens a ping slot every `2^periodicity` seconds.
+    pub fn periodicity(&self) -> u8 {
+        self.0[0] & 0x07
+    }
+}
+
+impl PingSlotChannelReqPayload<'_> {
+    /// The frequency of the ping slots, 0 restores the region default.
+    pub fn frequency(&self) -> Frequency<'_> {
+        Frequency::new_from_raw(&self.0[0..3])
+    }
+
+    /// The data rate of the ping slots.
+    pub fn data_rate(&self) -> DR {
+        DR::from(self.0[3] & 0x0f)
+    }
+}
+
+impl PingSlotChannelAnsPayload<'_> {
+    create_ack_fn!(
+        /// Channel frequency ok
+        channel_freq_ack,
+        0
+    );
+
+    create_ack_fn!(
+        /// Data rate ok
+        data_rate_ack,
+        1
+    );
+
+    /// Whether the device has accepted the new ping slot channel.
+    pub fn ack(&self) -> bool {
+        self.0[0] & 0x03 == 0x03
+    }
+}
+
+impl BeaconFreqReqPayload<'_> {
+    /// The frequency of the beacons, 0 restores the region default.
+    pub fn frequency(&self) -> Frequency<'_> {
+        Frequency::new_from_raw(&self.0[0..3])
+    }
+}
+
+impl BeaconFreqAnsPayload<'_> {
+    create_ack_fn!(
+        /// Beacon frequency ok
+        beacon_freq_ack,
+        0
+    );
+}
@@ -26,6 +26,16 @@ pub mod phy {
         pub const DEV_NONCE_LEN: usize = 2;
         pub const JOIN_REQUEST_PAYLOAD_LEN: usize = JOIN_EUI_LEN + DEV_EUI_LEN + DEV_NONCE_LEN;
         pub const JOIN_REQUEST_LEN: usize = MHDR_LEN + JOIN_REQUEST_PAYLOAD_LEN + MIC_LEN;
+
+        pub const REJOIN_TYPE_LEN: usize = 1;
+        pub const RJ_COUNT_LEN: usize = 2;
+        pub const REJOIN_REQUEST_0_2_PAYLOAD_LEN: usize =
+            REJOIN_TYPE_LEN + NET_ID_LEN + DEV_EUI_LEN + RJ_COUNT_LEN;
+        pub const REJOIN_REQUEST_1_PAYLOAD_LEN: usize =
+            REJOIN_TYPE_LEN + JOIN_EUI_LEN + DEV_EUI_LEN + RJ_COUNT_LEN;
+        pub const REJOIN_REQUEST_0_2_LEN: usize =
+            MHDR_LEN + REJOIN_REQUEST_0_2_PAYLOAD_LEN + MIC_LEN;
+        pub const REJOIN_REQUEST_1_LEN: usize = MHDR_LEN + REJOIN_REQUEST_1_PAYLOAD_LEN + MIC_LEN;
     }
 
     pub const PHY_PAYLOAD_MIN_LEN: usize = MHDR_LEN + mac::MAC_PAYLOAD_MIN + MIC_LEN;
@@ -17,8 +17,8 @@
 //!   first error, with the framing generated by the `CommandHandler` derive
 //!   from the enums' `#[cmd]` attributes.
 //! * Frames are built from plain structs ([`DataFrame`], [`JoinRequest`],
-//!   [`JoinAccept`]) with `build_into(&mut buf)`; the types make most
-//!   invalid frames unrepresentable.
+//!   [`RejoinRequest`], [`JoinAccept`]) with `build_into(&mut buf)`; the types
+//!   make most invalid frames unrepresentable.
 //! * One [`Error`] enum implementing `Display` and `core::error::Error`.
 //!
 //! # Examples
@@ -73,9 +73,13 @@
 //! assert_eq!(bytes.len(), 18);
 //! ```
 
-use crate::keys::{AES128, AppSKey, Crypto, MIC, NwkSKey};
+use crate::keys::{
+    AES128, AppSKey, Crypto, FNwkSIntKey, MIC, NetworkSessionCrypto, NwkSEncKey, NwkSKey,
+    SNwkSIntKey,
+};
 use crate::packet_length::phy::join::{
-    JOIN_ACCEPT_LEN, JOIN_ACCEPT_WITH_CFLIST_LEN, JOIN_REQUEST_LEN,
+    JOIN_ACCEPT_LEN, JOIN_ACCEPT_WITH_CFLIST_LEN, JOIN_REQUEST_LEN, REJOIN_REQUEST_0_2_LEN,
+    REJOIN_REQUEST_1_LEN,
 };
 use crate::packet_length::phy::{MHDR_LEN, MIC_LEN};
 use crate::securityhelpers;
@@ -91,6 +95,8 @@ pub enum Error {
     UnsupportedMajorVersion,
     /// The MHDR message type is RFU or Proprietary.
     UnsupportedMessageType,
+    /// The rejoin type of a rejoin request is RFU.
+    UnsupportedRejoinType,
     /// The MHDR message type does not match the frame type being parsed.
     UnexpectedMessageType,
     /// The MHDR message type is not one of the four data frame types.
@@ -121,6 +127,7 @@ impl core::fmt::Display for Error {
             Error::TooShort => "buffer shorter than minimal frame",
             Error::UnsupportedMajorVersion => "unsupported LoRaWAN major version",
             Error::UnsupportedMessageType => "RFU or proprietary MHDR message type",
+            Error::UnsupportedRejoinType => "RFU rejoin type",
             Error::UnexpectedMessageType => "MHDR message type does not match frame type",
             Error::NotADataFrame => "MHDR message type is not a data frame",
             Error::InvalidLength => "buffer length invalid for this frame type",
@@ -140,6 +147,7 @@ pub enum PhyPayload<'a> {
     JoinRequest(JoinRequestPayload<'a>),
     JoinAccept(EncryptedJoinAcceptPayload<'a>),
     Data(EncryptedDataPayload<'a>),
+    RejoinRequest(RejoinRequestPayload<'a>),
 }
 
 /// Parses and classifies a LoRaWAN physical payload.
@@ -155,6 +163,7 @@ pub fn parse(bytes: &[u8]) -> Result<PhyPayload<'_>, Error> {
         0 => Ok(PhyPayload::JoinRequest(JoinRequestPayload::parse(bytes)?)),
         1 => Ok(PhyPayload::JoinAccept(EncryptedJoinAcceptPayload::parse(bytes)?)),
         2..=5 => Ok(PhyPayload::Data(EncryptedDataPayload::parse(bytes)?)),
+        6 => Ok(PhyPayload::RejoinRequest(RejoinRequestPayload::parse(bytes)?)),
         _ => Err(Error::UnsupportedMessageType),
     }
 }
@@ -194,6 +203,21 @@ impl DataFrameType {
     }
 }
 
+/// The session state that enters the MIC of a LoRaWAN 1.1 data frame besides the frame itself.
+///
+/// LoRaWAN 1.0.x frames do not use it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct MicContext {
+    /// The low 16 bits of the FCnt of the confirmed frame this one acknowledges (ACK bit set),
+    /// zero otherwise.
+    pub conf_fcnt: u16,
+    /// Uplink only: the data rate index of the transmission.
+    pub tx_dr: u8,
+    /// Uplink only: the index of the channel used for the transmission.
+    pub tx_ch: u8,
+}
+
 /// Zero-copy view of the FHDR of a data frame.
 ///
 /// Only constructed by [`EncryptedDataPayload::parse`] /
@@ -357,6 +381,32 @@ impl<'a> EncryptedDataPayload<'a> {
         let without_mic = &self.bytes[..self.bytes.len() - MIC_LEN];
         self.mic() == securityhelpers::calculate_data_mic(without_mic, crypto, fcnt)
     }
+
+    /// Whether the LoRaWAN 1.1 MIC matches under the given 32-bit frame counter.
+    ///
+    /// Uplinks are checked against both integrity keys of `keys`, downlinks
+    /// against the SNwkSIntKey only; `nwk_s_enc` is not used.
+    #[inline]
+    pub fn validate_mic_1_1<C: Crypto>(
+        &self,
+        keys: &NetworkSessionCrypto<'_, C>,
+        fcnt: u32,
+        ctx: &MicContext,
+    ) -> bool {
+        let without_mic = &self.bytes[..self.bytes.len() - MIC_LEN];
+        let mic = if self.is_uplink() {
+            securityhelpers::calculate_uplink_mic_1_1(
+                without_mic,
+                keys.f_nwk_s_int,
+                keys.s_nwk_s_int,
+                fcnt,
+                ctx,
+            )
+        } else {
+            securityhelpers::calculate_downlink_mic_1_1(without_mic, keys.s_nwk_s_int, fcnt, ctx)
+        };
+        self.mic() == mic
+    }
 }
 
 /// A decrypted data frame, borrowing the buffer that was decrypted in place.
@@ -439,6 +489,46 @@ impl<'a> DecryptedDataPayload<'a> {
         Self::decrypt_in_place(buf, Some(nwk_crypto), app_crypto, fcnt)
     }
 
+    /// Parses `buf` and decrypts it in place under LoRaWAN 1.1 rules.
+    ///
+    /// Like [`Self::decrypt_in_place`], except that `nwk_crypto` is bound to
+    /// the NwkSEncKey and also decrypts FOpts, which LoRaWAN 1.1 encrypts.
+    /// Does NOT verify the MIC.
+    #[inline]
+    pub fn decrypt_in_place_1_1<C: Crypto>(
+        buf: &'a mut [u8],
+        nwk_crypto: &C,
+        app_crypto: Option<&C>,
+        fcnt: u32,
+    ) -> Result<Self, Error> {
+        let layout = Layout::validate(buf)?;
+        let f_opts_len = layout.fhdr_len - 7;
+        if f_opts_len > 0 {
+            let wire_fcnt = u16::from_le_bytes([buf[6], buf[7]]);
+            let full_fcnt = ((fcnt >> 16) << 16) | u32::from(wire_fcnt);
+            securityhelpers::encrypt_f_opts(buf, f_opts_len, full_fcnt, nwk_crypto);
+        }
+        Self::decrypt_in_place(buf, Some(nwk_crypto), app_crypto, fcnt)
+    }
+
+    /// Verifies the LoRaWAN 1.1 MIC, then decrypts in place.
+    ///
+    /// As with [`Self::check_mic_and_decrypt_in_place`], the buffer is left
+    /// untouched on `InvalidMic`.
+    #[inline]
+    pub fn check_mic_and_decrypt_in_place_1_1<C: Crypto>(
+        buf: &'a mut [u8],
+        keys: &NetworkSessionCrypto<'_, C>,
+        app_crypto: Option<&C>,
+        fcnt: u32,
+        ctx: &MicContext,
+    ) -> Result<Self, Error> {
+        if !EncryptedDataPayload::parse(buf)?.validate_mic_1_1(keys, fcnt, ctx) {
+            return Err(Error::InvalidMic);
+        }
+        Self::decrypt_in_place_1_1(buf, keys.nwk_s_enc, app_crypto, fcnt)
+    }
+
     data_view_accessors!();
 
     /// The decrypted FRMPayload.
@@ -474,6 +564,29 @@ fn extract_mic(bytes: &[u8]) -> MIC {
     MIC(arr(&bytes[bytes.len() - MIC_LEN..]))
 }
 
+/// The request a JoinAccept answers, which enters its LoRaWAN 1.1 MIC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum JoinReqType {
+    JoinRequest,
+    RejoinType0,
+    RejoinType1,
+    RejoinType2,
+}
+
+impl JoinReqType {
+    /// The JoinReqType octet of the MIC computation.
+    #[inline]
+    pub const fn wire_value(self) -> u8 {
+        match self {
+            Self::JoinRequest => 0xff,
+            Self::RejoinType0 => 0x00,
+            Self::RejoinType1 => 0x01,
+            Self::RejoinType2 => 0x02,
+        }
+    }
+}
+
 /// Zero-copy view of a JoinRequest frame.
 ///
 /// The fixed 23-byte length is checked once at parse time; the fixed-size
@@ -529,6 +642,99 @@ impl<'a> JoinRequestPayload<'a> {
     }
 }
 
+/// Zero-copy view of a LoRaWAN 1.1 rejoin request frame.
+///
+/// Types 0 and 2 carry the NetID and are signed with the SNwkSIntKey of the
+/// current session; type 1 carries the JoinEUI and is signed with the
+/// JSIntKey. The length is checked against the rejoin type at parse time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejoinRequestPayload<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> RejoinRequestPayload<'a> {
+    /// Parses and structurally validates a rejoin request.
+    #[inline]
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, Error> {
+        check_mhdr(bytes, 6)?;
+        let len = match bytes.get(1) {
+            Some(0 | 2) => REJOIN_REQUEST_0_2_LEN,
+            Some(1) => REJOIN_REQUEST_1_LEN,
+            Some(_) => return Err(Error::UnsupportedRejoinType),
+            None => return Err(Error::InvalidLength),
+        };
+        if bytes.len() != len {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Self { bytes })
+    }
+
+    /// The rejoin type, which also enters the MIC of the JoinAccept answering it.
+    #[inline]
+    pub fn rejoin_type(&self) -> JoinReqType {
+        match self.bytes[1] {
+            0 => JoinReqType::RejoinType0,
+            1 => JoinReqType::RejoinType1,
+            _ => JoinReqType::RejoinType2,
+        }
+    }
+
+    /// The NetID of the network the device is joined to, for types 0 and 2.
+    #[inline]
+    pub fn net_id(&self) -> Option<NetId> {
+        (self.bytes[1] != 1).then(|| NetId::from_wire_bytes(arr(&self.bytes[2..5])))
+    }
+
+    /// The JoinEUI, for type 1.
+    #[inline]
+    pub fn join_eui(&self) -> Option<JoinEui> {
+        (self.bytes[1] == 1).then(|| JoinEui::from_wire_bytes(arr(&self.bytes[2..10])))
+    }
+
+    #[inline]
+    pub fn dev_eui(&self) -> DevEui {
+        let offset = self.eui_offset();
+        DevEui::from_wire_bytes(arr(&self.bytes[offset..offset + 8]))
+    }
+
+    /// RJcount0 for types 0 and 2, RJcount1 for type 1. It takes the place of
+    /// the DevNonce in the JoinAccept MIC and the session key derivation.
+    #[inline]
+    pub fn rj_count(&self) -> u16 {
+        let offset = self.eui_offset() + 8;
+        u16::from_le_bytes([self.bytes[offset], self.bytes[offset + 1]])
+    }
+
+    #[inline]
+    pub fn mic(&self) -> MIC {
+        extract_mic(self.bytes)
+    }
+
+    /// Whether the MIC matches.
+    ///
+    /// `crypto` must be bound to the SNwkSIntKey for types 0 and 2, to the
+    /// JSIntKey for type 1.
+    #[inline]
+    pub fn validate_mic<C: Crypto>(&self, crypto: &C) -> bool {
+        let without_mic = &self.bytes[..self.bytes.len() - MIC_LEN];
+        self.mic() == securityhelpers::calculate_mic(without_mic, crypto)
+    }
+
+    /// The raw frame bytes.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn eui_offset(&self) -> usize {
+        if self.bytes[1] == 1 {
+            10
+        } else {
+            5
+        }
+    }
+}
+
 #[inline]
 fn validate_join_accept_structure(bytes: &[u8]) -> Result<(), Error> {
     check_mhdr(bytes, 1)?;
@@ -623,6 +829,37 @@ impl<'a> DecryptedJoinAcceptPayload<'a> {
         self.mic() == securityhelpers::calculate_mic(without_mic, crypto)
     }
 
+    /// Whether the MIC matches under the LoRaWAN 1.1 scheme, used when
+    /// [`Self::opt_neg`] is set.
+    ///
+    /// `crypto` must be bound to the JSIntKey; `join_eui` and `dev_nonce` are
+    /// those of the request being answered.
+    #[inline]
+    pub fn validate_mic_1_1<C: Crypto>(
+        &self,
+        crypto: &C,
+        join_req_type: JoinReqType,
+        join_eui: &JoinEui,
+        dev_nonce: &DevNonce,
+    ) -> bool {
+        let without_mic = &self.bytes[..self.bytes.len() - MIC_LEN];
+        self.mic()
+            == securityhelpers::calculate_join_accept_mic_1_1(
+                without_mic,
+                crypto,
+                join_req_type.wire_value(),
+                join_eui,
+                dev_nonce,
+            )
+    }
+
+    /// Whether the network operates LoRaWAN 1.1 or later (the OptNeg bit of
+    /// DLSettings). When unset, a 1.1 device falls back to 1.0.x behavior.
+    #[inline]
+    pub fn opt_neg(&self) -> bool {
+        self.dl_settings().opt_neg()
+    }
+
     /// The server nonce (called AppNonce before LoRaWAN 1.0.4).
     #[inline]
     pub fn join_nonce(&self) -> JoinNonce {
@@ -700,6 +937,74 @@ impl<'a> DecryptedJoinAcceptPayload<'a> {
         AppSKey(self.derive_session_key(0x02, dev_nonce, crypto))
     }
 
+    /// Derives the LoRaWAN 1.1 forwarding network session integrity key.
+    ///
+    /// `crypto` must be bound to the NwkKey.
+    #[inline]
+    pub fn derive_f_nwk_s_int_key<C: Crypto>(
+        &self,
+        join_eui: &JoinEui,
+        dev_nonce: DevNonce,
+        crypto: &C,
+    ) -> FNwkSIntKey {
+        FNwkSIntKey(self.derive_session_key_1_1(0x01, join_eui, dev_nonce, crypto))
+    }
+
+    /// Derives the LoRaWAN 1.1 serving network session integrity key.
+    ///
+    /// `crypto` must be bound to the NwkKey.
+    #[inline]
+    pub fn derive_s_nwk_s_int_key<C: Crypto>(
+        &self,
+        join_eui: &JoinEui,
+        dev_nonce: DevNonce,
+        crypto: &C,
+    ) -> SNwkSIntKey {
+        SNwkSIntKey(self.derive_session_key_1_1(0x03, join_eui, dev_nonce, crypto))
+    }
+
+    /// Derives the LoRaWAN 1.1 network session encryption key.
+    ///
+    /// `crypto` must be bound to the NwkKey.
+    #[inline]
+    pub fn derive_nwk_s_enc_key<C: Crypto>(
+        &self,
+        join_eui: &JoinEui,
+        dev_nonce: DevNonce,
+        crypto: &C,
+    ) -> NwkSEncKey {
+        NwkSEncKey(self.derive_session_key_1_1(0x04, join_eui, dev_nonce, crypto))
+    }
+
+    /// Derives the LoRaWAN 1.1 application session key.
+    ///
+    /// `crypto` must be bound to the AppKey.
+    #[inline]
+    pub fn derive_appskey_1_1<C: Crypto>(
+        &self,
+        join_eui: &JoinEui,
+        dev_nonce: DevNonce,
+        crypto: &C,
+    ) -> AppSKey {
+        AppSKey(self.derive_session_key_1_1(0x02, join_eui, dev_nonce, crypto))
+    }
+
+    fn derive_session_key_1_1<C: Crypto>(
+        &self,
+        first_byte: u8,
+        join_eui: &JoinEui,
+        dev_nonce: DevNonce,
+        crypto: &C,
+    ) -> AES128 {
+        let mut block = [0u8; 16];
+        block[0] = first_byte;
+        block[1..4].copy_from_slice(self.join_nonce().as_wire_bytes());
+        block[4..12].copy_from_slice(join_eui.as_wire_bytes());
+        block[12..14].copy_from_slice(dev_nonce.as_wire_bytes());
+        crypto.encrypt_block(&mut block);
+        AES128(block)
+    }
+
     fn derive_session_key<C: Crypto>(
         &self,
         first_byte: u8,
@@ -982,6 +1287,12 @@ impl FCtrl {
         !self.uplink && self.byte & 0x10 != 0
     }
 
+    /// Class B enabled; uplink only.
+    #[inline]
+    pub const fn class_b(&self) -> bool {
+        self.uplink && self.byte & 0x10 != 0
+    }
+
     #[inline]
     pub const fn f_opts_len(&self) -> usize {
         (self.byte & 0x0f) as usize
@@ -1,4 +1,5 @@
 use super::keys::{Crypto, MIC};
+use super::parser::{DevNonce, JoinEui, MicContext};
 
 /// calculate_data_mic computes the MIC of a correct data packet.
 pub fn calculate_data_mic(data: &[u8], crypto: &dyn Crypto, fcnt: u32) -> MIC {
@@ -11,6 +12,46 @@ pub fn calculate_data_mic(data: &[u8], crypto: &dyn Crypto, fcnt: u32) -> MIC {
     MIC(crypto.calculate_mic(&b0[..], data))
 }
 
+/// calculate_uplink_mic_1_1 computes the LoRaWAN 1.1 MIC of an uplink data packet: the first
+/// half of the CMAC over B1 under the SNwkSIntKey followed by the first half of the CMAC over B0
+/// under the FNwkSIntKey.
+pub fn calculate_uplink_mic_1_1(
+    data: &[u8],
+    f_crypto: &dyn Crypto,
+    s_crypto: &dyn Crypto,
+    fcnt: u32,
+    ctx: &MicContext,
+) -> MIC {
+    let mut b0 = [0; 16];
+    generate_helper_block(data, 0x49, fcnt, &mut b0[..16]);
+    b0[15] = data.len() as u8;
+
+    let mut b1 = b0;
+    b1[1..3].copy_from_slice(&ctx.conf_fcnt.to_le_bytes());
+    b1[3] = ctx.tx_dr;
+    b1[4] = ctx.tx_ch;
+
+    let cmac_f = f_crypto.calculate_mic(&b0[..], data);
+    let cmac_s = s_crypto.calculate_mic(&b1[..], data);
+    MIC([cmac_s[0], cmac_s[1], cmac_f[0], cmac_f[1]])
+}
+
+/// calculate_downlink_mic_1_1 computes the LoRaWAN 1.1 MIC of a downlink data packet, which
+/// carries ConfFCnt in B0.
+pub fn calculate_downlink_mic_1_1(
+    data: &[u8],
+    s_crypto: &dyn Crypto,
+    fcnt: u32,
+    ctx: &MicContext,
+) -> MIC {
+    let mut b0 = [0; 16];
+    generate_helper_block(data, 0x49, fcnt, &mut b0[..16]);
+    b0[1..3].copy_from_slice(&ctx.conf_fcnt.to_le_bytes());
+    b0[15] = data.len() as u8;
+
+    MIC(s_crypto.calculate_mic(&b0[..], data))
+}
+
 fn generate_helper_block(data: &[u8], first: u8, fcnt: u32, res: &mut [u8]) {
     res[0] = first;
     // res[1..5] are 0
@@ -30,6 +71,31 @@ pub fn calculate_mic(data: &[u8], crypto: &dyn Crypto) -> MIC {
     MIC(crypto.calculate_mic(&[], data))
 }
 
+/// calculate_join_accept_mic_1_1 computes the MIC of a JoinAccept sent with the OptNeg bit set,
+/// which also covers the request being answered.
+pub fn calculate_join_accept_mic_1_1(
+    data: &[u8],
+    crypto: &dyn Crypto,
+    join_req_type: u8,
+    join_eui: &JoinEui,
+    dev_nonce: &DevNonce,
+) -> MIC {
+    let mut prefix = [0u8; 11];
+    prefix[0] = join_req_type;
+    prefix[1..9].copy_from_slice(join_eui.as_wire_bytes());
+    prefix[9..11].copy_from_slice(dev_nonce.as_wire_bytes());
+    MIC(crypto.calculate_mic(&prefix, data))
+}
+
+/// encrypt_f_opts encrypts the FOpts of a LoRaWAN 1.1 data packet in place.
+///
+/// LoRaWAN 1.1.0 uses the first block of the FRMPayload keystream, bound to the NwkSEncKey.
+pub fn encrypt_f_opts(phy_payload: &mut [u8], f_opts_len: usize, fcnt: u32, crypto: &dyn Crypto) {
+    // MHDR (1) + DevAddr (4) + FCtrl (1) + FCnt (2)
+    const F_OPTS_START: usize = 8;
+    encrypt_frm_data_payload(phy_payload, F_OPTS_START, F_OPTS_START + f_opts_len, fcnt, crypto);
+}
+
 /// encrypt_frm_data_payload encrypts bytes
 pub fn encrypt_frm_data_payload(
     phy_payload: &mut [u8],
@@ -74,6 +74,30 @@ fixed_len_struct_impl_to_string_msb! {
     AppSKey, 16;
 }
 
+fixed_len_struct_impl_to_string_msb! {
+    NwkKey, 16;
+}
+
+fixed_len_struct_impl_to_string_msb! {
+    FNwkSIntKey, 16;
+}
+
+fixed_len_struct_impl_to_string_msb! {
+    SNwkSIntKey, 16;
+}
+
+fixed_len_struct_impl_to_string_msb! {
+    NwkSEncKey, 16;
+}
+
+fixed_len_struct_impl_to_string_msb! {
+    JSIntKey, 16;
+}
+
+fixed_len_struct_impl_to_string_msb! {
+    JSEncKey, 16;
+}
+
 fixed_len_struct_impl_to_string_msb! {
     McRootKey, 16;
 }
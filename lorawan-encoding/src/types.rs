@@ -4,6 +4,7 @@ use crate::maccommands::Error;
 
 /// ChannelMask represents the ChannelMask from LoRaWAN.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 pub struct ChannelMask<const N: usize>([u8; N]);
 
 impl<const N: usize> Default for ChannelMask<N> {
@@ -276,6 +277,12 @@ impl DLSettings {
         DR::from(self.0 & 0xf)
     }
 
+    /// The OptNeg bit of a JoinAccept: set by LoRaWAN 1.1+ networks to signal
+    /// that the 1.1 security scheme is in use.
+    pub fn opt_neg(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
     /// The integer value of the DL Settings.
     pub fn raw_value(&self) -> u8 {
         self.0
@@ -0,0 +1,107 @@
+use lorawan::beacon::*;
+use lorawan::default_crypto::DefaultCrypto;
+use lorawan::keys::AES128;
+use lorawan::parser::DevAddr;
+
+fn eu868_beacon() -> [u8; 17] {
+    [
+        0x00, 0x00, 0x00, 0x00, 0x02, 0xcc, 0xa2, 0x7e, 0x00, 0x01, 0x20, 0x00, 0x00, 0x81, 0x03,
+        0xde, 0x55,
+    ]
+}
+
+#[test]
+fn test_layout_lengths() {
+    assert_eq!(BeaconLayout::SHORT.frame_len(), 17);
+    assert_eq!(BeaconLayout::MEDIUM.frame_len(), 19);
+    assert_eq!(BeaconLayout::CN470.frame_len(), 19);
+    assert_eq!(BeaconLayout::LONG.frame_len(), 23);
+}
+
+#[test]
+fn test_parse_beacon() {
+    let bytes = eu868_beacon();
+    let beacon = Beacon::parse(&bytes, BeaconLayout::SHORT).unwrap();
+    assert_eq!(beacon.time(), 0xcc02_0000);
+    assert_eq!(
+        beacon.gw_specific(),
+        Some(GwSpecific { info_desc: 0, info: [0x01, 0x20, 0x00, 0x00, 0x81, 0x03] })
+    );
+}
+
+#[test]
+fn test_parse_cn470_beacon() {
+    let bytes = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xcc, 0xa2, 0x7e, 0x00, 0x01, 0x20, 0x00, 0x00, 0x81,
+        0x03, 0x00, 0x50, 0xd4,
+    ];
+    let beacon = Beacon::parse(&bytes, BeaconLayout::CN470).unwrap();
+    assert_eq!(beacon.time(), 0xcc02_0000);
+    assert_eq!(
+        beacon.gw_specific(),
+        Some(GwSpecific { info_desc: 0, info: [0x01, 0x20, 0x00, 0x00, 0x81, 0x03] })
+    );
+    // The IN865 layout of the same length places the time and CRCs elsewhere
+    assert_eq!(Beacon::parse(&bytes, BeaconLayout::MEDIUM), Err(Error::InvalidCrc));
+}
+
+#[test]
+fn test_parse_beacon_wrong_length() {
+    let bytes = eu868_beacon();
+    assert_eq!(Beacon::parse(&bytes, BeaconLayout::LONG), Err(Error::InvalidLength));
+    assert_eq!(Beacon::parse(&bytes[..16], BeaconLayout::SHORT), Err(Error::InvalidLength));
+}
+
+#[test]
+fn test_parse_beacon_invalid_common_crc() {
+    let mut bytes = eu868_beacon();
+    bytes[3] ^= 0x01;
+    assert_eq!(Beacon::parse(&bytes, BeaconLayout::SHORT), Err(Error::InvalidCrc));
+}
+
+#[test]
+fn test_parse_beacon_invalid_gw_specific_crc() {
+    let mut bytes = eu868_beacon();
+    bytes[10] ^= 0x01;
+    let beacon = Beacon::parse(&bytes, BeaconLayout::SHORT).unwrap();
+    assert_eq!(beacon.time(), 0xcc02_0000);
+    assert_eq!(beacon.gw_specific(), None);
+}
+
+#[test]
+fn test_beacon_creator() {
+    let mut creator = BeaconCreator::new(BeaconLayout::SHORT);
+    let bytes = creator
+        .set_time(0xcc02_0000)
+        .set_gw_specific(GwSpecific { info_desc: 0, info: [0x01, 0x20, 0x00, 0x00, 0x81, 0x03] })
+        .build();
+    assert_eq!(bytes, eu868_beacon());
+
+    for layout in [BeaconLayout::MEDIUM, BeaconLayout::LONG] {
+        let mut creator = BeaconCreator::new(layout);
+        let bytes = creator.set_time(1_000_000_000).build();
+        assert_eq!(bytes.len(), layout.frame_len());
+        assert_eq!(Beacon::parse(bytes, layout).unwrap().time(), 1_000_000_000);
+    }
+}
+
+#[test]
+fn test_ping_period() {
+    assert_eq!(ping_period(0), 32);
+    assert_eq!(ping_period(7), 4096);
+}
+
+#[test]
+fn test_ping_offset() {
+    let crypto = DefaultCrypto::new(&AES128([0; 16]));
+    let dev_addr = DevAddr::from_value(0x2601_1234);
+    for periodicity in 0..=7 {
+        let offset = ping_offset(&crypto, 1_234_567_808, &dev_addr, periodicity);
+        assert!(offset < ping_period(periodicity));
+    }
+    // The offset changes between beacon periods, spreading collisions between devices.
+    let offsets: Vec<u16> = (0..8)
+        .map(|n| ping_offset(&crypto, 1_234_567_808 + n * BEACON_PERIOD, &dev_addr, 7))
+        .collect();
+    assert!(offsets.windows(2).any(|w| w[0] != w[1]));
+}
@@ -1,6 +1,6 @@
 //! Tests for the borrowed-view parser and creator.
 
-use lorawan::creator::{DataFrame, JoinAccept, JoinRequest, Payload};
+use lorawan::creator::{DataFrame, JoinAccept, JoinRequest, Payload, RejoinRequest};
 use lorawan::default_crypto::{DefaultCrypto, DefaultNetworkCrypto};
 use lorawan::keys::{AES128, AppKey, AppSKey, MIC, NwkSKey};
 use lorawan::parser::*;
@@ -327,8 +327,10 @@ fn parse_classifies_by_mhdr() {
     assert!(matches!(parse(&phy_join_accept_payload()), Ok(PhyPayload::JoinAccept(_))));
     assert!(matches!(parse(&phy_dataup_payload()), Ok(PhyPayload::Data(_))));
     assert_eq!(parse(&[]).unwrap_err(), Error::TooShort);
-    // RFU and Proprietary message types
-    assert_eq!(parse(&[0xc0, 0, 0, 0]).unwrap_err(), Error::UnsupportedMessageType);
+    // Rejoin requests are classified, then checked against their rejoin type
+    assert_eq!(parse(&[0xc0, 0, 0, 0]).unwrap_err(), Error::InvalidLength);
+    assert_eq!(parse(&[0xc0, 3, 0, 0]).unwrap_err(), Error::UnsupportedRejoinType);
+    // Proprietary message type
     assert_eq!(parse(&[0xe0, 0, 0, 0]).unwrap_err(), Error::UnsupportedMessageType);
     // Bad major version
     assert_eq!(parse(&[0x01]).unwrap_err(), Error::UnsupportedMajorVersion);
@@ -395,6 +397,66 @@ fn join_request_round_trip() {
     assert_eq!(jr.dev_nonce(), DevNonce::from_wire_bytes([0xcc, 0xdd]));
 }
 
+// ---------------------------------------------------------------------------
+// RejoinRequest
+// ---------------------------------------------------------------------------
+
+#[test]
+fn rejoin_request_type_0_and_2_round_trip() {
+    let crypto = DefaultCrypto::new(&AES128([3; 16]));
+    let net_id = NetId::from_value(0x000013);
+    let dev_eui = DevEui::from_wire_bytes([8, 7, 6, 5, 4, 3, 2, 1]);
+    for (request, join_req_type) in [
+        (RejoinRequest::Type0 { net_id, dev_eui, rj_count0: 0x0102 }, JoinReqType::RejoinType0),
+        (RejoinRequest::Type2 { net_id, dev_eui, rj_count0: 0x0102 }, JoinReqType::RejoinType2),
+    ] {
+        let mut buf = [0u8; 32];
+        let built = request.build_into(&mut buf, &crypto).unwrap().to_vec();
+        assert_eq!(built.len(), 19);
+        assert_eq!(&built[13..15], &[0x02, 0x01]);
+
+        let Ok(PhyPayload::RejoinRequest(rejoin)) = parse(&built) else {
+            panic!("Did not parse a rejoin request");
+        };
+        assert!(rejoin.validate_mic(&crypto));
+        assert!(!rejoin.validate_mic(&DefaultCrypto::new(&AES128([4; 16]))));
+        assert_eq!(rejoin.rejoin_type(), join_req_type);
+        assert_eq!(rejoin.net_id(), Some(net_id));
+        assert_eq!(rejoin.join_eui(), None);
+        assert_eq!(rejoin.dev_eui(), dev_eui);
+        assert_eq!(rejoin.rj_count(), 0x0102);
+    }
+}
+
+#[test]
+fn rejoin_request_type_1_round_trip() {
+    let crypto = DefaultCrypto::new(&AES128([3; 16]));
+    let join_eui = JoinEui::from_wire_bytes([1, 2, 3, 4, 5, 6, 7, 8]);
+    let dev_eui = DevEui::from_wire_bytes([8, 7, 6, 5, 4, 3, 2, 1]);
+    let request = RejoinRequest::Type1 { join_eui, dev_eui, rj_count1: 7 };
+    assert_eq!(request.join_req_type(), JoinReqType::RejoinType1);
+    assert_eq!(request.build_into(&mut [0u8; 23], &crypto).unwrap_err(), Error::BufferTooShort);
+
+    let mut buf = [0u8; 24];
+    let built = request.build_into(&mut buf, &crypto).unwrap().to_vec();
+    let rejoin = RejoinRequestPayload::parse(&built).unwrap();
+    assert!(rejoin.validate_mic(&crypto));
+    assert_eq!(rejoin.rejoin_type(), JoinReqType::RejoinType1);
+    assert_eq!(rejoin.net_id(), None);
+    assert_eq!(rejoin.join_eui(), Some(join_eui));
+    assert_eq!(rejoin.dev_eui(), dev_eui);
+    assert_eq!(rejoin.rj_count(), 7);
+
+    // A type 1 request is longer than a type 0 request.
+    let mut type_0 = built.clone();
+    type_0[1] = 0;
+    assert_eq!(RejoinRequestPayload::parse(&type_0).unwrap_err(), Error::InvalidLength);
+    assert_eq!(
+        RejoinRequestPayload::parse(&phy_join_request_payload()).unwrap_err(),
+        Error::UnexpectedMessageType
+    );
+}
+
 // ---------------------------------------------------------------------------
 // JoinAccept
 // ---------------------------------------------------------------------------
@@ -768,6 +830,212 @@ mod creators {
     }
 }
 
+// ---------------------------------------------------------------------------
+// LoRaWAN 1.1 security: split network keys, FOpts encryption, ConfFCnt
+// ---------------------------------------------------------------------------
+
+mod lorawan_1_1 {
+    use super::*;
+    use core::num::NonZeroU8;
+    use lorawan::keys::{JSIntKey, NetworkSessionCrypto, NwkKey};
+
+    fn keys() -> [DefaultCrypto; 3] {
+        [
+            DefaultCrypto::new(&AES128([5; 16])),
+            DefaultCrypto::new(&AES128([6; 16])),
+            DefaultCrypto::new(&AES128([7; 16])),
+        ]
+    }
+
+    /// With all network keys equal and an all-zero context, the 1.1 MIC
+    /// degenerates to the 1.0 one: B0 is unchanged for downlinks and B1
+    /// equals B0 for uplinks.
+    #[test]
+    fn mic_matches_1_0_when_keys_collapse() {
+        let nwk = DefaultCrypto::new(&AES128([2; 16]));
+        let keys = NetworkSessionCrypto { f_nwk_s_int: &nwk, s_nwk_s_int: &nwk, nwk_s_enc: &nwk };
+
+        let down = phy_datadown_payload();
+        let phy = EncryptedDataPayload::parse(&down).unwrap();
+        assert!(phy.validate_mic_1_1(&keys, 76543, &MicContext::default()));
+
+        let up = phy_dataup_payload();
+        let phy = EncryptedDataPayload::parse(&up).unwrap();
+        let mic = phy.mic().0;
+        let mut buf = [0u8; 64];
+        let frame = DataFrame {
+            frame_type: DataFrameType::UnconfirmedUp,
+            dev_addr: DevAddr::from_value(0x01020304),
+            adr: true,
+            fcnt: 1,
+            payload: Payload::Data { f_port: NonZeroU8::new(1).unwrap(), data: b"hello" },
+            ..Default::default()
+        };
+        let app = DefaultCrypto::new(&AES128([1; 16]));
+        let built = frame.build_into_1_1(&mut buf, &keys, Some(&app), &MicContext::default());
+        let built = built.unwrap();
+        assert_eq!(built[built.len() - 4..], [mic[0], mic[1], mic[0], mic[1]]);
+    }
+
+    #[test]
+    fn uplink_round_trip_encrypts_f_opts() {
+        let [f, s, e] = keys();
+        let keys = NetworkSessionCrypto { f_nwk_s_int: &f, s_nwk_s_int: &s, nwk_s_enc: &e };
+        let app = DefaultCrypto::new(&AES128([8; 16]));
+        let ctx = MicContext { conf_fcnt: 3, tx_dr: 5, tx_ch: 2 };
+        let frame = DataFrame {
+            frame_type: DataFrameType::ConfirmedUp,
+            dev_addr: DevAddr::from_value(0x01020304),
+            ack: true,
+            fcnt: 0x0001_0002,
+            f_opts: &[0x0b, 0x01],
+            payload: Payload::Data { f_port: NonZeroU8::new(3).unwrap(), data: b"data" },
+            ..Default::default()
+        };
+        let mut buf = [0u8; 64];
+        let mut rt = frame.build_into_1_1(&mut buf, &keys, Some(&app), &ctx).unwrap().to_vec();
+
+        let phy = EncryptedDataPayload::parse(&rt).unwrap();
+        assert_ne!(phy.fhdr().f_opts(), &[0x0b, 0x01]);
+        // Every context field enters the MIC.
+        assert!(phy.validate_mic_1_1(&keys, 0x0001_0002, &ctx));
+        for other in [
+            MicContext { conf_fcnt: 4, ..ctx },
+            MicContext { tx_dr: 4, ..ctx },
+            MicContext { tx_ch: 3, ..ctx },
+        ] {
+            assert!(!phy.validate_mic_1_1(&keys, 0x0001_0002, &other));
+        }
+
+        let dec = DecryptedDataPayload::check_mic_and_decrypt_in_place_1_1(
+            &mut rt,
+            &keys,
+            Some(&app),
+            0x0001_0002,
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(dec.fhdr().f_opts(), &[0x0b, 0x01]);
+        assert_eq!(dec.frm_payload(), FrmPayload::Data(b"data"));
+    }
+
+    #[test]
+    fn resign_uplink_for_another_channel() {
+        let [f, s, e] = keys();
+        let keys = NetworkSessionCrypto { f_nwk_s_int: &f, s_nwk_s_int: &s, nwk_s_enc: &e };
+        let ctx = MicContext { conf_fcnt: 0, tx_dr: 3, tx_ch: 1 };
+        let frame = DataFrame {
+            dev_addr: DevAddr::from_value(0x01020304),
+            fcnt: 0x0001_0002,
+            payload: Payload::Data { f_port: NonZeroU8::new(3).unwrap(), data: b"data" },
+            ..Default::default()
+        };
+        let app = DefaultCrypto::new(&AES128([8; 16]));
+        let mut buf = [0u8; 64];
+        let mut rt = frame.build_into_1_1(&mut buf, &keys, Some(&app), &ctx).unwrap().to_vec();
+
+        let other = MicContext { tx_ch: 6, ..ctx };
+        DataFrame::resign_uplink_1_1(&mut rt, &keys, 0x0001_0002, &other).unwrap();
+        let phy = EncryptedDataPayload::parse(&rt).unwrap();
+        assert!(phy.validate_mic_1_1(&keys, 0x0001_0002, &other));
+        assert!(!phy.validate_mic_1_1(&keys, 0x0001_0002, &ctx));
+
+        let err = DataFrame::resign_uplink_1_1(&mut rt[..8], &keys, 0, &ctx).unwrap_err();
+        assert_eq!(err, Error::TooShort);
+    }
+
+    #[test]
+    fn downlink_mic_covers_conf_fcnt() {
+        let [f, s, e] = keys();
+        let keys = NetworkSessionCrypto { f_nwk_s_int: &f, s_nwk_s_int: &s, nwk_s_enc: &e };
+        let ctx = MicContext { conf_fcnt: 9, ..Default::default() };
+        let frame = DataFrame {
+            frame_type: DataFrameType::UnconfirmedDown,
+            dev_addr: DevAddr::from_value(0x01020304),
+            ack: true,
+            fcnt: 1,
+            payload: Payload::MacCommands(&[0x0b, 0x01]),
+            ..Default::default()
+        };
+        let mut buf = [0u8; 64];
+        let mut rt = frame.build_into_1_1(&mut buf, &keys, None, &ctx).unwrap().to_vec();
+        let err = DecryptedDataPayload::check_mic_and_decrypt_in_place_1_1(
+            &mut rt,
+            &keys,
+            None,
+            1,
+            &MicContext::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err, Error::InvalidMic);
+        let dec =
+            DecryptedDataPayload::check_mic_and_decrypt_in_place_1_1(&mut rt, &keys, None, 1, &ctx)
+                .unwrap();
+        assert_eq!(dec.frm_payload(), FrmPayload::MacCommands(&[0x0b, 0x01]));
+    }
+
+    #[test]
+    fn join_accept_with_opt_neg_round_trips() {
+        let nwk_key = NwkKey::from([1; 16]);
+        let dev_eui = DevEui::from_value(0x0102030405060708);
+        let join_eui = JoinEui::from_value(0x1112131415161718);
+        let dev_nonce = DevNonce::from_value(7);
+        let nwk_crypto = DefaultCrypto::new(nwk_key.inner());
+        let js_int_key = JSIntKey::derive_from(&nwk_crypto, &dev_eui);
+        let js_int_crypto = DefaultCrypto::new(js_int_key.inner());
+        let ja = JoinAccept {
+            join_nonce: JoinNonce::from_value(3),
+            net_id: NetId::from_value(0),
+            dev_addr: DevAddr::from_value(0x01020304),
+            dl_settings: lorawan::types::DLSettings::new(0x80),
+            rx_delay: 1,
+            c_f_list: None,
+        };
+        let mut buf = [0u8; 33];
+        let mut rt = ja
+            .build_into_1_1(
+                &mut buf,
+                &DefaultNetworkCrypto::new(nwk_key.inner()),
+                &js_int_crypto,
+                JoinReqType::JoinRequest,
+                &join_eui,
+                &dev_nonce,
+            )
+            .unwrap()
+            .to_vec();
+
+        let dec = DecryptedJoinAcceptPayload::decrypt_in_place(&mut rt, &nwk_crypto).unwrap();
+        assert!(dec.opt_neg());
+        assert!(!dec.validate_mic(&nwk_crypto));
+        assert!(dec.validate_mic_1_1(
+            &js_int_crypto,
+            JoinReqType::JoinRequest,
+            &join_eui,
+            &dev_nonce
+        ));
+        assert!(!dec.validate_mic_1_1(
+            &js_int_crypto,
+            JoinReqType::RejoinType0,
+            &join_eui,
+            &dev_nonce
+        ));
+
+        // Each network session key is derived with its own prefix.
+        let f = dec.derive_f_nwk_s_int_key(&join_eui, dev_nonce, &nwk_crypto);
+        let s = dec.derive_s_nwk_s_int_key(&join_eui, dev_nonce, &nwk_crypto);
+        let e = dec.derive_nwk_s_enc_key(&join_eui, dev_nonce, &nwk_crypto);
+        assert_ne!(f.inner(), s.inner());
+        assert_ne!(s.inner(), e.inner());
+        let mut block = [0u8; 16];
+        block[0] = 0x03;
+        block[1..4].copy_from_slice(JoinNonce::from_value(3).as_wire_bytes());
+        block[4..12].copy_from_slice(join_eui.as_wire_bytes());
+        block[12..14].copy_from_slice(dev_nonce.as_wire_bytes());
+        lorawan::keys::Crypto::encrypt_block(&nwk_crypto, &mut block);
+        assert_eq!(s.inner().0, block);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // DevEui / JoinEui distinct newtypes
 // ---------------------------------------------------------------------------
@@ -200,6 +200,14 @@ fn test_dl_channel_req_creator() {
     assert_eq!(res, [DlChannelReqPayload::cid(), 0x03, 0x12, 0x34, 0x56]);
 }
 
+#[test]
+fn test_rekey_ind_creator() {
+    let mut creator = RekeyIndCreator::new();
+    let res = creator.set_minor_version(1).unwrap().build();
+    assert_eq!(res, [RekeyIndPayload::cid(), 0x01]);
+    assert!(creator.set_minor_version(16).is_err());
+}
+
 #[test]
 fn test_device_time_req_creator() {
     let creator = DeviceTimeReqCreator::new();
@@ -207,6 +215,15 @@ fn test_device_time_req_creator() {
     assert_eq!(res, [DeviceTimeReqPayload::cid()]);
 }
 
+#[test]
+fn test_device_time_ans_round_trip() {
+    let mut creator = DeviceTimeAnsCreator::new();
+    creator.set_seconds(1_234_567_890).set_nano_seconds(500_000_000).unwrap();
+    let payload = DeviceTimeAnsPayload::new(&creator.build()[1..]).unwrap();
+    assert_eq!(payload.seconds(), 1_234_567_890);
+    assert_eq!(payload.nano_seconds(), 500_000_000);
+}
+
 #[test]
 fn test_device_time_ans_creator() {
     let mut creator = DeviceTimeAnsCreator::new();
@@ -216,6 +233,79 @@ fn test_device_time_ans_creator() {
     assert_eq!(res, [DeviceTimeAnsPayload::cid(), 64, 226, 1, 0, 31]);
 }
 
+#[test]
+fn test_force_rejoin_req_creator() {
+    let mut creator = ForceRejoinReqCreator::new();
+    creator.set_period(3).unwrap().set_max_retries(2).unwrap();
+    creator.set_rejoin_type(2).unwrap().set_data_rate(5).unwrap();
+    let res = creator.build();
+    assert_eq!(res, [ForceRejoinReqPayload::cid(), 0x25, 0x1a]);
+    let payload = ForceRejoinReqPayload::new(&res[1..]).unwrap();
+    assert_eq!(payload.period(), 3);
+    assert_eq!(payload.max_retries(), 2);
+    assert_eq!(payload.rejoin_type(), 2);
+    assert!(creator.set_period(8).is_err());
+    assert!(creator.set_max_retries(8).is_err());
+    assert!(creator.set_rejoin_type(8).is_err());
+    assert!(creator.set_data_rate(16).is_err());
+}
+
+#[test]
+fn test_rejoin_param_setup_req_creator() {
+    let mut creator = RejoinParamSetupReqCreator::new();
+    let res = creator.set_max_time_n(5).unwrap().set_max_count_n(10).unwrap().build();
+    assert_eq!(res, [RejoinParamSetupReqPayload::cid(), 0x5a]);
+    assert_eq!(
+        creator.set_max_count_n(16).err(),
+        Some(lorawan::maccommandcreator::Error::RejoinParamOutOfRange)
+    );
+}
+
+#[test]
+fn test_rejoin_param_setup_ans_creator() {
+    let mut creator = RejoinParamSetupAnsCreator::new();
+    let res = creator.set_time_ack(true).build();
+    assert_eq!(res, [RejoinParamSetupAnsPayload::cid(), 0x01]);
+}
+
+#[test]
+fn test_ping_slot_info_req_creator() {
+    let mut creator = PingSlotInfoReqCreator::new();
+    let res = creator.set_periodicity(7).unwrap().build();
+    assert_eq!(res, [PingSlotInfoReqPayload::cid(), 0x07]);
+    assert_eq!(
+        creator.set_periodicity(8).err(),
+        Some(lorawan::maccommandcreator::Error::PeriodicityOutOfRange)
+    );
+}
+
+#[test]
+fn test_ping_slot_channel_req_creator() {
+    let mut creator = PingSlotChannelReqCreator::new();
+    creator.set_frequency(&[0x12, 0x34, 0x56]).set_data_rate(0x03).unwrap();
+    let res = creator.build();
+    assert_eq!(res, [PingSlotChannelReqPayload::cid(), 0x12, 0x34, 0x56, 0x03]);
+    assert!(creator.set_data_rate(0x10).is_err());
+}
+
+#[test]
+fn test_ping_slot_channel_ans_creator() {
+    let mut creator = PingSlotChannelAnsCreator::new();
+    let res = creator.set_channel_frequency_ack(true).set_data_rate_ack(true).build();
+    assert_eq!(res, [PingSlotChannelAnsPayload::cid(), 0x03]);
+}
+
+#[test]
+fn test_beacon_freq_creators() {
+    let mut creator = BeaconFreqReqCreator::new();
+    let res = creator.set_frequency(&[0x12, 0x34, 0x56]).build();
+    assert_eq!(res, [BeaconFreqReqPayload::cid(), 0x12, 0x34, 0x56]);
+
+    let mut creator = BeaconFreqAnsCreator::new();
+    let res = creator.set_beacon_frequency_ack(true).build();
+    assert_eq!(res, [BeaconFreqAnsPayload::cid(), 0x01]);
+}
+
 #[test]
 fn test_build_mac_commands() {
     let rx_timing_setup_req =
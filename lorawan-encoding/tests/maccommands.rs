@@ -245,6 +245,61 @@ fn test_dl_channel_ans() {
     );
 }
 
+#[test]
+fn test_rekey_ind() {
+    let data = [0x1];
+    test_helper!(UplinkMacCommand, data, RekeyInd, RekeyIndPayload, 1, (minor_version, 1),);
+}
+
+#[test]
+fn test_rekey_conf() {
+    let data = [0x1];
+    test_helper!(DownlinkMacCommand, data, RekeyConf, RekeyConfPayload, 1, (minor_version, 1),);
+}
+
+#[test]
+fn test_force_rejoin_req() {
+    let data = [0x25, 0x1a];
+    test_helper!(
+        DownlinkMacCommand,
+        data,
+        ForceRejoinReq,
+        ForceRejoinReqPayload,
+        2,
+        (period, 3),
+        (max_retries, 2),
+        (rejoin_type, 2),
+        (data_rate, DR::_5),
+    );
+}
+
+#[test]
+fn test_rejoin_param_setup_req() {
+    let data = [0x5a];
+    test_helper!(
+        DownlinkMacCommand,
+        data,
+        RejoinParamSetupReq,
+        RejoinParamSetupReqPayload,
+        1,
+        (max_time_n, 5),
+        (max_count_n, 10),
+    );
+}
+
+#[test]
+fn test_rejoin_param_setup_ans() {
+    let data = [0x1];
+    test_helper!(
+        UplinkMacCommand,
+        data,
+        RejoinParamSetupAns,
+        RejoinParamSetupAnsPayload,
+        1,
+        (time_ack, true),
+    );
+}
+
 #[test]
 fn test_parse_mac_commands_empty_downlink() {
     assert_eq!(parse_downlink_mac_commands(&[]).count(), 0);
@@ -263,11 +318,85 @@ fn test_device_time_ans() {
         DeviceTimeAns,
         DeviceTimeAnsPayload,
         5,
-        (seconds, 16909060),
+        (seconds, 0x04030201),
         (nano_seconds, 0x5 * 3906250),
     );
 }
 
+#[test]
+fn test_ping_slot_info_req() {
+    let data = [0x0d];
+    test_helper!(
+        UplinkMacCommand,
+        data,
+        PingSlotInfoReq,
+        PingSlotInfoReqPayload,
+        1,
+        (periodicity, 5),
+    );
+}
+
+#[test]
+fn test_ping_slot_info_ans() {
+    test_helper!(DownlinkMacCommand, PingSlotInfoAns, PingSlotInfoAnsPayload);
+}
+
+#[test]
+fn test_ping_slot_channel_req() {
+    let data = [0x28, 0x76, 0x84, 0x03];
+    test_helper!(
+        DownlinkMacCommand,
+        data,
+        PingSlotChannelReq,
+        PingSlotChannelReqPayload,
+        4,
+        (frequency, Frequency::new_from_raw(&data[0..3])),
+        (data_rate, DR::_3),
+    );
+}
+
+#[test]
+fn test_ping_slot_channel_ans() {
+    let data = [0x2];
+    test_helper!(
+        UplinkMacCommand,
+        data,
+        PingSlotChannelAns,
+        PingSlotChannelAnsPayload,
+        1,
+        (channel_freq_ack, false),
+        (data_rate_ack, true),
+        (ack, false),
+    );
+}
+
+#[test]
+fn test_beacon_freq_req() {
+    let data = [0xd2, 0xad, 0x84];
+    test_helper!(
+        DownlinkMacCommand,
+        data,
+        BeaconFreqReq,
+        BeaconFreqReqPayload,
+        3,
+        (frequency, Frequency::new_from_raw(&data[..])),
+    );
+    assert_eq!(BeaconFreqReqPayload::new(&data[..]).unwrap().frequency().value(), 869_525_000);
+}
+
+#[test]
+fn test_beacon_freq_ans() {
+    let data = [0x1];
+    test_helper!(
+        UplinkMacCommand,
+        data,
+        BeaconFreqAns,
+        BeaconFreqAnsPayload,
+        1,
+        (beacon_freq_ack, true),
+    );
+}
+
 #[test]
 fn test_parse_mac_commands_empty_uplink() {
     assert_eq!(parse_uplink_mac_commands(&[]).count(), 0);
@@ -18,3 +18,9 @@ fn test_mac_join_accept() {
 fn test_mac_join_request() {
     assert_eq!(23, packet_length::phy::join::JOIN_REQUEST_LEN);
 }
+
+#[test]
+fn test_mac_rejoin_request() {
+    assert_eq!(19, packet_length::phy::join::REJOIN_REQUEST_0_2_LEN);
+    assert_eq!(24, packet_length::phy::join::REJOIN_REQUEST_1_LEN);
+}